@@ -1,10 +1,11 @@
 use clap::Parser;
 // No specific handlers needed for basic binary
+use test_rig::lib_utils::report_error_and_exit;
 use test_rig::state_handlers::{
     ConnectingHandler, GettingVersionHandler, InitialHandler, ParsingConfigHandler,
     TestingConnectionHandler, VerifyingDatabaseHandler,
 };
-use test_rig::{CommonArgs, print_error_and_exit, print_success, print_test_header};
+use test_rig::{CommonArgs, print_startup_banner, print_success, print_test_header};
 use test_rig::{State, StateMachine};
 
 #[derive(Parser, Debug)]
@@ -16,8 +17,17 @@ pub struct Args {
 }
 
 impl Args {
-    pub fn print_connection_info(&self) {
-        self.common.print_connection_info();
+    /// Print the settings this run uses
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration cannot be loaded.
+    pub fn print_startup_banner(&self) -> test_rig::Result<()> {
+        print_startup_banner(
+            &self.common.effective_config()?,
+            &[("Mode", "basic connection test".to_string())],
+        );
+        Ok(())
     }
     /// Initialize logging system
     ///
@@ -44,12 +54,22 @@ async fn main() {
     print_test_header("TiDB Basic Connection Test");
     let args = Args::parse();
     args.init_logging().expect("Failed to initialize logging");
-    args.print_connection_info();
+    args.print_startup_banner()
+        .expect("Failed to load configuration");
     let (host, user, password, database) = args
         .get_connection_info()
         .expect("Failed to get connection info");
 
+    // --hosts configures failover endpoints for the workflow
+    let host = args.common.hosts.clone().unwrap_or(host);
+
     let mut machine = StateMachine::new();
+    args.common
+        .context_settings()
+        .unwrap_or_else(|e| {
+            report_error_and_exit("Invalid configuration", &e, args.common.error_format, None)
+        })
+        .configure(&mut machine);
 
     // Register core state handlers
     machine.register_handler(State::Initial, Box::new(InitialHandler));
@@ -64,6 +84,11 @@ async fn main() {
 
     match machine.run().await {
         Ok(()) => print_success("Basic connection test completed successfully!"),
-        Err(e) => print_error_and_exit("Basic connection test failed", &e),
+        Err(e) => report_error_and_exit(
+            "Basic connection test failed",
+            &e,
+            args.common.error_format,
+            Some(&machine.get_current_state().to_string()),
+        ),
     }
 }
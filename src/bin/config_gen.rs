@@ -32,6 +32,13 @@
 //! - `--username`: Database username (default: root)
 //! - `--database`: Database name (optional)
 //! - `--log-level`: Log level (default: info)
+//! - `--from-connection`: Connect to `--host` and fill in the config from what the server reports,
+//!   including its version
+//! - `--password-file`: Password for `--from-connection` when `TIDB_PASSWORD` is not set; never
+//!   written to the file
+//! - `--tls-ca`, `--tls-cert`, `--tls-key`, `--tls-skip-verify`: TLS settings for
+//!   `--from-connection`, also written to the config
+//! - `config-diff <LEFT> <RIGHT>`: Print the fields that differ between two config files
 //!
 //! ## Usage
 //!
@@ -44,6 +51,16 @@
 //!
 //! # Generate configuration with custom output path
 //! cargo run --bin config_gen -- --output my_config.json --host prod-tidb:4000 --log-level debug
+//!
+//! # Bootstrap a configuration from a running server, picking its first user database
+//! TIDB_PASSWORD=secret cargo run --bin config_gen -- --from-connection --host prod-tidb:4000 --username appuser
+//!
+//! # The same over TLS, with the password in a mounted secret
+//! cargo run --bin config_gen -- --from-connection --host prod-tidb:4000 --tls-ca ca.pem \
+//!     --password-file /run/secrets/tidb
+//!
+//! # Compare a dev and a prod configuration (passwords are redacted)
+//! cargo run --bin config_gen -- config-diff dev.toml prod.toml
 //! ```
 //!
 //! ## Output
@@ -73,11 +90,192 @@
 //! ```
 
 use clap::Command;
-use std::path::PathBuf;
+use mysql::PooledConn;
+use mysql::prelude::Queryable;
+use std::path::{Path, PathBuf};
+use test_rig::config::{AppConfig, ConfigDifference};
+use test_rig::connection::{
+    TlsConfig, create_connection_with_tls, get_server_version, parse_connection_string,
+};
+use test_rig::errors::ConnectError;
+use test_rig::password::{EnvOrPromptProvider, FilePasswordProvider, PasswordProvider};
 use test_rig::{
     ConfigBuilder, apply_extensions_to_command, apply_extensions_to_config, print_extensions_help,
 };
 
+/// The `config-diff` subcommand
+fn config_diff_command() -> Command {
+    Command::new("config-diff")
+        .about("Print the fields that differ between two config files")
+        .arg(
+            clap::Arg::new("left")
+                .required(true)
+                .help("First config file, e.g. dev"),
+        )
+        .arg(
+            clap::Arg::new("right")
+                .required(true)
+                .help("Second config file, e.g. prod"),
+        )
+}
+
+/// Load two config files and return their field-level differences
+///
+/// # Errors
+///
+/// Returns an error if either file cannot be loaded.
+fn diff_config_files(left: &Path, right: &Path) -> test_rig::Result<Vec<ConfigDifference>> {
+    Ok(AppConfig::from_file(left)?.diff(&AppConfig::from_file(right)?))
+}
+
+/// Schemas every server has; never picked as the configured database
+const SYSTEM_SCHEMAS: [&str; 6] = [
+    "INFORMATION_SCHEMA",
+    "PERFORMANCE_SCHEMA",
+    "METRICS_SCHEMA",
+    "mysql",
+    "sys",
+    "lightning_task_info",
+];
+
+/// What `--from-connection` learned about the server
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DiscoveredServer {
+    version: Option<String>,
+    databases: Vec<String>,
+}
+
+/// Read the server version and the databases visible to the user
+///
+/// # Errors
+///
+/// Returns an error if either query fails.
+fn discover_server(conn: &mut PooledConn) -> test_rig::Result<DiscoveredServer> {
+    Ok(DiscoveredServer {
+        version: get_server_version(conn)?,
+        databases: conn.query("SHOW DATABASES")?,
+    })
+}
+
+/// Assemble a config for `host` from what the server reported
+///
+/// `database` must exist on the server when given; otherwise the first
+/// non-system database is used, if there is one. The server version is
+/// recorded in `database.server_version`.
+///
+/// # Errors
+///
+/// Returns an error if `database` is not among the discovered databases.
+fn config_from_discovery(
+    host: &str,
+    username: &str,
+    database: Option<&str>,
+    log_level: &str,
+    discovered: &DiscoveredServer,
+) -> test_rig::Result<AppConfig> {
+    let database = match database {
+        Some(wanted) => Some(
+            discovered
+                .databases
+                .iter()
+                .find(|db| db.as_str() == wanted)
+                .ok_or_else(|| {
+                    ConnectError::Configuration(format!(
+                        "Database '{wanted}' not found on {host}; available: {}",
+                        discovered.databases.join(", ")
+                    ))
+                })?,
+        ),
+        None => discovered.databases.iter().find(|db| {
+            !SYSTEM_SCHEMAS
+                .iter()
+                .any(|system| system.eq_ignore_ascii_case(db))
+        }),
+    };
+
+    let mut builder = ConfigBuilder::new()
+        .host(host)
+        .username(username)
+        .log_level(log_level);
+    if let Some(database) = database {
+        builder = builder.database(database.clone());
+    }
+    if let Some(version) = &discovered.version {
+        builder = builder.server_version(version.clone());
+    }
+    Ok(builder.build())
+}
+
+/// Connect to `host`, over TLS if `tls` is given, and build a config from
+/// what the server reports
+///
+/// # Errors
+///
+/// Returns an error if the connection or discovery fails, or `database`
+/// does not exist on the server.
+fn config_from_connection(
+    host: &str,
+    username: &str,
+    password: &str,
+    database: Option<&str>,
+    log_level: &str,
+    tls: Option<&TlsConfig>,
+) -> test_rig::Result<AppConfig> {
+    let (hostname, port) = parse_connection_string(host)?;
+    let mut conn = create_connection_with_tls(&hostname, port, username, password, None, tls)?;
+    let discovered = discover_server(&mut conn)?;
+    println!(
+        "Connected to {host}: server version {}, {} database(s)",
+        discovered.version.as_deref().unwrap_or("unknown"),
+        discovered.databases.len()
+    );
+    config_from_discovery(host, username, database, log_level, &discovered)
+}
+
+/// TLS settings from the `--tls-*` flags, or `None` if none was given
+fn tls_from_args(args: &clap::ArgMatches) -> Option<TlsConfig> {
+    let path = |name: &str| args.get_one::<String>(name).map(PathBuf::from);
+    let tls = TlsConfig {
+        ca: path("tls-ca"),
+        cert: path("tls-cert"),
+        key: path("tls-key"),
+        skip_verify: args.get_flag("tls-skip-verify"),
+    };
+    (tls != TlsConfig::default()).then_some(tls)
+}
+
+/// Password for `--from-connection`: `TIDB_PASSWORD` (or `DATABASE_URL` /
+/// `MYSQL_PWD`), then `--password-file`, else empty
+///
+/// # Errors
+///
+/// Returns an error if the password file cannot be read.
+fn connection_password(args: &clap::ArgMatches) -> test_rig::Result<String> {
+    let provider = EnvOrPromptProvider::new(false);
+    let provider = match args.get_one::<String>("password-file") {
+        Some(path) => provider.with_file(FilePasswordProvider::new(path)),
+        None => provider,
+    };
+    Ok(provider.password()?.unwrap_or_default())
+}
+
+/// Run `config-diff`, printing one line per differing field
+fn run_config_diff(args: &clap::ArgMatches) -> Result<(), Box<dyn std::error::Error>> {
+    let left = Path::new(args.get_one::<String>("left").unwrap());
+    let right = Path::new(args.get_one::<String>("right").unwrap());
+    let differences = diff_config_files(left, right)?;
+    if differences.is_empty() {
+        println!("{} and {} are identical", left.display(), right.display());
+    } else {
+        println!("--- {}", left.display());
+        println!("+++ {}", right.display());
+        for difference in &differences {
+            println!("  {difference}");
+        }
+    }
+    Ok(())
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Build the CLI command with extensions
     let app = Command::new("config-gen")
@@ -118,13 +316,53 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .long("log-level")
                 .help("Log level")
                 .default_value("info"),
-        );
+        )
+        .arg(
+            clap::Arg::new("from-connection")
+                .long("from-connection")
+                .help("Connect to --host and fill in the config from what the server reports")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            clap::Arg::new("password-file")
+                .long("password-file")
+                .value_name("PATH")
+                .help("File with the password for --from-connection, used when TIDB_PASSWORD is not set; not written to the config"),
+        )
+        .arg(
+            clap::Arg::new("tls-ca")
+                .long("tls-ca")
+                .value_name("PATH")
+                .help("CA certificate for verifying the server; enables TLS"),
+        )
+        .arg(
+            clap::Arg::new("tls-cert")
+                .long("tls-cert")
+                .value_name("PATH")
+                .help("Client certificate for mutual TLS (requires --tls-key)"),
+        )
+        .arg(
+            clap::Arg::new("tls-key")
+                .long("tls-key")
+                .value_name("PATH")
+                .help("Client private key for mutual TLS (requires --tls-cert)"),
+        )
+        .arg(
+            clap::Arg::new("tls-skip-verify")
+                .long("tls-skip-verify")
+                .help("Connect over TLS without verifying the server certificate")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .subcommand(config_diff_command());
 
     // Apply extensions to the command
     let app = apply_extensions_to_command(app);
 
     // Parse arguments
     let args = app.get_matches();
+    if let Some(("config-diff", diff_args)) = args.subcommand() {
+        return run_config_diff(diff_args);
+    }
 
     // Extract core arguments
     let output = args.get_one::<String>("output").unwrap().to_string();
@@ -135,18 +373,32 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .get_one::<String>("database")
         .map(std::string::ToString::to_string);
     let log_level = args.get_one::<String>("log-level").unwrap().to_string();
-
-    // Create configuration using builder pattern
-    let mut builder = ConfigBuilder::new()
-        .host(host)
-        .username(username)
-        .log_level(log_level);
-
-    if let Some(database) = database {
-        builder = builder.database(database);
-    }
-
-    let mut config = builder.build();
+    let tls = tls_from_args(&args);
+
+    let mut config = if args.get_flag("from-connection") {
+        let password = connection_password(&args)?;
+        config_from_connection(
+            &host,
+            &username,
+            &password,
+            database.as_deref(),
+            &log_level,
+            tls.as_ref(),
+        )?
+    } else {
+        // Create configuration using builder pattern
+        let mut builder = ConfigBuilder::new()
+            .host(host)
+            .username(username)
+            .log_level(log_level);
+
+        if let Some(database) = database {
+            builder = builder.database(database);
+        }
+
+        builder.build()
+    };
+    config.database.tls = tls;
 
     // Apply extensions to the configuration
     apply_extensions_to_config(&args, &mut config)?;
@@ -187,6 +439,7 @@ mod tests {
     use std::fs;
     use tempfile::NamedTempFile;
     use test_rig::config::{AppConfig, ConfigBuilder};
+    use test_rig::test_support::EnvVarGuard;
 
     #[test]
     fn test_args_parsing() {
@@ -383,6 +636,166 @@ mod tests {
         assert!(content.contains("[test]"));
     }
 
+    #[test]
+    fn test_config_diff_reports_changed_fields() {
+        let dir = tempfile::tempdir().unwrap();
+        let dev = dir.path().join("dev.toml");
+        let prod = dir.path().join("prod.json");
+        fs::write(
+            &dev,
+            "[database]\nhost = \"localhost:4000\"\npassword = \"devpass\"\n[logging]\nlevel = \"debug\"\n",
+        )
+        .unwrap();
+        fs::write(
+            &prod,
+            r#"{"database": {"host": "prod-tidb:4000", "password": "prodpass", "pool_size": 10},
+                "logging": {"level": "debug"}, "test": {"rows": 100}}"#,
+        )
+        .unwrap();
+
+        let matches = Command::new("config-gen")
+            .subcommand(config_diff_command())
+            .get_matches_from([
+                "config-gen",
+                "config-diff",
+                dev.to_str().unwrap(),
+                prod.to_str().unwrap(),
+            ]);
+        let (name, diff_args) = matches.subcommand().unwrap();
+        assert_eq!(name, "config-diff");
+        assert!(run_config_diff(diff_args).is_ok());
+
+        let differences = diff_config_files(&dev, &prod).unwrap();
+        let lines: Vec<String> = differences.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "database.host: \"localhost:4000\" -> \"prod-tidb:4000\"",
+                "database.password: ******** -> ********",
+                "database.pool_size: 5 -> 10",
+                "test.rows: 10 -> 100",
+            ]
+        );
+        assert!(diff_config_files(&dev, &dev).unwrap().is_empty());
+        assert!(diff_config_files(&dev, &dir.path().join("missing.json")).is_err());
+    }
+
+    #[test]
+    fn test_config_from_discovered_values() {
+        let discovered = DiscoveredServer {
+            version: Some("8.0.11-TiDB-v7.5.1".to_string()),
+            databases: [
+                "INFORMATION_SCHEMA",
+                "METRICS_SCHEMA",
+                "mysql",
+                "orders",
+                "test",
+            ]
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+        };
+
+        let config =
+            config_from_discovery("tidb-1:4000", "app", None, "info", &discovered).unwrap();
+        assert_eq!(config.database.host, "tidb-1:4000");
+        assert_eq!(config.database.username, "app");
+        assert_eq!(config.database.database.as_deref(), Some("orders"));
+        assert_eq!(config.database.password, None);
+        assert_eq!(
+            config.database.server_version.as_deref(),
+            Some("8.0.11-TiDB-v7.5.1")
+        );
+        assert!(config.validate().is_ok());
+
+        let config =
+            config_from_discovery("tidb-1:4000", "app", Some("test"), "debug", &discovered)
+                .unwrap();
+        assert_eq!(config.database.database.as_deref(), Some("test"));
+        assert_eq!(config.logging.level, "debug");
+
+        let err = config_from_discovery("tidb-1:4000", "app", Some("nope"), "info", &discovered)
+            .unwrap_err();
+        assert!(err.to_string().contains("Database 'nope' not found"));
+
+        let system_only = DiscoveredServer {
+            version: None,
+            databases: vec!["mysql".to_string(), "information_schema".to_string()],
+        };
+        let config =
+            config_from_discovery("tidb-1:4000", "app", None, "info", &system_only).unwrap();
+        assert_eq!(config.database.database, None);
+        assert_eq!(config.database.server_version, None);
+    }
+
+    fn connection_args(argv: &[&str]) -> clap::ArgMatches {
+        Command::new("test-bin")
+            .arg(clap::Arg::new("password-file").long("password-file"))
+            .arg(clap::Arg::new("tls-ca").long("tls-ca"))
+            .arg(clap::Arg::new("tls-cert").long("tls-cert"))
+            .arg(clap::Arg::new("tls-key").long("tls-key"))
+            .arg(
+                clap::Arg::new("tls-skip-verify")
+                    .long("tls-skip-verify")
+                    .action(clap::ArgAction::SetTrue),
+            )
+            .get_matches_from(std::iter::once("test-bin").chain(argv.iter().copied()))
+    }
+
+    #[test]
+    fn test_tls_from_args() {
+        assert_eq!(tls_from_args(&connection_args(&[])), None);
+        assert_eq!(
+            tls_from_args(&connection_args(&["--tls-ca", "ca.pem"])),
+            Some(TlsConfig {
+                ca: Some(PathBuf::from("ca.pem")),
+                ..TlsConfig::default()
+            })
+        );
+        assert_eq!(
+            tls_from_args(&connection_args(&["--tls-skip-verify"])),
+            Some(TlsConfig {
+                skip_verify: true,
+                ..TlsConfig::default()
+            })
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_connection_password_sources() {
+        let mut env = EnvVarGuard::cleared(&["TIDB_PASSWORD", "DATABASE_URL", "MYSQL_PWD"]);
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("pw");
+        fs::write(&file, "filepw\n").unwrap();
+        let with_file = connection_args(&["--password-file", file.to_str().unwrap()]);
+
+        assert_eq!(connection_password(&connection_args(&[])).unwrap(), "");
+        assert_eq!(connection_password(&with_file).unwrap(), "filepw");
+        assert!(
+            connection_password(&connection_args(&["--password-file", "/nonexistent/pw"])).is_err()
+        );
+        env.set("TIDB_PASSWORD", "envpw");
+        assert_eq!(connection_password(&with_file).unwrap(), "envpw");
+    }
+
+    #[test]
+    fn test_discover_live_server() {
+        let Some(mut conn) = test_rig::connection::connect_from_env().expect("failed to connect")
+        else {
+            eprintln!("TIDB_HOST not set, skipping live config discovery test");
+            return;
+        };
+        let discovered = discover_server(&mut conn).unwrap();
+        assert!(discovered.version.is_some());
+        assert!(
+            discovered
+                .databases
+                .iter()
+                .any(|db| db.eq_ignore_ascii_case("INFORMATION_SCHEMA"))
+        );
+    }
+
     #[test]
     fn test_config_validation() {
         let config = ConfigBuilder::new()
@@ -24,8 +24,13 @@
 //! 1. **Initial** → **`ParsingConfig`** → **Connecting**
 //! 2. **`CreatingTable`**: Create a dedicated test table for isolation testing
 //! 3. **`PopulatingData`**: Insert test rows into the table
-//! 4. **`TestingIsolation`**: Run concurrent transactions to verify isolation
-//! 5. **`VerifyingResults`**: Check and report the results
+//! 4. **`TestingIsolation`**: Commit an update from a second connection while a transaction at
+//!    the chosen isolation level is open, and check whether the transaction sees it. The
+//!    table is also read before, during and after a concurrent update and insert, and a value
+//!    computed from a stale read is written back, to classify the anomalies observed.
+//!    Both connections' ids and reads of row 1 are kept in the test context
+//! 5. **`VerifyingResults`**: Report each anomaly observed and whether the isolation level
+//!    permits it
 //! 6. **Completed**
 //!
 //! ## Features
@@ -45,6 +50,9 @@
 //! # Custom number of test rows
 //! cargo run --bin isolation --features isolation_test -- --test-rows 20
 //!
+//! # Check READ COMMITTED instead of the default REPEATABLE READ
+//! cargo run --bin isolation --features isolation_test -- --isolation-level read-committed
+//!
 //! # With configuration file
 //! cargo run --bin isolation --features isolation_test -- -c config.json
 //! ```
@@ -72,13 +80,21 @@ use clap::Command;
 use clap::Parser;
 use mysql::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
 use std::time::Duration;
 use test_rig::ConfigExtension;
+use test_rig::cleanup::ScratchTable;
+use test_rig::common_states::{ConnParams, register_standard_handlers};
 use test_rig::errors::Result;
+use test_rig::lib_utils::report_error_and_exit;
+use test_rig::report::{ReportFormat, StepRecorder};
+use test_rig::state_machine_dynamic::StateMetadata;
 use test_rig::{
     CommonArgs, ConnectError, DynamicState, DynamicStateContext, DynamicStateHandler,
-    DynamicStateMachine, dynamic_state, print_error_and_exit, print_success, print_test_header,
-    register_transitions,
+    DynamicStateMachine, IsolationLevel, dynamic_state, print_startup_banner, print_success,
+    print_test_header, register_transitions, run_in_transaction,
 };
 use thiserror::Error;
 
@@ -123,6 +139,12 @@ impl ConfigExtension for IsolationConfigExtension {
                 .help("Number of test rows to create for isolation testing")
                 .default_value("10"),
         )
+        .arg(
+            clap::Arg::new("isolation-level")
+                .long("isolation-level")
+                .help("Transaction isolation level to test (e.g. repeatable-read, read-committed)")
+                .default_value("repeatable-read"),
+        )
     }
 
     fn build_config(
@@ -135,6 +157,10 @@ impl ConfigExtension for IsolationConfigExtension {
         {
             config.test.rows = rows;
         }
+        config.test.validate()?;
+        if let Some(level) = args.get_one::<String>("isolation-level") {
+            level.parse::<IsolationLevel>()?;
+        }
         Ok(())
     }
 
@@ -143,7 +169,7 @@ impl ConfigExtension for IsolationConfigExtension {
     }
 
     fn get_help_text(&self) -> &'static str {
-        "Adds --test-rows option for isolation testing"
+        "Adds --test-rows and --isolation-level options for isolation testing"
     }
 }
 
@@ -159,14 +185,38 @@ pub struct IsolationTestArgs {
     #[command(flatten)]
     pub common: CommonArgs,
     /// Number of test rows to create for isolation testing
-    #[arg(long, default_value = "10")]
+    #[arg(long, default_value = "10", value_parser = clap::value_parser!(u32).range(1..))]
     pub test_rows: u32,
+    /// Transaction isolation level to test (e.g. repeatable-read, read-committed)
+    #[arg(long, default_value = "repeatable-read")]
+    pub isolation_level: IsolationLevel,
+    /// Write a machine-readable report of each step to this file
+    #[arg(long, value_name = "PATH")]
+    pub report_file: Option<PathBuf>,
+    /// Format of --report-file
+    #[arg(long, value_enum, default_value = "junit")]
+    pub report_format: ReportFormat,
+    /// Write the transition graph as a Mermaid state diagram to this file and exit
+    #[arg(long, value_name = "PATH")]
+    pub export_mermaid: Option<PathBuf>,
 }
 
 impl IsolationTestArgs {
-    pub fn print_connection_info(&self) {
-        self.common.print_connection_info();
-        println!("  Test Rows: {}", self.test_rows);
+    /// Print the settings this run uses
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration cannot be loaded.
+    pub fn print_startup_banner(&self) -> test_rig::Result<()> {
+        print_startup_banner(
+            &self.common.effective_config()?,
+            &[
+                ("Mode", "isolation test".to_string()),
+                ("Test Rows", self.test_rows.to_string()),
+                ("Isolation Level", self.isolation_level.to_string()),
+            ],
+        );
+        Ok(())
     }
     /// Initialize logging system
     ///
@@ -200,10 +250,36 @@ struct TestRow {
     created_at: String,
 }
 
+/// Row 1 as read by one connection during the isolation check
+#[derive(Debug, Clone, PartialEq)]
+struct ConnectionReads {
+    /// Server-side id from `CONNECTION_ID()`
+    connection_id: u64,
+    /// Values of row 1, in read order
+    values: Vec<i32>,
+}
+
+impl ConnectionReads {
+    fn new(conn: &mut mysql::PooledConn) -> Result<Self> {
+        let connection_id = conn
+            .query_first("SELECT CONNECTION_ID()")?
+            .unwrap_or_default();
+        Ok(Self {
+            connection_id,
+            values: Vec::new(),
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 struct IsolationTestContext {
     test_table_name: String,
     test_results: Vec<String>,
+    anomalies: Vec<IsolationAnomaly>,
+    /// Reads by the connection running the transaction under test
+    reader: Option<ConnectionReads>,
+    /// Reads by the second connection that commits the competing update
+    writer: Option<ConnectionReads>,
     phase: IsolationTestPhase,
 }
 
@@ -220,6 +296,9 @@ impl IsolationTestContext {
         Self {
             test_table_name: format!("isolation_test_{}", chrono::Utc::now().timestamp()),
             test_results: Vec::new(),
+            anomalies: Vec::new(),
+            reader: None,
+            writer: None,
             phase: IsolationTestPhase::Initial,
         }
     }
@@ -235,10 +314,7 @@ mod isolation_states {
     use super::{DynamicState, dynamic_state};
 
     // Re-export common states
-    pub use test_rig::common_states::{
-        completed, connecting, getting_version, parsing_config, testing_connection,
-        verifying_database,
-    };
+    pub use test_rig::common_states::completed;
 
     // Test-specific states
     pub fn creating_table() -> DynamicState {
@@ -255,149 +331,6 @@ mod isolation_states {
     }
 }
 
-// Adapter for InitialHandler to DynamicStateHandler
-struct InitialHandlerAdapter;
-#[async_trait]
-impl DynamicStateHandler for InitialHandlerAdapter {
-    async fn enter(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(dynamic_state!("initial", "Initial"))
-    }
-    async fn execute(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(isolation_states::parsing_config())
-    }
-    async fn exit(&self, _context: &mut DynamicStateContext) -> test_rig::Result<()> {
-        Ok(())
-    }
-}
-
-// Adapter for ParsingConfigHandler to DynamicStateHandler
-struct ParsingConfigHandlerAdapter {
-    host: String,
-    user: String,
-    password: String,
-    database: Option<String>,
-}
-#[async_trait]
-impl DynamicStateHandler for ParsingConfigHandlerAdapter {
-    async fn enter(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(isolation_states::parsing_config())
-    }
-    async fn execute(&self, context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        let (host, port) = test_rig::connection::parse_connection_string(&self.host)?;
-        context.host = host;
-        context.port = port;
-        context.username.clone_from(&self.user);
-        context.password.clone_from(&self.password);
-        context.database.clone_from(&self.database);
-        Ok(isolation_states::connecting())
-    }
-    async fn exit(&self, _context: &mut DynamicStateContext) -> test_rig::Result<()> {
-        Ok(())
-    }
-}
-
-// Adapter for ConnectingHandler to DynamicStateHandler
-struct ConnectingHandlerAdapter;
-#[async_trait]
-impl DynamicStateHandler for ConnectingHandlerAdapter {
-    async fn enter(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(isolation_states::connecting())
-    }
-    async fn execute(&self, context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        let pool = test_rig::connection::create_connection_pool(
-            &context.host,
-            context.port,
-            &context.username,
-            &context.password,
-            context.database.as_deref(),
-        )?;
-        let conn = pool.get_conn()?;
-        context.connection = Some(conn);
-        Ok(isolation_states::testing_connection())
-    }
-    async fn exit(&self, _context: &mut DynamicStateContext) -> test_rig::Result<()> {
-        Ok(())
-    }
-}
-
-// Adapter for TestingConnectionHandler to DynamicStateHandler
-struct TestingConnectionHandlerAdapter;
-#[async_trait]
-impl DynamicStateHandler for TestingConnectionHandlerAdapter {
-    async fn enter(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(isolation_states::testing_connection())
-    }
-    async fn execute(&self, context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        if let Some(ref mut conn) = context.connection {
-            let result: std::result::Result<Vec<mysql::Row>, mysql::Error> =
-                conn.exec("SELECT 1", ());
-            match result {
-                Ok(_) => Ok(isolation_states::verifying_database()),
-                Err(e) => Err(format!("Connection test failed: {e}").into()),
-            }
-        } else {
-            Err("No connection available for testing".into())
-        }
-    }
-    async fn exit(&self, _context: &mut DynamicStateContext) -> test_rig::Result<()> {
-        Ok(())
-    }
-}
-
-// Adapter for VerifyingDatabaseHandler to DynamicStateHandler
-struct VerifyingDatabaseHandlerAdapter;
-#[async_trait]
-impl DynamicStateHandler for VerifyingDatabaseHandlerAdapter {
-    async fn enter(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(isolation_states::verifying_database())
-    }
-    async fn execute(&self, context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        if let Some(ref mut conn) = context.connection {
-            if let Some(ref db_name) = context.database {
-                let query = format!("USE `{db_name}`");
-                match conn.query_drop(query) {
-                    Ok(()) => Ok(isolation_states::getting_version()),
-                    Err(e) => Err(format!("Database verification failed: {e}").into()),
-                }
-            } else {
-                Ok(isolation_states::getting_version())
-            }
-        } else {
-            Err("No connection available for database verification".into())
-        }
-    }
-    async fn exit(&self, _context: &mut DynamicStateContext) -> test_rig::Result<()> {
-        Ok(())
-    }
-}
-
-// Adapter for GettingVersionHandler to DynamicStateHandler
-struct GettingVersionHandlerAdapter;
-#[async_trait]
-impl DynamicStateHandler for GettingVersionHandlerAdapter {
-    async fn enter(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(isolation_states::getting_version())
-    }
-    async fn execute(&self, context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        if let Some(ref mut conn) = context.connection {
-            let version_query = "SELECT VERSION()";
-            match conn.query_first::<String, _>(version_query) {
-                Ok(Some(version)) => {
-                    context.server_version = Some(version.clone());
-                    Ok(isolation_states::creating_table())
-                }
-                Ok(None) => Err("No version returned from server".into()),
-                Err(e) => Err(format!("Failed to get server version: {e}").into()),
-            }
-        } else {
-            Err("No connection available for getting version".into())
-        }
-    }
-    async fn exit(&self, _context: &mut DynamicStateContext) -> test_rig::Result<()> {
-        Ok(())
-    }
-}
-
 /// Handler for creating test table
 pub struct CreatingTableHandler;
 
@@ -430,6 +363,8 @@ impl DynamicStateHandler for CreatingTableHandler {
             match conn.query_drop(&create_table_sql) {
                 Ok(()) => {
                     println!("✓ Test table '{table_name}' created successfully");
+                    let guard = scratch_table_guard(context, table_name);
+                    context.set_custom_data("isolation_scratch_table".to_string(), guard);
                     Ok(isolation_states::populating_data())
                 }
                 Err(e) => {
@@ -449,13 +384,33 @@ impl DynamicStateHandler for CreatingTableHandler {
     }
 }
 
+/// Guard dropping `table_name` on a fresh connection to the same server
+fn scratch_table_guard(
+    context: &DynamicStateContext,
+    table_name: String,
+) -> ScratchTable<mysql::PooledConn> {
+    let (host, port, user, password, database) = (
+        context.host.clone(),
+        context.port,
+        context.username.clone(),
+        context.password.clone(),
+        context.database.clone(),
+    );
+    ScratchTable::new(table_name, move || {
+        test_rig::connection::create_connection(&host, port, &user, &password, database.as_deref())
+    })
+}
+
 /// Handler for populating test data
-pub struct PopulatingDataHandler;
+pub struct PopulatingDataHandler {
+    /// Rows to insert, with ids 1 through `rows`
+    rows: u32,
+}
 
 #[async_trait]
 impl DynamicStateHandler for PopulatingDataHandler {
     async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
-        println!("Populating test table with 10 rows...");
+        println!("Populating test table with {} rows...", self.rows);
         Ok(isolation_states::populating_data())
     }
 
@@ -470,8 +425,7 @@ impl DynamicStateHandler for PopulatingDataHandler {
         };
 
         if let Some(ref mut conn) = context.connection {
-            // Insert 10 test rows
-            for i in 1..=10 {
+            for i in 1..=self.rows {
                 let insert_sql =
                     format!("INSERT INTO {table_name} (id, name, value) VALUES (?, ?, ?)");
                 conn.exec_drop(&insert_sql, (i, format!("row_{i}"), i * 10))?;
@@ -502,13 +456,262 @@ impl DynamicStateHandler for PopulatingDataHandler {
     }
 }
 
+/// Amount the concurrent writer adds to row 1
+const CONCURRENT_DELTA: i32 = 100;
+
+/// Whether a transaction at `level` should see a row committed by another connection
+/// after the transaction's first read
+fn sees_concurrent_commits(level: IsolationLevel) -> bool {
+    matches!(
+        level,
+        IsolationLevel::ReadUncommitted | IsolationLevel::ReadCommitted
+    )
+}
+
+/// Check the values read before and after another connection committed an update
+///
+/// Returns a description of the outcome, as `Err` if it violates `level`.
+fn check_isolation(
+    level: IsolationLevel,
+    before: i32,
+    after: i32,
+) -> std::result::Result<String, String> {
+    let committed = before + CONCURRENT_DELTA;
+    match (sees_concurrent_commits(level), after) {
+        (true, value) if value == committed => Ok(format!(
+            "{level}: concurrent commit visible ({before} -> {after})"
+        )),
+        (false, value) if value == before => Ok(format!(
+            "{level}: snapshot unchanged by concurrent commit ({before})"
+        )),
+        (true, _) => Err(format!(
+            "{level}: expected to read committed value {committed}, read {after}"
+        )),
+        (false, _) => Err(format!(
+            "{level}: snapshot changed from {before} to {after} within the transaction"
+        )),
+    }
+}
+
+/// Anomalies a transaction can observe when another connection writes concurrently
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IsolationAnomaly {
+    /// Read a change the other connection had not committed
+    DirtyRead,
+    /// Read a different value for the same row later in the transaction
+    NonRepeatableRead,
+    /// Saw rows appear or disappear later in the transaction
+    PhantomRead,
+    /// Overwrote a committed change with a value computed from an older read
+    LostUpdate,
+}
+
+impl IsolationAnomaly {
+    const ALL: [IsolationAnomaly; 4] = [
+        IsolationAnomaly::DirtyRead,
+        IsolationAnomaly::NonRepeatableRead,
+        IsolationAnomaly::PhantomRead,
+        IsolationAnomaly::LostUpdate,
+    ];
+
+    /// Whether `level` allows this anomaly, per ANSI SQL for the read anomalies
+    ///
+    /// A read-modify-write without locking loses updates below SERIALIZABLE on
+    /// both `MySQL` and `TiDB` (pessimistic REPEATABLE READ writes over the
+    /// newer committed row instead of failing), so lost updates are only
+    /// forbidden at SERIALIZABLE.
+    fn permitted_at(self, level: IsolationLevel) -> bool {
+        match self {
+            IsolationAnomaly::DirtyRead => level == IsolationLevel::ReadUncommitted,
+            IsolationAnomaly::NonRepeatableRead => sees_concurrent_commits(level),
+            IsolationAnomaly::PhantomRead | IsolationAnomaly::LostUpdate => {
+                level != IsolationLevel::Serializable
+            }
+        }
+    }
+}
+
+impl fmt::Display for IsolationAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            IsolationAnomaly::DirtyRead => "dirty read",
+            IsolationAnomaly::NonRepeatableRead => "non-repeatable read",
+            IsolationAnomaly::PhantomRead => "phantom read",
+            IsolationAnomaly::LostUpdate => "lost update",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Row id to value, as read by the transaction under test
+type Snapshot = BTreeMap<i32, i32>;
+
+/// What the transaction under test read around another connection's writes
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Observation {
+    /// Rows read before the other connection changed anything
+    before: Snapshot,
+    /// Rows read while the other connection's update and insert were uncommitted
+    during: Snapshot,
+    /// Rows read after the other connection committed
+    after: Snapshot,
+    /// Row 1 as first read, and its final value after the transaction wrote back
+    /// that read + 1 over a concurrently committed increment; `None` if the
+    /// server rejected the write
+    lost_update: Option<(i32, i32)>,
+}
+
+impl Observation {
+    /// Anomalies shown by the snapshots, in [`IsolationAnomaly::ALL`] order
+    fn classify(&self) -> Vec<IsolationAnomaly> {
+        let mut anomalies = Vec::new();
+        if self.during != self.before {
+            anomalies.push(IsolationAnomaly::DirtyRead);
+        }
+        if self
+            .before
+            .iter()
+            .any(|(id, value)| self.after.get(id).is_some_and(|after| after != value))
+        {
+            anomalies.push(IsolationAnomaly::NonRepeatableRead);
+        }
+        if !self.before.keys().eq(self.after.keys()) {
+            anomalies.push(IsolationAnomaly::PhantomRead);
+        }
+        if let Some((read, final_value)) = self.lost_update
+            && final_value == read + 1
+        {
+            anomalies.push(IsolationAnomaly::LostUpdate);
+        }
+        anomalies
+    }
+}
+
+/// One result line per anomaly: whether it was observed and whether `level` permits it
+fn describe_anomalies(level: IsolationLevel, observed: &[IsolationAnomaly]) -> Vec<String> {
+    IsolationAnomaly::ALL
+        .iter()
+        .map(|anomaly| {
+            let permitted = anomaly.permitted_at(level);
+            match (observed.contains(anomaly), permitted) {
+                (false, _) => format!("✓ {anomaly}: not observed"),
+                (true, true) => format!("✓ {anomaly}: observed, permitted at {level}"),
+                (true, false) => format!("⚠️  {anomaly}: observed, not permitted at {level}"),
+            }
+        })
+        .collect()
+}
+
+/// MySQL deadlock and `TiDB` write conflict errors, raised when the server refuses a lost update
+fn is_write_conflict(error: &ConnectError) -> bool {
+    matches!(error, ConnectError::Connection(mysql::Error::MySqlError(e)) if e.code == 1213 || e.code == 9007)
+}
+
+fn read_snapshot(conn: &mut mysql::PooledConn, table_name: &str) -> Result<Snapshot> {
+    let rows: Vec<(i32, i32)> = conn.query(format!("SELECT id, value FROM {table_name}"))?;
+    Ok(rows.into_iter().collect())
+}
+
+/// Read the table in a transaction at `level` while `writer` updates row 1 and
+/// inserts a row, then check for a lost update in a second transaction
+fn observe_concurrent_writes(
+    conn: &mut mysql::PooledConn,
+    writer: &mut mysql::PooledConn,
+    table_name: &str,
+    level: IsolationLevel,
+) -> Result<Observation> {
+    let update_sql =
+        format!("UPDATE {table_name} SET value = value + {CONCURRENT_DELTA} WHERE id = 1");
+    let next_id: i32 = writer
+        .query_first(format!("SELECT COALESCE(MAX(id), 0) + 1 FROM {table_name}"))?
+        .unwrap_or(1);
+
+    let (before, during, after) = run_in_transaction(conn, level, |conn| {
+        let before = read_snapshot(conn, table_name)?;
+        let mut write = writer.start_transaction(mysql::TxOpts::default())?;
+        write.query_drop(&update_sql)?;
+        write.exec_drop(
+            format!("INSERT INTO {table_name} (id, name, value) VALUES (?, ?, ?)"),
+            (next_id, format!("row_{next_id}"), next_id * 10),
+        )?;
+        let during = read_snapshot(conn, table_name)?;
+        write.commit()?;
+        let after = read_snapshot(conn, table_name)?;
+        Ok((before, during, after))
+    })?;
+
+    let select_sql = format!("SELECT value FROM {table_name} WHERE id = 1");
+    let written = run_in_transaction(conn, level, |conn| {
+        let read: i32 = conn
+            .query_first(&select_sql)?
+            .ok_or_else(|| ConnectError::Validation(format!("Row 1 missing from {table_name}")))?;
+        writer.query_drop(&update_sql)?;
+        conn.exec_drop(
+            format!("UPDATE {table_name} SET value = ? WHERE id = 1"),
+            (read + 1,),
+        )?;
+        Ok(read)
+    });
+    let lost_update = match written {
+        Ok(read) => Some((read, writer.query_first(&select_sql)?.unwrap_or_default())),
+        Err(e) if is_write_conflict(&e) => None,
+        Err(e) => return Err(e),
+    };
+
+    Ok(Observation {
+        before,
+        during,
+        after,
+        lost_update,
+    })
+}
+
+/// Compare the reader's values of row 1 before and after the writer's commit
+/// and store both connections' reads in `ctx`
+fn record_isolation_check(
+    ctx: &mut IsolationTestContext,
+    level: IsolationLevel,
+    reader: ConnectionReads,
+    writer: ConnectionReads,
+) {
+    if reader.connection_id == writer.connection_id {
+        ctx.add_result(&format!(
+            "⚠️  Reader and writer share connection {}; the check is not concurrent",
+            reader.connection_id
+        ));
+    } else {
+        ctx.add_result(&format!(
+            "✓ Reader connection {} and writer connection {} are distinct",
+            reader.connection_id, writer.connection_id
+        ));
+    }
+    if let [before, after] = reader.values[..] {
+        ctx.add_result(&format!("✓ Read row 1 under {level}: {before}"));
+        ctx.add_result(&format!(
+            "✓ Second connection committed value + {CONCURRENT_DELTA}, read back {:?}",
+            writer.values
+        ));
+        match check_isolation(level, before, after) {
+            Ok(outcome) => ctx.add_result(&format!("✓ {outcome}")),
+            Err(violation) => ctx.add_result(&format!("⚠️  {violation}")),
+        }
+    }
+    ctx.reader = Some(reader);
+    ctx.writer = Some(writer);
+}
+
 /// Handler for testing isolation
-pub struct TestingIsolationHandler;
+pub struct TestingIsolationHandler {
+    pub isolation: IsolationLevel,
+}
 
 #[async_trait]
 impl DynamicStateHandler for TestingIsolationHandler {
     async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
-        println!("Testing transaction isolation with concurrent operations...");
+        println!(
+            "Testing {} isolation with a concurrent writer...",
+            self.isolation
+        );
         Ok(isolation_states::testing_isolation())
     }
 
@@ -522,40 +725,38 @@ impl DynamicStateHandler for TestingIsolationHandler {
             return Err("Isolation test context not found".into());
         };
 
-        if let Some(ref mut conn) = context.connection {
-            // Start a transaction
-            conn.query_drop("START TRANSACTION")?;
-
-            // Read initial state
-            let initial_query = format!("SELECT * FROM {table_name} ORDER BY id LIMIT 5");
-            let initial_rows: Vec<TestRow> = conn.exec(&initial_query, ())?;
+        // Second connection that commits an update while the transaction is open
+        let mut writer =
+            test_rig::connection::create_connection_with_config(&context.database_config())?;
 
-            // Simulate concurrent modification (in a real scenario, this would be in another connection)
-            // For this test, we'll just update a row
-            let update_sql = format!("UPDATE {table_name} SET value = value + 100 WHERE id = 1");
-            conn.query_drop(&update_sql)?;
-
-            // Read again to test isolation
-            let final_query = format!("SELECT * FROM {table_name} ORDER BY id LIMIT 5");
-            let final_rows: Vec<TestRow> = conn.exec(&final_query, ())?;
-
-            // Commit the transaction
-            conn.query_drop("COMMIT")?;
+        if let Some(ref mut conn) = context.connection {
+            let select_sql = format!("SELECT * FROM {table_name} WHERE id = 1");
+            let update_sql =
+                format!("UPDATE {table_name} SET value = value + {CONCURRENT_DELTA} WHERE id = 1");
+            let read_value = |conn: &mut mysql::PooledConn| -> Result<i32> {
+                let row: Option<TestRow> = conn.exec_first(&select_sql, ())?;
+                row.map(|row| row.value).ok_or_else(|| {
+                    ConnectError::Validation(format!("Row 1 missing from {table_name}"))
+                })
+            };
+            let mut reader_reads = ConnectionReads::new(conn)?;
+            let mut writer_reads = ConnectionReads::new(&mut writer)?;
+            run_in_transaction(conn, self.isolation, |conn| {
+                reader_reads.values.push(read_value(conn)?);
+                writer.query_drop(&update_sql)?;
+                writer_reads.values.push(read_value(&mut writer)?);
+                reader_reads.values.push(read_value(conn)?);
+                Ok(())
+            })?;
+            let observation =
+                observe_concurrent_writes(conn, &mut writer, &table_name, self.isolation)?;
 
             // Update test context after database operations
             if let Some(ctx) =
                 context.get_custom_data_mut::<IsolationTestContext>("isolation_test_context")
             {
-                ctx.add_result(&format!("✓ Initial read: {} rows", initial_rows.len()));
-                ctx.add_result("✓ Updated row with id = 1");
-                ctx.add_result(&format!("✓ Final read: {} rows", final_rows.len()));
-
-                // Check for isolation violations
-                if initial_rows.len() == final_rows.len() {
-                    ctx.add_result("✓ Transaction isolation maintained");
-                } else {
-                    ctx.add_result("⚠️  Potential isolation violation detected");
-                }
+                record_isolation_check(ctx, self.isolation, reader_reads, writer_reads);
+                ctx.anomalies = observation.classify();
                 ctx.phase = IsolationTestPhase::TestingIsolation;
             }
 
@@ -573,7 +774,9 @@ impl DynamicStateHandler for TestingIsolationHandler {
 }
 
 /// Handler for verifying results
-pub struct VerifyingResultsHandler;
+pub struct VerifyingResultsHandler {
+    pub isolation: IsolationLevel,
+}
 
 #[async_trait]
 impl DynamicStateHandler for VerifyingResultsHandler {
@@ -590,6 +793,11 @@ impl DynamicStateHandler for VerifyingResultsHandler {
             return Err("Isolation test context not found".into());
         };
 
+        println!("\n=== Isolation Anomalies ({}) ===", self.isolation);
+        for line in describe_anomalies(self.isolation, &test_context.anomalies) {
+            test_context.add_result(&line);
+        }
+
         // Print all results
         println!("\n=== Isolation Test Results ===");
         for result in &test_context.test_results {
@@ -625,51 +833,30 @@ async fn main() -> test_rig::errors::Result<()> {
     // Register configuration extensions
     register_extensions();
 
-    print_test_header("TiDB Repeatable Read Isolation Test");
+    print_test_header("TiDB Transaction Isolation Test");
     // Parse command line arguments using the specific args type
     let args = IsolationTestArgs::parse();
     args.init_logging()?;
-    args.print_connection_info();
+    args.common.init_retry_classification()?;
+    args.print_startup_banner()?;
     let (host, user, password, _database) = args.get_connection_info()?;
     let database = args.get_database().unwrap_or_else(|| "test".to_string());
 
     // Create and configure the dynamic state machine
     let mut machine = DynamicStateMachine::new();
 
-    // Register handlers manually to include custom version handler
-    register_isolation_handlers(&mut machine, host, user, password, Some(database));
+    // Register the standard connection flow and the isolation test states
+    register_isolation_handlers(
+        &mut machine,
+        host,
+        user,
+        password,
+        Some(database),
+        args.isolation_level,
+        args.test_rows,
+    );
 
     // Register valid transitions
-    register_transitions!(
-        machine,
-        dynamic_state!("initial", "Initial"),
-        [isolation_states::parsing_config()]
-    );
-    register_transitions!(
-        machine,
-        isolation_states::parsing_config(),
-        [isolation_states::connecting()]
-    );
-    register_transitions!(
-        machine,
-        isolation_states::connecting(),
-        [isolation_states::testing_connection()]
-    );
-    register_transitions!(
-        machine,
-        isolation_states::testing_connection(),
-        [isolation_states::verifying_database()]
-    );
-    register_transitions!(
-        machine,
-        isolation_states::verifying_database(),
-        [isolation_states::getting_version()]
-    );
-    register_transitions!(
-        machine,
-        isolation_states::getting_version(),
-        [isolation_states::creating_table()]
-    );
     register_transitions!(
         machine,
         isolation_states::creating_table(),
@@ -691,13 +878,41 @@ async fn main() -> test_rig::errors::Result<()> {
         [isolation_states::completed()]
     );
 
-    // Run the state machine
-    match machine.run().await {
+    if let Some(path) = &args.export_mermaid {
+        machine.export_mermaid(path)?;
+        println!("Wrote transition graph to {}", path.display());
+        return Ok(());
+    }
+
+    args.common
+        .context_settings()?
+        .configure_dynamic(&mut machine);
+
+    // Run the state machine, recording each state as a report step
+    let recorder = StepRecorder::new("isolation");
+    machine.add_observer(Box::new(recorder.clone()));
+    let result = machine.run().await;
+    if let Some(path) = &args.report_file {
+        let report = recorder.finish(&result, &machine);
+        if let Err(e) = report.write_to_file(path, args.report_format) {
+            eprintln!("Failed to write report to {}: {e}", path.display());
+        }
+    }
+
+    match result {
         Ok(()) => {
             print_success("Isolation test completed successfully!");
         }
         Err(e) => {
-            print_error_and_exit("Isolation test failed", &e);
+            let state = machine.get_current_state().name().to_string();
+            // Exiting skips destructors, so drop the scratch table first
+            drop(machine);
+            report_error_and_exit(
+                "Isolation test failed",
+                &e,
+                args.common.error_format,
+                Some(&state),
+            );
         }
     }
     Ok(())
@@ -710,36 +925,14 @@ fn register_isolation_handlers(
     user: String,
     password: String,
     database: Option<String>,
+    isolation: IsolationLevel,
+    rows: u32,
 ) {
-    // Register standard connection handlers
-    state_machine.register_handler(
-        dynamic_state!("initial", "Initial"),
-        Box::new(InitialHandlerAdapter),
-    );
-    state_machine.register_handler(
-        isolation_states::parsing_config(),
-        Box::new(ParsingConfigHandlerAdapter {
-            host,
-            user,
-            password,
-            database,
-        }),
-    );
-    state_machine.register_handler(
-        isolation_states::connecting(),
-        Box::new(ConnectingHandlerAdapter),
-    );
-    state_machine.register_handler(
-        isolation_states::testing_connection(),
-        Box::new(TestingConnectionHandlerAdapter),
-    );
-    state_machine.register_handler(
-        isolation_states::verifying_database(),
-        Box::new(VerifyingDatabaseHandlerAdapter),
-    );
-    state_machine.register_handler(
-        isolation_states::getting_version(),
-        Box::new(GettingVersionHandlerAdapter),
+    // Register standard connection handlers, handing off to table creation
+    register_standard_handlers(
+        state_machine,
+        ConnParams::new(host, user, password, database),
+        isolation_states::creating_table(),
     );
 
     // Register isolation test handlers
@@ -749,16 +942,34 @@ fn register_isolation_handlers(
     );
     state_machine.register_handler(
         isolation_states::populating_data(),
-        Box::new(PopulatingDataHandler),
+        Box::new(PopulatingDataHandler { rows }),
     );
     state_machine.register_handler(
         isolation_states::testing_isolation(),
-        Box::new(TestingIsolationHandler),
+        Box::new(TestingIsolationHandler { isolation }),
     );
     state_machine.register_handler(
         isolation_states::verifying_results(),
-        Box::new(VerifyingResultsHandler),
+        Box::new(VerifyingResultsHandler { isolation }),
     );
+
+    // A failure while testing or verifying invalidates the run; setup
+    // failures point at the environment instead
+    for (state, category, critical) in [
+        (isolation_states::creating_table(), "setup", false),
+        (isolation_states::populating_data(), "setup", false),
+        (isolation_states::testing_isolation(), "workload", true),
+        (isolation_states::verifying_results(), "verification", true),
+    ] {
+        state_machine.register_state_metadata(
+            state,
+            StateMetadata {
+                category: Some(category.to_string()),
+                critical,
+                ..StateMetadata::default()
+            },
+        );
+    }
 }
 
 #[cfg(test)]
@@ -769,6 +980,31 @@ mod tests {
     use tempfile::NamedTempFile;
     use test_rig::config::{AppConfig, ConfigBuilder, TestConfig};
 
+    #[test]
+    fn test_isolation_states_registered_as_critical() {
+        let mut machine = DynamicStateMachine::new();
+        register_isolation_handlers(
+            &mut machine,
+            "localhost:4000".to_string(),
+            "root".to_string(),
+            String::new(),
+            None,
+            IsolationLevel::RepeatableRead,
+            10,
+        );
+        let verifying = machine
+            .state_metadata(&isolation_states::verifying_results())
+            .unwrap();
+        assert!(verifying.critical);
+        assert_eq!(verifying.category.as_deref(), Some("verification"));
+        assert!(
+            !machine
+                .state_metadata(&isolation_states::creating_table())
+                .unwrap()
+                .critical
+        );
+    }
+
     #[test]
     fn test_isolation_test_context() {
         let context = IsolationTestContext::new();
@@ -802,16 +1038,233 @@ mod tests {
             "testuser",
         ]);
         assert_eq!(args.test_rows, 20);
-        assert_eq!(args.common.host, "localhost:4000");
-        assert_eq!(args.common.user, "testuser");
+        assert_eq!(args.common.host.as_deref(), Some("localhost:4000"));
+        assert_eq!(args.common.user.as_deref(), Some("testuser"));
+    }
+
+    #[test]
+    fn test_isolation_level_arg_parsing() {
+        for (arg, level) in [
+            ("read-uncommitted", IsolationLevel::ReadUncommitted),
+            ("read-committed", IsolationLevel::ReadCommitted),
+            ("repeatable-read", IsolationLevel::RepeatableRead),
+            ("serializable", IsolationLevel::Serializable),
+        ] {
+            let args = IsolationTestArgs::parse_from(["test-bin", "--isolation-level", arg]);
+            assert_eq!(args.isolation_level, level);
+        }
+        assert!(
+            IsolationTestArgs::try_parse_from(["test-bin", "--isolation-level", "snapshot"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_check_isolation_expectations() {
+        assert!(check_isolation(IsolationLevel::RepeatableRead, 10, 10).is_ok());
+        assert!(check_isolation(IsolationLevel::RepeatableRead, 10, 110).is_err());
+        assert!(check_isolation(IsolationLevel::Serializable, 10, 10).is_ok());
+        assert!(check_isolation(IsolationLevel::ReadCommitted, 10, 110).is_ok());
+        assert!(check_isolation(IsolationLevel::ReadCommitted, 10, 10).is_err());
+        assert!(check_isolation(IsolationLevel::ReadUncommitted, 10, 110).is_ok());
+    }
+
+    fn snapshot(rows: &[(i32, i32)]) -> Snapshot {
+        rows.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_classify_anomalies_from_snapshots() {
+        let before = snapshot(&[(1, 10), (2, 20)]);
+        let isolated = Observation {
+            before: before.clone(),
+            during: before.clone(),
+            after: before.clone(),
+            lost_update: None,
+        };
+        assert!(isolated.classify().is_empty());
+
+        // Uncommitted update and insert visible, then the committed ones
+        let read_uncommitted = Observation {
+            during: snapshot(&[(1, 110), (2, 20), (3, 30)]),
+            after: snapshot(&[(1, 110), (2, 20), (3, 30)]),
+            ..isolated.clone()
+        };
+        assert_eq!(
+            read_uncommitted.classify(),
+            vec![
+                IsolationAnomaly::DirtyRead,
+                IsolationAnomaly::NonRepeatableRead,
+                IsolationAnomaly::PhantomRead,
+            ]
+        );
+
+        // Committed update seen but the insert is not
+        let non_repeatable = Observation {
+            after: snapshot(&[(1, 110), (2, 20)]),
+            ..isolated.clone()
+        };
+        assert_eq!(
+            non_repeatable.classify(),
+            vec![IsolationAnomaly::NonRepeatableRead]
+        );
+
+        let phantom = Observation {
+            after: snapshot(&[(1, 10), (2, 20), (3, 30)]),
+            ..isolated.clone()
+        };
+        assert_eq!(phantom.classify(), vec![IsolationAnomaly::PhantomRead]);
+
+        // The write-back of 10 + 1 replaced the concurrent 10 + 100
+        let lost = Observation {
+            lost_update: Some((10, 11)),
+            ..isolated.clone()
+        };
+        assert_eq!(lost.classify(), vec![IsolationAnomaly::LostUpdate]);
+        let kept = Observation {
+            lost_update: Some((10, 111)),
+            ..isolated
+        };
+        assert!(kept.classify().is_empty());
+    }
+
+    #[test]
+    fn test_anomalies_permitted_by_level() {
+        use IsolationAnomaly::*;
+        assert!(DirtyRead.permitted_at(IsolationLevel::ReadUncommitted));
+        assert!(!DirtyRead.permitted_at(IsolationLevel::ReadCommitted));
+        assert!(NonRepeatableRead.permitted_at(IsolationLevel::ReadCommitted));
+        assert!(!NonRepeatableRead.permitted_at(IsolationLevel::RepeatableRead));
+        assert!(LostUpdate.permitted_at(IsolationLevel::RepeatableRead));
+        assert!(!LostUpdate.permitted_at(IsolationLevel::Serializable));
+        assert!(PhantomRead.permitted_at(IsolationLevel::RepeatableRead));
+        assert!(!PhantomRead.permitted_at(IsolationLevel::Serializable));
+
+        let lines = describe_anomalies(
+            IsolationLevel::RepeatableRead,
+            &[NonRepeatableRead, LostUpdate],
+        );
+        assert_eq!(
+            lines,
+            vec![
+                "✓ dirty read: not observed",
+                "⚠️  non-repeatable read: observed, not permitted at REPEATABLE READ",
+                "✓ phantom read: not observed",
+                "✓ lost update: observed, permitted at REPEATABLE READ",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_isolation_check_uses_two_connections() {
+        let Some(params) = test_rig::env_params::EnvParams::tidb() else {
+            eprintln!("TIDB_HOST not set, skipping live isolation check");
+            return;
+        };
+        let (host, port) = test_rig::connection::parse_host_port(&params.host).unwrap();
+        let user = params.user.unwrap_or_else(|| "root".to_string());
+        let password = params.password.unwrap_or_default();
+        let database = params.database.unwrap_or_else(|| "test".to_string());
+        test_rig::connection::create_connection(&host, port, &user, &password, None)
+            .unwrap()
+            .query_drop(format!("CREATE DATABASE IF NOT EXISTS `{database}`"))
+            .unwrap();
+
+        let mut context = DynamicStateContext::new();
+        context.connection = Some(
+            test_rig::connection::create_connection(&host, port, &user, &password, Some(&database))
+                .unwrap(),
+        );
+        (context.host, context.port) = (host, port);
+        (context.username, context.password) = (user, password);
+        context.database = Some(database);
+
+        let isolation = IsolationLevel::RepeatableRead;
+        CreatingTableHandler.execute(&mut context).await.unwrap();
+        PopulatingDataHandler { rows: 10 }
+            .execute(&mut context)
+            .await
+            .unwrap();
+        TestingIsolationHandler { isolation }
+            .execute(&mut context)
+            .await
+            .unwrap();
+
+        let ctx = context
+            .get_custom_data::<IsolationTestContext>("isolation_test_context")
+            .unwrap();
+        let reader = ctx.reader.as_ref().unwrap();
+        let writer = ctx.writer.as_ref().unwrap();
+        assert_ne!(reader.connection_id, writer.connection_id);
+        assert_eq!(reader.values.len(), 2);
+        assert_eq!(writer.values, vec![reader.values[0] + CONCURRENT_DELTA]);
+        // Either verdict is concrete: the snapshot held or the anomaly is named
+        assert!(
+            ctx.test_results
+                .iter()
+                .any(|r| r.contains(&format!("{isolation}: ")))
+        );
+        assert_eq!(
+            describe_anomalies(isolation, &ctx.anomalies).len(),
+            IsolationAnomaly::ALL.len()
+        );
+    }
+
+    #[test]
+    fn test_config_extension_adds_isolation_level() {
+        let app = IsolationConfigExtension.add_cli_args(Command::new("config-gen"));
+        assert!(
+            app.get_arguments()
+                .any(|arg| arg.get_id() == "isolation-level")
+        );
+
+        let matches = app
+            .clone()
+            .try_get_matches_from(["config-gen", "--isolation-level", "read-committed"])
+            .unwrap();
+        let mut config = AppConfig::default();
+        assert!(
+            IsolationConfigExtension
+                .build_config(&matches, &mut config)
+                .is_ok()
+        );
+
+        let matches = app
+            .clone()
+            .try_get_matches_from(["config-gen", "--isolation-level", "snapshot"])
+            .unwrap();
+        assert!(
+            IsolationConfigExtension
+                .build_config(&matches, &mut config)
+                .is_err()
+        );
+
+        let matches = app
+            .try_get_matches_from(["config-gen", "--test-rows", "0"])
+            .unwrap();
+        let err = IsolationConfigExtension
+            .build_config(&matches, &mut config)
+            .unwrap_err();
+        assert!(err.to_string().contains("test.rows must be greater than 0"));
     }
 
     #[test]
     fn test_isolation_test_args_defaults() {
         let args = IsolationTestArgs::parse_from(["test-bin"]);
         assert_eq!(args.test_rows, 10); // default value
-        assert_eq!(args.common.host, "localhost:4000"); // default value
-        assert_eq!(args.common.user, "root"); // default value
+        assert_eq!(args.isolation_level, IsolationLevel::RepeatableRead);
+        assert!(IsolationTestArgs::try_parse_from(["test-bin", "--test-rows", "0"]).is_err());
+        assert_eq!(args.common.host, None); // default value
+        assert_eq!(args.common.user, None); // default value
+        assert_eq!(args.report_file, None);
+        assert_eq!(args.report_format, ReportFormat::Junit);
+
+        let args = IsolationTestArgs::parse_from(["test-bin", "--report-file", "isolation.xml"]);
+        assert_eq!(args.report_file, Some(PathBuf::from("isolation.xml")));
+        assert_eq!(args.export_mermaid, None);
+
+        let args = IsolationTestArgs::parse_from(["test-bin", "--export-mermaid", "graph.mmd"]);
+        assert_eq!(args.export_mermaid, Some(PathBuf::from("graph.mmd")));
     }
 
     #[test]
@@ -830,8 +1283,8 @@ mod tests {
 
         // Test that all fields are properly set
         assert_eq!(args.test_rows, 5);
-        assert_eq!(args.common.host, "testhost:4000");
-        assert_eq!(args.common.user, "testuser");
+        assert_eq!(args.common.host.as_deref(), Some("testhost:4000"));
+        assert_eq!(args.common.user.as_deref(), Some("testuser"));
         assert_eq!(args.common.database, Some("testdb".to_string()));
 
         // Test the helper methods
@@ -1,48 +1,23 @@
-#![allow(non_snake_case)]
 use async_trait::async_trait;
-use chrono::{NaiveDateTime, Utc};
+use chrono::Utc;
 use clap::Parser;
-use mysql::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::path::PathBuf;
 use std::time::Duration;
+use test_rig::common_states::{ConnParams, register_standard_handlers};
+use test_rig::config::unsupported_extension;
+use test_rig::config_reload::{Reloadable, SharedConfig};
 use test_rig::errors::{ConnectError, Result};
+use test_rig::import_jobs::{ImportJob, all_terminal, show_import_job, show_import_jobs};
+use test_rig::lib_utils::report_error_and_exit;
+use test_rig::report::{ReportFormat, StepRecorder};
 use test_rig::{
     CommonArgs, DynamicState, DynamicStateContext, DynamicStateHandler, DynamicStateMachine,
-    dynamic_state, print_error_and_exit, print_success, print_test_header, register_transitions,
+    dynamic_state, print_startup_banner, print_success, print_test_header, register_transitions,
 };
 use tokio::time::sleep;
 
-// Import job types needed for the binary
-#[derive(Debug, Clone, FromRow)]
-pub struct ImportJob {
-    #[allow(non_snake_case)]
-    pub Job_ID: i32,
-    #[allow(non_snake_case)]
-    pub Data_Source: String,
-    #[allow(non_snake_case)]
-    pub Target_Table: String,
-    #[allow(non_snake_case)]
-    pub Table_ID: i32,
-    #[allow(non_snake_case)]
-    pub Phase: String,
-    #[allow(non_snake_case)]
-    pub Status: String,
-    #[allow(non_snake_case)]
-    pub Source_File_Size: String,
-    #[allow(non_snake_case)]
-    pub Imported_Rows: Option<i64>,
-    #[allow(non_snake_case)]
-    pub Result_Message: String,
-    #[allow(non_snake_case)]
-    pub Create_Time: Option<NaiveDateTime>,
-    #[allow(non_snake_case)]
-    pub Start_Time: Option<NaiveDateTime>,
-    #[allow(non_snake_case)]
-    pub End_Time: Option<NaiveDateTime>,
-    #[allow(non_snake_case)]
-    pub Created_By: String,
-}
-
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ImportJobInfo {
     pub job_id: String,
@@ -75,10 +50,7 @@ mod job_monitor_states {
     use super::{DynamicState, dynamic_state};
 
     // Re-export common states
-    pub use test_rig::common_states::{
-        completed, connecting, getting_version, parsing_config, testing_connection,
-        verifying_database,
-    };
+    pub use test_rig::common_states::completed;
 
     // Test-specific states
     pub fn checking_import_jobs() -> DynamicState {
@@ -89,149 +61,6 @@ mod job_monitor_states {
     }
 }
 
-// Adapter for InitialHandler to DynamicStateHandler
-struct InitialHandlerAdapter;
-#[async_trait]
-impl DynamicStateHandler for InitialHandlerAdapter {
-    async fn enter(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(dynamic_state!("initial", "Initial"))
-    }
-    async fn execute(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(job_monitor_states::parsing_config())
-    }
-    async fn exit(&self, _context: &mut DynamicStateContext) -> test_rig::Result<()> {
-        Ok(())
-    }
-}
-
-// Adapter for ParsingConfigHandler to DynamicStateHandler
-struct ParsingConfigHandlerAdapter {
-    host: String,
-    user: String,
-    password: String,
-    database: Option<String>,
-}
-#[async_trait]
-impl DynamicStateHandler for ParsingConfigHandlerAdapter {
-    async fn enter(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(job_monitor_states::parsing_config())
-    }
-    async fn execute(&self, context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        let (host, port) = test_rig::connection::parse_connection_string(&self.host)?;
-        context.host = host;
-        context.port = port;
-        context.username.clone_from(&self.user);
-        context.password.clone_from(&self.password);
-        context.database.clone_from(&self.database);
-        Ok(job_monitor_states::connecting())
-    }
-    async fn exit(&self, _context: &mut DynamicStateContext) -> test_rig::Result<()> {
-        Ok(())
-    }
-}
-
-// Adapter for ConnectingHandler to DynamicStateHandler
-struct ConnectingHandlerAdapter;
-#[async_trait]
-impl DynamicStateHandler for ConnectingHandlerAdapter {
-    async fn enter(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(job_monitor_states::connecting())
-    }
-    async fn execute(&self, context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        let pool = test_rig::connection::create_connection_pool(
-            &context.host,
-            context.port,
-            &context.username,
-            &context.password,
-            context.database.as_deref(),
-        )?;
-        let conn = pool.get_conn()?;
-        context.connection = Some(conn);
-        Ok(job_monitor_states::testing_connection())
-    }
-    async fn exit(&self, _context: &mut DynamicStateContext) -> test_rig::Result<()> {
-        Ok(())
-    }
-}
-
-// Adapter for TestingConnectionHandler to DynamicStateHandler
-struct TestingConnectionHandlerAdapter;
-#[async_trait]
-impl DynamicStateHandler for TestingConnectionHandlerAdapter {
-    async fn enter(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(job_monitor_states::testing_connection())
-    }
-    async fn execute(&self, context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        if let Some(ref mut conn) = context.connection {
-            let result: std::result::Result<Vec<mysql::Row>, mysql::Error> =
-                conn.exec("SELECT 1", ());
-            match result {
-                Ok(_) => Ok(job_monitor_states::verifying_database()),
-                Err(e) => Err(format!("Connection test failed: {e}").into()),
-            }
-        } else {
-            Err("No connection available for testing".into())
-        }
-    }
-    async fn exit(&self, _context: &mut DynamicStateContext) -> test_rig::Result<()> {
-        Ok(())
-    }
-}
-
-// Adapter for VerifyingDatabaseHandler to DynamicStateHandler
-struct VerifyingDatabaseHandlerAdapter;
-#[async_trait]
-impl DynamicStateHandler for VerifyingDatabaseHandlerAdapter {
-    async fn enter(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(job_monitor_states::verifying_database())
-    }
-    async fn execute(&self, context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        if let Some(ref mut conn) = context.connection {
-            if let Some(ref db_name) = context.database {
-                let query = format!("USE `{db_name}`");
-                match conn.query_drop(query) {
-                    Ok(()) => Ok(job_monitor_states::getting_version()),
-                    Err(e) => Err(format!("Database verification failed: {e}").into()),
-                }
-            } else {
-                Ok(job_monitor_states::getting_version())
-            }
-        } else {
-            Err("No connection available for database verification".into())
-        }
-    }
-    async fn exit(&self, _context: &mut DynamicStateContext) -> test_rig::Result<()> {
-        Ok(())
-    }
-}
-
-// Adapter for GettingVersionHandler to DynamicStateHandler
-struct GettingVersionHandlerAdapter;
-#[async_trait]
-impl DynamicStateHandler for GettingVersionHandlerAdapter {
-    async fn enter(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(job_monitor_states::getting_version())
-    }
-    async fn execute(&self, context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        if let Some(ref mut conn) = context.connection {
-            let version_query = "SELECT VERSION()";
-            match conn.query_first::<String, _>(version_query) {
-                Ok(Some(version)) => {
-                    context.server_version = Some(version.clone());
-                    Ok(job_monitor_states::checking_import_jobs())
-                }
-                Ok(None) => Err("No version returned from server".into()),
-                Err(e) => Err(format!("Failed to get server version: {e}").into()),
-            }
-        } else {
-            Err("No connection available for getting version".into())
-        }
-    }
-    async fn exit(&self, _context: &mut DynamicStateContext) -> test_rig::Result<()> {
-        Ok(())
-    }
-}
-
 /// Handler for checking import jobs
 pub struct CheckingImportJobsHandler;
 
@@ -244,17 +73,11 @@ impl DynamicStateHandler for CheckingImportJobsHandler {
 
     async fn execute(&self, context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
         if let Some(ref mut conn) = context.connection {
-            // Execute SHOW IMPORT JOBS
-            let query = "SHOW IMPORT JOBS";
-            let results: Vec<ImportJob> = conn.exec(query, ())?;
-
-            // Extract job IDs where End_Time is NULL
-            let mut active_jobs = Vec::new();
-            for job in results {
-                if job.End_Time.is_none() {
-                    active_jobs.push(job.Job_ID.to_string());
-                }
-            }
+            let active_jobs: Vec<String> = show_import_jobs(conn)?
+                .iter()
+                .filter(|job| job.is_active())
+                .map(|job| job.job_id.to_string())
+                .collect();
 
             // Store active jobs in context for next state
             context.set_custom_data("active_import_jobs".to_string(), active_jobs.clone());
@@ -279,15 +102,118 @@ impl DynamicStateHandler for CheckingImportJobsHandler {
     }
 }
 
+/// Print one status line for `job`
+fn print_job_status(job: &ImportJob) {
+    let format_time = |time: Option<chrono::NaiveDateTime>| {
+        time.map_or_else(
+            || "N/A".to_string(),
+            |t| t.format("%Y-%m-%d %H:%M:%S").to_string(),
+        )
+    };
+    if !job.is_active() {
+        println!(
+            "Job_ID: {} | Status: {} | End_Time: {}",
+            job.job_id,
+            job.status,
+            format_time(job.end_time)
+        );
+    } else {
+        // Calculate time elapsed using UTC for consistency
+        let now = Utc::now().naive_utc();
+        let elapsed = now - job.start_time.unwrap_or(now);
+        let elapsed_h = elapsed.num_seconds() / 3600;
+        let elapsed_m = (elapsed.num_seconds() % 3600) / 60;
+        let elapsed_s = elapsed.num_seconds() % 60;
+        let progress = job.progress_ratio().map_or_else(
+            || "N/A".to_string(),
+            |ratio| format!("{:.1}%", ratio * 100.0),
+        );
+        println!(
+            "Job_ID: {} | Phase: {} | Start_Time: {} | Source_File_Size: {} | Imported_Rows: {} | Progress: {} | Time elapsed: {:02}:{:02}:{:02}",
+            job.job_id,
+            job.phase,
+            format_time(job.start_time),
+            job.source_file_size,
+            job.imported_rows.unwrap_or(0),
+            progress,
+            elapsed_h,
+            elapsed_m,
+            elapsed_s
+        );
+    }
+}
+
 /// Handler for showing import job details
 pub struct ShowingImportJobDetailsHandler {
     monitor_duration: u64,
+    /// Read before every update so reloaded settings apply to the next one
+    config: SharedConfig<ImportJobConfig>,
 }
 
 impl ShowingImportJobDetailsHandler {
     #[must_use]
     pub fn new(monitor_duration: u64) -> Self {
-        Self { monitor_duration }
+        Self {
+            monitor_duration,
+            config: SharedConfig::new(ImportJobConfig::default()),
+        }
+    }
+
+    /// Take the update interval and detail setting from `config`
+    #[must_use]
+    pub fn with_config(mut self, config: SharedConfig<ImportJobConfig>) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Call `poll` every `update_interval` seconds until `monitor_duration`
+    /// has passed or every job has stopped
+    ///
+    /// Returns the number of polls made. Time is read from the tokio clock,
+    /// so tests can run it with time paused. The interval is checked against
+    /// the duration by [`ImportJobConfig::validate`] before connecting.
+    async fn monitor<F>(&self, mut poll: F) -> Result<usize>
+    where
+        F: FnMut() -> Result<Vec<ImportJob>>,
+    {
+        let start_time = tokio::time::Instant::now();
+        let duration = Duration::from_secs(self.monitor_duration);
+        let mut polls = 0;
+
+        while start_time.elapsed() < duration {
+            let settings = self.config.current();
+            println!(
+                "\n--- Import Job Status Update ({}s remaining) ---",
+                (duration - start_time.elapsed()).as_secs()
+            );
+
+            let jobs = poll()?;
+            polls += 1;
+            if settings.show_details {
+                for job in &jobs {
+                    print_job_status(job);
+                }
+            } else {
+                let active = jobs.iter().filter(|job| job.is_active()).count();
+                println!(
+                    "{active} active, {} stopped import job(s)",
+                    jobs.len() - active
+                );
+            }
+
+            if all_terminal(&jobs) {
+                let failed = jobs.iter().filter(|job| job.is_failed()).count();
+                println!(
+                    "✓ All {} import job(s) stopped ({failed} failed)",
+                    jobs.len()
+                );
+                break;
+            }
+
+            // Sleep before next update
+            sleep(Duration::from_secs(settings.update_interval.max(1))).await;
+        }
+        Ok(polls)
     }
 }
 
@@ -310,64 +236,29 @@ impl DynamicStateHandler for ShowingImportJobDetailsHandler {
                 return Err("No active import jobs found in context".into());
             };
 
-        if let Some(ref mut conn) = context.connection {
-            let start_time = std::time::Instant::now();
-            let duration = Duration::from_secs(self.monitor_duration);
-
-            while start_time.elapsed() < duration {
-                println!(
-                    "\n--- Import Job Status Update ({}s remaining) ---",
-                    (duration - start_time.elapsed()).as_secs()
-                );
-
-                for job_id in &active_jobs {
-                    let query = format!("SHOW IMPORT JOB {job_id}");
-                    let results: Vec<ImportJob> = conn.exec(&query, ())?;
-                    for job in results {
-                        if job.End_Time.is_none() {
-                            // Calculate time elapsed using UTC for consistency
-                            let now = Utc::now().naive_utc();
-                            let start_time = job.Start_Time.unwrap_or(now);
-                            let elapsed = now - start_time;
-                            let elapsed_h = elapsed.num_seconds() / 3600;
-                            let elapsed_m = (elapsed.num_seconds() % 3600) / 60;
-                            let elapsed_s = elapsed.num_seconds() % 60;
-                            println!(
-                                "Job_ID: {} | Phase: {} | Start_Time: {} | Source_File_Size: {} | Imported_Rows: {} | Time elapsed: {:02}:{:02}:{:02}",
-                                job.Job_ID,
-                                job.Phase,
-                                job.Start_Time.map_or_else(
-                                    || "N/A".to_string(),
-                                    |t| t.format("%Y-%m-%d %H:%M:%S").to_string()
-                                ),
-                                job.Source_File_Size,
-                                job.Imported_Rows.unwrap_or(0),
-                                elapsed_h,
-                                elapsed_m,
-                                elapsed_s
-                            );
-                        } else {
-                            println!(
-                                "Job_ID: {} | Status: Completed | End_Time: {}",
-                                job.Job_ID,
-                                job.End_Time.map_or_else(
-                                    || "N/A".to_string(),
-                                    |t| t.format("%Y-%m-%d %H:%M:%S").to_string()
-                                )
-                            );
-                        }
-                    }
-                }
-
-                // Sleep before next update
-                sleep(Duration::from_secs(5)).await;
+        let Some(ref mut conn) = context.connection else {
+            return Err("No connection available for showing import job details".into());
+        };
+        let job_ids = active_jobs
+            .iter()
+            .map(|job_id| {
+                job_id
+                    .parse()
+                    .map_err(|_| ConnectError::Parse(format!("Invalid import job id '{job_id}'")))
+            })
+            .collect::<Result<Vec<i64>>>()?;
+
+        self.monitor(|| {
+            let mut jobs = Vec::new();
+            for &job_id in &job_ids {
+                jobs.extend(show_import_job(conn, job_id)?);
             }
+            Ok(jobs)
+        })
+        .await?;
 
-            println!("✓ Import job monitoring completed");
-            Ok(job_monitor_states::completed())
-        } else {
-            Err("No connection available for showing import job details".into())
-        }
+        println!("✓ Import job monitoring completed");
+        Ok(job_monitor_states::completed())
     }
 
     async fn exit(&self, _context: &mut DynamicStateContext) -> test_rig::Result<()> {
@@ -412,9 +303,40 @@ fn default_show_details() -> bool {
     true
 }
 
+impl Reloadable for ImportJobConfig {
+    fn load(path: &Path) -> Result<Self> {
+        let config = Self::from_file_with_env(path)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// The update interval and detail setting apply from the next update; the
+    /// monitor duration is fixed when monitoring starts
+    fn apply_reload(&mut self, new: &Self) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.update_interval != new.update_interval {
+            self.update_interval = new.update_interval;
+            changed.push("update_interval");
+        }
+        if self.show_details != new.show_details {
+            self.show_details = new.show_details;
+            changed.push("show_details");
+        }
+        if self.monitor_duration != new.monitor_duration {
+            tracing::debug!(
+                "Ignoring monitor_duration change to {}s; restart to apply it",
+                new.monitor_duration
+            );
+        }
+        changed
+    }
+}
+
 impl ImportJobConfig {
     /// Load configuration from file
     ///
+    /// The format is chosen by extension: `.json` (or none), `.toml`, `.yaml` or `.yml`.
+    ///
     /// # Errors
     ///
     /// Returns an error if the file cannot be read or parsed.
@@ -434,12 +356,27 @@ impl ImportJobConfig {
                 let content = std::fs::read_to_string(path)?;
                 toml::from_str(&content).map_err(|e| ConnectError::from(e.to_string()))?
             }
-            _ => return Err(format!("Unsupported config file format: {extension}").into()),
+            "yaml" | "yml" => {
+                let content = std::fs::read_to_string(path)?;
+                serde_yaml::from_str(&content).map_err(|e| ConnectError::from(e.to_string()))?
+            }
+            _ => return Err(unsupported_extension(path, extension)),
         };
 
         Ok(config)
     }
 
+    /// Load configuration from file with environment variable overrides
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn from_file_with_env<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        let mut config = Self::from_file(path)?;
+        config.apply_environment_overrides();
+        Ok(config)
+    }
+
     /// Save configuration to file
     ///
     /// # Errors
@@ -459,7 +396,10 @@ impl ImportJobConfig {
             "toml" => {
                 toml::to_string_pretty(self).map_err(|e| ConnectError::from(e.to_string()))?
             }
-            _ => return Err(format!("Unsupported config file format: {extension}").into()),
+            "yaml" | "yml" => {
+                serde_yaml::to_string(self).map_err(|e| ConnectError::from(e.to_string()))?
+            }
+            _ => return Err(unsupported_extension(path, extension)),
         };
 
         std::fs::write(path, content)?;
@@ -496,7 +436,11 @@ impl ImportJobConfig {
             return Err("Update interval must be greater than 0".into());
         }
         if self.update_interval > self.monitor_duration {
-            return Err("Update interval cannot be greater than monitor duration".into());
+            return Err(format!(
+                "Update interval ({}s) cannot be greater than monitor duration ({}s)",
+                self.update_interval, self.monitor_duration
+            )
+            .into());
         }
         Ok(())
     }
@@ -509,13 +453,35 @@ struct Args {
     #[command(flatten)]
     common: CommonArgs,
 
-    /// Import job config file path (JSON or TOML)
+    /// Import job config file path (JSON, TOML or YAML)
     #[arg(long)]
     import_config: Option<String>,
 
     /// Duration to monitor import jobs in seconds (default: 300)
     #[arg(short = 't', long, default_value = "300")]
     monitor_duration: u64,
+
+    /// Reload the update interval and show_details when --import-config changes
+    #[cfg(feature = "hot_reload")]
+    #[arg(long, requires = "import_config")]
+    watch_config: bool,
+
+    /// Write a machine-readable report of each step to this file
+    #[arg(long, value_name = "PATH")]
+    report_file: Option<PathBuf>,
+
+    /// Format of --report-file
+    #[arg(long, value_enum, default_value = "junit")]
+    report_format: ReportFormat,
+
+    /// Write the transition graph as a Mermaid state diagram to this file and exit
+    #[arg(long, value_name = "PATH")]
+    export_mermaid: Option<PathBuf>,
+
+    /// Serve GET /healthz on this port while monitoring
+    #[cfg(feature = "health_check")]
+    #[arg(long)]
+    health_port: Option<u16>,
 }
 
 impl Args {
@@ -532,15 +498,14 @@ impl Args {
     /// Load import job configuration, merging CLI args and config file
     pub fn get_import_config(&self) -> Result<ImportJobConfig> {
         let mut config = if let Some(ref config_path) = self.import_config {
-            ImportJobConfig::from_file(config_path)
+            ImportJobConfig::from_file_with_env(config_path)
                 .map_err(|e| ConnectError::from(e.to_string()))?
         } else {
-            ImportJobConfig::default()
+            let mut config = ImportJobConfig::default();
+            config.apply_environment_overrides();
+            config
         };
 
-        // Apply environment overrides
-        config.apply_environment_overrides();
-
         // Override with CLI arguments if provided
         config.monitor_duration = self.monitor_duration;
 
@@ -559,66 +524,59 @@ async fn main() {
 
     let args = Args::parse();
     args.init_logging().expect("Failed to initialize logging");
+    args.common
+        .init_retry_classification()
+        .expect("Failed to load the retry classification");
+
+    // Validate the import job configuration before prompting or connecting
+    let import_config = args
+        .get_import_config()
+        .expect("Failed to load import job configuration");
 
     // Get connection info
     let (host, user, password, database) = args
         .get_connection_info()
         .expect("Failed to get connection info");
 
-    // Get import job configuration
-    let import_config = args
-        .get_import_config()
-        .expect("Failed to load import job configuration");
-
-    println!("Import Job Configuration:");
-    println!("  Monitor Duration: {}s", import_config.monitor_duration);
-    println!("  Update Interval: {}s", import_config.update_interval);
-    println!("  Show Details: {}", import_config.show_details);
+    print_startup_banner(
+        &args
+            .common
+            .effective_config()
+            .expect("Failed to load configuration"),
+        &[
+            ("Mode", "import job monitor".to_string()),
+            (
+                "Monitor Duration",
+                format!("{}s", import_config.monitor_duration),
+            ),
+            (
+                "Update Interval",
+                format!("{}s", import_config.update_interval),
+            ),
+            ("Show Details", import_config.show_details.to_string()),
+        ],
+    );
+    let import_config = SharedConfig::new(import_config);
+
+    #[cfg(feature = "hot_reload")]
+    let _config_watcher = match (&args.import_config, args.watch_config) {
+        (Some(path), true) => {
+            let watcher =
+                test_rig::config_reload::ConfigWatcher::watch(path, import_config.clone())
+                    .expect("Failed to watch import job configuration");
+            println!("  Watching {} for changes", watcher.path().display());
+            Some(watcher)
+        }
+        _ => None,
+    };
 
     // Create and configure the dynamic state machine
     let mut machine = DynamicStateMachine::new();
 
     // Register handlers manually to include generic version handler
-    register_job_monitor_handlers(
-        &mut machine,
-        host,
-        user,
-        password,
-        database,
-        import_config.monitor_duration,
-    );
+    register_job_monitor_handlers(&mut machine, host, user, password, database, import_config);
 
     // Register valid transitions
-    register_transitions!(
-        machine,
-        dynamic_state!("initial", "Initial"),
-        [job_monitor_states::parsing_config()]
-    );
-    register_transitions!(
-        machine,
-        job_monitor_states::parsing_config(),
-        [job_monitor_states::connecting()]
-    );
-    register_transitions!(
-        machine,
-        job_monitor_states::connecting(),
-        [job_monitor_states::testing_connection()]
-    );
-    register_transitions!(
-        machine,
-        job_monitor_states::testing_connection(),
-        [job_monitor_states::verifying_database()]
-    );
-    register_transitions!(
-        machine,
-        job_monitor_states::verifying_database(),
-        [job_monitor_states::getting_version()]
-    );
-    register_transitions!(
-        machine,
-        job_monitor_states::getting_version(),
-        [job_monitor_states::checking_import_jobs()]
-    );
     register_transitions!(
         machine,
         job_monitor_states::checking_import_jobs(),
@@ -633,13 +591,57 @@ async fn main() {
         [job_monitor_states::completed()]
     );
 
-    // Run the state machine
-    match machine.run().await {
+    if let Some(path) = &args.export_mermaid {
+        machine
+            .export_mermaid(path)
+            .expect("Failed to write the transition graph");
+        println!("Wrote transition graph to {}", path.display());
+        return;
+    }
+
+    #[cfg(feature = "health_check")]
+    let health = test_rig::health::HealthState::new();
+    #[cfg(feature = "health_check")]
+    if let Some(port) = args.health_port {
+        test_rig::health::start_health_server(port, health.clone())
+            .await
+            .expect("Failed to start health endpoint");
+    }
+
+    args.common
+        .context_settings()
+        .expect("Failed to load configuration")
+        .configure_dynamic(&mut machine);
+
+    // Run the state machine, recording each state as a report step
+    let recorder = StepRecorder::new("job_monitor");
+    machine.add_observer(Box::new(recorder.clone()));
+    let result = machine.run().await;
+    if let Some(path) = &args.report_file {
+        let report = recorder.finish(&result, &machine);
+        if let Err(e) = report.write_to_file(path, args.report_format) {
+            eprintln!("Failed to write report to {}: {e}", path.display());
+        }
+    }
+
+    #[cfg(feature = "health_check")]
+    health.set(if result.is_ok() {
+        test_rig::health::RunStatus::Completed
+    } else {
+        test_rig::health::RunStatus::Failed
+    });
+
+    match result {
         Ok(()) => {
             print_success("Job monitoring test completed successfully!");
         }
         Err(e) => {
-            print_error_and_exit("Job monitoring test failed", &e);
+            report_error_and_exit(
+                "Job monitoring test failed",
+                &e,
+                args.common.error_format,
+                Some(machine.get_current_state().name()),
+            );
         }
     }
 }
@@ -651,39 +653,13 @@ fn register_job_monitor_handlers(
     user: String,
     password: String,
     database: Option<String>,
-    monitor_duration: u64,
+    import_config: SharedConfig<ImportJobConfig>,
 ) {
-    // Register standard connection handlers
-    state_machine.register_handler(
-        dynamic_state!("initial", "Initial"),
-        Box::new(InitialHandlerAdapter),
-    );
-    state_machine.register_handler(
-        job_monitor_states::parsing_config(),
-        Box::new(ParsingConfigHandlerAdapter {
-            host,
-            user,
-            password,
-            database,
-        }),
-    );
-    state_machine.register_handler(
-        job_monitor_states::connecting(),
-        Box::new(ConnectingHandlerAdapter),
-    );
-    state_machine.register_handler(
-        job_monitor_states::testing_connection(),
-        Box::new(TestingConnectionHandlerAdapter),
-    );
-    state_machine.register_handler(
-        job_monitor_states::verifying_database(),
-        Box::new(VerifyingDatabaseHandlerAdapter),
-    );
-
-    // Register generic version handler that transitions to job monitoring
-    state_machine.register_handler(
-        job_monitor_states::getting_version(),
-        Box::new(GettingVersionHandlerAdapter),
+    // Register standard connection handlers, handing off to the job check
+    register_standard_handlers(
+        state_machine,
+        ConnParams::new(host, user, password, database),
+        job_monitor_states::checking_import_jobs(),
     );
 
     // Register job monitoring handlers
@@ -693,7 +669,10 @@ fn register_job_monitor_handlers(
     );
     state_machine.register_handler(
         job_monitor_states::showing_import_job_details(),
-        Box::new(ShowingImportJobDetailsHandler::new(monitor_duration)),
+        Box::new(
+            ShowingImportJobDetailsHandler::new(import_config.current().monitor_duration)
+                .with_config(import_config),
+        ),
     );
 }
 
@@ -703,6 +682,7 @@ mod tests {
     use serial_test::serial;
     use std::io::Write;
     use tempfile::NamedTempFile;
+    use test_rig::test_support::EnvVarGuard;
 
     #[test]
     fn test_args_parsing() {
@@ -715,17 +695,36 @@ mod tests {
             "--password",
             "testpass",
         ]);
-        assert_eq!(args.common.host, "localhost:4000");
-        assert_eq!(args.common.user, "testuser");
+        assert_eq!(args.common.host.as_deref(), Some("localhost:4000"));
+        assert_eq!(args.common.user.as_deref(), Some("testuser"));
         assert_eq!(args.common.password, Some("testpass".to_string()));
     }
 
     #[test]
     fn test_args_defaults() {
         let args = Args::parse_from(["test-bin"]);
-        assert_eq!(args.common.host, "localhost:4000"); // default value
-        assert_eq!(args.common.user, "root"); // default value
+        assert_eq!(args.common.host, None); // default value
+        assert_eq!(args.common.user, None); // default value
         assert_eq!(args.common.password, None); // default value
+        assert_eq!(args.report_file, None);
+        assert_eq!(args.report_format, ReportFormat::Junit);
+    }
+
+    #[test]
+    fn test_report_args() {
+        let args = Args::parse_from([
+            "test-bin",
+            "--report-file",
+            "report.json",
+            "--report-format",
+            "json",
+        ]);
+        assert_eq!(args.report_file, Some(PathBuf::from("report.json")));
+        assert_eq!(args.report_format, ReportFormat::Json);
+        assert!(Args::try_parse_from(["test-bin", "--report-format", "html"]).is_err());
+
+        let args = Args::parse_from(["test-bin", "--export-mermaid", "graph.mmd"]);
+        assert_eq!(args.export_mermaid, Some(PathBuf::from("graph.mmd")));
     }
 
     #[test]
@@ -762,12 +761,8 @@ mod tests {
     #[test]
     fn test_handler_registration() {
         // Test that we can create the handlers without errors
-        let _version_handler = GettingVersionHandlerAdapter;
         let _checking_handler = CheckingImportJobsHandler;
         let _details_handler = ShowingImportJobDetailsHandler::new(30);
-
-        // This test ensures the handlers can be instantiated
-        assert!(true);
     }
 
     #[test]
@@ -838,6 +833,128 @@ mod tests {
         assert_eq!(config.monitor_duration, 150); // CLI override takes precedence
         assert_eq!(config.update_interval, 10); // From config file
         assert!(config.show_details); // From config file
+
+        // An interval longer than the -t duration fails before connecting
+        let args = Args::parse_from([
+            "test-bin",
+            "--import-config",
+            file.path().to_str().unwrap(),
+            "-t",
+            "5",
+        ]);
+        let err = args.get_import_config().unwrap_err().to_string();
+        assert!(
+            err.contains("Update interval (10s) cannot be greater than monitor duration (5s)"),
+            "{err}"
+        );
+    }
+
+    fn job(job_id: i64, status: &str) -> ImportJob {
+        let columns = [
+            ("Job_ID", job_id.to_string()),
+            ("Status", status.to_string()),
+        ];
+        let columns: Vec<(String, String)> = columns
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        ImportJob::from_columns(&columns).unwrap()
+    }
+
+    fn handler(monitor_duration: u64, update_interval: u64) -> ShowingImportJobDetailsHandler {
+        let config = ImportJobConfig {
+            monitor_duration,
+            update_interval,
+            show_details: false,
+        };
+        ShowingImportJobDetailsHandler::new(monitor_duration).with_config(SharedConfig::new(config))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_monitor_polls_every_update_interval() {
+        // Polls at 0s and 1s; the 2s duration has passed after the second sleep.
+        // The clock is paused, so the sleeps advance it without waiting
+        let mut polls = 0;
+        let count = handler(2, 1)
+            .monitor(|| {
+                polls += 1;
+                Ok(vec![job(1, "running")])
+            })
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(polls, 2);
+
+        // Stops as soon as every job has finished, long before the duration
+        let mut statuses = ["running", "finished"].into_iter();
+        let count = handler(60, 1)
+            .monitor(|| Ok(vec![job(1, statuses.next().unwrap())]))
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    #[serial]
+    fn test_reload_updates_interval_and_details() {
+        let mut file = NamedTempFile::with_suffix(".json").unwrap();
+        file.write_all(br#"{"monitor_duration": 600, "update_interval": 5, "show_details": true}"#)
+            .unwrap();
+        let shared = SharedConfig::new(ImportJobConfig::load(file.path()).unwrap());
+
+        std::fs::write(
+            file.path(),
+            r#"{"monitor_duration": 60, "update_interval": 2, "show_details": false}"#,
+        )
+        .unwrap();
+        let changed = shared.reload_from(file.path()).unwrap();
+        assert_eq!(changed, vec!["update_interval", "show_details"]);
+
+        let current = shared.current();
+        assert_eq!(current.update_interval, 2);
+        assert!(!current.show_details);
+        assert_eq!(current.monitor_duration, 600);
+
+        // Invalid settings are rejected and the running config is kept
+        std::fs::write(
+            file.path(),
+            r#"{"monitor_duration": 60, "update_interval": 0}"#,
+        )
+        .unwrap();
+        assert!(shared.reload_from(file.path()).is_err());
+        assert_eq!(shared.current().update_interval, 2);
+    }
+
+    #[test]
+    #[serial]
+    fn test_yaml_round_trip_with_env_override() {
+        let config = ImportJobConfig {
+            monitor_duration: 240,
+            update_interval: 12,
+            show_details: false,
+        };
+        for suffix in [".yaml", ".yml"] {
+            let file = NamedTempFile::with_suffix(suffix).unwrap();
+            config.save_to_file(file.path()).unwrap();
+            let loaded = ImportJobConfig::from_file(file.path()).unwrap();
+            assert_eq!(loaded.monitor_duration, 240);
+            assert_eq!(loaded.update_interval, 12);
+            assert!(!loaded.show_details);
+        }
+
+        let file = NamedTempFile::with_suffix(".yaml").unwrap();
+        config.save_to_file(file.path()).unwrap();
+        let mut env = EnvVarGuard::default();
+        env.set("TIDB_UPDATE_INTERVAL", "30");
+        let loaded = ImportJobConfig::from_file_with_env(file.path()).unwrap();
+        drop(env);
+        assert_eq!(loaded.update_interval, 30);
+        assert_eq!(loaded.monitor_duration, 240);
+
+        let err = ImportJobConfig::from_file("jobs.ini")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("'.ini'"), "{err}");
     }
 
     #[test]
@@ -67,10 +67,11 @@
 //!
 //! ## Configuration
 //!
-//! The binary uses a `GlobalConfig` with these settings:
+//! The binary opens `--connection-count` connections (default 2) to the server
+//! given with `-H`/`-u`/`-d`, and uses a `GlobalConfig` with these settings:
 //! - **`test_duration`**: 120 seconds (2 minutes) - Total test duration
 //! - **`coordination_timeout`**: 30 seconds - Timeout for coordination events
-//! - **`max_connections`**: 3 - Maximum number of concurrent connections
+//! - **`max_connections`**: `--connection-count` - Maximum number of concurrent connections
 //!
 //! ## Output
 //!
@@ -93,8 +94,10 @@
 //! Uses the shared state machine framework from the main library.
 
 use clap::Parser;
+use test_rig::cli::{DEFAULT_MAX_CONNECTIONS, validate_connection_count};
+use test_rig::connection::parse_connection_string;
 use test_rig::connection_manager::CoordinationMessage;
-use test_rig::{CommonArgs, print_success, print_test_header};
+use test_rig::{CommonArgs, print_startup_banner, print_success, print_test_header};
 use test_rig::{ConnectionCoordinator, ConnectionInfo, GlobalConfig, MultiConnectionStateMachine};
 use tokio::sync::mpsc;
 
@@ -107,12 +110,34 @@ pub struct Args {
     /// Number of connections to create for multi-connection tests
     #[arg(long, default_value = "2")]
     pub connection_count: u32,
+    /// Upper bound on --connection-count, guarding against typos that would exhaust resources
+    #[arg(long, default_value_t = DEFAULT_MAX_CONNECTIONS)]
+    pub max_connections: u32,
 }
 
 impl Args {
-    pub fn print_connection_info(&self) {
-        self.common.print_connection_info();
-        println!("  Connection Count: {}", self.connection_count);
+    /// Print the settings this run uses
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration cannot be loaded.
+    pub fn print_startup_banner(&self) -> test_rig::Result<()> {
+        print_startup_banner(
+            &self.common.effective_config()?,
+            &[
+                ("Mode", "advanced multi-connection".to_string()),
+                ("Connection Count", self.connection_count.to_string()),
+            ],
+        );
+        Ok(())
+    }
+    /// Check `connection_count` against `max_connections`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the requested count exceeds the cap.
+    pub fn validate_connection_count(&self) -> test_rig::Result<()> {
+        validate_connection_count(self.connection_count, self.max_connections)
     }
     /// Initialize logging system
     ///
@@ -130,20 +155,47 @@ impl Args {
     pub fn get_connection_info(&self) -> test_rig::cli::ConnInfoResult {
         self.common.get_connection_info()
     }
+    /// `connection_count` connections to the server given on the command line,
+    /// with ids `conn-0`, `conn-1`, ...
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if connection information cannot be obtained or the
+    /// host is not in `host:port` form.
+    pub fn connection_infos(
+        &self,
+    ) -> Result<Vec<(String, ConnectionInfo)>, Box<dyn std::error::Error>> {
+        let (host, username, password, database) = self.get_connection_info()?;
+        let (host, port) = parse_connection_string(&host)?;
+        Ok((0..self.connection_count)
+            .map(|i| {
+                let info = ConnectionInfo {
+                    host: host.clone(),
+                    port,
+                    username: username.clone(),
+                    password: password.clone(),
+                    database: database.clone(),
+                    connection: None,
+                };
+                (format!("conn-{i}"), info)
+            })
+            .collect())
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     print_test_header("Advanced Multi-Connection TiDB Testing");
     let args = Args::parse();
+    args.validate_connection_count()?;
     args.init_logging()?;
-    args.print_connection_info();
+    args.print_startup_banner()?;
 
     // Create global configuration
     let config = GlobalConfig {
         test_duration: 120,       // 2 minutes
         coordination_timeout: 30, // 30 seconds
-        max_connections: 3,       // Max 3 connections
+        max_connections: args.connection_count as usize,
     };
 
     // Create channel for coordination
@@ -160,42 +212,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Create multi-connection state machine with the sender
     let mut multi_sm = MultiConnectionStateMachine::new(tx.clone());
 
-    // Define multiple connections
-    let connections = vec![
-        (
-            "primary".to_string(),
-            ConnectionInfo {
-                host: "tidb-primary.example.com".to_string(),
-                port: 4000,
-                username: "user1".to_string(),
-                password: "password1".to_string(),
-                database: Some("test_db".to_string()),
-                connection: None,
-            },
-        ),
-        (
-            "secondary".to_string(),
-            ConnectionInfo {
-                host: "tidb-secondary.example.com".to_string(),
-                port: 4000,
-                username: "user2".to_string(),
-                password: "password2".to_string(),
-                database: Some("test_db".to_string()),
-                connection: None,
-            },
-        ),
-        (
-            "backup".to_string(),
-            ConnectionInfo {
-                host: "tidb-backup.example.com".to_string(),
-                port: 4000,
-                username: "user3".to_string(),
-                password: "password3".to_string(),
-                database: Some("backup_db".to_string()),
-                connection: None,
-            },
-        ),
-    ];
+    // One connection per --connection-count to the server from the command line
+    let connections = args.connection_infos()?;
 
     // Add connections to the multi-state machine
     for (connection_id, connection_info) in connections {
@@ -1,9 +1,11 @@
 use async_trait::async_trait;
 use clap::Parser;
 use mysql::prelude::*;
+use test_rig::capabilities::require_tidb_version;
+use test_rig::common_states::ConnParams;
 use test_rig::{
-    CommonArgs, State, StateContext, StateHandler, StateMachine, print_error_and_exit,
-    print_success, print_test_header,
+    CommonArgs, ParsingConfigHandler, State, StateContext, StateHandler, StateMachine,
+    print_error_and_exit, print_startup_banner, print_success, print_test_header,
 };
 
 #[cfg(feature = "python_plugins")]
@@ -46,32 +48,6 @@ impl StateHandler for InitialHandlerAdapter {
     }
 }
 
-// Adapter for ParsingConfigHandler to StateHandler
-struct ParsingConfigHandlerAdapter {
-    host: String,
-    user: String,
-    password: String,
-    database: Option<String>,
-}
-#[async_trait]
-impl StateHandler for ParsingConfigHandlerAdapter {
-    async fn enter(&self, _context: &mut StateContext) -> test_rig::Result<State> {
-        Ok(State::ParsingConfig)
-    }
-    async fn execute(&self, context: &mut StateContext) -> test_rig::Result<State> {
-        let (host, port) = test_rig::connection::parse_connection_string(&self.host)?;
-        context.host = host;
-        context.port = port;
-        context.username.clone_from(&self.user);
-        context.password.clone_from(&self.password);
-        context.database.clone_from(&self.database);
-        Ok(State::Connecting)
-    }
-    async fn exit(&self, _context: &mut StateContext) -> test_rig::Result<()> {
-        Ok(())
-    }
-}
-
 // Adapter for ConnectingHandler to StateHandler
 struct ConnectingHandlerAdapter;
 #[async_trait]
@@ -128,6 +104,7 @@ impl StateHandler for VerifyingDatabaseHandlerAdapter {
         Ok(State::VerifyingDatabase)
     }
     async fn execute(&self, context: &mut StateContext) -> test_rig::Result<State> {
+        context.no_database = context.database.is_none();
         if let Some(ref mut conn) = context.connection {
             if let Some(ref db_name) = context.database {
                 let query = format!("USE `{db_name}`");
@@ -136,6 +113,7 @@ impl StateHandler for VerifyingDatabaseHandlerAdapter {
                     Err(e) => Err(format!("Database verification failed: {e}").into()),
                 }
             } else {
+                println!("⚠ No database specified; Python handlers must use database.table names");
                 Ok(State::GettingVersion)
             }
         } else {
@@ -160,6 +138,7 @@ impl StateHandler for GettingVersionHandlerAdapter {
             match conn.query_first::<String, _>(version_query) {
                 Ok(Some(version)) => {
                     context.server_version = Some(version.clone());
+                    require_tidb_version(&version, context.min_tidb_version)?;
                     Ok(State::Completed)
                 }
                 Ok(None) => Err("No version returned from server".into()),
@@ -186,23 +165,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .get_connection_info()
         .expect("Failed to get connection info");
 
-    println!("Python Module: {}", args.python_module);
-    println!("Connection Info:");
-    println!("  Host: {host}");
-    println!("  User: {user}");
-    println!("  Database: {database:?}");
+    print_startup_banner(
+        &args
+            .common
+            .effective_config()
+            .expect("Failed to load configuration"),
+        &[
+            ("Mode", "python handlers demo".to_string()),
+            ("Python Module", args.python_module.clone()),
+        ],
+    );
 
     // Create and configure the state machine
     let mut machine = StateMachine::new();
+    args.common
+        .context_settings()
+        .expect("Failed to load configuration")
+        .configure(&mut machine);
     machine.register_handler(State::Initial, Box::new(InitialHandlerAdapter));
     machine.register_handler(
         State::ParsingConfig,
-        Box::new(ParsingConfigHandlerAdapter {
-            host,
-            user,
-            password,
-            database,
-        }),
+        Box::new(ParsingConfigHandler::from_params(ConnParams::new(
+            host, user, password, database,
+        ))),
     );
     machine.register_handler(State::Connecting, Box::new(ConnectingHandlerAdapter));
     machine.register_handler(
@@ -226,7 +211,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             args.python_module
         );
         match load_python_handlers(&mut machine, &args.python_module) {
-            Ok(_) => println!("✓ Python handlers loaded successfully"),
+            Ok(states) => println!("✓ Loaded Python handlers for {} state(s)", states.len()),
             Err(e) => {
                 eprintln!("✗ Failed to load Python handlers: {}", e);
                 print_error_and_exit("Python handler loading failed", &e);
@@ -270,15 +255,15 @@ mod tests {
             "testuser",
         ]);
         assert_eq!(args.python_module, "test.handlers");
-        assert_eq!(args.common.host, "localhost:4000");
-        assert_eq!(args.common.user, "testuser");
+        assert_eq!(args.common.host.as_deref(), Some("localhost:4000"));
+        assert_eq!(args.common.user.as_deref(), Some("testuser"));
     }
 
     #[test]
     fn test_args_defaults() {
         let args = Args::parse_from(["test-bin"]);
         assert_eq!(args.python_module, "examples.python_handlers");
-        assert_eq!(args.common.host, "localhost:4000");
-        assert_eq!(args.common.user, "root");
+        assert_eq!(args.common.host, None);
+        assert_eq!(args.common.user, None);
     }
 }
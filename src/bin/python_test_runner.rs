@@ -1,13 +1,17 @@
 use clap::Parser;
-use test_rig::common::python_tests::{PYTHON_SUITES, PythonSuiteConfig};
-use test_rig::logging::{LogConfig, init_logging};
+use std::path::PathBuf;
+use test_rig::common::python_tests::{
+    PYTHON_SUITES, PythonRunOptions, PythonSuiteConfig, find_suite, format_plan, parse_python_env,
+};
+use test_rig::common::suite_report::{CrossSuiteSummary, SummaryLevel};
+use test_rig::logging::{LogConfig, LogFormat, Rotation, init_logging};
 use tracing::Level;
 
 #[derive(Parser, Debug)]
 #[command(name = "python-test-runner")]
 #[command(about = "Unified runner for all Python test suites")]
 pub struct Args {
-    /// Name of the suite to run (case-insensitive, e.g. DDL, Scale). If omitted, runs all suites.
+    /// Only run the named suite (ddl, import, scale, txn; case-insensitive). If omitted, runs all suites.
     #[arg(long)]
     suite: Option<String>,
     /// Run all suites
@@ -28,6 +32,21 @@ pub struct Args {
     /// Only run a specific test file (e.g. test_import_large.py)
     #[arg(long)]
     test_file: Option<String>,
+    /// Extra environment variable for the Python subprocess (KEY=VALUE, repeatable)
+    #[arg(long = "python-env", value_parser = parse_python_env)]
+    python_env: Vec<(String, String)>,
+    /// Python interpreter to run the tests with (default: python3)
+    #[arg(long)]
+    python_bin: Option<String>,
+    /// Project root used for PYTHONPATH and suite directories (default: detected crate root)
+    #[arg(long)]
+    project_root: Option<PathBuf>,
+    /// List the test files and handlers each suite would run, without running anything
+    #[arg(long)]
+    plan: bool,
+    /// How much the final summary prints: minimal (pass/fail), normal (counts) or detailed (every test)
+    #[arg(long, default_value = "normal")]
+    summary_level: SummaryLevel,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -78,52 +97,75 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         file_path: std::path::PathBuf::from("logs/python_test_runner.log"),
         max_file_size: 10,
         max_files: 5,
+        rotation: Rotation::Never,
         include_timestamps: true,
         include_thread_ids: false,
         include_file_line: true,
+        format: LogFormat::Text,
     };
     init_logging(&config)?;
 
-    let suites: Vec<&PythonSuiteConfig> = if args.all || args.suite.is_none() {
-        PYTHON_SUITES.iter().collect()
-    } else {
-        let name = args.suite.as_ref().unwrap().to_lowercase();
-        let found: Vec<_> = PYTHON_SUITES
-            .iter()
-            .filter(|s| s.name.to_lowercase() == name)
-            .collect();
-        if found.is_empty() {
-            eprintln!(
-                "No suite found with name '{}'. Available: {:?}",
-                name,
-                PYTHON_SUITES.iter().map(|s| s.name).collect::<Vec<_>>()
-            );
-            std::process::exit(1);
+    let suites: Vec<&PythonSuiteConfig> = if let Some(suite) = args.suite.as_ref()
+        && !args.all
+    {
+        match find_suite(suite) {
+            Ok(found) => vec![found],
+            Err(e) => {
+                eprintln!("❌ {e}");
+                std::process::exit(2);
+            }
         }
-        found
+    } else {
+        PYTHON_SUITES.iter().collect()
     };
 
-    let mut any_failed = false;
+    let mut summary = CrossSuiteSummary::new();
+    let mut plan_failed = false;
     for suite in suites {
-        println!("\n=== Running Python test suite: {} ===", suite.name);
-        let show_output =
-            args.output_level == OutputLevel::Verbose || args.output_level == OutputLevel::Debug;
-        let show_sql = args.show_sql || args.output_level == OutputLevel::Debug;
-        let real_db = args.real_db || args.db_type == DatabaseType::Real;
+        let options = PythonRunOptions {
+            show_output: args.output_level == OutputLevel::Verbose
+                || args.output_level == OutputLevel::Debug,
+            show_sql: args.show_sql || args.output_level == OutputLevel::Debug,
+            real_db: args.real_db || args.db_type == DatabaseType::Real,
+            python_env: args.python_env.clone(),
+            python_bin: args.python_bin.clone(),
+            project_root: args.project_root.clone(),
+        };
 
-        match suite
-            .run_suite_with_output_filtered(show_output, show_sql, real_db, args.test_file.as_deref())
-            .await
-        {
-            Ok(()) => println!("✅ Suite '{}' completed successfully", suite.name),
-            Err(e) => {
-                eprintln!("❌ Suite '{}' failed: {}", suite.name, e);
-                any_failed = true;
+        if args.plan {
+            match suite.plan(&options, args.test_file.as_deref()).await {
+                Ok(tests) => print!("{}", format_plan(suite, &options, &tests)),
+                Err(e) => {
+                    eprintln!("❌ Suite '{}': {}", suite.name, e);
+                    plan_failed = true;
+                }
             }
+            continue;
+        }
+
+        println!("\n=== Running Python test suite: {} ===", suite.name);
+        let result = suite
+            .run_suite_collecting(&options, args.test_file.as_deref())
+            .await;
+        if result.succeeded() {
+            println!("✅ Suite '{}' completed successfully", suite.name);
+        } else {
+            eprintln!(
+                "❌ Suite '{}' failed: {} of {} test file(s) failed",
+                suite.name,
+                result.failed,
+                result.passed + result.failed
+            );
         }
+        summary.add(result);
+    }
+
+    if !args.plan {
+        summary.print(args.summary_level);
     }
-    if any_failed {
-        std::process::exit(1);
+    let exit_code = summary.exit_code().max(i32::from(plan_failed));
+    if exit_code != 0 {
+        std::process::exit(exit_code);
     }
     Ok(())
 }
@@ -0,0 +1,395 @@
+//!
+//! # `TiDB` Query Diagnostics Binary
+//!
+//! Ad hoc query and query-plan inspection against a `TiDB` server.
+//!
+//! ## Subcommands
+//!
+//! - `query <SQL>`: Run a query and print its rows. Rows are streamed and capped at
+//!   `--max-rows` (default 1000); a note is printed when the result is truncated.
+//!   `--head N` / `--tail M` instead print the first N and last M rows with a
+//!   `... X more rows ...` marker in between.
+//! - `explain <SQL>`: Run `EXPLAIN` (or `EXPLAIN ANALYZE` with `--analyze`) and print the plan.
+//!   With `--baseline <file>` the plan is compared against a stored baseline and any
+//!   differences (e.g. an index no longer used) are flagged. `--save-baseline <file>`
+//!   stores the current plan for future comparisons.
+//! - `source <FILE>`: Run every statement in a SQL file, stopping at the first failure.
+//!   `DELIMITER` lines change the statement separator, as in the MySQL client.
+//!
+//! `--hosts a:4000,b:4000` tries several endpoints at once and uses the first that connects.
+//!
+//! `query` accepts several statements, run in order. Each gets a fresh connection unless
+//! `--continue-session` is given, in which case they share one session, so session
+//! variables and temporary tables set by one statement are visible to the next.
+//!
+//! `--record <file>` saves each query and its result during a live run; `--replay <file>`
+//! serves those results without a server, to reproduce a captured failure offline.
+//!
+//! ## Usage
+//!
+//! ```bash
+//! # Run a query, printing at most 50 rows
+//! cargo run --bin query_tool -- -d test query "SELECT * FROM big_table" --max-rows 50
+//!
+//! # Sample a large result: first 3 and last 2 rows
+//! cargo run --bin query_tool -- -d test query "SELECT * FROM big_table" --head 3 --tail 2
+//!
+//! # Print a plan
+//! cargo run --bin query_tool -- -H localhost:4000 -d test explain "SELECT * FROM t WHERE a = 1"
+//!
+//! # Record a baseline, then check for regressions later
+//! cargo run --bin query_tool -- -d test explain "SELECT * FROM t WHERE a = 1" --save-baseline plan.json
+//! cargo run --bin query_tool -- -d test explain "SELECT * FROM t WHERE a = 1" --baseline plan.json
+//!
+//! # Capture a result, then reproduce it without a database
+//! cargo run --bin query_tool -- -d test query "SELECT * FROM t" --record run.json
+//! cargo run --bin query_tool -- query "SELECT * FROM t" --replay run.json
+//!
+//! # Reuse session state between statements
+//! cargo run --bin query_tool -- -d test --continue-session query \
+//!     "CREATE TEMPORARY TABLE tmp AS SELECT * FROM t WHERE a > 10" "SELECT COUNT(*) FROM tmp"
+//!
+//! # Load a schema that defines stored procedures
+//! cargo run --bin query_tool -- -d test source schema.sql
+//! ```
+
+use clap::{Parser, Subcommand};
+use mysql::PooledConn;
+use std::path::PathBuf;
+use test_rig::endpoints::connect_first_available;
+use test_rig::errors::{ConnectError, Result};
+use test_rig::explain::{QueryPlan, diff_plans, explain};
+use test_rig::lib_utils::report_error_and_exit;
+use test_rig::query::{QueryOutput, RowLimit, RowSampler};
+use test_rig::recording::{QueryExecutor, QueryRecording, ReplayConnection};
+use test_rig::sql_script::run_script_file;
+use test_rig::{
+    CommonArgs, print_error_and_exit, print_startup_banner, print_success, print_test_header,
+};
+
+#[derive(Parser, Debug)]
+#[command(name = "query-tool")]
+#[command(about = "TiDB query and query-plan diagnostics")]
+pub struct Args {
+    #[command(flatten)]
+    pub common: CommonArgs,
+
+    /// Record every query and its result to this file (JSON)
+    #[arg(long, global = true, conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+
+    /// Replay results from a recording instead of connecting to a server
+    #[arg(long, global = true)]
+    pub replay: Option<PathBuf>,
+
+    /// Run every query on one connection so session state carries over between them
+    #[arg(long, global = true)]
+    pub continue_session: bool,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Run one or more queries in order and print their rows
+    Query {
+        /// SQL statements to run
+        #[arg(required = true, num_args = 1..)]
+        sql: Vec<String>,
+
+        /// Maximum number of rows to fetch (0 for no limit)
+        #[arg(long, default_value = "1000")]
+        max_rows: usize,
+
+        /// Print only the first N rows, with a count of omitted rows (combine with --tail)
+        #[arg(long, conflicts_with = "max_rows")]
+        head: Option<usize>,
+
+        /// Print only the last M rows, with a count of omitted rows (combine with --head)
+        #[arg(long, conflicts_with = "max_rows")]
+        tail: Option<usize>,
+    },
+    /// Run EXPLAIN on a query and optionally compare against a baseline plan
+    Explain {
+        /// SQL statement to explain
+        sql: String,
+
+        /// Use EXPLAIN ANALYZE (executes the query)
+        #[arg(long)]
+        analyze: bool,
+
+        /// Baseline plan file (JSON) to compare against
+        #[arg(long)]
+        baseline: Option<PathBuf>,
+
+        /// Save the current plan as a baseline file (JSON)
+        #[arg(long)]
+        save_baseline: Option<PathBuf>,
+    },
+    /// Run every statement in a SQL file (supports DELIMITER)
+    Source {
+        /// SQL file to run
+        file: PathBuf,
+    },
+}
+
+fn run(args: &Args) -> Result<()> {
+    if let Command::Query {
+        sql,
+        max_rows,
+        head,
+        tail,
+    } = &args.command
+    {
+        let limit = if head.is_some() || tail.is_some() {
+            RowLimit::Sample(RowSampler::new(head.unwrap_or(0), tail.unwrap_or(0)))
+        } else {
+            RowLimit::Max((*max_rows > 0).then_some(*max_rows))
+        };
+
+        // Replay needs no server at all; the recording is a single stream of results
+        if let Some(path) = &args.replay {
+            let recording = QueryRecording::load(path)?;
+            let mut replay = Some(ReplayConnection::new(recording));
+            run_queries(
+                sql,
+                limit,
+                true,
+                || {
+                    replay.take().ok_or_else(|| {
+                        ConnectError::StateMachine("Replay connection already in use".to_string())
+                    })
+                },
+                None,
+            )?;
+            return Ok(());
+        }
+
+        let mut recording = QueryRecording::default();
+        let result = run_queries(
+            sql,
+            limit,
+            args.continue_session,
+            || connect(args),
+            args.record.as_ref().map(|_| &mut recording),
+        );
+        if let Some(path) = &args.record {
+            recording.save(path)?;
+            println!(
+                "Recorded {} query(s) to {}",
+                recording.queries.len(),
+                path.display()
+            );
+        }
+        result?;
+        return Ok(());
+    }
+
+    if args.record.is_some() || args.replay.is_some() {
+        return Err(ConnectError::CliArgument(
+            "--record and --replay are only supported by the query subcommand".to_string(),
+        ));
+    }
+
+    let mut conn = connect(args)?;
+    if let Command::Source { file } = &args.command {
+        let count = run_script_file(&mut conn, file)?;
+        println!("Ran {count} statement(s) from {}", file.display());
+    }
+    if let Command::Explain {
+        sql,
+        analyze,
+        baseline,
+        save_baseline,
+    } = &args.command
+    {
+        let plan = explain(&mut conn, sql, *analyze)?;
+        plan.print();
+
+        if let Some(path) = save_baseline {
+            plan.save_baseline(path)?;
+            println!("Saved baseline plan to {}", path.display());
+        }
+
+        if let Some(path) = baseline {
+            let baseline = QueryPlan::load_baseline(path)?;
+            let differences = diff_plans(&baseline, &plan);
+            if !differences.is_empty() {
+                for difference in &differences {
+                    println!("  ⚠️  {difference}");
+                }
+                return Err(ConnectError::Validation(format!(
+                    "Plan differs from baseline {} ({} difference(s))",
+                    path.display(),
+                    differences.len()
+                )));
+            }
+            println!("Plan matches baseline {}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Run `sqls` in order and print each result
+///
+/// With `continue_session` every query runs on the first connection returned
+/// by `connect`; otherwise each query gets its own. Outcomes are appended to
+/// `recording` when one is given. Stops at the first failing query.
+fn run_queries<C: QueryExecutor>(
+    sqls: &[String],
+    limit: RowLimit,
+    continue_session: bool,
+    mut connect: impl FnMut() -> Result<C>,
+    mut recording: Option<&mut QueryRecording>,
+) -> Result<Vec<QueryOutput>> {
+    let mut session: Option<C> = None;
+    let mut outputs = Vec::with_capacity(sqls.len());
+    for sql in sqls {
+        let conn = match session.as_mut() {
+            Some(conn) if continue_session => conn,
+            _ => session.insert(connect()?),
+        };
+        let result = conn.run_query(sql, limit);
+        if let Some(recording) = recording.as_deref_mut() {
+            recording.push(sql, &result);
+        }
+        let output = result?;
+        if sqls.len() > 1 {
+            println!("-- {sql}");
+        }
+        output.print();
+        outputs.push(output);
+    }
+    Ok(outputs)
+}
+
+/// Connect to the first reachable configured endpoint
+fn connect(args: &Args) -> Result<PooledConn> {
+    let (_host, user, password, database) = args
+        .common
+        .get_connection_info()
+        .map_err(|e| ConnectError::CliArgument(e.to_string()))?;
+    let endpoints = args.common.get_endpoints()?;
+    let settings = args.common.context_settings()?;
+    if let Some(spec) = &settings.ensure_table {
+        test_rig::query::qualify_table(database.as_deref(), &spec.name)?;
+    }
+    let selected = connect_first_available(
+        &endpoints,
+        &user,
+        &password,
+        database.as_deref(),
+        settings.tls.as_ref(),
+        &settings.timeouts,
+    )?;
+    if endpoints.len() > 1 {
+        println!("Using endpoint {}", selected.endpoint);
+    }
+    let mut conn = selected.connection;
+    if let Some(spec) = &settings.ensure_table {
+        spec.ensure(&mut conn, database.as_deref())?;
+    }
+    Ok(conn)
+}
+
+fn main() {
+    print_test_header("TiDB Query Diagnostics");
+    let args = Args::parse();
+    if let Err(e) = args.common.init_logging() {
+        print_error_and_exit("Failed to initialize logging", e.as_ref());
+    }
+    let mode = if args.replay.is_some() {
+        "query diagnostics (replay)"
+    } else {
+        "query diagnostics"
+    };
+    print_startup_banner(
+        &args
+            .common
+            .effective_config()
+            .expect("Failed to load configuration"),
+        &[("Mode", mode.to_string())],
+    );
+
+    match run(&args) {
+        Ok(()) => print_success("Query diagnostics completed"),
+        Err(e) => report_error_and_exit(
+            "Query diagnostics failed",
+            &e,
+            args.common.error_format,
+            None,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_rig::test_support::MockConnection;
+
+    fn session_queries() -> Vec<String> {
+        vec!["SET @marker = 42".to_string(), "SELECT @marker".to_string()]
+    }
+
+    #[test]
+    fn test_continue_session_shares_one_connection() {
+        let mut connections = Vec::new();
+        let mut recording = QueryRecording::default();
+        run_queries(
+            &session_queries(),
+            RowLimit::Max(None),
+            true,
+            || {
+                let conn = MockConnection::default();
+                connections.push(conn.clone());
+                Ok(conn)
+            },
+            Some(&mut recording),
+        )
+        .unwrap();
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].statements(), session_queries());
+        assert_eq!(recording.queries.len(), 2);
+
+        let mut connections = Vec::new();
+        run_queries(
+            &session_queries(),
+            RowLimit::Max(None),
+            false,
+            || {
+                let conn = MockConnection::default();
+                connections.push(conn.clone());
+                Ok(conn)
+            },
+            None,
+        )
+        .unwrap();
+        assert_eq!(connections.len(), 2);
+        assert_eq!(connections[1].statements(), vec!["SELECT @marker"]);
+    }
+
+    #[test]
+    fn test_continue_session_against_live_tidb() {
+        let Some(conn) = test_rig::connection::connect_from_env().expect("failed to connect")
+        else {
+            eprintln!("TIDB_HOST not set, skipping live session test");
+            return;
+        };
+        // A second connection request would fail the run
+        let mut conn = Some(conn);
+        let outputs = run_queries(
+            &session_queries(),
+            RowLimit::Max(None),
+            true,
+            || {
+                conn.take()
+                    .ok_or_else(|| ConnectError::StateMachine("connected twice".to_string()))
+            },
+            None,
+        )
+        .unwrap();
+        assert_eq!(outputs[1].rows, vec![vec!["42".to_string()]]);
+    }
+}
@@ -46,6 +46,18 @@
 //! # Custom connection count
 //! cargo run --bin simple_multi_connection --features multi_connection -- --connection-count 5
 //!
+//! # Run at most 2 connections at a time
+//! cargo run --bin simple_multi_connection --features multi_connection -- --max-concurrent 2
+//!
+//! # Retry each failed connection up to 2 times
+//! cargo run --bin simple_multi_connection --features multi_connection -- --connection-retries 2
+//!
+//! # Export per-connection results for spreadsheet analysis
+//! cargo run --bin simple_multi_connection --features multi_connection -- --results-csv results.csv
+//!
+//! # Record connections that never succeeded, and why
+//! cargo run --bin simple_multi_connection --features multi_connection -- --dead-letter failed.json
+//!
 //! # With configuration file
 //! cargo run --bin simple_multi_connection --features multi_connection -- -c config.json
 //! ```
@@ -68,17 +80,25 @@
 //! or import job monitoring, see the `multi_connection.rs` binary.
 
 use clap::Parser;
+use serde::Serialize;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use async_trait::async_trait;
-use mysql::prelude::*;
+use test_rig::cli::{ContextSettings, DEFAULT_MAX_CONNECTIONS, validate_connection_count};
+use test_rig::common_states::{ConnParams, register_standard_handlers};
+use test_rig::connection::{format_host_port, parse_connection_string};
 use test_rig::errors::ConnectError;
 use test_rig::errors::StateError;
+use test_rig::latency::LatencyRecorder;
+use test_rig::lib_utils::{TaskResult, join_all_tasks};
 use test_rig::{
-    CommonArgs, DynamicState, DynamicStateContext, DynamicStateHandler, DynamicStateMachine,
-    dynamic_state, print_success, print_test_header, register_transitions,
+    CommonArgs, DynamicStateMachine, print_startup_banner, print_success, print_test_header,
 };
+use tokio::sync::Semaphore;
 use tokio::task::JoinHandle;
 
 #[derive(Parser, Debug)]
@@ -90,12 +110,47 @@ pub struct Args {
     /// Number of connections to create for multi-connection tests
     #[arg(long, default_value = "2")]
     pub connection_count: u32,
+    /// Upper bound on --connection-count, guarding against typos that would exhaust resources
+    #[arg(long, default_value_t = DEFAULT_MAX_CONNECTIONS)]
+    pub max_connections: u32,
+    /// Maximum number of connections running at the same time (default: number of CPUs)
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_concurrent: Option<u32>,
+    /// Re-run a failed connection up to this many times before marking it failed
+    #[arg(long, default_value_t = 0)]
+    pub connection_retries: u32,
+    /// Write per-connection results (id, host, status, version, error, duration) to this CSV file
+    #[arg(long, value_name = "PATH")]
+    pub results_csv: Option<PathBuf>,
+    /// Write the connections that failed every attempt (id, host, error, attempts) to this JSON file
+    #[arg(long, value_name = "PATH")]
+    pub dead_letter: Option<PathBuf>,
 }
 
 impl Args {
-    pub fn print_connection_info(&self) {
-        self.common.print_connection_info();
-        println!("  Connection Count: {}", self.connection_count);
+    /// Print the settings this run uses
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration cannot be loaded.
+    pub fn print_startup_banner(&self) -> test_rig::Result<()> {
+        let mut settings = vec![
+            ("Mode", "simple multi-connection".to_string()),
+            ("Connection Count", self.connection_count.to_string()),
+        ];
+        if let Some(max_concurrent) = self.max_concurrent {
+            settings.push(("Max Concurrent", max_concurrent.to_string()));
+        }
+        print_startup_banner(&self.common.effective_config()?, &settings);
+        Ok(())
+    }
+    /// Check `connection_count` against `max_connections`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the requested count exceeds the cap.
+    pub fn validate_connection_count(&self) -> test_rig::Result<()> {
+        validate_connection_count(self.connection_count, self.max_connections)
     }
     /// Initialize logging system
     ///
@@ -113,6 +168,28 @@ impl Args {
     pub fn get_connection_info(&self) -> test_rig::cli::ConnInfoResult {
         self.common.get_connection_info()
     }
+    /// `connection_count` connections to the server given on the command line,
+    /// with ids `conn-0`, `conn-1`, ...
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if connection information cannot be obtained or the
+    /// host is not in `host:port` form.
+    pub fn connection_configs(&self) -> Result<Vec<ConnectionConfig>, Box<dyn std::error::Error>> {
+        let (host, username, password, database) = self.get_connection_info()?;
+        let (host, port) = parse_connection_string(&host)?;
+        Ok((0..self.connection_count)
+            .map(|i| ConnectionConfig {
+                id: format!("conn-{i}"),
+                label: None,
+                host: host.clone(),
+                port,
+                username: username.clone(),
+                password: password.clone(),
+                database: database.clone(),
+            })
+            .collect())
+    }
 }
 
 /// Simple shared state for coordination
@@ -125,10 +202,16 @@ pub struct SharedTestState {
 #[derive(Debug, Clone)]
 pub struct ConnectionResult {
     pub connection_id: String,
+    /// Human-readable label shown in reports (defaults to the connection id)
+    pub label: String,
     pub host: String,
     pub status: ConnectionStatus,
     pub error: Option<String>,
     pub version: Option<String>,
+    /// Time from starting the connect until completion or failure, including retries
+    pub duration: Option<Duration>,
+    /// State machine runs made for this connection, including retries
+    pub attempts: u32,
 }
 
 #[derive(Debug, Clone)]
@@ -150,10 +233,29 @@ impl Default for SharedTestState {
     }
 }
 
+/// Called with the connection id and its new status on every status change
+pub type StatusObserver = Arc<dyn Fn(&str, &ConnectionStatus) + Send + Sync>;
+
+/// Default cap on concurrently running connections: the number of CPUs
+fn default_max_concurrency() -> usize {
+    std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+}
+
 /// Simple multi-connection coordinator
 pub struct SimpleMultiConnectionCoordinator {
     shared_state: Arc<Mutex<SharedTestState>>,
     connections: Vec<ConnectionConfig>,
+    /// Most connections running at once; the rest wait for a permit
+    max_concurrency: usize,
+    limiter: Arc<Semaphore>,
+    /// Notified whenever a connection's status changes
+    status_observer: Option<StatusObserver>,
+    /// Connect-to-version durations of the connections that completed
+    latencies: LatencyRecorder,
+    /// Times a failed connection is re-run before it is marked failed
+    connection_retries: u32,
+    /// TLS, tags and version floor applied to every connection's state machine
+    settings: ContextSettings,
 }
 
 impl Default for SimpleMultiConnectionCoordinator {
@@ -164,6 +266,8 @@ impl Default for SimpleMultiConnectionCoordinator {
 
 pub struct ConnectionConfig {
     pub id: String,
+    /// Optional human-readable label such as "us-east-primary"
+    pub label: Option<String>,
     pub host: String,
     pub port: u16,
     pub username: String,
@@ -174,152 +278,79 @@ pub struct ConnectionConfig {
 // Define custom states for the workflow
 mod multi_connection_states {
     // Re-export common states
-    pub use test_rig::common_states::{
-        completed, connecting, getting_version, parsing_config, testing_connection,
-        verifying_database,
-    };
+    pub use test_rig::common_states::completed;
 }
 
-// Adapter for InitialHandler to DynamicStateHandler
-struct InitialHandlerAdapter;
-#[async_trait]
-impl DynamicStateHandler for InitialHandlerAdapter {
-    async fn enter(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(dynamic_state!("initial", "Initial"))
-    }
-    async fn execute(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(multi_connection_states::parsing_config())
-    }
-    async fn exit(&self, _context: &mut DynamicStateContext) -> test_rig::Result<()> {
-        Ok(())
-    }
-}
+/// State machine running one connection through the connect-to-version flow
+fn build_connection_machine(
+    host: &str,
+    username: &str,
+    password: &str,
+    database: Option<&str>,
+) -> DynamicStateMachine {
+    let mut machine = DynamicStateMachine::new();
 
-// Adapter for ParsingConfigHandler to DynamicStateHandler
-struct ParsingConfigHandlerAdapter {
-    host: String,
-    user: String,
-    password: String,
-    database: Option<String>,
-}
-#[async_trait]
-impl DynamicStateHandler for ParsingConfigHandlerAdapter {
-    async fn enter(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(multi_connection_states::parsing_config())
-    }
-    async fn execute(&self, context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        let (host, port) = test_rig::connection::parse_connection_string(&self.host)?;
-        context.host = host;
-        context.port = port;
-        context.username.clone_from(&self.user);
-        context.password.clone_from(&self.password);
-        context.database.clone_from(&self.database);
-        Ok(multi_connection_states::connecting())
-    }
-    async fn exit(&self, _context: &mut DynamicStateContext) -> test_rig::Result<()> {
-        Ok(())
-    }
-}
+    let params = ConnParams::new(
+        host.to_string(),
+        username.to_string(),
+        password.to_string(),
+        database.map(ToString::to_string),
+    );
+    register_standard_handlers(&mut machine, params, multi_connection_states::completed());
 
-// Adapter for ConnectingHandler to DynamicStateHandler
-struct ConnectingHandlerAdapter;
-#[async_trait]
-impl DynamicStateHandler for ConnectingHandlerAdapter {
-    async fn enter(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(multi_connection_states::connecting())
-    }
-    async fn execute(&self, context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        let pool = test_rig::connection::create_connection_pool(
-            &context.host,
-            context.port,
-            &context.username,
-            &context.password,
-            context.database.as_deref(),
-        )?;
-        let conn = pool.get_conn()?;
-        context.connection = Some(conn);
-        Ok(multi_connection_states::testing_connection())
-    }
-    async fn exit(&self, _context: &mut DynamicStateContext) -> test_rig::Result<()> {
-        Ok(())
-    }
+    machine
 }
 
-// Adapter for TestingConnectionHandler to DynamicStateHandler
-struct TestingConnectionHandlerAdapter;
-#[async_trait]
-impl DynamicStateHandler for TestingConnectionHandlerAdapter {
-    async fn enter(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(multi_connection_states::testing_connection())
-    }
-    async fn execute(&self, context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        if let Some(ref mut conn) = context.connection {
-            let result: std::result::Result<Vec<mysql::Row>, mysql::Error> =
-                conn.exec("SELECT 1", ());
-            match result {
-                Ok(_) => Ok(multi_connection_states::verifying_database()),
-                Err(e) => Err(format!("Connection test failed: {e}").into()),
+/// Run `attempt` until it succeeds, retrying up to `retries` times after a failure
+///
+/// `attempt` receives the 1-based attempt number. Returns the last outcome and
+/// the number of attempts made.
+async fn run_with_retries<T, F, Fut>(retries: u32, mut attempt: F) -> (Result<T, ConnectError>, u32)
+where
+    F: FnMut(u32) -> Fut,
+    Fut: std::future::Future<Output = Result<T, ConnectError>>,
+{
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        match attempt(attempts).await {
+            Err(e) if attempts <= retries => {
+                tracing::warn!("Connection attempt {} failed, retrying: {}", attempts, e);
             }
-        } else {
-            Err("No connection available for testing".into())
+            outcome => return (outcome, attempts),
         }
     }
-    async fn exit(&self, _context: &mut DynamicStateContext) -> test_rig::Result<()> {
-        Ok(())
-    }
 }
 
-// Adapter for VerifyingDatabaseHandler to DynamicStateHandler
-struct VerifyingDatabaseHandlerAdapter;
-#[async_trait]
-impl DynamicStateHandler for VerifyingDatabaseHandlerAdapter {
-    async fn enter(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(multi_connection_states::verifying_database())
-    }
-    async fn execute(&self, context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        if let Some(ref mut conn) = context.connection {
-            if let Some(ref db_name) = context.database {
-                let query = format!("USE `{db_name}`");
-                match conn.query_drop(query) {
-                    Ok(()) => Ok(multi_connection_states::getting_version()),
-                    Err(e) => Err(format!("Database verification failed: {e}").into()),
-                }
-            } else {
-                Ok(multi_connection_states::getting_version())
-            }
-        } else {
-            Err("No connection available for database verification".into())
-        }
-    }
-    async fn exit(&self, _context: &mut DynamicStateContext) -> test_rig::Result<()> {
-        Ok(())
-    }
+/// Spawn `task`, holding a permit from `limiter` while it runs
+fn spawn_limited<F>(limiter: Arc<Semaphore>, task: F) -> JoinHandle<F::Output>
+where
+    F: std::future::Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(async move {
+        // The semaphore is never closed, so acquiring only waits for a free permit
+        let _permit = limiter.acquire_owned().await.ok();
+        task.await
+    })
 }
 
-// Adapter for GettingVersionHandler to DynamicStateHandler
-struct GettingVersionHandlerAdapter;
-#[async_trait]
-impl DynamicStateHandler for GettingVersionHandlerAdapter {
-    async fn enter(&self, _context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        Ok(multi_connection_states::getting_version())
-    }
-    async fn execute(&self, context: &mut DynamicStateContext) -> test_rig::Result<DynamicState> {
-        if let Some(ref mut conn) = context.connection {
-            let version_query = "SELECT VERSION()";
-            match conn.query_first::<String, _>(version_query) {
-                Ok(Some(version)) => {
-                    context.server_version = Some(version.clone());
-                    Ok(multi_connection_states::completed())
-                }
-                Ok(None) => Err("No version returned from server".into()),
-                Err(e) => Err(format!("Failed to get server version: {e}").into()),
-            }
-        } else {
-            Err("No connection available for getting version".into())
-        }
+/// Set the status of `connection_id` in `shared_state` and notify `observer`
+fn update_status(
+    shared_state: &Mutex<SharedTestState>,
+    observer: Option<&StatusObserver>,
+    connection_id: &str,
+    status: ConnectionStatus,
+    apply: impl FnOnce(&mut ConnectionResult),
+) {
+    if let Ok(mut state) = shared_state.lock()
+        && let Some(result) = state.connection_results.get_mut(connection_id)
+    {
+        result.status = status.clone();
+        apply(result);
     }
-    async fn exit(&self, _context: &mut DynamicStateContext) -> test_rig::Result<()> {
-        Ok(())
+    if let Some(observer) = observer {
+        observer(connection_id, &status);
     }
 }
 
@@ -329,9 +360,49 @@ impl SimpleMultiConnectionCoordinator {
         Self {
             shared_state: Arc::new(Mutex::new(SharedTestState::default())),
             connections: Vec::new(),
+            max_concurrency: default_max_concurrency(),
+            limiter: Arc::new(Semaphore::new(default_max_concurrency())),
+            status_observer: None,
+            latencies: LatencyRecorder::new(),
+            connection_retries: 0,
+            settings: ContextSettings::default(),
         }
     }
 
+    /// Run at most `max_concurrency` connections at the same time (at least one)
+    pub fn set_max_concurrency(&mut self, max_concurrency: usize) {
+        self.max_concurrency = max_concurrency.max(1);
+        self.limiter = Arc::new(Semaphore::new(self.max_concurrency));
+    }
+
+    /// Builder form of [`Self::set_max_concurrency`]
+    #[must_use]
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.set_max_concurrency(max_concurrency);
+        self
+    }
+
+    /// Most connections run at the same time
+    #[must_use]
+    pub fn max_concurrency(&self) -> usize {
+        self.max_concurrency
+    }
+
+    /// Call `observer` whenever a connection changes status
+    pub fn set_status_observer(&mut self, observer: StatusObserver) {
+        self.status_observer = Some(observer);
+    }
+
+    /// Settings applied to every connection's state machine
+    pub fn set_context_settings(&mut self, settings: ContextSettings) {
+        self.settings = settings;
+    }
+
+    /// Re-run a failed connection's state machine up to `retries` times
+    pub fn set_connection_retries(&mut self, retries: u32) {
+        self.connection_retries = retries;
+    }
+
     pub fn add_connection(&mut self, config: ConnectionConfig) {
         // Initialize connection result
         if let Ok(mut state) = self.shared_state.lock() {
@@ -339,10 +410,13 @@ impl SimpleMultiConnectionCoordinator {
                 config.id.clone(),
                 ConnectionResult {
                     connection_id: config.id.clone(),
+                    label: config.label.clone().unwrap_or_else(|| config.id.clone()),
                     host: config.host.clone(),
                     status: ConnectionStatus::NotStarted,
                     error: None,
                     version: None,
+                    duration: None,
+                    attempts: 0,
                 },
             );
         }
@@ -362,8 +436,9 @@ impl SimpleMultiConnectionCoordinator {
     #[allow(clippy::too_many_lines)]
     pub async fn run_all_connections(&self) -> Result<(), StateError> {
         println!(
-            "Starting {} connections concurrently...",
-            self.connections.len()
+            "Starting {} connections, at most {} at a time...",
+            self.connections.len(),
+            self.max_concurrency
         );
 
         let mut handles: Vec<JoinHandle<Result<(), ConnectError>>> = Vec::new();
@@ -371,93 +446,59 @@ impl SimpleMultiConnectionCoordinator {
         for connection in &self.connections {
             let shared_state = Arc::clone(&self.shared_state);
             let connection_id = connection.id.clone();
-            let host = connection.host.clone();
+            let host = format_host_port(&connection.host, connection.port);
             let username = connection.username.clone();
             let password = connection.password.clone();
             let database = connection.database.clone();
+            let latencies = self.latencies.clone();
+            let retries = self.connection_retries;
+            let settings = self.settings.clone();
+            let observer = self.status_observer.clone();
 
-            let handle = tokio::spawn(async move {
-                // Create dynamic state machine for this connection
-                let mut machine = DynamicStateMachine::new();
-
-                // Register handlers
-                machine.register_handler(
-                    dynamic_state!("initial", "Initial"),
-                    Box::new(InitialHandlerAdapter),
-                );
-                machine.register_handler(
-                    multi_connection_states::parsing_config(),
-                    Box::new(ParsingConfigHandlerAdapter {
-                        host,
-                        user: username,
-                        password,
-                        database,
-                    }),
-                );
-                machine.register_handler(
-                    multi_connection_states::connecting(),
-                    Box::new(ConnectingHandlerAdapter),
-                );
-                machine.register_handler(
-                    multi_connection_states::testing_connection(),
-                    Box::new(TestingConnectionHandlerAdapter),
-                );
-                machine.register_handler(
-                    multi_connection_states::verifying_database(),
-                    Box::new(VerifyingDatabaseHandlerAdapter),
-                );
-                machine.register_handler(
-                    multi_connection_states::getting_version(),
-                    Box::new(GettingVersionHandlerAdapter),
-                );
-
-                // Register valid transitions
-                register_transitions!(
-                    machine,
-                    dynamic_state!("initial", "Initial"),
-                    [multi_connection_states::parsing_config()]
-                );
-                register_transitions!(
-                    machine,
-                    multi_connection_states::parsing_config(),
-                    [multi_connection_states::connecting()]
-                );
-                register_transitions!(
-                    machine,
-                    multi_connection_states::connecting(),
-                    [multi_connection_states::testing_connection()]
-                );
-                register_transitions!(
-                    machine,
-                    multi_connection_states::testing_connection(),
-                    [multi_connection_states::verifying_database()]
-                );
-                register_transitions!(
-                    machine,
-                    multi_connection_states::verifying_database(),
-                    [multi_connection_states::getting_version()]
-                );
-                register_transitions!(
-                    machine,
-                    multi_connection_states::getting_version(),
-                    [multi_connection_states::completed()]
+            let handle = spawn_limited(Arc::clone(&self.limiter), async move {
+                update_status(
+                    &shared_state,
+                    observer.as_ref(),
+                    &connection_id,
+                    ConnectionStatus::Connecting,
+                    |_| {},
                 );
 
-                // Update status to connecting
-                if let Ok(mut state) = shared_state.lock()
-                    && let Some(result) = state.connection_results.get_mut(&connection_id)
-                {
-                    result.status = ConnectionStatus::Connecting;
-                }
+                // Run a fresh state machine per attempt, timing the connect through version flow
+                let started = std::time::Instant::now();
+                let (outcome, attempts) = run_with_retries(retries, |attempt| {
+                    if attempt > 1 {
+                        println!("↻ Retrying connection {connection_id} (attempt {attempt})");
+                    }
+                    let mut machine =
+                        build_connection_machine(&host, &username, &password, database.as_deref());
+                    settings.configure_dynamic(&mut machine);
+                    async move {
+                        let attempt_started = std::time::Instant::now();
+                        machine.run().await?;
+                        Ok((
+                            machine.get_context().server_version.clone(),
+                            attempt_started.elapsed(),
+                        ))
+                    }
+                })
+                .await;
 
-                // Run the state machine
-                match machine.run().await {
-                    Ok(()) => {
-                        // Update status to completed
+                match outcome {
+                    Ok((version, elapsed)) => {
+                        latencies.record(elapsed);
+                        update_status(
+                            &shared_state,
+                            observer.as_ref(),
+                            &connection_id,
+                            ConnectionStatus::Completed,
+                            |result| {
+                                result.version = version;
+                                result.duration = Some(started.elapsed());
+                                result.attempts = attempts;
+                            },
+                        );
                         if let Ok(mut state) = shared_state.lock() {
-                            if let Some(result) = state.connection_results.get_mut(&connection_id) {
-                                result.status = ConnectionStatus::Completed;
-                            }
                             state.global_status = "All connections completed".to_string();
                         }
                         println!("✓ Connection {connection_id} completed successfully");
@@ -465,14 +506,20 @@ impl SimpleMultiConnectionCoordinator {
                         Ok(())
                     }
                     Err(e) => {
-                        // Update status to failed
-                        if let Ok(mut state) = shared_state.lock()
-                            && let Some(result) = state.connection_results.get_mut(&connection_id)
-                        {
-                            result.status = ConnectionStatus::Failed;
-                            result.error = Some(e.to_string());
-                        }
-                        eprintln!("✗ Connection {connection_id} failed: {e}");
+                        update_status(
+                            &shared_state,
+                            observer.as_ref(),
+                            &connection_id,
+                            ConnectionStatus::Failed,
+                            |result| {
+                                result.error = Some(e.to_string());
+                                result.duration = Some(started.elapsed());
+                                result.attempts = attempts;
+                            },
+                        );
+                        eprintln!(
+                            "✗ Connection {connection_id} failed after {attempts} attempt(s): {e}"
+                        );
                         Err(e)
                     }
                 }
@@ -482,73 +529,190 @@ impl SimpleMultiConnectionCoordinator {
         }
 
         // Wait for all connections to complete
-        for handle in handles {
-            match handle.await {
-                Ok(Ok(())) => {}
-                Ok(Err(e)) => eprintln!("Connection task failed: {e}"),
-                Err(e) => eprintln!("Task join failed: {e}"),
+        for result in join_all_tasks(handles).await {
+            match result {
+                TaskResult::Success(()) => {}
+                TaskResult::HandlerError(e) => eprintln!("Connection task failed: {e}"),
+                TaskResult::JoinError(e) => eprintln!("Task join failed: {e}"),
             }
         }
 
         Ok(())
     }
 
-    /// Print final results
-    pub fn print_results(&self) {
+    /// Format final results, one entry per connection
+    #[must_use]
+    pub fn format_results(&self) -> String {
+        use std::fmt::Write;
+
+        let mut output = String::new();
         if let Ok(state) = self.shared_state.lock() {
-            println!("\n=== Final Results ===");
-            println!("Global Status: {}", state.global_status);
-            println!("\nConnection Results:");
+            let _ = writeln!(output, "\n=== Final Results ===");
+            let _ = writeln!(output, "Global Status: {}", state.global_status);
+            let _ = writeln!(output, "\nConnection Results:");
 
             for (conn_id, result) in &state.connection_results {
-                println!("  {}: {:?} - {}", conn_id, result.status, result.host);
+                let name = if result.label == *conn_id {
+                    conn_id.clone()
+                } else {
+                    format!("{} ({conn_id})", result.label)
+                };
+                let _ = writeln!(output, "  {}: {:?} - {}", name, result.status, result.host);
                 if let Some(error) = &result.error {
-                    println!("    Error: {error}");
+                    let _ = writeln!(output, "    Error: {error}");
                 }
                 if let Some(version) = &result.version {
-                    println!("    Version: {version}");
+                    let _ = writeln!(output, "    Version: {version}");
+                }
+                if result.attempts > 1 {
+                    let _ = writeln!(output, "    Attempts: {}", result.attempts);
                 }
             }
         }
+        if let Some(summary) = self.latencies.summary() {
+            let _ = writeln!(output, "\nConnect Latency (all connections): {summary}");
+        }
+        output
+    }
+
+    /// Print final results
+    pub fn print_results(&self) {
+        print!("{}", self.format_results());
+    }
+
+    /// Write one CSV row per connection to `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created or written.
+    pub fn export_csv(&self, path: &Path) -> std::io::Result<()> {
+        let results: Vec<ConnectionResult> = self
+            .shared_state
+            .lock()
+            .map(|state| state.connection_results.values().cloned().collect())
+            .unwrap_or_default();
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        write_results_csv(&results, &mut file)?;
+        file.flush()
+    }
+
+    /// Write the failed connections to `path` as a JSON array
+    ///
+    /// The file is written even when nothing failed, so its presence shows
+    /// the run finished.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created or written.
+    pub fn export_dead_letter(&self, path: &Path) -> std::io::Result<()> {
+        let results: Vec<ConnectionResult> = self
+            .shared_state
+            .lock()
+            .map(|state| state.connection_results.values().cloned().collect())
+            .unwrap_or_default();
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        write_dead_letter(&results, &mut file)?;
+        file.flush()
+    }
+}
+
+/// One permanently failed connection in the dead-letter file
+#[derive(Debug, Serialize)]
+struct DeadLetter<'a> {
+    id: &'a str,
+    host: &'a str,
+    error: Option<&'a str>,
+    attempts: u32,
+}
+
+/// Write the `Failed` entries of `results` as a pretty-printed JSON array,
+/// sorted by connection id
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn write_dead_letter<W: Write>(
+    results: &[ConnectionResult],
+    out: &mut W,
+) -> std::io::Result<()> {
+    let mut failed: Vec<DeadLetter> = results
+        .iter()
+        .filter(|result| matches!(result.status, ConnectionStatus::Failed))
+        .map(|result| DeadLetter {
+            id: &result.connection_id,
+            host: &result.host,
+            error: result.error.as_deref(),
+            attempts: result.attempts,
+        })
+        .collect();
+    failed.sort_by(|a, b| a.id.cmp(b.id));
+    serde_json::to_writer_pretty(&mut *out, &failed)?;
+    writeln!(out)
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+fn csv_field(value: &str) -> Cow<'_, str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Write `results` as CSV with an `id,host,status,version,error,duration_ms` header
+///
+/// Rows are sorted by connection id; missing values are left empty.
+///
+/// # Errors
+///
+/// Returns an error if writing to `out` fails.
+pub fn write_results_csv<W: Write>(
+    results: &[ConnectionResult],
+    out: &mut W,
+) -> std::io::Result<()> {
+    let mut sorted: Vec<&ConnectionResult> = results.iter().collect();
+    sorted.sort_by(|a, b| a.connection_id.cmp(&b.connection_id));
+
+    writeln!(out, "id,host,status,version,error,duration_ms")?;
+    for result in sorted {
+        let status = format!("{:?}", result.status);
+        let duration = result
+            .duration
+            .map(|d| d.as_millis().to_string())
+            .unwrap_or_default();
+        writeln!(
+            out,
+            "{},{},{},{},{},{}",
+            csv_field(&result.connection_id),
+            csv_field(&result.host),
+            csv_field(&status),
+            csv_field(result.version.as_deref().unwrap_or_default()),
+            csv_field(result.error.as_deref().unwrap_or_default()),
+            duration
+        )?;
     }
+    Ok(())
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     print_test_header("Simple Multi-Connection TiDB Testing");
     let args = Args::parse();
+    args.validate_connection_count()?;
     args.init_logging()?;
-    args.print_connection_info();
+    args.common.init_retry_classification()?;
+    args.print_startup_banner()?;
 
     let mut coordinator = SimpleMultiConnectionCoordinator::new();
+    if let Some(max_concurrent) = args.max_concurrent {
+        coordinator.set_max_concurrency(max_concurrent as usize);
+    }
+    coordinator.set_connection_retries(args.connection_retries);
+    coordinator.set_context_settings(args.common.context_settings()?);
 
-    // Add multiple connections
-    coordinator.add_connection(ConnectionConfig {
-        id: "primary".to_string(),
-        host: "tidb-primary.example.com".to_string(),
-        port: 4000,
-        username: "user1".to_string(),
-        password: "password1".to_string(),
-        database: Some("test_db".to_string()),
-    });
-
-    coordinator.add_connection(ConnectionConfig {
-        id: "secondary".to_string(),
-        host: "tidb-secondary.example.com".to_string(),
-        port: 4000,
-        username: "user2".to_string(),
-        password: "password2".to_string(),
-        database: Some("test_db".to_string()),
-    });
-
-    coordinator.add_connection(ConnectionConfig {
-        id: "backup".to_string(),
-        host: "tidb-backup.example.com".to_string(),
-        port: 4000,
-        username: "user3".to_string(),
-        password: "password3".to_string(),
-        database: Some("backup_db".to_string()),
-    });
+    for config in args.connection_configs()? {
+        coordinator.add_connection(config);
+    }
 
     // Run all connections concurrently
     if let Err(e) = coordinator.run_all_connections().await {
@@ -558,6 +722,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Print results
     coordinator.print_results();
+    if let Some(path) = &args.results_csv {
+        coordinator.export_csv(path)?;
+        println!("Wrote connection results to {}", path.display());
+    }
+    if let Some(path) = &args.dead_letter {
+        coordinator.export_dead_letter(path)?;
+        println!("Wrote failed connections to {}", path.display());
+    }
 
     print_success("Multi-connection testing completed!");
     Ok(())
@@ -579,16 +751,111 @@ mod tests {
             "testuser",
         ]);
         assert_eq!(args.connection_count, 5);
-        assert_eq!(args.common.host, "localhost:4000");
-        assert_eq!(args.common.user, "testuser");
+        assert_eq!(args.common.host.as_deref(), Some("localhost:4000"));
+        assert_eq!(args.common.user.as_deref(), Some("testuser"));
     }
 
     #[test]
     fn test_args_defaults() {
         let args = Args::parse_from(["test-bin"]);
         assert_eq!(args.connection_count, 2); // default value
-        assert_eq!(args.common.host, "localhost:4000"); // default value
-        assert_eq!(args.common.user, "root"); // default value
+        assert_eq!(args.common.host, None); // default value
+        assert_eq!(args.common.user, None); // default value
+    }
+
+    #[test]
+    fn test_connection_count_above_cap_is_rejected() {
+        let args = Args::parse_from(["test-bin", "--connection-count", "100000"]);
+        assert_eq!(args.max_connections, DEFAULT_MAX_CONNECTIONS);
+        assert!(args.validate_connection_count().is_err());
+
+        let args = Args::parse_from([
+            "test-bin",
+            "--connection-count",
+            "1500",
+            "--max-connections",
+            "2000",
+        ]);
+        assert!(args.validate_connection_count().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_spawn_limited_caps_concurrency() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let limiter = Arc::new(Semaphore::new(3));
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let completed = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..10)
+            .map(|_| {
+                let running = Arc::clone(&running);
+                let peak = Arc::clone(&peak);
+                let completed = Arc::clone(&completed);
+                spawn_limited(Arc::clone(&limiter), async move {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    completed.fetch_add(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap();
+        }
+
+        assert_eq!(completed.load(Ordering::SeqCst), 10);
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+        assert!(Args::try_parse_from(["test-bin", "--max-concurrent", "0"]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_bounded_concurrency_runs_every_connection() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let connecting = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let mut coordinator = SimpleMultiConnectionCoordinator::new().with_max_concurrency(2);
+        let (observed, observed_peak) = (Arc::clone(&connecting), Arc::clone(&peak));
+        coordinator.set_status_observer(Arc::new(move |_, status| match status {
+            ConnectionStatus::Connecting => {
+                let now = observed.fetch_add(1, Ordering::SeqCst) + 1;
+                observed_peak.fetch_max(now, Ordering::SeqCst);
+            }
+            ConnectionStatus::Completed | ConnectionStatus::Failed => {
+                observed.fetch_sub(1, Ordering::SeqCst);
+            }
+            _ => {}
+        }));
+
+        // Nothing listens on port 1, so every connection is refused
+        for i in 0..6 {
+            coordinator.add_connection(ConnectionConfig {
+                id: format!("conn-{i}"),
+                label: None,
+                host: "127.0.0.1".to_string(),
+                port: 1,
+                username: "root".to_string(),
+                password: String::new(),
+                database: None,
+            });
+        }
+        coordinator.run_all_connections().await.unwrap();
+
+        let state = coordinator.get_shared_state();
+        let state = state.lock().unwrap();
+        assert_eq!(state.connection_results.len(), 6);
+        assert!(
+            state
+                .connection_results
+                .values()
+                .all(|result| matches!(result.status, ConnectionStatus::Failed))
+        );
+        assert_eq!(connecting.load(Ordering::SeqCst), 0);
+        let peak = peak.load(Ordering::SeqCst);
+        assert!((1..=2).contains(&peak), "peak concurrency was {peak}");
     }
 
     #[test]
@@ -602,6 +869,7 @@ mod tests {
     fn test_connection_config_creation() {
         let config = ConnectionConfig {
             id: "test-1".to_string(),
+            label: None,
             host: "localhost".to_string(),
             port: 4000,
             username: "testuser".to_string(),
@@ -615,10 +883,248 @@ mod tests {
         assert_eq!(config.database, Some("testdb".to_string()));
     }
 
+    #[test]
+    fn test_label_appears_in_results() {
+        let mut coordinator = SimpleMultiConnectionCoordinator::new();
+        for (id, label) in [("primary", Some("us-east-primary")), ("replica", None)] {
+            coordinator.add_connection(ConnectionConfig {
+                id: id.to_string(),
+                label: label.map(ToString::to_string),
+                host: "localhost".to_string(),
+                port: 4000,
+                username: "root".to_string(),
+                password: String::new(),
+                database: None,
+            });
+        }
+
+        let output = coordinator.format_results();
+        assert!(output.contains("us-east-primary (primary): NotStarted"));
+        assert!(output.contains("  replica: NotStarted"));
+        assert!(!output.contains("Connect Latency"));
+    }
+
+    #[test]
+    fn test_latency_summary_in_results() {
+        let coordinator = SimpleMultiConnectionCoordinator::new();
+        for millis in [10, 20, 30, 40] {
+            coordinator
+                .latencies
+                .record(std::time::Duration::from_millis(millis));
+        }
+
+        let output = coordinator.format_results();
+        assert!(output.contains("Connect Latency (all connections): n=4 mean=25ms p50=20ms"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_connection_succeeds_on_retry() {
+        let (outcome, attempts) = run_with_retries(2, |attempt| async move {
+            if attempt == 1 {
+                Err(ConnectError::Network("connection reset".to_string()))
+            } else {
+                Ok(attempt)
+            }
+        })
+        .await;
+        assert_eq!(outcome.unwrap(), 2);
+        assert_eq!(attempts, 2);
+
+        // Without retries the first failure is final
+        let (outcome, attempts) = run_with_retries(0, |_| async {
+            Err::<(), _>(ConnectError::Network("connection reset".to_string()))
+        })
+        .await;
+        assert!(outcome.is_err());
+        assert_eq!(attempts, 1);
+
+        // Retries stop at the limit
+        let (outcome, attempts) = run_with_retries(3, |_| async {
+            Err::<(), _>(ConnectError::Network("connection reset".to_string()))
+        })
+        .await;
+        assert!(outcome.is_err());
+        assert_eq!(attempts, 4);
+        assert_eq!(Args::parse_from(["test-bin"]).connection_retries, 0);
+    }
+
+    #[test]
+    fn test_results_csv_rows() {
+        let results = vec![
+            ConnectionResult {
+                connection_id: "secondary".to_string(),
+                label: "secondary".to_string(),
+                host: "tidb-2:4000".to_string(),
+                status: ConnectionStatus::Failed,
+                error: Some("Access denied for user \"root\", using password".to_string()),
+                version: None,
+                duration: Some(Duration::from_millis(250)),
+                attempts: 1,
+            },
+            ConnectionResult {
+                connection_id: "primary".to_string(),
+                label: "us-east-primary".to_string(),
+                host: "tidb-1:4000".to_string(),
+                status: ConnectionStatus::Completed,
+                error: None,
+                version: Some("8.0.11-TiDB-v7.5.1".to_string()),
+                duration: Some(Duration::from_millis(42)),
+                attempts: 1,
+            },
+            ConnectionResult {
+                connection_id: "backup".to_string(),
+                label: "backup".to_string(),
+                host: "tidb-3:4000".to_string(),
+                status: ConnectionStatus::NotStarted,
+                error: None,
+                version: None,
+                duration: None,
+                attempts: 0,
+            },
+        ];
+
+        let mut out = Vec::new();
+        write_results_csv(&results, &mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                "id,host,status,version,error,duration_ms",
+                "backup,tidb-3:4000,NotStarted,,,",
+                "primary,tidb-1:4000,Completed,8.0.11-TiDB-v7.5.1,,42",
+                "secondary,tidb-2:4000,Failed,,\"Access denied for user \"\"root\"\", using password\",250",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dead_letter_lists_failed_connections() {
+        let failed = |id: &str, host: &str, error: &str, attempts| ConnectionResult {
+            connection_id: id.to_string(),
+            label: id.to_string(),
+            host: host.to_string(),
+            status: ConnectionStatus::Failed,
+            error: Some(error.to_string()),
+            version: None,
+            duration: Some(Duration::from_millis(100)),
+            attempts,
+        };
+        let coordinator = SimpleMultiConnectionCoordinator::new();
+        {
+            let mut state = coordinator.shared_state.lock().unwrap();
+            for result in [
+                failed("conn_2", "tidb-2:4000", "Connection refused", 3),
+                failed("conn_1", "tidb-1:4000", "Access denied for user 'root'", 1),
+                ConnectionResult {
+                    connection_id: "conn_0".to_string(),
+                    label: "conn_0".to_string(),
+                    host: "tidb-0:4000".to_string(),
+                    status: ConnectionStatus::Completed,
+                    error: None,
+                    version: Some("8.0.11-TiDB-v7.5.1".to_string()),
+                    duration: Some(Duration::from_millis(42)),
+                    attempts: 2,
+                },
+            ] {
+                state
+                    .connection_results
+                    .insert(result.connection_id.clone(), result);
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dead_letter.json");
+        coordinator.export_dead_letter(&path).unwrap();
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            written,
+            serde_json::json!([
+                {
+                    "id": "conn_1",
+                    "host": "tidb-1:4000",
+                    "error": "Access denied for user 'root'",
+                    "attempts": 1
+                },
+                {
+                    "id": "conn_2",
+                    "host": "tidb-2:4000",
+                    "error": "Connection refused",
+                    "attempts": 3
+                }
+            ])
+        );
+
+        let mut out = Vec::new();
+        write_dead_letter(&[], &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "[]\n");
+        assert!(Args::parse_from(["test-bin"]).dead_letter.is_none());
+    }
+
+    #[test]
+    fn test_connection_machine_graph_is_valid() {
+        let machine = build_connection_machine("localhost:4000", "root", "", Some("test"));
+        assert!(machine.validate_graph().is_ok());
+    }
+
+    #[test]
+    fn test_connection_count_generates_connections() {
+        let args = Args::parse_from([
+            "test-bin",
+            "--connection-count",
+            "4",
+            "-H",
+            "tidb.example.com:4001",
+            "-u",
+            "app",
+            "--password",
+            "secret",
+            "-d",
+            "orders",
+        ]);
+        let mut coordinator = SimpleMultiConnectionCoordinator::new();
+        for config in args.connection_configs().unwrap() {
+            coordinator.add_connection(config);
+        }
+
+        assert_eq!(coordinator.connections.len(), 4);
+        let ids: Vec<&str> = coordinator
+            .connections
+            .iter()
+            .map(|c| c.id.as_str())
+            .collect();
+        assert_eq!(ids, ["conn-0", "conn-1", "conn-2", "conn-3"]);
+        for config in &coordinator.connections {
+            assert_eq!(config.host, "tidb.example.com");
+            assert_eq!(config.port, 4001);
+            assert_eq!(config.username, "app");
+            assert_eq!(config.password, "secret");
+            assert_eq!(config.database.as_deref(), Some("orders"));
+        }
+        assert_eq!(
+            coordinator
+                .get_shared_state()
+                .lock()
+                .unwrap()
+                .connection_results
+                .len(),
+            4
+        );
+    }
+
     #[test]
     fn test_coordinator_creation() {
         let coordinator = SimpleMultiConnectionCoordinator::new();
         assert_eq!(coordinator.connections.len(), 0);
+        assert!(coordinator.max_concurrency() >= 1);
+        assert_eq!(
+            SimpleMultiConnectionCoordinator::new()
+                .with_max_concurrency(0)
+                .max_concurrency(),
+            1
+        );
 
         // Test that shared state is accessible
         let shared_state = coordinator.get_shared_state();
@@ -0,0 +1,218 @@
+//! # Server Capability Detection
+//!
+//! Parses the `TiDB` release out of `SELECT VERSION()` (e.g.
+//! `8.0.11-TiDB-v7.5.0`) so tests can skip features the connected server
+//! does not support instead of failing on them.
+
+use crate::connection::get_server_version;
+use crate::errors::{ConnectError, Result};
+use mysql::PooledConn;
+use std::fmt;
+use std::str::FromStr;
+
+/// A `TiDB` release version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TidbVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl TidbVersion {
+    #[must_use]
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse the `TiDB` release from a `VERSION()` string
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConnectError::Parse` if the string has no `-TiDB-v<x.y.z>` part.
+    pub fn parse(version: &str) -> Result<Self> {
+        let invalid = || ConnectError::Parse(format!("Not a TiDB version string: '{version}'"));
+        let release = version
+            .split("-TiDB-v")
+            .nth(1)
+            .ok_or_else(invalid)?
+            .split(|c: char| c != '.' && !c.is_ascii_digit())
+            .next()
+            .unwrap_or_default();
+
+        let mut parts = release.split('.').map(str::parse::<u32>);
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(Ok(major)), Some(Ok(minor)), Some(Ok(patch))) => {
+                Ok(Self::new(major, minor, patch))
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Whether this release supports `capability`
+    #[must_use]
+    pub fn supports(self, capability: Capability) -> bool {
+        self >= capability.min_version()
+    }
+}
+
+impl fmt::Display for TidbVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for TidbVersion {
+    type Err = ConnectError;
+
+    /// Parse a plain release such as `7.5.0` or `v7.5.0`
+    fn from_str(release: &str) -> Result<Self> {
+        let digits = release.strip_prefix('v').unwrap_or(release);
+        let mut parts = digits.split('.').map(str::parse::<u32>);
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(Ok(major)), Some(Ok(minor)), Some(Ok(patch)), None) => {
+                Ok(Self::new(major, minor, patch))
+            }
+            _ => Err(ConnectError::Parse(format!(
+                "Invalid TiDB version '{release}'; expected MAJOR.MINOR.PATCH, e.g. 7.5.0"
+            ))),
+        }
+    }
+}
+
+/// Check a `VERSION()` string against the `--require-tidb-version` minimum
+///
+/// Does nothing if `minimum` is `None`.
+///
+/// # Errors
+///
+/// Returns `ConnectError::Database` if the server is older than `minimum` or
+/// its version cannot be parsed.
+pub fn require_tidb_version(server_version: &str, minimum: Option<TidbVersion>) -> Result<()> {
+    let Some(minimum) = minimum else {
+        return Ok(());
+    };
+    let found = TidbVersion::parse(server_version).map_err(|_| {
+        ConnectError::Database(format!(
+            "Cannot check required TiDB {minimum}: server reported '{server_version}'"
+        ))
+    })?;
+    if found < minimum {
+        return Err(ConnectError::Database(format!(
+            "TiDB {found} is older than the required {minimum}"
+        )));
+    }
+    Ok(())
+}
+
+/// Optional server features that tests depend on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    /// `JSON` columns and `JSON_*` functions
+    Json,
+    /// `AUTO_RANDOM` primary keys
+    AutoRandom,
+    /// `RANGE` and `HASH` table partitioning
+    Partitioning,
+    /// Row time-to-live (`TTL = col + INTERVAL ...`)
+    Ttl,
+}
+
+impl Capability {
+    /// First `TiDB` release with this feature
+    #[must_use]
+    pub const fn min_version(self) -> TidbVersion {
+        match self {
+            Capability::Json => TidbVersion::new(2, 1, 0),
+            Capability::AutoRandom => TidbVersion::new(4, 0, 0),
+            Capability::Partitioning => TidbVersion::new(3, 0, 0),
+            Capability::Ttl => TidbVersion::new(6, 5, 0),
+        }
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Capability::Json => write!(f, "JSON"),
+            Capability::AutoRandom => write!(f, "AUTO_RANDOM"),
+            Capability::Partitioning => write!(f, "partitioning"),
+            Capability::Ttl => write!(f, "TTL"),
+        }
+    }
+}
+
+/// Query the server version and parse its `TiDB` release
+///
+/// # Errors
+///
+/// Returns an error if the version query fails or the server is not `TiDB`.
+pub fn detect_version(conn: &mut PooledConn) -> Result<TidbVersion> {
+    let version = get_server_version(conn)?
+        .ok_or_else(|| ConnectError::Database("No version returned from server".to_string()))?;
+    TidbVersion::parse(&version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tidb_version() {
+        assert_eq!(
+            TidbVersion::parse("8.0.11-TiDB-v7.5.0").unwrap(),
+            TidbVersion::new(7, 5, 0)
+        );
+        assert_eq!(
+            TidbVersion::parse("5.7.25-TiDB-v6.1.2-serverless").unwrap(),
+            TidbVersion::new(6, 1, 2)
+        );
+        assert!(TidbVersion::parse("8.0.33").is_err());
+        assert!(TidbVersion::parse("5.7.25-TiDB-vnext").is_err());
+    }
+
+    #[test]
+    fn test_parse_required_version() {
+        assert_eq!(
+            "7.5.0".parse::<TidbVersion>().unwrap(),
+            TidbVersion::new(7, 5, 0)
+        );
+        assert_eq!(
+            "v6.1.2".parse::<TidbVersion>().unwrap(),
+            TidbVersion::new(6, 1, 2)
+        );
+        assert!("7.5".parse::<TidbVersion>().is_err());
+        assert!("7.5.0.1".parse::<TidbVersion>().is_err());
+        assert!("latest".parse::<TidbVersion>().is_err());
+    }
+
+    #[test]
+    fn test_require_tidb_version() {
+        let minimum = Some(TidbVersion::new(7, 1, 0));
+        assert!(require_tidb_version("8.0.11-TiDB-v7.1.0", minimum).is_ok());
+        assert!(require_tidb_version("8.0.11-TiDB-v8.5.1", minimum).is_ok());
+
+        let err = require_tidb_version("8.0.11-TiDB-v6.5.3", minimum).unwrap_err();
+        assert!(matches!(err, ConnectError::Database(_)));
+        assert_eq!(
+            err.to_string(),
+            "Database error: TiDB v6.5.3 is older than the required v7.1.0"
+        );
+        assert!(require_tidb_version("8.0.33", minimum).is_err());
+
+        assert!(require_tidb_version("8.0.33", None).is_ok());
+    }
+
+    #[test]
+    fn test_capability_gating() {
+        assert!(TidbVersion::new(7, 5, 0).supports(Capability::Json));
+        assert!(TidbVersion::new(2, 1, 0).supports(Capability::Json));
+        assert!(!TidbVersion::new(2, 0, 11).supports(Capability::Json));
+        assert!(TidbVersion::new(4, 0, 0).supports(Capability::AutoRandom));
+        assert!(!TidbVersion::new(3, 1, 2).supports(Capability::AutoRandom));
+        assert!(!TidbVersion::new(2, 1, 19).supports(Capability::Partitioning));
+    }
+}
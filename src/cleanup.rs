@@ -0,0 +1,176 @@
+//! # Scratch Table Cleanup
+//!
+//! Test binaries create scratch tables such as `isolation_test_<timestamp>`.
+//! A [`ScratchTable`] guard drops its table when it goes out of scope, so a
+//! run that errors or panics part way through does not leave the table behind.
+//!
+//! `Drop` cannot be async and the connection that created the table may be
+//! borrowed, broken or mid-transaction by then, so cleanup always opens a
+//! fresh connection from the guard's factory and blocks until the table is
+//! dropped. On a multi-threaded tokio runtime the worker is handed over with
+//! `block_in_place` first, so other tasks keep running. If that connection
+//! cannot be established the table is left in place and a warning is logged. Cleanup does not run if the process exits without
+//! unwinding, e.g. via `std::process::exit`, so drop the guard first.
+
+use crate::errors::Result;
+use crate::query::RowLimit;
+use crate::recording::QueryExecutor;
+use tokio::runtime::RuntimeFlavor;
+
+/// Opens the connection used to drop the table
+type ConnectionFactory<C> = Box<dyn Fn() -> Result<C> + Send + Sync>;
+
+/// Drops a scratch table on a fresh connection when dropped
+pub struct ScratchTable<C: QueryExecutor> {
+    table: String,
+    connect: ConnectionFactory<C>,
+    armed: bool,
+}
+
+impl<C: QueryExecutor> ScratchTable<C> {
+    /// Guard `table`, which should already exist, dropping it via a connection from `connect`
+    #[must_use]
+    pub fn new(
+        table: impl Into<String>,
+        connect: impl Fn() -> Result<C> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            table: table.into(),
+            connect: Box::new(connect),
+            armed: true,
+        }
+    }
+
+    /// Name of the guarded table, as given to [`ScratchTable::new`]
+    #[must_use]
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// Keep the table, e.g. to inspect it after a failed run
+    pub fn disarm(&mut self) {
+        self.armed = false;
+    }
+
+    fn drop_table(&self) -> Result<()> {
+        let mut conn = (self.connect)()?;
+        let sql = format!("DROP TABLE IF EXISTS {}", self.table);
+        conn.run_query(&sql, RowLimit::Max(Some(0)))?;
+        Ok(())
+    }
+}
+
+impl<C: QueryExecutor> Drop for ScratchTable<C> {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        // Let the runtime move other tasks off this worker while the DROP blocks;
+        // a current-thread runtime has nowhere to move them, so just block
+        let outcome = match tokio::runtime::Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                tokio::task::block_in_place(|| self.drop_table())
+            }
+            _ => self.drop_table(),
+        };
+        match outcome {
+            Ok(()) => tracing::debug!("Dropped scratch table {}", self.table),
+            Err(e) => tracing::warn!("Failed to drop scratch table {}: {}", self.table, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ConnectError;
+    use crate::test_support::MockConnection;
+    use mysql::prelude::Queryable;
+
+    /// Factory handing out clones of `conn`, which share its statement log
+    fn factory(
+        conn: &MockConnection,
+    ) -> impl Fn() -> Result<MockConnection> + Send + Sync + 'static {
+        let conn = conn.clone();
+        move || Ok(conn.clone())
+    }
+
+    #[test]
+    fn test_drop_uses_fresh_connection() {
+        let conn = MockConnection::default();
+        let guard = ScratchTable::new("isolation_test_1", factory(&conn));
+        assert_eq!(guard.table(), "isolation_test_1");
+        assert!(conn.statements().is_empty());
+
+        drop(guard);
+        assert_eq!(
+            conn.statements(),
+            vec!["DROP TABLE IF EXISTS isolation_test_1"]
+        );
+    }
+
+    #[test]
+    fn test_disarmed_guard_keeps_table() {
+        let conn = MockConnection::default();
+        let mut guard = ScratchTable::new("isolation_test_2", factory(&conn));
+        guard.disarm();
+        drop(guard);
+        assert!(conn.statements().is_empty());
+    }
+
+    #[test]
+    fn test_failed_connection_does_not_panic() {
+        let guard = ScratchTable::<MockConnection>::new("isolation_test_3", || {
+            Err(ConnectError::Network("connection refused".to_string()))
+        });
+        drop(guard);
+    }
+
+    #[tokio::test]
+    async fn test_drop_inside_async_runtime() {
+        let conn = MockConnection::default();
+        drop(ScratchTable::new("isolation_test_4", factory(&conn)));
+        assert_eq!(conn.statements().len(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_drop_inside_multi_thread_runtime() {
+        let conn = MockConnection::default();
+        drop(ScratchTable::new("isolation_test_5", factory(&conn)));
+        assert_eq!(
+            conn.statements(),
+            vec!["DROP TABLE IF EXISTS isolation_test_5"]
+        );
+    }
+
+    #[test]
+    fn test_scratch_table_dropped_against_live_tidb() {
+        let Some(mut conn) =
+            crate::connection::connect_from_env().expect("failed to connect to TIDB_HOST")
+        else {
+            eprintln!("TIDB_HOST not set, skipping live scratch table test");
+            return;
+        };
+        let database = std::env::var("TIDB_DATABASE").unwrap_or_else(|_| "test".to_string());
+        let table = format!("scratch_cleanup_{}", std::process::id());
+        conn.query_drop(format!("CREATE DATABASE IF NOT EXISTS `{database}`"))
+            .unwrap();
+        conn.query_drop(format!("CREATE TABLE `{database}`.`{table}` (id INT)"))
+            .unwrap();
+
+        let guard = ScratchTable::new(format!("`{database}`.`{table}`"), || {
+            crate::connection::connect_from_env()?
+                .ok_or_else(|| ConnectError::Configuration("TIDB_HOST not set".to_string()))
+        });
+        drop(guard);
+
+        let remaining: Option<u64> = conn
+            .exec_first(
+                "SELECT COUNT(*) FROM information_schema.tables \
+                 WHERE table_schema = ? AND table_name = ?",
+                (&database, &table),
+            )
+            .unwrap();
+        assert_eq!(remaining, Some(0));
+    }
+}
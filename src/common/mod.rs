@@ -1,3 +1,4 @@
 //! Common modules shared between workspaces
 
 pub mod python_tests;
+pub mod suite_report;
@@ -1,14 +1,191 @@
 //! Common Python test infrastructure for test workspaces
 
+use crate::common::suite_report::SuiteResult;
 use crate::config::DatabaseConfig;
+use crate::errors::{ConnectError, RetryConfig};
 use crate::state_machine::StateMachine;
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Interpreter used when `--python-bin` is not given
+pub const DEFAULT_PYTHON_BIN: &str = "python3";
+
+/// Prefix of the line a Python test prints to report its result as JSON
+pub const RESULT_MARKER: &str = "__RESULT__";
+
+/// Outcome reported by a Python test
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PythonTestStatus {
+    Passed,
+    Failed,
+}
+
+/// Result a Python test reports on its final `__RESULT__{json}` line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PythonTestResult {
+    pub status: PythonTestStatus,
+    #[serde(default)]
+    pub module: Option<String>,
+    /// Handler that failed, if the failure happened inside a handler
+    #[serde(default)]
+    pub handler: Option<String>,
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Python exception class name, e.g. `AssertionError`
+    #[serde(default)]
+    pub error_type: Option<String>,
+    /// Handlers that were executed
+    #[serde(default)]
+    pub handlers: Vec<String>,
+}
+
+impl PythonTestResult {
+    /// Parse the last `__RESULT__` line from a test's stdout
+    ///
+    /// Returns `None` when no result line is present or it is not valid JSON, in
+    /// which case callers fall back to the exit status and output scanning.
+    #[must_use]
+    pub fn parse(stdout: &str) -> Option<Self> {
+        stdout
+            .lines()
+            .rev()
+            .find_map(|line| line.trim().strip_prefix(RESULT_MARKER))
+            .and_then(|json| serde_json::from_str(json).ok())
+    }
+
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.status == PythonTestStatus::Passed
+    }
+
+    /// Failure description, including the handler when known
+    #[must_use]
+    pub fn failure_message(&self) -> String {
+        let message = self.message.as_deref().unwrap_or("Unknown error");
+        match &self.handler {
+            Some(handler) => format!("{handler}: {message}"),
+            None => message.to_string(),
+        }
+    }
+}
+
+/// A failed Python test file, returned (boxed) by the single-test runners
+#[derive(Debug, Clone)]
+pub struct PythonTestFailure {
+    pub path: PathBuf,
+    /// Error message extracted from the result line or the output
+    pub message: String,
+    /// Structured result, if the test printed one
+    pub result: Option<PythonTestResult>,
+}
+
+impl std::fmt::Display for PythonTestFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Test failed: {}", self.path.display())
+    }
+}
+
+impl std::error::Error for PythonTestFailure {}
+
+/// Pick the most useful error line from a failed test's output
+///
+/// Used when the test did not print a `__RESULT__` line.
+fn scan_error_message<'a>(stdout: &'a str, stderr: &'a str) -> &'a str {
+    ["❌ Failed to execute handler for", "❌ Failed to execute"]
+        .iter()
+        .find_map(|marker| {
+            stderr
+                .lines()
+                .chain(stdout.lines())
+                .find(|line| line.contains(marker))
+        })
+        .or_else(|| {
+            // No specific error format found, use the first non-empty line of stderr or stdout
+            stderr
+                .lines()
+                .chain(stdout.lines())
+                .find(|line| !line.trim().is_empty() && !line.contains("Traceback"))
+        })
+        .unwrap_or("Unknown error")
+}
+
+/// Run a Python command, turning a missing interpreter into an actionable error
+///
+/// # Errors
+///
+/// Returns `ConnectError::Configuration` if `python_bin` cannot be found, or the
+/// underlying I/O error if the process fails to start for another reason.
+pub fn run_python_command(
+    command: &mut std::process::Command,
+    python_bin: &str,
+) -> Result<std::process::Output, ConnectError> {
+    command.output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            ConnectError::Configuration(format!(
+                "Python interpreter '{python_bin}' was not found. Install Python 3 or point \
+                 --python-bin at an existing interpreter."
+            ))
+        } else {
+            ConnectError::Io(e)
+        }
+    })
+}
+
+/// Retry settings for the harness database connection
+///
+/// `TIDB_CONNECT_RETRIES` sets the number of attempts and
+/// `TIDB_CONNECT_RETRY_DELAY_MS` the initial backoff delay, so harness startup
+/// can tolerate a database that is still coming up.
+#[must_use]
+pub fn connection_retry_config_from_env() -> RetryConfig {
+    let mut config = RetryConfig::default();
+    if let Some(retries) = std::env::var("TIDB_CONNECT_RETRIES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+    {
+        config.max_retries = retries;
+    }
+    if let Some(delay_ms) = std::env::var("TIDB_CONNECT_RETRY_DELAY_MS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+    {
+        config.base_delay = Duration::from_millis(delay_ms);
+    }
+    config
+}
 
 /// Common trait for Python test runners
 pub trait PythonTestRunner: Send + Sync {
     fn name(&self) -> &str;
     fn test_dir(&self) -> &str;
 
+    /// Retry settings used by [`PythonTestRunner::test_connection`]
+    fn connection_retry_config(&self) -> RetryConfig {
+        connection_retry_config_from_env()
+    }
+
+    /// Open (and immediately close) a connection using `config`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection cannot be established.
+    fn connect_database(&self, config: &DatabaseConfig) -> Result<(), ConnectError> {
+        let (host, port) = crate::connection::parse_host_port(&config.host)?;
+        let password = config.password.as_deref().unwrap_or("");
+        let database = config.database.as_deref();
+
+        let _connection = crate::connection::create_connection(
+            &host,
+            port,
+            &config.username,
+            password,
+            database,
+        )?;
+        Ok(())
+    }
+
     fn test_connection(
         &self,
     ) -> impl std::future::Future<Output = Result<(), Box<dyn std::error::Error>>> + Send {
@@ -21,20 +198,17 @@ pub trait PythonTestRunner: Send + Sync {
                 password: Some("testpass".to_string()),
                 database: Some("testdb".to_string()),
                 pool_size: 5,
+                pool_min: 0,
                 timeout_secs: 30,
+                checkout_timeout_secs: 10,
+                tls: None,
+                statement_timeout_secs: None,
+                server_version: None,
             };
 
-            let (host, port) = crate::connection::parse_host_port(&config.host)?;
-            let password = config.password.as_deref().unwrap_or("");
-            let database = config.database.as_deref();
-
-            let _connection = crate::connection::create_connection(
-                &host,
-                port,
-                &config.username,
-                password,
-                database,
-            )?;
+            let retry_config = self.connection_retry_config();
+            crate::retry::retry_with_backoff(&retry_config, || self.connect_database(&config))
+                .await?;
             tracing::info!("Database connection successful");
             Ok(())
         }
@@ -186,8 +360,6 @@ try:
     try:
         handler_class_name = handler_classes[0]
         handler_class = test_namespace[handler_class_name]
-        handler = handler_class()
-        print(f"✅ Successfully instantiated {{handler_class_name}} for {MODULE_NAME}")
         
         # Create a context for testing
         if REAL_DB:
@@ -227,6 +399,13 @@ try:
                 connection=mock_connection
             )
         
+        # Handlers that need configuration build themselves from the context
+        if hasattr(handler_class, 'from_context'):
+            handler = handler_class.from_context(context)
+        else:
+            handler = handler_class()
+        print(f"✅ Successfully instantiated {{handler_class_name}} for {MODULE_NAME}")
+        
         # Execute the handler's enter method
         print(f"🔧 Executing {{handler_class_name}}.enter()...")
         enter_result = handler.enter(context)
@@ -295,11 +474,11 @@ except Exception as e:
         test_path: &Path,
         temp_script: &std::path::PathBuf,
     ) -> Result<std::process::Output, Box<dyn std::error::Error>> {
-        let output = std::process::Command::new("python3")
+        let mut command = std::process::Command::new(DEFAULT_PYTHON_BIN);
+        command
             .arg(temp_script)
-            .current_dir(test_path.parent().unwrap())
-            .output()?;
-        Ok(output)
+            .current_dir(test_path.parent().unwrap());
+        Ok(run_python_command(&mut command, DEFAULT_PYTHON_BIN)?)
     }
 
     /// Handle test execution results
@@ -378,6 +557,10 @@ pub struct PythonSuiteConfig {
     pub name: &'static str,
     pub test_dir: &'static str,
     pub module_prefix: &'static str,
+    /// Database this suite's tests use instead of `TIDB_DATABASE`
+    pub database: Option<&'static str>,
+    /// Session variables set on this suite's connections
+    pub session_vars: &'static [(&'static str, &'static str)],
 }
 
 /// List all Python test suites here
@@ -386,26 +569,292 @@ pub static PYTHON_SUITES: &[PythonSuiteConfig] = &[
         name: "DDL",
         test_dir: "src/ddl",
         module_prefix: "src.ddl",
+        database: None,
+        session_vars: &[],
     },
     PythonSuiteConfig {
         name: "import",
         test_dir: "src/load_data",
         module_prefix: "src.load_data",
+        database: None,
+        session_vars: &[],
     },
     PythonSuiteConfig {
         name: "Scale",
         test_dir: "src/scale",
         module_prefix: "src.scale",
+        database: None,
+        session_vars: &[],
     },
     PythonSuiteConfig {
         name: "Txn",
         test_dir: "src/txn",
         module_prefix: "src.txn",
+        database: None,
+        session_vars: &[],
     },
     // Add more suites here as needed
 ];
 
+/// A test file a run would execute and the handlers it defines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedTest {
+    pub path: PathBuf,
+    /// Handlers in the order the harness runs them
+    pub handlers: Vec<PlannedHandler>,
+}
+
+/// A handler class the harness would run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedHandler {
+    pub name: String,
+    /// Built with `from_context(context)` rather than a no-argument constructor
+    pub from_context: bool,
+}
+
+impl PlannedHandler {
+    /// Calls the harness makes for this handler, in order
+    #[must_use]
+    pub fn sequence(&self) -> String {
+        let construct = if self.from_context {
+            format!("{}.from_context(context)", self.name)
+        } else {
+            format!("{}()", self.name)
+        };
+        format!("{construct} -> enter(context) -> execute(context) -> exit(context)")
+    }
+}
+
+/// `PyStateHandler` subclasses defined in a Python test file, in definition order
+///
+/// Mirrors the harness, which runs every top-level class whose name ends in
+/// `Handler`, does not start with `_` and that derives from `PyStateHandler`,
+/// building it with `from_context` when the class defines one.
+#[must_use]
+pub fn planned_handlers(source: &str) -> Vec<PlannedHandler> {
+    let mut handlers: Vec<PlannedHandler> = Vec::new();
+    let mut in_handler = false;
+    for line in source.lines() {
+        if let Some((name, bases)) = line
+            .strip_prefix("class ")
+            .and_then(|rest| rest.split_once('('))
+        {
+            let name = name.trim();
+            in_handler = name.ends_with("Handler")
+                && !name.starts_with('_')
+                && bases.contains("PyStateHandler");
+            if in_handler {
+                handlers.push(PlannedHandler {
+                    name: name.to_string(),
+                    from_context: false,
+                });
+            }
+        } else if line.starts_with(|c: char| !c.is_whitespace() && c != '#') {
+            in_handler = false;
+        } else if in_handler
+            && line.trim_start().starts_with("def from_context(")
+            && let Some(handler) = handlers.last_mut()
+        {
+            handler.from_context = true;
+        }
+    }
+    handlers
+}
+
+/// Render a suite's plan: the connection the harness would use, then each
+/// test file in run order with the calls made on each of its handlers
+///
+/// `options` are the run options before the suite's own overrides.
+#[must_use]
+pub fn format_plan(
+    suite: &PythonSuiteConfig,
+    options: &PythonRunOptions,
+    tests: &[PlannedTest],
+) -> String {
+    let options = suite.suite_options(options);
+    let mut out = format!(
+        "Suite {} ({}): {} test file(s)\n",
+        suite.name,
+        suite.test_dir,
+        tests.len()
+    );
+    if options.real_db {
+        let env = |var: &str, default: &str| {
+            options
+                .harness_env(var)
+                .unwrap_or_else(|| default.to_string())
+        };
+        out.push_str(&format!(
+            "  Connection: TiDB at {} as {}, database {}\n",
+            env("TIDB_HOST", "localhost:4000"),
+            env("TIDB_USER", "root"),
+            env("TIDB_DATABASE", "test")
+        ));
+        if let Some(vars) = options.harness_env("TIDB_SESSION_VARS") {
+            out.push_str(&format!("  Session variables: {vars}\n"));
+        }
+    } else {
+        out.push_str("  Connection: mock, no server (pass --real-db to use TiDB)\n");
+    }
+    for (i, test) in tests.iter().enumerate() {
+        let file = test.path.file_name().map_or_else(
+            || test.path.display().to_string(),
+            |n| n.to_string_lossy().to_string(),
+        );
+        out.push_str(&format!("  {}. {file}\n", i + 1));
+        if test.handlers.is_empty() {
+            out.push_str("     (no handler classes found; the test would fail)\n");
+        }
+        for handler in &test.handlers {
+            out.push_str(&format!("     {}\n", handler.sequence()));
+        }
+    }
+    if tests.iter().any(|test| test.handlers.len() > 1) {
+        out.push_str("  A failing handler skips the rest of its file; later files still run\n");
+    }
+    out
+}
+
+/// Look up a suite in [`PYTHON_SUITES`] by name, case-insensitively
+///
+/// # Errors
+///
+/// Returns `ConnectError::CliArgument` listing the valid suite names if `name`
+/// does not match any suite.
+pub fn find_suite(name: &str) -> Result<&'static PythonSuiteConfig, ConnectError> {
+    PYTHON_SUITES
+        .iter()
+        .find(|suite| suite.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| {
+            let valid: Vec<String> = PYTHON_SUITES
+                .iter()
+                .map(|suite| suite.name.to_lowercase())
+                .collect();
+            ConnectError::CliArgument(format!(
+                "Unknown suite '{name}'. Valid suites: {}",
+                valid.join(", ")
+            ))
+        })
+}
+
+/// Options controlling how Python test files are executed
+#[derive(Clone, Debug, Default)]
+pub struct PythonRunOptions {
+    pub show_output: bool,
+    pub show_sql: bool,
+    pub real_db: bool,
+    /// Extra `KEY=VALUE` pairs injected into the Python subprocess environment
+    pub python_env: Vec<(String, String)>,
+    /// Python interpreter to run (defaults to `python3`)
+    pub python_bin: Option<String>,
+    /// Project root used for `PYTHONPATH` and to resolve suite directories
+    pub project_root: Option<PathBuf>,
+}
+
+impl PythonRunOptions {
+    /// Interpreter to run, falling back to [`DEFAULT_PYTHON_BIN`]
+    #[must_use]
+    pub fn python_bin(&self) -> &str {
+        self.python_bin.as_deref().unwrap_or(DEFAULT_PYTHON_BIN)
+    }
+
+    /// Project root, falling back to [`default_project_root`]
+    #[must_use]
+    pub fn project_root(&self) -> PathBuf {
+        self.project_root
+            .clone()
+            .unwrap_or_else(default_project_root)
+    }
+
+    /// Value of `var` in the Python subprocess: the last `--python-env` pair
+    /// for it, else the inherited environment
+    #[must_use]
+    pub fn harness_env(&self, var: &str) -> Option<String> {
+        self.python_env
+            .iter()
+            .rev()
+            .find(|(key, _)| key == var)
+            .map(|(_, value)| value.clone())
+            .or_else(|| std::env::var(var).ok())
+    }
+}
+
+/// Detect the project root
+///
+/// Uses the crate root this library was built from when it still exists, so
+/// tests can be run from any directory, and the current directory otherwise.
+#[must_use]
+pub fn default_project_root() -> PathBuf {
+    let crate_root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    if crate_root.join("src").is_dir() {
+        crate_root
+    } else {
+        std::env::current_dir().unwrap_or_default()
+    }
+}
+
+/// Parse and validate a `KEY=VALUE` pair for `--python-env`
+///
+/// # Errors
+///
+/// Returns an error if there is no `=` or the key is not a valid environment variable name.
+pub fn parse_python_env(pair: &str) -> Result<(String, String), String> {
+    let (key, value) = pair
+        .split_once('=')
+        .ok_or_else(|| format!("Invalid --python-env '{pair}': expected KEY=VALUE"))?;
+    let valid_key = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_key {
+        return Err(format!(
+            "Invalid --python-env key '{key}': use letters, digits and underscores"
+        ));
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
 impl PythonSuiteConfig {
+    /// Environment variables that apply this suite's connection overrides
+    ///
+    /// The database replaces `TIDB_DATABASE`; session variables are passed as a
+    /// JSON object in `TIDB_SESSION_VARS` and set after each real connection.
+    #[must_use]
+    pub fn override_env(&self) -> Vec<(String, String)> {
+        let mut env = Vec::new();
+        if let Some(database) = self.database {
+            env.push(("TIDB_DATABASE".to_string(), database.to_string()));
+        }
+        if !self.session_vars.is_empty() {
+            let vars: serde_json::Map<String, serde_json::Value> = self
+                .session_vars
+                .iter()
+                .map(|(name, value)| ((*name).to_string(), (*value).into()))
+                .collect();
+            env.push((
+                "TIDB_SESSION_VARS".to_string(),
+                serde_json::Value::Object(vars).to_string(),
+            ));
+        }
+        env
+    }
+
+    /// Run options for this suite with its overrides applied
+    ///
+    /// Suite overrides win over the inherited environment; explicit
+    /// `--python-env` pairs win over both.
+    #[must_use]
+    pub fn suite_options(&self, options: &PythonRunOptions) -> PythonRunOptions {
+        let mut options = options.clone();
+        options.python_env = self
+            .override_env()
+            .into_iter()
+            .chain(options.python_env)
+            .collect();
+        options
+    }
+
     /// Run a test suite with output control
     ///
     /// # Errors
@@ -417,21 +866,13 @@ impl PythonSuiteConfig {
         show_sql: bool,
         real_db: bool,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        tracing::info!("Running Python test suite: {}", self.name);
-        let test_files = PythonSuiteConfig::discover_test_files(self.test_dir).await?;
-        tracing::info!("Found {} test files in {}", test_files.len(), self.test_dir);
-        for test_file in test_files {
-            tracing::info!("Running test: {}", test_file.display());
-            PythonSuiteConfig::run_single_python_test(
-                &test_file,
-                self.module_prefix,
-                show_output,
-                show_sql,
-                real_db,
-            )?;
-        }
-        tracing::info!("All Python tests completed successfully for {}", self.name);
-        Ok(())
+        let options = PythonRunOptions {
+            show_output,
+            show_sql,
+            real_db,
+            ..PythonRunOptions::default()
+        };
+        self.run_suite_with_options(&options, None).await
     }
 
     /// Run a test suite with output control, filtered by a specific test file.
@@ -445,10 +886,104 @@ impl PythonSuiteConfig {
         show_sql: bool,
         real_db: bool,
         test_file: Option<&str>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let options = PythonRunOptions {
+            show_output,
+            show_sql,
+            real_db,
+            ..PythonRunOptions::default()
+        };
+        self.run_suite_with_options(&options, test_file).await
+    }
+
+    /// Run a test suite with the given options, optionally filtered by a specific test file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no test file is found or the test suite execution fails.
+    pub async fn run_suite_with_options(
+        &self,
+        options: &PythonRunOptions,
+        test_file: Option<&str>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         tracing::info!("Running Python test suite: {}", self.name);
-        let test_files = PythonSuiteConfig::discover_test_files(self.test_dir).await?;
-        tracing::info!("Found {} test files in {}", test_files.len(), self.test_dir);
+        let filtered_files = self.selected_test_files(options, test_file).await?;
+        let options = self.suite_options(options);
+        for test_file in filtered_files {
+            tracing::info!("Running test: {}", test_file.display());
+            PythonSuiteConfig::run_single_python_test_with_options(
+                &test_file,
+                self.module_prefix,
+                &options,
+            )?;
+        }
+        tracing::info!("All Python tests completed successfully for {}", self.name);
+        Ok(())
+    }
+
+    /// Run every selected test file, continuing past failures, and record the outcome
+    pub async fn run_suite_collecting(
+        &self,
+        options: &PythonRunOptions,
+        test_file: Option<&str>,
+    ) -> SuiteResult {
+        let started = std::time::Instant::now();
+        let mut result = SuiteResult::new(self.name);
+        match self.selected_test_files(options, test_file).await {
+            Ok(files) => {
+                let options = self.suite_options(options);
+                for test_file in files {
+                    tracing::info!("Running test: {}", test_file.display());
+                    let test = test_file
+                        .file_name()
+                        .map_or_else(String::new, |n| n.to_string_lossy().to_string());
+                    match PythonSuiteConfig::run_single_python_test_with_options(
+                        &test_file,
+                        self.module_prefix,
+                        &options,
+                    ) {
+                        Ok(()) => result.record_pass(&test),
+                        Err(e) => match e.downcast_ref::<PythonTestFailure>() {
+                            Some(failure) => result.record_failure(
+                                &test,
+                                failure
+                                    .result
+                                    .as_ref()
+                                    .and_then(|r| r.error_type.as_deref()),
+                                &failure.message,
+                            ),
+                            None => result.record_failure(&test, None, &e.to_string()),
+                        },
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("❌ Suite '{}': {}", self.name, e);
+                result.record_failure(self.test_dir, None, &e.to_string());
+            }
+        }
+        result.duration = started.elapsed();
+        result
+    }
+
+    /// Test files a run would execute, resolved against the project root
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `test_file` is given but not found in the suite.
+    pub async fn selected_test_files(
+        &self,
+        options: &PythonRunOptions,
+        test_file: Option<&str>,
+    ) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        let test_dir = options.project_root().join(self.test_dir);
+        let test_files =
+            PythonSuiteConfig::discover_test_files(&test_dir.to_string_lossy()).await?;
+        tracing::info!(
+            "Found {} test files in {}",
+            test_files.len(),
+            test_dir.display()
+        );
         let filtered_files: Vec<_> = if let Some(file) = test_file {
             test_files
                 .into_iter()
@@ -458,20 +993,37 @@ impl PythonSuiteConfig {
             test_files
         };
         if filtered_files.is_empty() {
-            return Err(format!("No test file '{}' found in suite {}", test_file.unwrap_or(""), self.name).into());
-        }
-        for test_file in filtered_files {
-            tracing::info!("Running test: {}", test_file.display());
-            PythonSuiteConfig::run_single_python_test(
-                &test_file,
-                self.module_prefix,
-                show_output,
-                show_sql,
-                real_db,
-            )?;
+            return Err(format!(
+                "No test file '{}' found in suite {}",
+                test_file.unwrap_or(""),
+                self.name
+            )
+            .into());
         }
-        tracing::info!("All Python tests completed successfully for {}", self.name);
-        Ok(())
+        Ok(filtered_files)
+    }
+
+    /// Describe what a run of this suite would execute, without running anything
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the suite has no matching test files or one cannot be read.
+    pub async fn plan(
+        &self,
+        options: &PythonRunOptions,
+        test_file: Option<&str>,
+    ) -> Result<Vec<PlannedTest>, Box<dyn std::error::Error>> {
+        let files = self.selected_test_files(options, test_file).await?;
+        files
+            .into_iter()
+            .map(|path| {
+                let source = std::fs::read_to_string(&path)?;
+                Ok(PlannedTest {
+                    handlers: planned_handlers(&source),
+                    path,
+                })
+            })
+            .collect()
     }
 
     /// Discover test files in a directory
@@ -515,14 +1067,75 @@ impl PythonSuiteConfig {
     /// # Panics
     ///
     /// Panics if the test path has an invalid file stem.
-    #[allow(clippy::too_many_lines)]
     pub fn run_single_python_test(
         test_path: &std::path::Path,
-        _module_prefix: &str,
+        module_prefix: &str,
         show_output: bool,
         show_sql: bool,
         real_db: bool,
     ) -> Result<(), Box<dyn std::error::Error>> {
+        let options = PythonRunOptions {
+            show_output,
+            show_sql,
+            real_db,
+            ..PythonRunOptions::default()
+        };
+        PythonSuiteConfig::run_single_python_test_with_options(test_path, module_prefix, &options)
+    }
+
+    /// Build the Python command that runs a generated test script
+    #[must_use]
+    pub fn build_python_command(
+        temp_script: &Path,
+        parent_dir: &Path,
+        options: &PythonRunOptions,
+    ) -> std::process::Command {
+        let mut command = std::process::Command::new(options.python_bin());
+        command
+            .arg(temp_script)
+            .current_dir(parent_dir)
+            .env("PYTHONPATH", options.project_root());
+
+        // Set SQL logging environment variable
+        if options.show_sql {
+            command.env("SHOW_SQL", "true");
+        }
+        // Set real DB environment variable
+        if options.real_db {
+            command.env("REAL_DB", "true");
+        }
+
+        // Pass TiDB environment variables to the Python subprocess
+        for var in ["TIDB_HOST", "TIDB_USER", "TIDB_PASSWORD", "TIDB_DATABASE"] {
+            if let Ok(value) = std::env::var(var) {
+                command.env(var, value);
+            }
+        }
+
+        // User-supplied variables last so they can override the defaults above
+        for (key, value) in &options.python_env {
+            command.env(key, value);
+        }
+
+        command
+    }
+
+    /// Run a single Python test file with the given options
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the test execution fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the test path has an invalid file stem.
+    #[allow(clippy::too_many_lines)]
+    pub fn run_single_python_test_with_options(
+        test_path: &std::path::Path,
+        _module_prefix: &str,
+        options: &PythonRunOptions,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (show_output, show_sql) = (options.show_output, options.show_sql);
         let module_name = test_path.file_stem().unwrap().to_str().unwrap();
         let parent_dir = test_path.parent().unwrap();
 
@@ -532,6 +1145,7 @@ impl PythonSuiteConfig {
 import sys
 import os
 import inspect
+import json
 
 # Add the project root to Python path so imports work correctly
 project_root = os.path.abspath('.')
@@ -540,6 +1154,10 @@ sys.path.insert(0, project_root)
 SHOW_SQL = os.environ.get('SHOW_SQL', 'false').lower() == 'true'
 REAL_DB = os.environ.get('REAL_DB', 'false').lower() == 'true'
 
+def report_result(status, **details):
+    # Final machine-readable line parsed by the Rust harness
+    print("{RESULT_MARKER}" + json.dumps(dict(status=status, module="{module_name}", **details)))
+
 try:
     # Execute the test file directly
     with open('{}', 'r') as f:
@@ -568,6 +1186,7 @@ try:
 
     if not handler_classes:
         print(f"❌ No handler classes found in {module_name}")
+        report_result("failed", message="No handler classes found")
         sys.exit(1)
 
     print(f"✅ Found handler classes: {{', '.join(handler_classes)}}")
@@ -577,8 +1196,6 @@ try:
         try:
             print(f"\n--- Executing {{handler_class_name}} ---")
             handler_class = test_namespace[handler_class_name]
-            handler = handler_class()
-            print(f"✅ Successfully instantiated {{handler_class_name}} for {module_name}")
             
             # Create a context for testing
             if REAL_DB:
@@ -618,6 +1235,13 @@ try:
                     connection=mock_connection
                 )
             
+            # Handlers that need configuration build themselves from the context
+            if hasattr(handler_class, 'from_context'):
+                handler = handler_class.from_context(context)
+            else:
+                handler = handler_class()
+            print(f"✅ Successfully instantiated {{handler_class_name}} for {module_name}")
+            
             # Execute the handler's enter method
             print(f"🔧 Executing {{handler_class_name}}.enter()...")
             enter_result = handler.enter(context)
@@ -637,6 +1261,7 @@ try:
             # Get the current line number from the test file
             current_line = inspect.currentframe().f_lineno
             print(f"❌ Failed to execute handler {{handler_class_name}} for {module_name} (line {{current_line}}): {{str(e)}}")
+            report_result("failed", handler=handler_class_name, message=str(e), error_type=type(e).__name__)
             # Print full stack trace for debugging
             import traceback
             print("Full stack trace:")
@@ -644,11 +1269,13 @@ try:
             sys.exit(1)
 
     print(f"✅ Test passed for {module_name}")
+    report_result("passed", handlers=handler_classes)
     
 except Exception as e:
     # Get the current line number from the test file
     current_line = inspect.currentframe().f_lineno
     print(f"❌ Failed to execute {module_name} (line {{current_line}}): {{str(e)}}")
+    report_result("failed", message=str(e), error_type=type(e).__name__)
     # Print full stack trace for debugging
     import traceback
     print("Full stack trace:")
@@ -664,40 +1291,24 @@ except Exception as e:
         ));
         std::fs::write(&temp_script, test_content)?;
 
-        let mut command = std::process::Command::new("python3");
-        command
-            .arg(&temp_script)
-            .current_dir(parent_dir)
-            .env("PYTHONPATH", std::env::current_dir().unwrap());
-
-        // Set SQL logging environment variable
-        if show_sql {
-            command.env("SHOW_SQL", "true");
-        }
-        // Set real DB environment variable
-        if real_db {
-            command.env("REAL_DB", "true");
-        }
-
-        // Pass TiDB environment variables to the Python subprocess
-        if let Ok(host) = std::env::var("TIDB_HOST") {
-            command.env("TIDB_HOST", host);
-        }
-        if let Ok(user) = std::env::var("TIDB_USER") {
-            command.env("TIDB_USER", user);
-        }
-        if let Ok(password) = std::env::var("TIDB_PASSWORD") {
-            command.env("TIDB_PASSWORD", password);
-        }
-        if let Ok(database) = std::env::var("TIDB_DATABASE") {
-            command.env("TIDB_DATABASE", database);
-        }
-
-        let output = command.output()?;
+        let mut command =
+            PythonSuiteConfig::build_python_command(&temp_script, parent_dir, options);
+        let output = match run_python_command(&mut command, options.python_bin()) {
+            Ok(output) => output,
+            Err(e) => {
+                let _ = std::fs::remove_file(&temp_script);
+                return Err(e.into());
+            }
+        };
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr);
+        // Prefer the structured result line; older scripts only have the exit status
+        let result = PythonTestResult::parse(&stdout);
+        let passed = result
+            .as_ref()
+            .map_or(output.status.success(), PythonTestResult::passed);
 
-        if output.status.success() {
+        if passed {
             tracing::info!("✅ Test passed: {}", test_path.display());
             println!("✅ Test passed: {}", test_path.display());
             if show_output && !stdout.is_empty() {
@@ -722,6 +1333,7 @@ except Exception as e:
                                 // Empty line, skip it
                                 j += 1;
                             } else if next_line.starts_with("🔧")
+                                || next_line.starts_with(RESULT_MARKER)
                                 || next_line.starts_with("✅")
                                 || next_line.starts_with("❌")
                                 || next_line.starts_with("Enter result:")
@@ -757,39 +1369,11 @@ except Exception as e:
                 println!("stdout length: {}", stdout.len());
             }
 
-            // Extract just the error message from stderr or stdout, avoiding stack traces
-            let error_message = if stderr.contains("❌ Failed to execute handler for") {
-                stderr
-                    .lines()
-                    .find(|line| line.contains("❌ Failed to execute handler for"))
-                    .unwrap_or("Unknown error")
-            } else if stdout.contains("❌ Failed to execute handler for") {
-                stdout
-                    .lines()
-                    .find(|line| line.contains("❌ Failed to execute handler for"))
-                    .unwrap_or("Unknown error")
-            } else if stderr.contains("❌ Failed to execute") {
-                stderr
-                    .lines()
-                    .find(|line| line.contains("❌ Failed to execute"))
-                    .unwrap_or("Unknown error")
-            } else if stdout.contains("❌ Failed to execute") {
-                stdout
-                    .lines()
-                    .find(|line| line.contains("❌ Failed to execute"))
-                    .unwrap_or("Unknown error")
-            } else {
-                // If no specific error format found, show the first non-empty line of stderr or stdout
-                stderr
-                    .lines()
-                    .find(|line| !line.trim().is_empty() && !line.contains("Traceback"))
-                    .or_else(|| {
-                        stdout
-                            .lines()
-                            .find(|line| !line.trim().is_empty() && !line.contains("Traceback"))
-                    })
-                    .unwrap_or("Unknown error")
-            };
+            // Extract just the error message, avoiding stack traces
+            let error_message = result.as_ref().map_or_else(
+                || scan_error_message(&stdout, &stderr).to_string(),
+                PythonTestResult::failure_message,
+            );
 
             tracing::error!(
                 "❌ Test failed: {} - {}",
@@ -801,9 +1385,351 @@ except Exception as e:
                 test_path.display(),
                 error_message
             );
-            return Err(format!("Test failed: {}", test_path.display()).into());
+            return Err(PythonTestFailure {
+                path: test_path.to_path_buf(),
+                message: error_message,
+                result,
+            }
+            .into());
         }
         let _ = std::fs::remove_file(temp_script);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::EnvVarGuard;
+    use serial_test::serial;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Runner whose connection factory fails a fixed number of times
+    struct FlakyRunner {
+        failures: usize,
+        attempts: AtomicUsize,
+    }
+
+    impl PythonTestRunner for FlakyRunner {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        fn test_dir(&self) -> &str {
+            "."
+        }
+
+        fn connection_retry_config(&self) -> RetryConfig {
+            RetryConfig {
+                max_retries: 5,
+                base_delay: Duration::from_millis(1),
+                max_delay: Duration::from_millis(5),
+                backoff_multiplier: 2.0,
+            }
+        }
+
+        fn connect_database(&self, _config: &DatabaseConfig) -> Result<(), ConnectError> {
+            let attempt = self.attempts.fetch_add(1, Ordering::SeqCst) + 1;
+            if attempt <= self.failures {
+                Err(ConnectError::Network("database not ready".to_string()))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connection_retries_until_database_is_up() {
+        let runner = FlakyRunner {
+            failures: 2,
+            attempts: AtomicUsize::new(0),
+        };
+        assert!(runner.test_connection().await.is_ok());
+        assert_eq!(runner.attempts.load(Ordering::SeqCst), 3);
+
+        let runner = FlakyRunner {
+            failures: 10,
+            attempts: AtomicUsize::new(0),
+        };
+        assert!(runner.test_connection().await.is_err());
+        assert_eq!(runner.attempts.load(Ordering::SeqCst), 5);
+    }
+
+    #[test]
+    fn test_parse_python_env() {
+        assert_eq!(
+            parse_python_env("MY_VAR=a=b").unwrap(),
+            ("MY_VAR".to_string(), "a=b".to_string())
+        );
+        assert_eq!(
+            parse_python_env("EMPTY=").unwrap(),
+            ("EMPTY".to_string(), String::new())
+        );
+        assert!(parse_python_env("NO_EQUALS").is_err());
+        assert!(parse_python_env("=value").is_err());
+        assert!(parse_python_env("1BAD=value").is_err());
+        assert!(parse_python_env("BAD-KEY=value").is_err());
+    }
+
+    #[test]
+    fn test_python_env_is_set_on_command() {
+        let options = PythonRunOptions {
+            python_env: vec![
+                ("HANDLER_MODE".to_string(), "strict".to_string()),
+                ("REAL_DB".to_string(), "override".to_string()),
+            ],
+            real_db: true,
+            ..PythonRunOptions::default()
+        };
+        let command = PythonSuiteConfig::build_python_command(
+            Path::new("script.py"),
+            Path::new("."),
+            &options,
+        );
+        let envs: std::collections::HashMap<_, _> = command
+            .get_envs()
+            .filter_map(|(k, v)| Some((k.to_str()?.to_string(), v?.to_str()?.to_string())))
+            .collect();
+
+        assert_eq!(envs.get("HANDLER_MODE").map(String::as_str), Some("strict"));
+        assert_eq!(envs.get("REAL_DB").map(String::as_str), Some("override"));
+    }
+
+    #[test]
+    fn test_find_suite() {
+        assert_eq!(find_suite("ddl").unwrap().test_dir, "src/ddl");
+        assert_eq!(find_suite("TXN").unwrap().name, "Txn");
+
+        let err = find_suite("nope").unwrap_err().to_string();
+        assert!(err.contains("Unknown suite 'nope'"));
+        assert!(err.contains("ddl, import, scale, txn"));
+    }
+
+    #[test]
+    fn test_suite_overrides_are_set_for_that_suite_only() {
+        let import = PythonSuiteConfig {
+            name: "import",
+            test_dir: "src/load_data",
+            module_prefix: "src.load_data",
+            database: Some("staging"),
+            session_vars: &[("tidb_mem_quota_query", "1073741824")],
+        };
+        let ddl = find_suite("ddl").unwrap();
+
+        let options = import.suite_options(&PythonRunOptions {
+            python_env: vec![("HANDLER_MODE".to_string(), "strict".to_string())],
+            ..PythonRunOptions::default()
+        });
+        let command =
+            PythonSuiteConfig::build_python_command(Path::new("s.py"), Path::new("."), &options);
+        let envs: std::collections::HashMap<_, _> = command
+            .get_envs()
+            .filter_map(|(k, v)| Some((k.to_str()?.to_string(), v?.to_str()?.to_string())))
+            .collect();
+        assert_eq!(
+            envs.get("TIDB_DATABASE").map(String::as_str),
+            Some("staging")
+        );
+        assert_eq!(
+            envs.get("TIDB_SESSION_VARS").map(String::as_str),
+            Some(r#"{"tidb_mem_quota_query":"1073741824"}"#)
+        );
+        assert_eq!(envs.get("HANDLER_MODE").map(String::as_str), Some("strict"));
+
+        let ddl_options = ddl.suite_options(&PythonRunOptions::default());
+        assert!(ddl_options.python_env.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_plan_lists_discovered_files() {
+        let root = tempfile::tempdir().unwrap();
+        let ddl_dir = root.path().join("src/ddl");
+        std::fs::create_dir_all(&ddl_dir).unwrap();
+        std::fs::write(
+            ddl_dir.join("test_create.py"),
+            "class CreateHandler(PyStateHandler):\n    pass\nclass Helper(object):\n    pass\n\
+             class FillHandler(PyStateHandler):\n    @classmethod\n    def from_context(cls, context):\n        return cls()\n",
+        )
+        .unwrap();
+        std::fs::write(ddl_dir.join("test_drop.py"), "x = 1\n").unwrap();
+        std::fs::write(ddl_dir.join("helpers.py"), "").unwrap();
+
+        let suite = find_suite("ddl").unwrap();
+        let options = PythonRunOptions {
+            project_root: Some(root.path().to_path_buf()),
+            ..PythonRunOptions::default()
+        };
+        let plan = suite.plan(&options, None).await.unwrap();
+        assert_eq!(plan.len(), 2);
+        let names: Vec<_> = plan[0].handlers.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, vec!["CreateHandler", "FillHandler"]);
+        assert!(!plan[0].handlers[0].from_context);
+        assert!(plan[0].handlers[1].from_context);
+        assert!(plan[1].handlers.is_empty());
+
+        let output = format_plan(suite, &options, &plan);
+        let expected = "Suite DDL (src/ddl): 2 test file(s)\n  \
+            Connection: mock, no server (pass --real-db to use TiDB)\n  \
+            1. test_create.py\n     \
+            CreateHandler() -> enter(context) -> execute(context) -> exit(context)\n     \
+            FillHandler.from_context(context) -> enter(context) -> execute(context) -> exit(context)\n  \
+            2. test_drop.py\n     \
+            (no handler classes found; the test would fail)\n  \
+            A failing handler skips the rest of its file; later files still run\n";
+        assert_eq!(output, expected);
+
+        // A real-DB plan shows where the suite's overrides point the harness
+        let import = PythonSuiteConfig {
+            name: "import",
+            test_dir: "src/ddl",
+            module_prefix: "src.ddl",
+            database: Some("staging"),
+            session_vars: &[("tidb_mem_quota_query", "1073741824")],
+        };
+        let options = PythonRunOptions {
+            real_db: true,
+            python_env: vec![
+                ("TIDB_HOST".to_string(), "tidb-1:4000".to_string()),
+                ("TIDB_USER".to_string(), "app".to_string()),
+            ],
+            ..options
+        };
+        let output = format_plan(&import, &options, &plan[1..]);
+        assert!(output.contains("Connection: TiDB at tidb-1:4000 as app, database staging\n"));
+        assert!(output.contains(r#"Session variables: {"tidb_mem_quota_query":"1073741824"}"#));
+        assert!(output.contains("1. test_drop.py"));
+        assert!(!output.contains("A failing handler"));
+    }
+
+    #[test]
+    fn test_result_line_is_parsed() {
+        let stdout = "✅ Found handler classes: CreateTableHandler\n\
+                      ❌ Failed to execute handler CreateTableHandler\n\
+                      __RESULT__{\"status\": \"failed\", \"module\": \"test_create\", \
+                      \"handler\": \"CreateTableHandler\", \"message\": \"table exists\"}\n";
+        let result = PythonTestResult::parse(stdout).unwrap();
+        assert!(!result.passed());
+        assert_eq!(result.module.as_deref(), Some("test_create"));
+        assert_eq!(result.failure_message(), "CreateTableHandler: table exists");
+
+        let passed = PythonTestResult::parse(
+            "__RESULT__{\"status\": \"passed\", \"handlers\": [\"AHandler\"]}",
+        )
+        .unwrap();
+        assert!(passed.passed());
+        assert_eq!(passed.handlers, vec!["AHandler"]);
+    }
+
+    #[test]
+    fn test_missing_result_line_falls_back_to_scanning() {
+        let stdout =
+            "✅ Successfully executed test_x\n❌ Failed to execute test_x (line 3): boom\n";
+        assert!(PythonTestResult::parse(stdout).is_none());
+        assert!(PythonTestResult::parse("__RESULT__not json").is_none());
+        assert_eq!(
+            scan_error_message(stdout, ""),
+            "❌ Failed to execute test_x (line 3): boom"
+        );
+    }
+
+    #[test]
+    fn test_project_root_is_used_for_pythonpath() {
+        let options = PythonRunOptions {
+            project_root: Some(PathBuf::from("/opt/checkout/tidb-test-rig")),
+            ..PythonRunOptions::default()
+        };
+        let command = PythonSuiteConfig::build_python_command(
+            Path::new("script.py"),
+            Path::new("/tmp"),
+            &options,
+        );
+        let pythonpath = command
+            .get_envs()
+            .find(|(k, _)| *k == "PYTHONPATH")
+            .and_then(|(_, v)| v);
+        assert_eq!(
+            pythonpath,
+            Some(std::ffi::OsStr::new("/opt/checkout/tidb-test-rig"))
+        );
+
+        // Without an explicit root the crate root is detected, independent of the cwd
+        assert_eq!(
+            PythonRunOptions::default().project_root(),
+            PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        );
+    }
+
+    #[test]
+    fn test_handler_from_context_is_preferred() {
+        if std::process::Command::new(DEFAULT_PYTHON_BIN)
+            .arg("--version")
+            .output()
+            .is_err()
+        {
+            eprintln!("Skipping test_handler_from_context_is_preferred: python3 not available");
+            return;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let test_file = dir.path().join("test_from_context.py");
+        std::fs::write(
+            &test_file,
+            r#"
+from src.common.test_rig_python import PyStateHandler, PyStateContext, PyState
+
+
+class ConfiguredHandler(PyStateHandler):
+    def __init__(self, database):
+        super().__init__()
+        self.database = database
+
+    @classmethod
+    def from_context(cls, context: PyStateContext):
+        return cls(database=context.database)
+
+    def execute(self, context: PyStateContext) -> str:
+        assert self.database == context.database, "from_context was not used"
+        return PyState.completed()
+"#,
+        )
+        .unwrap();
+
+        let result = PythonSuiteConfig::run_single_python_test_with_options(
+            &test_file,
+            "tests",
+            &PythonRunOptions::default(),
+        );
+        assert!(result.is_ok(), "{result:?}");
+    }
+
+    #[test]
+    fn test_missing_python_interpreter_is_reported_clearly() {
+        let dir = tempfile::tempdir().unwrap();
+        let test_file = dir.path().join("test_missing_python.py");
+        std::fs::write(&test_file, "pass\n").unwrap();
+
+        let options = PythonRunOptions {
+            python_bin: Some("/nonexistent/bin/python-bogus".to_string()),
+            ..PythonRunOptions::default()
+        };
+        let err =
+            PythonSuiteConfig::run_single_python_test_with_options(&test_file, "tests", &options)
+                .unwrap_err()
+                .to_string();
+
+        assert!(err.contains("/nonexistent/bin/python-bogus"));
+        assert!(err.contains("--python-bin"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_connection_retry_config_from_env() {
+        let mut env = EnvVarGuard::default();
+        env.set("TIDB_CONNECT_RETRIES", "7");
+        env.set("TIDB_CONNECT_RETRY_DELAY_MS", "250");
+        let config = connection_retry_config_from_env();
+        drop(env);
+        assert_eq!(config.max_retries, 7);
+        assert_eq!(config.base_delay, Duration::from_millis(250));
+    }
+}
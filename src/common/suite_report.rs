@@ -0,0 +1,450 @@
+//! Cross-suite reporting for Python test runs
+//!
+//! Each suite run produces a [`SuiteResult`]; a [`CrossSuiteSummary`] rolls
+//! them up into a final table and an overall exit code, and groups failures by
+//! suite and [`FailureCategory`]. [`SummaryLevel`] picks how much of that is
+//! printed at the end of a run.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::time::Duration;
+
+/// How much the end-of-run summary prints
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SummaryLevel {
+    /// A single pass/fail line
+    Minimal,
+    /// The per-suite table and grouped failures
+    #[default]
+    Normal,
+    /// Everything in `Normal` plus the result of every test file
+    Detailed,
+}
+
+impl std::str::FromStr for SummaryLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "minimal" => Ok(SummaryLevel::Minimal),
+            "normal" => Ok(SummaryLevel::Normal),
+            "detailed" => Ok(SummaryLevel::Detailed),
+            _ => Err(format!(
+                "Unknown summary level: {s} (expected minimal, normal or detailed)"
+            )),
+        }
+    }
+}
+
+/// Broad cause of a failed Python test
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FailureCategory {
+    Connection,
+    SqlSyntax,
+    Assertion,
+    Other,
+}
+
+impl FailureCategory {
+    /// Classify a failure from the Python exception type (if reported) and message
+    #[must_use]
+    pub fn classify(error_type: Option<&str>, message: &str) -> Self {
+        let message = message.to_lowercase();
+        if error_type == Some("AssertionError") {
+            FailureCategory::Assertion
+        } else if message.contains("1064") || message.contains("syntax") {
+            FailureCategory::SqlSyntax
+        } else if error_type.is_some_and(|t| t.contains("Interface") || t.contains("Connection"))
+            || ["connect", "connection", "2003", "2013", "access denied"]
+                .iter()
+                .any(|needle| message.contains(needle))
+        {
+            FailureCategory::Connection
+        } else if message.contains("assert") {
+            FailureCategory::Assertion
+        } else {
+            FailureCategory::Other
+        }
+    }
+
+    /// ANSI color used when printing this category to a terminal
+    fn color(self) -> &'static str {
+        match self {
+            FailureCategory::Connection => "\x1b[33m",
+            FailureCategory::SqlSyntax => "\x1b[31m",
+            FailureCategory::Assertion => "\x1b[35m",
+            FailureCategory::Other => "\x1b[0m",
+        }
+    }
+}
+
+impl fmt::Display for FailureCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailureCategory::Connection => write!(f, "connection"),
+            FailureCategory::SqlSyntax => write!(f, "SQL syntax"),
+            FailureCategory::Assertion => write!(f, "assertion"),
+            FailureCategory::Other => write!(f, "other"),
+        }
+    }
+}
+
+/// A single failed test file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestFailure {
+    pub suite: String,
+    pub test: String,
+    pub category: FailureCategory,
+    pub message: String,
+}
+
+/// Group failures by suite, then by category
+#[must_use]
+pub fn group_failures(
+    failures: &[TestFailure],
+) -> BTreeMap<&str, BTreeMap<FailureCategory, Vec<&TestFailure>>> {
+    let mut groups: BTreeMap<&str, BTreeMap<FailureCategory, Vec<&TestFailure>>> = BTreeMap::new();
+    for failure in failures {
+        groups
+            .entry(failure.suite.as_str())
+            .or_default()
+            .entry(failure.category)
+            .or_default()
+            .push(failure);
+    }
+    groups
+}
+
+/// Outcome of running one Python test suite
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuiteResult {
+    pub name: String,
+    /// Test files that passed
+    pub passed: usize,
+    /// Names of the test files that passed, in run order
+    pub passed_tests: Vec<String>,
+    /// Test files that failed
+    pub failed: usize,
+    pub duration: Duration,
+    pub failures: Vec<TestFailure>,
+}
+
+impl SuiteResult {
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            passed: 0,
+            passed_tests: Vec::new(),
+            failed: 0,
+            duration: Duration::ZERO,
+            failures: Vec::new(),
+        }
+    }
+
+    /// Record a passed test file
+    pub fn record_pass(&mut self, test: &str) {
+        self.passed += 1;
+        self.passed_tests.push(test.to_string());
+    }
+
+    /// Record a failed test file
+    pub fn record_failure(&mut self, test: &str, error_type: Option<&str>, message: &str) {
+        self.failed += 1;
+        self.failures.push(TestFailure {
+            suite: self.name.clone(),
+            test: test.to_string(),
+            category: FailureCategory::classify(error_type, message),
+            message: message.to_string(),
+        });
+    }
+
+    #[must_use]
+    pub fn succeeded(&self) -> bool {
+        self.failed == 0
+    }
+}
+
+/// Roll-up of several suite results
+#[derive(Debug, Clone, Default)]
+pub struct CrossSuiteSummary {
+    pub suites: Vec<SuiteResult>,
+}
+
+impl CrossSuiteSummary {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, result: SuiteResult) {
+        self.suites.push(result);
+    }
+
+    #[must_use]
+    pub fn total_passed(&self) -> usize {
+        self.suites.iter().map(|s| s.passed).sum()
+    }
+
+    #[must_use]
+    pub fn total_failed(&self) -> usize {
+        self.suites.iter().map(|s| s.failed).sum()
+    }
+
+    #[must_use]
+    pub fn total_duration(&self) -> Duration {
+        self.suites.iter().map(|s| s.duration).sum()
+    }
+
+    /// Process exit code for the whole run: `0` if every suite passed, `1` otherwise
+    #[must_use]
+    pub fn exit_code(&self) -> i32 {
+        i32::from(self.suites.iter().any(|s| !s.succeeded()))
+    }
+
+    /// Render the summary table
+    #[must_use]
+    pub fn format_table(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(output, "\n=== Cross-Suite Summary ===");
+        let _ = writeln!(
+            output,
+            "{:<12} {:>8} {:>8} {:>10}  Status",
+            "Suite", "Passed", "Failed", "Duration"
+        );
+        for suite in &self.suites {
+            let _ = writeln!(
+                output,
+                "{:<12} {:>8} {:>8} {:>9.2}s  {}",
+                suite.name,
+                suite.passed,
+                suite.failed,
+                suite.duration.as_secs_f64(),
+                if suite.succeeded() { "✅" } else { "❌" }
+            );
+        }
+        let _ = writeln!(
+            output,
+            "{:<12} {:>8} {:>8} {:>9.2}s  {}",
+            "Total",
+            self.total_passed(),
+            self.total_failed(),
+            self.total_duration().as_secs_f64(),
+            if self.exit_code() == 0 { "✅" } else { "❌" }
+        );
+        output
+    }
+
+    /// Render failures grouped by suite and category, with counts per group
+    ///
+    /// Category headings are colored with ANSI codes when `color` is set.
+    #[must_use]
+    pub fn format_failures(&self, color: bool) -> String {
+        let failures: Vec<TestFailure> = self
+            .suites
+            .iter()
+            .flat_map(|s| s.failures.iter().cloned())
+            .collect();
+        let mut output = String::new();
+        if failures.is_empty() {
+            return output;
+        }
+
+        let _ = writeln!(output, "\n=== Failures ({}) ===", failures.len());
+        for (suite, categories) in group_failures(&failures) {
+            let count: usize = categories.values().map(Vec::len).sum();
+            let _ = writeln!(output, "{suite} ({count})");
+            for (category, failures) in categories {
+                let (start, end) = if color {
+                    (category.color(), "\x1b[0m")
+                } else {
+                    ("", "")
+                };
+                let _ = writeln!(output, "  {start}{category} ({}){end}", failures.len());
+                for failure in failures {
+                    let _ = writeln!(output, "    {}: {}", failure.test, failure.message);
+                }
+            }
+        }
+        output
+    }
+
+    /// Render every test file's result, suite by suite
+    #[must_use]
+    pub fn format_results(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(output, "\n=== Results ===");
+        for suite in &self.suites {
+            let _ = writeln!(output, "{}", suite.name);
+            for test in &suite.passed_tests {
+                let _ = writeln!(output, "  ✅ {test}");
+            }
+            for failure in &suite.failures {
+                let _ = writeln!(output, "  ❌ {}: {}", failure.test, failure.message);
+            }
+        }
+        output
+    }
+
+    /// Render the end-of-run summary at `level`
+    #[must_use]
+    pub fn summary(&self, level: SummaryLevel, color: bool) -> String {
+        if level == SummaryLevel::Minimal {
+            let status = if self.exit_code() == 0 {
+                "✅ PASS"
+            } else {
+                "❌ FAIL"
+            };
+            return format!("\n{status}\n");
+        }
+        let mut output = self.format_table();
+        output.push_str(&self.format_failures(color));
+        if level == SummaryLevel::Detailed {
+            output.push_str(&self.format_results());
+        }
+        output
+    }
+
+    pub fn print(&self, level: SummaryLevel) {
+        use std::io::IsTerminal;
+
+        print!("{}", self.summary(level, std::io::stdout().is_terminal()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suite(name: &str, passed: usize, failed: usize, millis: u64) -> SuiteResult {
+        SuiteResult {
+            name: name.to_string(),
+            passed,
+            passed_tests: (0..passed).map(|i| format!("test_{i}.py")).collect(),
+            failed,
+            duration: Duration::from_millis(millis),
+            failures: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_rollup_totals_and_exit_code() {
+        let mut summary = CrossSuiteSummary::new();
+        summary.add(suite("DDL", 8, 0, 1500));
+        summary.add(suite("import", 2, 1, 500));
+        summary.add(suite("Txn", 3, 0, 250));
+
+        assert_eq!(summary.total_passed(), 13);
+        assert_eq!(summary.total_failed(), 1);
+        assert_eq!(summary.total_duration(), Duration::from_millis(2250));
+        assert_eq!(summary.exit_code(), 1);
+
+        let table = summary.format_table();
+        assert!(table.contains("import"));
+        assert!(table.contains("2.25s"));
+    }
+
+    #[test]
+    fn test_all_passing_suites_exit_zero() {
+        let mut summary = CrossSuiteSummary::new();
+        summary.add(suite("DDL", 8, 0, 10));
+        summary.add(suite("Scale", 4, 0, 10));
+        assert_eq!(summary.exit_code(), 0);
+        assert_eq!(CrossSuiteSummary::new().exit_code(), 0);
+    }
+
+    #[test]
+    fn test_failure_classification() {
+        assert_eq!(
+            FailureCategory::classify(Some("AssertionError"), "expected 3 rows"),
+            FailureCategory::Assertion
+        );
+        assert_eq!(
+            FailureCategory::classify(
+                Some("ProgrammingError"),
+                "1064 (42000): You have an error in your SQL syntax"
+            ),
+            FailureCategory::SqlSyntax
+        );
+        assert_eq!(
+            FailureCategory::classify(
+                Some("InterfaceError"),
+                "2003: Can't connect to MySQL server"
+            ),
+            FailureCategory::Connection
+        );
+        assert_eq!(
+            FailureCategory::classify(None, "Lost connection to server"),
+            FailureCategory::Connection
+        );
+        assert_eq!(
+            FailureCategory::classify(None, "name 'x' is not defined"),
+            FailureCategory::Other
+        );
+    }
+
+    #[test]
+    fn test_failures_grouped_by_suite_and_category() {
+        let mut ddl = suite("DDL", 5, 0, 10);
+        ddl.record_failure("test_a.py", Some("AssertionError"), "wrong column");
+        ddl.record_failure("test_b.py", Some("AssertionError"), "missing index");
+        ddl.record_failure("test_c.py", None, "1064: syntax error near 'TABL'");
+        let mut import = suite("import", 1, 0, 10);
+        import.record_failure("test_d.py", Some("InterfaceError"), "Can't connect");
+
+        let failures: Vec<TestFailure> = ddl
+            .failures
+            .iter()
+            .chain(&import.failures)
+            .cloned()
+            .collect();
+        let groups = group_failures(&failures);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups["DDL"][&FailureCategory::Assertion].len(), 2);
+        assert_eq!(groups["DDL"][&FailureCategory::SqlSyntax].len(), 1);
+        assert_eq!(groups["import"][&FailureCategory::Connection].len(), 1);
+
+        let mut summary = CrossSuiteSummary::new();
+        summary.add(ddl);
+        summary.add(import);
+        assert_eq!(summary.total_failed(), 4);
+        let output = summary.format_failures(false);
+        assert!(output.contains("=== Failures (4) ==="));
+        assert!(output.contains("DDL (3)"));
+        assert!(output.contains("  assertion (2)"));
+        assert!(output.contains("  SQL syntax (1)"));
+        assert!(!output.contains('\x1b'));
+        assert!(
+            summary
+                .format_failures(true)
+                .contains("\x1b[35massertion (2)")
+        );
+    }
+
+    #[test]
+    fn test_summary_levels() {
+        let mut ddl = suite("DDL", 2, 0, 10);
+        ddl.record_failure("test_fk.py", Some("AssertionError"), "missing index");
+        let mut summary = CrossSuiteSummary::new();
+        summary.add(ddl);
+        summary.add(suite("Txn", 1, 0, 10));
+
+        let minimal = summary.summary(SummaryLevel::Minimal, false);
+        assert_eq!(minimal.trim(), "❌ FAIL");
+
+        let normal = summary.summary(SummaryLevel::Normal, false);
+        assert!(normal.contains("=== Cross-Suite Summary ==="));
+        assert!(normal.contains("test_fk.py: missing index"));
+        assert!(!normal.contains("=== Results ==="));
+
+        let detailed = summary.summary(SummaryLevel::Detailed, false);
+        assert!(detailed.starts_with(&normal));
+        assert!(detailed.contains("  ✅ test_1.py"));
+        assert!(detailed.contains("  ❌ test_fk.py: missing index"));
+        assert!(minimal.lines().count() < normal.lines().count());
+        assert!(normal.lines().count() < detailed.lines().count());
+
+        assert_eq!("Detailed".parse(), Ok(SummaryLevel::Detailed));
+        assert!("verbose".parse::<SummaryLevel>().is_err());
+    }
+}
@@ -8,6 +8,7 @@ from dataclasses import dataclass
 import threading
 import time
 import os
+import json
 
 # Check if SQL logging is enabled
 SHOW_SQL = os.environ.get('SHOW_SQL', 'false').lower() == 'true'
@@ -141,6 +142,9 @@ class RealPyConnection:
         else:
             self._server_type = 'Unknown'
         print(f"[RealPyConnection] Connected to server type: {self._server_type} (version: {version})")
+        # Per-suite session variables, passed by the Rust harness as a JSON object
+        for name, value in json.loads(os.environ.get('TIDB_SESSION_VARS', '{}')).items():
+            cursor.execute(f"SET SESSION {name} = %s", (value,))
         cursor.close()
 
     def execute_query(self, query: str):
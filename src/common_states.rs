@@ -2,9 +2,28 @@
 //!
 //! Shared state definitions for common workflow states used across multiple binaries.
 //! This module provides reusable state functions to eliminate code duplication.
+//!
+//! [`register_standard_handlers`] registers the connect-to-version flow that
+//! every dynamic binary starts with, so a binary only registers its own states.
+//! Binaries that assemble the flow themselves can reuse
+//! [`StandardParsingHandler`] with a [`ConnParams`].
 
+use crate::capabilities::require_tidb_version;
+use crate::connection::{connect_with_retry_config, parse_connection_string};
 use crate::dynamic_state;
-use crate::state_machine_dynamic::DynamicState;
+use crate::errors::{Result, RetryConfig};
+use crate::state_machine::restore_session;
+use crate::state_machine_dynamic::{
+    DynamicState, DynamicStateContext, DynamicStateHandler, DynamicStateMachine,
+};
+use async_trait::async_trait;
+use mysql::prelude::Queryable;
+
+/// Initial state
+#[must_use]
+pub fn initial() -> DynamicState {
+    dynamic_state!("initial", "Initial")
+}
 
 /// Parsing configuration state
 #[must_use]
@@ -41,3 +60,398 @@ pub fn getting_version() -> DynamicState {
 pub fn completed() -> DynamicState {
     dynamic_state!("completed", "Completed")
 }
+
+/// Connection settings copied into the context by [`StandardParsingHandler`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnParams {
+    /// Server in `host:port` form
+    pub host: String,
+    pub user: String,
+    pub password: String,
+    pub database: Option<String>,
+}
+
+impl ConnParams {
+    #[must_use]
+    pub fn new(host: String, user: String, password: String, database: Option<String>) -> Self {
+        Self {
+            host,
+            user,
+            password,
+            database,
+        }
+    }
+}
+
+/// From the `(host, user, password, database)` tuple returned by
+/// `CommonArgs::get_connection_info`
+impl From<(String, String, String, Option<String>)> for ConnParams {
+    fn from((host, user, password, database): (String, String, String, Option<String>)) -> Self {
+        Self::new(host, user, password, database)
+    }
+}
+
+/// Handler for the parsing-config state: splits the host into host and port
+/// and stores the connection settings in the context
+pub struct StandardParsingHandler {
+    params: ConnParams,
+}
+
+impl StandardParsingHandler {
+    #[must_use]
+    pub fn new(params: ConnParams) -> Self {
+        Self { params }
+    }
+}
+
+#[async_trait]
+impl DynamicStateHandler for StandardParsingHandler {
+    async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+        Ok(parsing_config())
+    }
+    async fn execute(&self, context: &mut DynamicStateContext) -> Result<DynamicState> {
+        let (host, port) = parse_connection_string(&self.params.host)?;
+        context.host = host;
+        context.port = port;
+        context.username.clone_from(&self.params.user);
+        context.password.clone_from(&self.params.password);
+        context.database.clone_from(&self.params.database);
+        Ok(connecting())
+    }
+    async fn exit(&self, _context: &mut DynamicStateContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Register handlers and transitions for `initial` through `getting_version`
+///
+/// The flow is initial, parsing config, connecting (retrying transient
+/// failures), `SELECT 1`, `USE` the database if one is given, and
+/// `SELECT VERSION()`, which also enforces `min_tidb_version` from the context.
+/// The version handler then moves to `next_state`, the first test-specific
+/// state. Handlers registered later for the same states replace these.
+pub fn register_standard_handlers(
+    machine: &mut DynamicStateMachine,
+    params: ConnParams,
+    next_state: DynamicState,
+) {
+    machine.register_handler(initial(), Box::new(StartHandler));
+    machine.register_handler(
+        parsing_config(),
+        Box::new(StandardParsingHandler::new(params)),
+    );
+    machine.register_handler(connecting(), Box::new(ConnectingHandler));
+    machine.register_handler(testing_connection(), Box::new(TestingConnectionHandler));
+    machine.register_handler(verifying_database(), Box::new(VerifyingDatabaseHandler));
+    machine.register_handler(
+        getting_version(),
+        Box::new(GettingVersionHandler {
+            next_state: next_state.clone(),
+        }),
+    );
+
+    machine.register_transitions(initial(), vec![parsing_config()]);
+    machine.register_transitions(parsing_config(), vec![connecting()]);
+    machine.register_transitions(connecting(), vec![testing_connection()]);
+    machine.register_transitions(testing_connection(), vec![verifying_database()]);
+    machine.register_transitions(verifying_database(), vec![getting_version()]);
+    machine.register_transitions(getting_version(), vec![next_state]);
+}
+
+struct StartHandler;
+
+#[async_trait]
+impl DynamicStateHandler for StartHandler {
+    async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+        Ok(initial())
+    }
+    async fn execute(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+        Ok(parsing_config())
+    }
+    async fn exit(&self, _context: &mut DynamicStateContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct ConnectingHandler;
+
+#[async_trait]
+impl DynamicStateHandler for ConnectingHandler {
+    async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+        Ok(connecting())
+    }
+    async fn execute(&self, context: &mut DynamicStateContext) -> Result<DynamicState> {
+        let mut conn =
+            connect_with_retry_config(&context.database_config(), &RetryConfig::default()).await?;
+        restore_session(&mut conn, &context.session_sql)?;
+        context.connection = Some(conn);
+        Ok(testing_connection())
+    }
+    async fn exit(&self, _context: &mut DynamicStateContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct TestingConnectionHandler;
+
+#[async_trait]
+impl DynamicStateHandler for TestingConnectionHandler {
+    async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+        Ok(testing_connection())
+    }
+    async fn execute(&self, context: &mut DynamicStateContext) -> Result<DynamicState> {
+        let Some(ref mut conn) = context.connection else {
+            return Err("No connection available for testing".into());
+        };
+        match conn.query_drop("SELECT 1") {
+            Ok(()) => Ok(verifying_database()),
+            Err(e) => Err(format!("Connection test failed: {e}").into()),
+        }
+    }
+    async fn exit(&self, _context: &mut DynamicStateContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct VerifyingDatabaseHandler;
+
+#[async_trait]
+impl DynamicStateHandler for VerifyingDatabaseHandler {
+    async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+        Ok(verifying_database())
+    }
+    async fn execute(&self, context: &mut DynamicStateContext) -> Result<DynamicState> {
+        if let Some(ref spec) = context.ensure_table {
+            context.qualify_table(&spec.name)?;
+        }
+        let Some(ref mut conn) = context.connection else {
+            return Err("No connection available for database verification".into());
+        };
+        if let Some(ref db_name) = context.database
+            && let Err(e) = conn.query_drop(format!("USE `{db_name}`"))
+        {
+            return Err(format!("Database verification failed: {e}").into());
+        }
+        if let Some(ref spec) = context.ensure_table {
+            spec.ensure(conn, context.database.as_deref())?;
+            println!("✓ Table '{}' ensured", spec.name);
+        }
+        Ok(getting_version())
+    }
+    async fn exit(&self, _context: &mut DynamicStateContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct GettingVersionHandler {
+    next_state: DynamicState,
+}
+
+#[async_trait]
+impl DynamicStateHandler for GettingVersionHandler {
+    async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+        Ok(getting_version())
+    }
+    async fn execute(&self, context: &mut DynamicStateContext) -> Result<DynamicState> {
+        let Some(ref mut conn) = context.connection else {
+            return Err("No connection available for getting version".into());
+        };
+        match conn.query_first::<String, _>("SELECT VERSION()") {
+            Ok(Some(version)) => {
+                context.server_version = Some(version.clone());
+                require_tidb_version(&version, context.min_tidb_version)?;
+                Ok(self.next_state.clone())
+            }
+            Ok(None) => Err("No version returned from server".into()),
+            Err(e) => Err(format!("Failed to get server version: {e}").into()),
+        }
+    }
+    async fn exit(&self, _context: &mut DynamicStateContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ConnectError;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    fn running_tests() -> DynamicState {
+        dynamic_state!("running_tests", "Running Tests")
+    }
+
+    /// Stands in for a state that needs a server, moving straight to `next`
+    struct MockHandler {
+        next: DynamicState,
+    }
+
+    #[async_trait]
+    impl DynamicStateHandler for MockHandler {
+        async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+            Ok(self.next.clone())
+        }
+        async fn execute(&self, context: &mut DynamicStateContext) -> Result<DynamicState> {
+            if self.next == testing_connection() && context.host.is_empty() {
+                return Err(ConnectError::StateMachine("config was not parsed".into()));
+            }
+            if self.next == running_tests() {
+                context.server_version = Some("8.0.11-TiDB-v7.5.0".to_string());
+            }
+            Ok(self.next.clone())
+        }
+        async fn exit(&self, _context: &mut DynamicStateContext) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// The test-specific state registered by a binary
+    struct CustomHandler {
+        ran: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl DynamicStateHandler for CustomHandler {
+        async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+            Ok(running_tests())
+        }
+        async fn execute(&self, context: &mut DynamicStateContext) -> Result<DynamicState> {
+            assert!(context.server_version.is_some());
+            self.ran.store(true, Ordering::SeqCst);
+            Ok(completed())
+        }
+        async fn exit(&self, _context: &mut DynamicStateContext) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn machine_with_custom_state(host: &str, ran: &Arc<AtomicBool>) -> DynamicStateMachine {
+        let mut machine = DynamicStateMachine::new();
+        let params = ConnParams::new(
+            host.to_string(),
+            "root".to_string(),
+            String::new(),
+            Some("test".to_string()),
+        );
+        register_standard_handlers(&mut machine, params, running_tests());
+        machine.register_handler(
+            running_tests(),
+            Box::new(CustomHandler {
+                ran: Arc::clone(ran),
+            }),
+        );
+        machine.register_transitions(running_tests(), vec![completed()]);
+        machine
+    }
+
+    #[tokio::test]
+    async fn test_parsing_handler_populates_context() {
+        let handler = StandardParsingHandler::new(ConnParams::from((
+            "tidb.example:4001".to_string(),
+            "app".to_string(),
+            "secret".to_string(),
+            Some("orders".to_string()),
+        )));
+        let mut context = DynamicStateContext::new();
+        assert_eq!(handler.execute(&mut context).await.unwrap(), connecting());
+        assert_eq!(context.host, "tidb.example");
+        assert_eq!(context.port, 4001);
+        assert_eq!(context.username, "app");
+        assert_eq!(context.password, "secret");
+        assert_eq!(context.database.as_deref(), Some("orders"));
+    }
+
+    #[tokio::test]
+    async fn test_standard_handlers_run_to_custom_state() {
+        let ran = Arc::new(AtomicBool::new(false));
+        let mut machine = machine_with_custom_state("tidb.example:4000", &ran);
+        assert_eq!(machine.validate_graph(), Ok(()));
+
+        // Replace the server-facing states with mocks
+        for (state, next) in [
+            (connecting(), testing_connection()),
+            (testing_connection(), verifying_database()),
+            (verifying_database(), getting_version()),
+            (getting_version(), running_tests()),
+        ] {
+            machine.register_handler(state, Box::new(MockHandler { next }));
+        }
+
+        machine.run().await.unwrap();
+        assert!(ran.load(Ordering::SeqCst));
+        let context = machine.get_context();
+        assert_eq!(context.host, "tidb.example");
+        assert_eq!(context.port, 4000);
+        assert_eq!(context.database.as_deref(), Some("test"));
+        assert_eq!(machine.get_current_state(), &completed());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_host_fails_while_parsing_config() {
+        let ran = Arc::new(AtomicBool::new(false));
+        let mut machine = machine_with_custom_state("no-port", &ran);
+        assert!(machine.run().await.is_err());
+        assert_eq!(machine.get_current_state(), &parsing_config());
+        assert!(!ran.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_standard_handlers_against_live_tidb() {
+        let Ok(host) = std::env::var("TIDB_HOST") else {
+            eprintln!("TIDB_HOST not set, skipping live standard handlers test");
+            return;
+        };
+        let ran = Arc::new(AtomicBool::new(false));
+        let mut machine = DynamicStateMachine::new();
+        let params = ConnParams::new(
+            host,
+            std::env::var("TIDB_USER").unwrap_or_else(|_| "root".to_string()),
+            std::env::var("TIDB_PASSWORD").unwrap_or_default(),
+            None,
+        );
+        register_standard_handlers(&mut machine, params, running_tests());
+        machine.register_handler(
+            running_tests(),
+            Box::new(CustomHandler {
+                ran: Arc::clone(&ran),
+            }),
+        );
+        machine.register_transitions(running_tests(), vec![completed()]);
+        machine.run().await.unwrap();
+        assert!(ran.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_ensure_table_requires_database() {
+        let mut context = DynamicStateContext::new();
+        context.ensure_table =
+            Some(crate::schema::TableSpec::new("events", "id INT PRIMARY KEY").unwrap());
+        let err = VerifyingDatabaseHandler
+            .execute(&mut context)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ConnectError::Configuration(_)));
+        assert!(err.to_string().contains("Cannot use table 'events'"));
+    }
+
+    #[tokio::test]
+    async fn test_connecting_runs_session_sql() {
+        let Some(params) = crate::env_params::EnvParams::tidb() else {
+            eprintln!("TIDB_HOST not set, skipping session SQL test");
+            return;
+        };
+        let (host, port) = crate::connection::parse_host_port(&params.host).unwrap();
+        let mut context = DynamicStateContext::new();
+        context.host = host;
+        context.port = port;
+        context.username = params.user.unwrap_or_else(|| "root".to_string());
+        context.password = params.password.unwrap_or_default();
+        context.session_sql = vec!["SET @init_marker = 3".to_string()];
+        ConnectingHandler.execute(&mut context).await.unwrap();
+
+        let conn = context.connection.as_mut().unwrap();
+        let marker: Option<i64> = conn.query_first("SELECT @init_marker").unwrap().unwrap();
+        assert_eq!(marker, Some(3));
+    }
+}
@@ -3,9 +3,13 @@
 //! Configuration management with support for files (JSON/TOML), environment variables,
 //! and programmatic setup. Provides validation, defaults, and builder patterns.
 
+use crate::connection::{TlsConfig, parse_connection_string};
 use crate::errors::{ConnectError, Result};
+use mysql::{Opts, OptsBuilder, PoolConstraints, PoolOpts};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// Main configuration structure for the `TiDB` connection and testing framework
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -21,6 +25,14 @@ pub struct AppConfig {
     /// Test-specific settings
     #[serde(default)]
     pub test: TestConfig,
+
+    /// Server error codes whose retry classification overrides the built-in one
+    #[serde(default)]
+    pub retry: RetryClassificationConfig,
+
+    /// Connect, statement, per-state and total run timeouts
+    #[serde(default)]
+    pub timeouts: Timeouts,
     // Import job monitoring settings moved to job_monitor.rs
 }
 
@@ -43,15 +55,42 @@ pub struct DatabaseConfig {
     #[serde(default)]
     pub database: Option<String>,
 
-    /// Connection pool size
+    /// Connection pool size (maximum number of connections)
     #[serde(default = "default_pool_size")]
     pub pool_size: u32,
 
+    /// Minimum number of connections the pool keeps open
+    #[serde(default)]
+    pub pool_min: u32,
+
     /// Connection timeout in seconds
     #[serde(default = "default_timeout")]
     pub timeout_secs: u64,
+
+    /// How long to wait for a free pooled connection before reporting the
+    /// pool as exhausted, in seconds
+    #[serde(default = "default_checkout_timeout")]
+    pub checkout_timeout_secs: u64,
+
+    /// TLS settings; connections are unencrypted when absent
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+
+    /// Socket read and write timeout for each statement, in seconds; no limit when absent
+    #[serde(default)]
+    pub statement_timeout_secs: Option<u64>,
+
+    /// Server version reported when the config was generated; informational only
+    #[serde(default)]
+    pub server_version: Option<String>,
 }
 
+/// Values accepted for `logging.level`, compared case-insensitively
+pub const LOG_LEVELS: [&str; 5] = ["trace", "debug", "info", "warn", "error"];
+
+/// Values accepted for `logging.format`, compared case-insensitively
+pub const LOG_FORMATS: [&str; 2] = ["text", "json"];
+
 /// Logging configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoggingConfig {
@@ -88,6 +127,210 @@ pub struct TestConfig {
     pub verbose: bool,
 }
 
+/// Every timeout of a run, in seconds
+///
+/// Defaults come from here, a `[timeouts]` config section overrides them and
+/// the `--timeout-*` flags override both. The connection layer applies
+/// `connect_secs` and `statement_secs` (see [`Timeouts::apply_to`]); the state
+/// machines apply `state_secs` and `total_secs`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timeouts {
+    /// Establishing a TCP connection to the server
+    #[serde(default = "default_timeout")]
+    pub connect_secs: u64,
+
+    /// Reading or writing one statement on the socket; no limit when absent
+    #[serde(default)]
+    pub statement_secs: Option<u64>,
+
+    /// One state of a state machine (enter, execute and exit); no limit when absent
+    #[serde(default)]
+    pub state_secs: Option<u64>,
+
+    /// A whole state machine run; no limit when absent
+    #[serde(default)]
+    pub total_secs: Option<u64>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            connect_secs: default_timeout(),
+            statement_secs: None,
+            state_secs: None,
+            total_secs: None,
+        }
+    }
+}
+
+impl Timeouts {
+    #[must_use]
+    pub fn connect(&self) -> Duration {
+        Duration::from_secs(self.connect_secs)
+    }
+
+    #[must_use]
+    pub fn statement(&self) -> Option<Duration> {
+        self.statement_secs.map(Duration::from_secs)
+    }
+
+    #[must_use]
+    pub fn state(&self) -> Option<Duration> {
+        self.state_secs.map(Duration::from_secs)
+    }
+
+    #[must_use]
+    pub fn total(&self) -> Option<Duration> {
+        self.total_secs.map(Duration::from_secs)
+    }
+
+    /// Set the connect and statement timeouts of `database`
+    pub fn apply_to(&self, database: &mut DatabaseConfig) {
+        database.timeout_secs = self.connect_secs;
+        database.statement_timeout_secs = self.statement_secs;
+    }
+}
+
+/// Retry classification of server error codes
+///
+/// Codes listed here take precedence over the built-in classification used
+/// when connecting (see [`crate::connection::is_retryable_connect_error`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryClassificationConfig {
+    /// Error codes to retry, e.g. site-specific transient errors
+    #[serde(default)]
+    pub retryable_codes: Vec<u16>,
+
+    /// Error codes never to retry, even if retried by default
+    #[serde(default)]
+    pub non_retryable_codes: Vec<u16>,
+}
+
+impl RetryClassificationConfig {
+    /// Configured classification of `code`, or `None` to use the default
+    #[must_use]
+    pub fn classify(&self, code: u16) -> Option<bool> {
+        if self.non_retryable_codes.contains(&code) {
+            Some(false)
+        } else if self.retryable_codes.contains(&code) {
+            Some(true)
+        } else {
+            None
+        }
+    }
+
+    /// Check that no code is listed as both retryable and non-retryable
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConnectError::Configuration` naming the conflicting codes.
+    pub fn validate(&self) -> Result<()> {
+        let conflicts: Vec<String> = self
+            .retryable_codes
+            .iter()
+            .filter(|code| self.non_retryable_codes.contains(code))
+            .map(ToString::to_string)
+            .collect();
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(ConnectError::Configuration(format!(
+                "Error code(s) {} listed as both retryable and non-retryable",
+                conflicts.join(", ")
+            )))
+        }
+    }
+}
+
+impl LoggingConfig {
+    /// Check that `level` and `format` are known values
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the field and the accepted values if either is unknown.
+    pub fn validate(&self) -> Result<()> {
+        check_one_of("logging.level", &self.level, &LOG_LEVELS)?;
+        check_one_of("logging.format", &self.format, &LOG_FORMATS)
+    }
+}
+
+impl TestConfig {
+    /// Check the settings data-driven tests such as the isolation test rely on
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `rows` is 0.
+    pub fn validate(&self) -> Result<()> {
+        if self.rows == 0 {
+            return Err(ConnectError::Configuration(
+                "test.rows must be greater than 0".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Shown in place of a password in [`ConfigDifference`]
+const REDACTED: &str = "********";
+
+/// One field that differs between two configurations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigDifference {
+    /// Dotted field path, e.g. `database.host`
+    pub path: String,
+    /// Value in the first configuration as JSON, `None` if unset
+    pub left: Option<String>,
+    /// Value in the second configuration as JSON, `None` if unset
+    pub right: Option<String>,
+}
+
+impl fmt::Display for ConfigDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |value: &Option<String>| value.clone().unwrap_or_else(|| "(unset)".to_string());
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.path,
+            show(&self.left),
+            show(&self.right)
+        )
+    }
+}
+
+/// Collect the leaves of `value` as `(dotted path, value)` pairs
+///
+/// Arrays are leaves, so a changed list shows up as one difference.
+fn flatten_value(
+    prefix: &str,
+    value: &serde_json::Value,
+    out: &mut Vec<(String, serde_json::Value)>,
+) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten_value(&path, value, out);
+            }
+        }
+        _ => out.push((prefix.to_string(), value.clone())),
+    }
+}
+
+fn check_one_of(field: &str, value: &str, allowed: &[&str]) -> Result<()> {
+    if allowed.iter().any(|a| a.eq_ignore_ascii_case(value)) {
+        Ok(())
+    } else {
+        Err(ConnectError::Configuration(format!(
+            "Unknown {field} '{value}'; expected one of {}",
+            allowed.join(", ")
+        )))
+    }
+}
+
 // ImportJobConfig moved to job_monitor.rs
 
 impl Default for DatabaseConfig {
@@ -98,11 +341,55 @@ impl Default for DatabaseConfig {
             password: None,
             database: None,
             pool_size: default_pool_size(),
+            pool_min: 0,
             timeout_secs: default_timeout(),
+            checkout_timeout_secs: default_checkout_timeout(),
+            tls: None,
+            statement_timeout_secs: None,
+            server_version: None,
         }
     }
 }
 
+impl DatabaseConfig {
+    /// Pool size constraints from `pool_min` and `pool_size`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `pool_min` is greater than `pool_size`.
+    pub fn pool_constraints(&self) -> Result<PoolConstraints> {
+        let (min, max) = (self.pool_min as usize, self.pool_size as usize);
+        PoolConstraints::new(min, max).ok_or_else(|| {
+            ConnectError::Configuration(format!(
+                "Database pool_min ({min}) must not exceed pool_size ({max})"
+            ))
+        })
+    }
+
+    /// Connection options for this configuration, including pool constraints
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the host is invalid or the pool constraints are inconsistent.
+    pub fn opts(&self) -> Result<Opts> {
+        let (host, port) = parse_connection_string(&self.host)?;
+        let pool_opts = PoolOpts::default().with_constraints(self.pool_constraints()?);
+        let statement_timeout = self.statement_timeout_secs.map(Duration::from_secs);
+        let builder = OptsBuilder::new()
+            .ip_or_hostname(Some(host))
+            .tcp_port(port)
+            .user(Some(&self.username))
+            .pass(self.password.as_deref())
+            .db_name(self.database.as_deref())
+            .tcp_connect_timeout(Some(Duration::from_secs(self.timeout_secs)))
+            .read_timeout(statement_timeout)
+            .write_timeout(statement_timeout)
+            .pool_opts(pool_opts)
+            .ssl_opts(self.tls.as_ref().map(TlsConfig::ssl_opts).transpose()?);
+        Ok(builder.into())
+    }
+}
+
 impl Default for LoggingConfig {
     fn default() -> Self {
         Self {
@@ -139,6 +426,9 @@ fn default_pool_size() -> u32 {
 fn default_timeout() -> u64 {
     30
 }
+fn default_checkout_timeout() -> u64 {
+    10
+}
 fn default_log_level() -> String {
     "info".to_string()
 }
@@ -156,44 +446,121 @@ fn default_test_timeout() -> u64 {
 }
 // Import job default functions moved to job_monitor.rs
 
+/// Error for a config path whose extension is not JSON, TOML or YAML
+#[must_use]
+pub fn unsupported_extension(path: &Path, extension: &str) -> ConnectError {
+    ConnectError::Configuration(format!(
+        "Unsupported config file extension '.{extension}' for {}; expected .json, .toml, .yaml or .yml",
+        path.display()
+    ))
+}
+
+/// Parse a config file into a JSON value, without resolving `extends`
+fn read_config_value(path: &Path) -> Result<serde_json::Value> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("json");
+    if !matches!(extension, "json" | "toml" | "yaml" | "yml") {
+        return Err(unsupported_extension(path, extension));
+    }
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| ConnectError::Configuration(format!("Failed to read config file: {e}")))?;
+
+    let value = match extension {
+        "json" => serde_json::from_str(&content).map_err(|e| {
+            ConnectError::Configuration(format!("Failed to parse JSON config: {e}"))
+        })?,
+        "toml" => toml::from_str(&content).map_err(|e| {
+            ConnectError::Configuration(format!("Failed to parse TOML config: {e}"))
+        })?,
+        _ => serde_yaml::from_str(&content).map_err(|e| {
+            ConnectError::Configuration(format!("Failed to parse YAML config: {e}"))
+        })?,
+    };
+    Ok(value)
+}
+
+/// Parse `path` with its `extends` chain merged in
+///
+/// `chain` holds the canonical paths of the files currently being loaded,
+/// outermost first, and is used to detect cycles.
+fn load_config_value(path: &Path, chain: &mut Vec<PathBuf>) -> Result<serde_json::Value> {
+    let mut value = read_config_value(path)?;
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| ConnectError::Configuration(format!("Failed to read config file: {e}")))?;
+    if chain.contains(&canonical) {
+        let cycle: Vec<String> = chain
+            .iter()
+            .chain(std::iter::once(&canonical))
+            .map(|p| p.display().to_string())
+            .collect();
+        return Err(ConnectError::Configuration(format!(
+            "Config files extend each other in a cycle: {}",
+            cycle.join(" -> ")
+        )));
+    }
+
+    let extends = value
+        .as_object_mut()
+        .and_then(|table| table.remove("extends"));
+    match extends {
+        None => Ok(value),
+        Some(serde_json::Value::String(base)) => {
+            let base_path = path.parent().unwrap_or(Path::new(".")).join(base);
+            chain.push(canonical);
+            let base = load_config_value(&base_path, chain);
+            chain.pop();
+            let mut merged = base?;
+            merge_config_values(&mut merged, value);
+            Ok(merged)
+        }
+        Some(other) => Err(ConnectError::Configuration(format!(
+            "'extends' in {} must be a file path, got {other}",
+            path.display()
+        ))),
+    }
+}
+
+/// Merge `overlay` into `base`: tables key by key, any other value replaces the base's
+fn merge_config_values(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (base, overlay) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_config_values(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
 impl AppConfig {
     /// Load configuration from a file
     ///
+    /// The format is chosen by extension: `.json` (or none), `.toml`, `.yaml` or `.yml`.
+    ///
+    /// A top-level `extends` key names a base config file, relative to the
+    /// directory of the file that contains it. The base is loaded first (and
+    /// may itself extend another file) and the current file's values are
+    /// merged over it: tables are merged key by key, anything else replaces
+    /// the base value. Base and override may use different formats.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the file cannot be read or parsed.
+    /// Returns an error if a file cannot be read or parsed, or if the
+    /// `extends` chain contains a cycle.
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path = path.as_ref();
-        let extension = path
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .unwrap_or("json");
-
-        let config = match extension {
-            "json" => {
-                let content = std::fs::read_to_string(path).map_err(|e| {
-                    ConnectError::Configuration(format!("Failed to read config file: {e}"))
-                })?;
-                serde_json::from_str(&content).map_err(|e| {
-                    ConnectError::Configuration(format!("Failed to parse JSON config: {e}"))
-                })?
-            }
-            "toml" => {
-                let content = std::fs::read_to_string(path).map_err(|e| {
-                    ConnectError::Configuration(format!("Failed to read config file: {e}"))
-                })?;
-                toml::from_str(&content).map_err(|e| {
-                    ConnectError::Configuration(format!("Failed to parse TOML config: {e}"))
-                })?
-            }
-            _ => {
-                return Err(ConnectError::Configuration(format!(
-                    "Unsupported config file format: {extension}"
-                )));
-            }
-        };
-
-        Ok(config)
+        let value = load_config_value(path, &mut Vec::new())?;
+        serde_json::from_value(value).map_err(|e| {
+            ConnectError::Configuration(format!("Invalid config in {}: {e}", path.display()))
+        })
     }
 
     /// Load configuration from file with environment variable overrides
@@ -275,11 +642,10 @@ impl AppConfig {
             "toml" => toml::to_string_pretty(self).map_err(|e| {
                 ConnectError::Configuration(format!("Failed to serialize config: {e}"))
             })?,
-            _ => {
-                return Err(ConnectError::Configuration(format!(
-                    "Unsupported config file format: {extension}"
-                )));
-            }
+            "yaml" | "yml" => serde_yaml::to_string(self).map_err(|e| {
+                ConnectError::Configuration(format!("Failed to serialize config: {e}"))
+            })?,
+            _ => return Err(unsupported_extension(path, extension)),
         };
 
         std::fs::write(path, content).map_err(|e| {
@@ -289,6 +655,49 @@ impl AppConfig {
         Ok(())
     }
 
+    /// Field-level differences from `self` to `other`, sorted by path
+    ///
+    /// Fields are compared by their serialized values, so a field set to its
+    /// default in one file and omitted in the other is not a difference.
+    /// Password values are redacted.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> Vec<ConfigDifference> {
+        let (mut left, mut right) = (Vec::new(), Vec::new());
+        // Serializing plain data cannot fail
+        flatten_value(
+            "",
+            &serde_json::to_value(self).unwrap_or_default(),
+            &mut left,
+        );
+        flatten_value(
+            "",
+            &serde_json::to_value(other).unwrap_or_default(),
+            &mut right,
+        );
+        let left: std::collections::BTreeMap<_, _> = left.into_iter().collect();
+        let right: std::collections::BTreeMap<_, _> = right.into_iter().collect();
+
+        let mut paths: Vec<&String> = left.keys().chain(right.keys()).collect();
+        paths.sort();
+        paths.dedup();
+        paths
+            .into_iter()
+            .filter(|path| left.get(*path) != right.get(*path))
+            .map(|path| {
+                let show = |value: Option<&serde_json::Value>| match value {
+                    None | Some(serde_json::Value::Null) => None,
+                    Some(_) if path.ends_with("password") => Some(REDACTED.to_string()),
+                    Some(value) => Some(value.to_string()),
+                };
+                ConfigDifference {
+                    path: path.clone(),
+                    left: show(left.get(path)),
+                    right: show(right.get(path)),
+                }
+            })
+            .collect()
+    }
+
     /// Get the database password, checking environment variables if not set in config
     #[must_use]
     pub fn get_password(&self) -> Option<String> {
@@ -319,12 +728,14 @@ impl AppConfig {
                 "Database pool size must be greater than 0".to_string(),
             ));
         }
+        self.database.pool_constraints()?;
         if self.database.timeout_secs == 0 {
             return Err(ConnectError::Configuration(
                 "Database timeout must be greater than 0".to_string(),
             ));
         }
-        Ok(())
+        self.logging.validate()?;
+        self.retry.validate()
     }
 }
 
@@ -371,18 +782,73 @@ impl ConfigBuilder {
         self
     }
 
+    /// Maximum number of pooled connections
+    #[must_use]
+    pub fn pool_size(mut self, size: u32) -> Self {
+        self.config.database.pool_size = size;
+        self
+    }
+
+    /// Connection timeout in seconds
+    #[must_use]
+    pub fn timeout_secs(mut self, secs: u64) -> Self {
+        self.config.database.timeout_secs = secs;
+        self
+    }
+
+    /// Connect over TLS with these settings
+    #[must_use]
+    pub fn tls(mut self, tls: TlsConfig) -> Self {
+        self.config.database.tls = Some(tls);
+        self
+    }
+
+    /// Record the server version the config was generated against
+    #[must_use]
+    pub fn server_version(mut self, version: impl Into<String>) -> Self {
+        self.config.database.server_version = Some(version.into());
+        self
+    }
+
     #[must_use]
     pub fn log_level(mut self, level: impl Into<String>) -> Self {
         self.config.logging.level = level.into();
         self
     }
 
+    /// Log format (`json` or `text`)
+    #[must_use]
+    pub fn log_format(mut self, format: impl Into<String>) -> Self {
+        self.config.logging.format = format.into();
+        self
+    }
+
+    /// Also log to this file
+    #[must_use]
+    pub fn log_file(mut self, file: impl Into<String>) -> Self {
+        self.config.logging.file = Some(file.into());
+        self
+    }
+
     #[must_use]
     pub fn test_rows(mut self, rows: u32) -> Self {
         self.config.test.rows = rows;
         self
     }
 
+    /// Test timeout in seconds
+    #[must_use]
+    pub fn test_timeout(mut self, secs: u64) -> Self {
+        self.config.test.timeout_secs = secs;
+        self
+    }
+
+    #[must_use]
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.config.test.verbose = verbose;
+        self
+    }
+
     // Removed monitor_duration
 
     #[must_use]
@@ -394,10 +860,85 @@ impl ConfigBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::EnvVarGuard;
     use serial_test::serial;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
+    #[test]
+    fn test_pool_constraints_from_config() {
+        let config = DatabaseConfig {
+            pool_min: 2,
+            pool_size: 8,
+            ..DatabaseConfig::default()
+        };
+        let opts = config.opts().unwrap();
+        let constraints = opts.get_pool_opts().constraints();
+        assert_eq!(constraints.min(), 2);
+        assert_eq!(constraints.max(), 8);
+
+        let invalid = AppConfig {
+            database: DatabaseConfig {
+                pool_min: 9,
+                pool_size: 8,
+                ..DatabaseConfig::default()
+            },
+            ..AppConfig::default()
+        };
+        let err = invalid.validate().unwrap_err().to_string();
+        assert!(err.contains("pool_min (9) must not exceed pool_size (8)"));
+        assert!(invalid.database.opts().is_err());
+    }
+
+    #[test]
+    fn test_tls_section_sets_ssl_opts() {
+        let toml = r#"
+            [database]
+            host = "h:4000"
+            [database.tls]
+            ca = "/etc/tidb/ca.pem"
+        "#;
+        let config: AppConfig = toml::from_str(toml).unwrap();
+        let tls = config.database.tls.as_ref().unwrap();
+        assert_eq!(tls.ca.as_deref(), Some(Path::new("/etc/tidb/ca.pem")));
+        assert!(!tls.skip_verify);
+        let opts = config.database.opts().unwrap();
+        assert!(opts.get_ssl_opts().is_some());
+
+        assert!(
+            DatabaseConfig::default()
+                .opts()
+                .unwrap()
+                .get_ssl_opts()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_retry_section() {
+        let toml = r#"
+            [retry]
+            retryable_codes = [8028, 9999]
+            non_retryable_codes = [1040]
+        "#;
+        let config: AppConfig = toml::from_str(toml).unwrap();
+        assert_eq!(config.retry.classify(9999), Some(true));
+        assert_eq!(config.retry.classify(1040), Some(false));
+        assert_eq!(config.retry.classify(1045), None);
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            AppConfig::default().retry,
+            RetryClassificationConfig::default()
+        );
+
+        let conflicting = RetryClassificationConfig {
+            retryable_codes: vec![1040, 8028],
+            non_retryable_codes: vec![1040],
+        };
+        let err = conflicting.validate().unwrap_err().to_string();
+        assert!(err.contains("1040 listed as both"));
+    }
+
     #[test]
     #[serial]
     fn test_default_config() {
@@ -455,22 +996,12 @@ mod tests {
     #[test]
     #[serial]
     fn test_env_override() {
-        let prev = std::env::var("TIDB_HOST").ok();
         let unique = "envhost_config_test";
-        unsafe {
-            std::env::set_var("TIDB_HOST", unique);
-        }
+        let mut env = EnvVarGuard::default();
+        env.set("TIDB_HOST", unique);
         let mut config = AppConfig::default();
         config.apply_environment_overrides();
         assert_eq!(config.database.host, unique);
-        match prev {
-            Some(val) => unsafe {
-                std::env::set_var("TIDB_HOST", val);
-            },
-            None => unsafe {
-                std::env::remove_var("TIDB_HOST");
-            },
-        }
     }
 
     #[test]
@@ -482,4 +1013,257 @@ mod tests {
         let loaded = AppConfig::from_file(file.path()).unwrap();
         assert_eq!(loaded.database.host, "localhost:4000");
     }
+
+    #[test]
+    #[serial]
+    fn test_save_and_load_yaml_with_env_override() {
+        let mut config = AppConfig::default();
+        config.database.host = "yaml-host:4000".to_string();
+        config.test.rows = 42;
+        for suffix in [".yaml", ".yml"] {
+            let file = tempfile::Builder::new().suffix(suffix).tempfile().unwrap();
+            config.save_to_file(file.path()).unwrap();
+            let content = std::fs::read_to_string(file.path()).unwrap();
+            assert!(content.contains("host: yaml-host:4000"));
+            let loaded = AppConfig::from_file(file.path()).unwrap();
+            assert_eq!(loaded.database.host, "yaml-host:4000");
+            assert_eq!(loaded.test.rows, 42);
+        }
+
+        let file = tempfile::Builder::new().suffix(".yaml").tempfile().unwrap();
+        config.save_to_file(file.path()).unwrap();
+        let mut env = EnvVarGuard::default();
+        env.set("TIDB_TEST_ROWS", "7");
+        let loaded = AppConfig::from_file_with_env(file.path()).unwrap();
+        drop(env);
+        assert_eq!(loaded.test.rows, 7);
+        assert_eq!(loaded.database.host, "yaml-host:4000");
+    }
+
+    #[test]
+    #[serial]
+    fn test_extends_merges_base_under_override() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("shared")).unwrap();
+        std::fs::write(
+            dir.path().join("shared/base.toml"),
+            r#"
+                [database]
+                host = "staging-tidb:4000"
+                username = "tester"
+                database = "orders"
+                [logging]
+                level = "debug"
+                [test]
+                rows = 25
+                [retry]
+                retryable_codes = [8028]
+            "#,
+        )
+        .unwrap();
+        let prod = dir.path().join("prod.json");
+        std::fs::write(
+            &prod,
+            r#"{
+                "extends": "shared/base.toml",
+                "database": {"host": "prod-tidb:4000"},
+                "retry": {"retryable_codes": [9007]}
+            }"#,
+        )
+        .unwrap();
+
+        let config = AppConfig::from_file(&prod).unwrap();
+        // Overridden by prod.json
+        assert_eq!(config.database.host, "prod-tidb:4000");
+        assert_eq!(config.retry.retryable_codes, vec![9007]);
+        // Inherited from shared/base.toml
+        assert_eq!(config.database.username, "tester");
+        assert_eq!(config.database.database.as_deref(), Some("orders"));
+        assert_eq!(config.logging.level, "debug");
+        assert_eq!(config.test.rows, 25);
+        // Defaults where neither file sets a value
+        assert_eq!(
+            config.database.pool_size,
+            DatabaseConfig::default().pool_size
+        );
+    }
+
+    #[test]
+    fn test_extends_cycle_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.yaml");
+        let b = dir.path().join("b.json");
+        std::fs::write(&a, "extends: b.json\ndatabase:\n  host: a:4000\n").unwrap();
+        std::fs::write(&b, r#"{"extends": "a.yaml"}"#).unwrap();
+
+        let err = AppConfig::from_file(&a).unwrap_err();
+        assert!(matches!(err, ConnectError::Configuration(_)));
+        let message = err.to_string();
+        assert!(message.contains("cycle"), "{message}");
+        assert!(message.contains("a.yaml -> "), "{message}");
+
+        let self_extending = dir.path().join("self.json");
+        std::fs::write(&self_extending, r#"{"extends": "./self.json"}"#).unwrap();
+        assert!(AppConfig::from_file(&self_extending).is_err());
+
+        let missing_base = dir.path().join("missing.json");
+        std::fs::write(&missing_base, r#"{"extends": "nope.json"}"#).unwrap();
+        assert!(AppConfig::from_file(&missing_base).is_err());
+    }
+
+    #[test]
+    fn test_timeouts_reach_connection_opts() {
+        let config: AppConfig = toml::from_str(
+            r#"
+                [timeouts]
+                connect_secs = 5
+                statement_secs = 20
+                total_secs = 600
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.timeouts.state_secs, None);
+        assert_eq!(config.timeouts.total(), Some(Duration::from_secs(600)));
+
+        let mut database = DatabaseConfig::default();
+        config.timeouts.apply_to(&mut database);
+        let opts = database.opts().unwrap();
+        assert_eq!(opts.get_tcp_connect_timeout(), Some(Duration::from_secs(5)));
+        assert_eq!(opts.get_read_timeout(), Some(&Duration::from_secs(20)));
+        assert_eq!(opts.get_write_timeout(), Some(&Duration::from_secs(20)));
+
+        let defaults = Timeouts::default();
+        assert_eq!(
+            defaults.connect_secs,
+            DatabaseConfig::default().timeout_secs
+        );
+        let opts = DatabaseConfig::default().opts().unwrap();
+        assert_eq!(opts.get_read_timeout(), None);
+    }
+
+    #[test]
+    fn test_builder_sets_every_field() {
+        let tls = TlsConfig {
+            ca: Some(PathBuf::from("/etc/tidb/ca.pem")),
+            ..TlsConfig::default()
+        };
+        let config = ConfigBuilder::new()
+            .host("tidb:4000")
+            .username("tester")
+            .password("pw")
+            .database("orders")
+            .pool_size(32)
+            .timeout_secs(5)
+            .tls(tls.clone())
+            .log_level("debug")
+            .log_format("json")
+            .log_file("logs/run.log")
+            .test_rows(100)
+            .test_timeout(600)
+            .verbose(true)
+            .build();
+
+        assert_eq!(config.database.host, "tidb:4000");
+        assert_eq!(config.database.username, "tester");
+        assert_eq!(config.database.password.as_deref(), Some("pw"));
+        assert_eq!(config.database.database.as_deref(), Some("orders"));
+        assert_eq!(config.database.pool_size, 32);
+        assert_eq!(config.database.timeout_secs, 5);
+        assert_eq!(config.database.tls, Some(tls));
+        assert_eq!(config.logging.level, "debug");
+        assert_eq!(config.logging.format, "json");
+        assert_eq!(config.logging.file.as_deref(), Some("logs/run.log"));
+        assert_eq!(config.test.rows, 100);
+        assert_eq!(config.test.timeout_secs, 600);
+        assert!(config.test.verbose);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_logging_and_test_sections() {
+        let valid = AppConfig::default();
+        assert!(valid.validate().is_ok());
+        assert!(valid.test.validate().is_ok());
+
+        let mut upper = AppConfig::default();
+        upper.logging.level = "DEBUG".to_string();
+        upper.logging.format = "Json".to_string();
+        assert!(upper.validate().is_ok());
+
+        let mut bad_level = AppConfig::default();
+        bad_level.logging.level = "verbsoe".to_string();
+        let err = bad_level.validate().unwrap_err();
+        assert!(matches!(err, ConnectError::Configuration(_)));
+        assert!(
+            err.to_string().contains(
+                "Unknown logging.level 'verbsoe'; expected one of trace, debug, info, warn, error"
+            ),
+            "{err}"
+        );
+
+        let mut bad_format = AppConfig::default();
+        bad_format.logging.format = "yaml".to_string();
+        let err = bad_format.validate().unwrap_err();
+        assert!(matches!(err, ConnectError::Configuration(_)));
+        assert!(
+            err.to_string()
+                .contains("Unknown logging.format 'yaml'; expected one of text, json"),
+            "{err}"
+        );
+
+        let no_rows = TestConfig {
+            rows: 0,
+            ..AppConfig::default().test
+        };
+        let err = no_rows.validate().unwrap_err();
+        assert!(matches!(err, ConnectError::Configuration(_)));
+        assert!(err.to_string().contains("test.rows must be greater than 0"));
+    }
+
+    #[test]
+    fn test_diff_reports_changed_fields() {
+        let dev = AppConfig::default();
+        assert!(dev.diff(&dev.clone()).is_empty());
+
+        let mut prod = dev.clone();
+        prod.database.host = "prod-tidb:4000".to_string();
+        prod.database.password = Some("s3cret".to_string());
+        prod.logging.level = "warn".to_string();
+        prod.timeouts.total_secs = Some(600);
+
+        let diff = dev.diff(&prod);
+        let paths: Vec<&str> = diff.iter().map(|d| d.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "database.host",
+                "database.password",
+                "logging.level",
+                "timeouts.total_secs"
+            ]
+        );
+        assert_eq!(
+            diff[0].to_string(),
+            "database.host: \"localhost:4000\" -> \"prod-tidb:4000\""
+        );
+        assert_eq!(
+            diff[1].to_string(),
+            "database.password: (unset) -> ********"
+        );
+        assert!(!diff.iter().any(|d| d.to_string().contains("s3cret")));
+        assert_eq!(diff[3].right.as_deref(), Some("600"));
+    }
+
+    #[test]
+    fn test_unsupported_extension_is_named() {
+        let file = tempfile::Builder::new().suffix(".ini").tempfile().unwrap();
+        let err = AppConfig::from_file(file.path()).unwrap_err().to_string();
+        assert!(err.contains("'.ini'"), "{err}");
+        assert!(err.contains(".yaml"), "{err}");
+        let err = AppConfig::default()
+            .save_to_file(file.path())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("'.ini'"), "{err}");
+    }
 }
@@ -0,0 +1,245 @@
+//! # Config Hot-Reload
+//!
+//! Long-running monitors read their settings through a [`SharedConfig`] so
+//! operators can change safe settings, such as the poll interval, by editing
+//! the config file instead of restarting. [`Reloadable::apply_reload`] decides
+//! which fields are copied from the new file; changes that would need a new
+//! connection are logged and ignored.
+//!
+//! With the `hot_reload` feature, [`ConfigWatcher`] watches the file and
+//! reloads it whenever it changes.
+
+use crate::errors::Result;
+use std::path::Path;
+#[cfg(feature = "hot_reload")]
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+/// A configuration whose safe fields can change while a run is in progress
+pub trait Reloadable: Clone + Send + Sync + 'static {
+    /// Load the configuration from `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    fn load(path: &Path) -> Result<Self>;
+
+    /// Copy the fields that are safe to change at runtime from `new`
+    ///
+    /// Returns the names of the fields that changed. Implementations should log
+    /// and skip changes that require reconnecting.
+    fn apply_reload(&mut self, new: &Self) -> Vec<&'static str>;
+}
+
+/// Configuration shared between a running monitor and its reloader
+#[derive(Debug, Default)]
+pub struct SharedConfig<T> {
+    inner: Arc<RwLock<T>>,
+}
+
+impl<T> Clone for SharedConfig<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T: Reloadable> SharedConfig<T> {
+    #[must_use]
+    pub fn new(config: T) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(config)),
+        }
+    }
+
+    /// Snapshot of the current configuration
+    #[must_use]
+    pub fn current(&self) -> T {
+        match self.inner.read() {
+            Ok(config) => config.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    /// Re-read `path` and apply its safe changes
+    ///
+    /// Returns the names of the fields that changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be loaded; the configuration is
+    /// left unchanged.
+    pub fn reload_from(&self, path: &Path) -> Result<Vec<&'static str>> {
+        let new = T::load(path)?;
+        let mut config = match self.inner.write() {
+            Ok(config) => config,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let changed = config.apply_reload(&new);
+        if !changed.is_empty() {
+            tracing::info!(
+                "Reloaded {}: updated {}",
+                path.display(),
+                changed.join(", ")
+            );
+        }
+        Ok(changed)
+    }
+}
+
+/// Reloads a [`SharedConfig`] whenever its file changes; stops when dropped
+#[cfg(feature = "hot_reload")]
+pub struct ConfigWatcher {
+    path: PathBuf,
+    _watcher: notify::RecommendedWatcher,
+}
+
+#[cfg(feature = "hot_reload")]
+impl ConfigWatcher {
+    /// Watch `path` and reload `config` from it on every change
+    ///
+    /// The parent directory is watched so that editors which replace the file
+    /// instead of writing it in place are also picked up. Files that fail to
+    /// load are logged and ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file system watcher cannot be created.
+    pub fn watch<T: Reloadable>(path: impl Into<PathBuf>, config: SharedConfig<T>) -> Result<Self> {
+        use notify::{EventKind, RecursiveMode, Watcher};
+
+        let path = path.into();
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let file_name = path.file_name().map(ToOwned::to_owned);
+        let reload_path = path.clone();
+
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else { return };
+                let relevant = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+                    && event
+                        .paths
+                        .iter()
+                        .any(|changed| changed.file_name() == file_name.as_deref());
+                if relevant && let Err(e) = config.reload_from(&reload_path) {
+                    tracing::warn!("Ignoring invalid config {}: {}", reload_path.display(), e);
+                }
+            })
+            .map_err(|e| {
+                crate::errors::ConnectError::Configuration(format!("Cannot watch config: {e}"))
+            })?;
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(|e| {
+                crate::errors::ConnectError::Configuration(format!(
+                    "Cannot watch {}: {e}",
+                    dir.display()
+                ))
+            })?;
+
+        Ok(Self {
+            path,
+            _watcher: watcher,
+        })
+    }
+
+    /// The watched file
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ConnectError;
+    use serde::Deserialize;
+
+    #[derive(Debug, Clone, PartialEq, Deserialize)]
+    struct MonitorSettings {
+        host: String,
+        poll_interval: u64,
+        max_lag_secs: u64,
+    }
+
+    impl Reloadable for MonitorSettings {
+        fn load(path: &Path) -> Result<Self> {
+            let content = std::fs::read_to_string(path)?;
+            serde_json::from_str(&content).map_err(|e| ConnectError::Configuration(e.to_string()))
+        }
+
+        fn apply_reload(&mut self, new: &Self) -> Vec<&'static str> {
+            let mut changed = Vec::new();
+            if self.poll_interval != new.poll_interval {
+                self.poll_interval = new.poll_interval;
+                changed.push("poll_interval");
+            }
+            if self.max_lag_secs != new.max_lag_secs {
+                self.max_lag_secs = new.max_lag_secs;
+                changed.push("max_lag_secs");
+            }
+            if self.host != new.host {
+                tracing::warn!(
+                    "Ignoring host change to {}; requires reconnecting",
+                    new.host
+                );
+            }
+            changed
+        }
+    }
+
+    fn write_settings(path: &Path, host: &str, poll_interval: u64, max_lag_secs: u64) {
+        std::fs::write(
+            path,
+            format!(
+                r#"{{"host": "{host}", "poll_interval": {poll_interval}, "max_lag_secs": {max_lag_secs}}}"#
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_reload_applies_safe_fields_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("monitor.json");
+        write_settings(&path, "tidb-1:4000", 5, 30);
+        let shared = SharedConfig::new(MonitorSettings::load(&path).unwrap());
+
+        write_settings(&path, "tidb-2:4000", 1, 60);
+        let changed = shared.reload_from(&path).unwrap();
+        assert_eq!(changed, vec!["poll_interval", "max_lag_secs"]);
+
+        let current = shared.current();
+        assert_eq!(current.poll_interval, 1);
+        assert_eq!(current.max_lag_secs, 60);
+        assert_eq!(current.host, "tidb-1:4000");
+
+        // A broken file leaves the configuration untouched
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(shared.reload_from(&path).is_err());
+        assert_eq!(shared.current(), current);
+    }
+
+    #[cfg(feature = "hot_reload")]
+    #[test]
+    fn test_watcher_picks_up_file_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("monitor.json");
+        write_settings(&path, "tidb-1:4000", 5, 30);
+        let shared = SharedConfig::new(MonitorSettings::load(&path).unwrap());
+        let watcher = ConfigWatcher::watch(&path, shared.clone()).unwrap();
+        assert_eq!(watcher.path(), path);
+
+        write_settings(&path, "tidb-1:4000", 2, 30);
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while shared.current().poll_interval != 2 && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        assert_eq!(shared.current().poll_interval, 2);
+    }
+}
@@ -3,28 +3,44 @@
 //! Low-level database connection utilities and parsing functions.
 //! Provides connection pool creation, connection testing, and host/port parsing.
 
-use crate::errors::{ConnectionError, Result};
+use crate::config::{DatabaseConfig, RetryClassificationConfig, Timeouts};
+use crate::env_params::EnvParams;
+use crate::errors::{ConnectError, ConnectionError, Result, RetryConfig};
+use crate::retry::retry_with_backoff_if;
 use mysql::prelude::*;
-use mysql::{OptsBuilder, Pool, PooledConn};
+use mysql::{DriverError, Opts, OptsBuilder, Pool, PooledConn, SslOpts};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
 
-/// Parse host and port from a string in format "host:port"
+/// Parse host and port from a string in format "host:port" or "[ipv6]:port"
+///
+/// The brackets around an IPv6 host are removed.
 ///
 /// # Errors
 ///
 /// Returns an error if the string format is invalid or port cannot be parsed.
 pub fn parse_host_port(host_port: &str) -> Result<(String, u16)> {
-    let parts: Vec<&str> = host_port.split(':').collect();
-    if parts.len() != 2 {
+    let split = match host_port.strip_prefix('[') {
+        Some(bracketed) => bracketed
+            .split_once("]:")
+            .filter(|(host, _)| !host.is_empty()),
+        None => host_port
+            .split_once(':')
+            .filter(|(_, port)| !port.contains(':')),
+    };
+    let Some((host, port)) = split else {
         return Err(ConnectionError::ConnectFailed {
             host: host_port.to_string(),
             port: 0,
-            message: "Host must be in format hostname:port".to_string(),
+            message: "Host must be in format hostname:port or [ipv6]:port".to_string(),
         }
         .into());
-    }
+    };
 
-    let host = parts[0].to_string();
-    let port = parts[1]
+    let host = host.to_string();
+    let port = port
         .parse::<u16>()
         .map_err(|_| ConnectionError::ConnectFailed {
             host: host.clone(),
@@ -35,6 +51,17 @@ pub fn parse_host_port(host_port: &str) -> Result<(String, u16)> {
     Ok((host, port))
 }
 
+/// Join a host and port into the form [`parse_host_port`] accepts, bracketing
+/// IPv6 hosts
+#[must_use]
+pub fn format_host_port(host: &str, port: u16) -> String {
+    if host.contains(':') {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
+    }
+}
+
 /// Parse connection string in format "hostname:port"
 ///
 /// # Errors
@@ -62,18 +89,70 @@ pub fn parse_user_pass(user_pass: &str) -> Result<(String, String)> {
     Ok((parts[0].to_string(), parts[1].to_string()))
 }
 
-/// Create a connection pool
+/// TLS settings for connections
+///
+/// Connecting over TLS needs the `tls` feature; without it the driver rejects
+/// TLS connections.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TlsConfig {
+    /// CA certificate used to verify the server
+    #[serde(default)]
+    pub ca: Option<PathBuf>,
+
+    /// Client certificate chain for mutual TLS (requires `key`)
+    #[serde(default)]
+    pub cert: Option<PathBuf>,
+
+    /// Client private key for mutual TLS (requires `cert`)
+    #[serde(default)]
+    pub key: Option<PathBuf>,
+
+    /// Skip server certificate and host name verification
+    #[serde(default)]
+    pub skip_verify: bool,
+}
+
+impl TlsConfig {
+    /// Driver SSL options for this configuration
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConnectError::Configuration` if only one of `cert` and `key` is
+    /// set, or a client certificate is given without the `tls` feature.
+    pub fn ssl_opts(&self) -> Result<SslOpts> {
+        let opts = SslOpts::default()
+            .with_root_cert_path(self.ca.clone())
+            .with_danger_skip_domain_validation(self.skip_verify)
+            .with_danger_accept_invalid_certs(self.skip_verify);
+        match (&self.cert, &self.key) {
+            (None, None) => Ok(opts),
+            #[cfg(feature = "tls")]
+            (Some(cert), Some(key)) => Ok(opts
+                .with_client_identity(Some(mysql::ClientIdentity::new(cert.clone(), key.clone())))),
+            #[cfg(not(feature = "tls"))]
+            (Some(_), Some(_)) => Err(ConnectError::Configuration(
+                "TLS client certificates require building with the `tls` feature".to_string(),
+            )),
+            _ => Err(ConnectError::Configuration(
+                "TLS client certificate and key must be given together".to_string(),
+            )),
+        }
+    }
+}
+
+/// Connection options for a single server, with TLS if `tls` is given
 ///
 /// # Errors
 ///
-/// Returns an error if the connection pool cannot be created.
-pub fn create_connection_pool(
+/// Returns an error if the TLS configuration is invalid.
+pub fn connection_opts(
     host: &str,
     port: u16,
     user: &str,
     password: &str,
     database: Option<&str>,
-) -> Result<Pool> {
+    tls: Option<&TlsConfig>,
+) -> Result<Opts> {
     let mut builder = OptsBuilder::new()
         .ip_or_hostname(Some(host))
         .tcp_port(port)
@@ -83,9 +162,134 @@ pub fn create_connection_pool(
     if let Some(db) = database {
         builder = builder.db_name(Some(db));
     }
+    if let Some(tls) = tls {
+        builder = builder.ssl_opts(tls.ssl_opts()?);
+    }
 
-    let pool = Pool::new(builder)?;
-    Ok(pool)
+    Ok(builder.into())
+}
+
+/// Create a connection pool with the default `DatabaseConfig` pool size and
+/// connect timeout
+///
+/// # Errors
+///
+/// Returns an error if the connection pool cannot be created.
+pub fn create_connection_pool(
+    host: &str,
+    port: u16,
+    user: &str,
+    password: &str,
+    database: Option<&str>,
+) -> Result<Pool> {
+    create_connection_pool_with_tls(host, port, user, password, database, None)
+}
+
+/// Create a connection pool with default pool settings, connecting over TLS if
+/// `tls` is given
+///
+/// # Errors
+///
+/// Returns an error if the TLS configuration is invalid or the connection pool
+/// cannot be created.
+pub fn create_connection_pool_with_tls(
+    host: &str,
+    port: u16,
+    user: &str,
+    password: &str,
+    database: Option<&str>,
+    tls: Option<&TlsConfig>,
+) -> Result<Pool> {
+    let config = database_config(host, port, user, password, database, tls);
+    create_connection_pool_with_config(&config)
+}
+
+/// [`DatabaseConfig`] for a single server, with default pool size and timeout
+pub(crate) fn database_config(
+    host: &str,
+    port: u16,
+    user: &str,
+    password: &str,
+    database: Option<&str>,
+    tls: Option<&TlsConfig>,
+) -> DatabaseConfig {
+    DatabaseConfig {
+        host: format_host_port(host, port),
+        username: user.to_string(),
+        password: Some(password.to_string()),
+        database: database.map(ToString::to_string),
+        tls: tls.cloned(),
+        ..DatabaseConfig::default()
+    }
+}
+
+/// Create a connection pool from a [`DatabaseConfig`]
+///
+/// The pool holds between `pool_min` and `pool_size` connections, and each new
+/// connection gives up after `timeout_secs`.
+///
+/// # Errors
+///
+/// Returns an error if the configuration is invalid or the pool cannot be created.
+pub fn create_connection_pool_with_config(config: &DatabaseConfig) -> Result<Pool> {
+    Ok(Pool::new(config.opts()?)?)
+}
+
+/// Something connections can be checked out of, such as a [`Pool`]
+pub trait ConnectionSource {
+    type Conn;
+
+    /// Check out a connection, waiting at most `timeout` for one to free up
+    ///
+    /// # Errors
+    ///
+    /// Returns `DriverError::Timeout` if no connection became free in time,
+    /// or the error from opening a new connection.
+    fn try_checkout(&self, timeout: Duration) -> std::result::Result<Self::Conn, mysql::Error>;
+}
+
+impl ConnectionSource for Pool {
+    type Conn = PooledConn;
+
+    fn try_checkout(&self, timeout: Duration) -> std::result::Result<PooledConn, mysql::Error> {
+        self.try_get_conn(timeout)
+    }
+}
+
+/// Check out a connection, reporting an exhausted pool instead of blocking
+///
+/// # Errors
+///
+/// Returns `ConnectError::Resource` naming the pool size if every one of the
+/// `pool_size` connections is still in use after `timeout`, or the
+/// underlying error if a new connection cannot be opened.
+pub fn checkout_connection<S: ConnectionSource>(
+    source: &S,
+    timeout: Duration,
+    pool_size: u32,
+) -> Result<S::Conn> {
+    source.try_checkout(timeout).map_err(|e| match e {
+        mysql::Error::DriverError(DriverError::Timeout) => ConnectionError::PoolExhausted {
+            max_connections: pool_size as usize,
+            waited: timeout,
+        }
+        .into(),
+        e => e.into(),
+    })
+}
+
+/// Check out a connection from a pool created with `config`, waiting up to
+/// its `checkout_timeout_secs`
+///
+/// # Errors
+///
+/// Returns an error if the pool is exhausted or a connection cannot be opened.
+pub fn get_pooled_connection(pool: &Pool, config: &DatabaseConfig) -> Result<PooledConn> {
+    checkout_connection(
+        pool,
+        Duration::from_secs(config.checkout_timeout_secs),
+        config.pool_size,
+    )
 }
 
 /// Create a single connection
@@ -100,10 +304,221 @@ pub fn create_connection(
     password: &str,
     database: Option<&str>,
 ) -> Result<PooledConn> {
-    let pool = create_connection_pool(host, port, user, password, database)?;
-    let conn = pool.get_conn()?;
+    create_connection_with_tls(host, port, user, password, database, None)
+}
+
+/// Create a single connection, over TLS if `tls` is given
+///
+/// # Errors
+///
+/// Returns an error if the TLS configuration is invalid or the connection
+/// cannot be established.
+pub fn create_connection_with_tls(
+    host: &str,
+    port: u16,
+    user: &str,
+    password: &str,
+    database: Option<&str>,
+    tls: Option<&TlsConfig>,
+) -> Result<PooledConn> {
+    create_connection_with_timeouts(
+        host,
+        port,
+        user,
+        password,
+        database,
+        tls,
+        &Timeouts::default(),
+    )
+}
+
+/// Create a single connection with the connect and statement timeouts of `timeouts`
+///
+/// # Errors
+///
+/// Returns an error if the TLS configuration is invalid or the connection
+/// cannot be established.
+pub fn create_connection_with_timeouts(
+    host: &str,
+    port: u16,
+    user: &str,
+    password: &str,
+    database: Option<&str>,
+    tls: Option<&TlsConfig>,
+    timeouts: &Timeouts,
+) -> Result<PooledConn> {
+    let mut config = database_config(host, port, user, password, database, tls);
+    timeouts.apply_to(&mut config);
+    create_connection_with_config(&config)
+}
+
+/// Create a single connection to the server described by `config`, including
+/// its TLS settings and timeouts
+///
+/// # Errors
+///
+/// Returns an error if the TLS configuration is invalid or the connection
+/// cannot be established.
+pub fn create_connection_with_config(config: &DatabaseConfig) -> Result<PooledConn> {
+    let pool = create_connection_pool_with_config(config)?;
+    get_pooled_connection(&pool, config)
+}
+
+/// Server error codes worth retrying while connecting: too many connections,
+/// server shutdown in progress, and `TiDB`'s PD/TiKV timeout, busy and region
+/// unavailable errors
+const RETRYABLE_SERVER_CODES: &[u16] = &[1040, 1053, 9001, 9002, 9003, 9005];
+
+/// Process-wide overrides installed with [`set_retry_classification`]
+static RETRY_CLASSIFICATION: OnceLock<RwLock<RetryClassificationConfig>> = OnceLock::new();
+
+/// Install the `retry` section of the configuration as the classification
+/// [`is_retryable_connect_error`] consults, replacing any previous one
+pub fn set_retry_classification(classification: RetryClassificationConfig) {
+    let lock =
+        RETRY_CLASSIFICATION.get_or_init(|| RwLock::new(RetryClassificationConfig::default()));
+    if let Ok(mut current) = lock.write() {
+        *current = classification;
+    }
+}
+
+/// Whether a failed connection attempt is worth retrying
+///
+/// Server error codes listed in the installed retry classification (see
+/// [`set_retry_classification`]) are retried or not as configured. Otherwise
+/// refused connections, timeouts and transient server errors are retried; bad
+/// credentials, unknown databases and configuration errors are not.
+#[must_use]
+pub fn is_retryable_connect_error(error: &ConnectError) -> bool {
+    match RETRY_CLASSIFICATION.get().and_then(|lock| lock.read().ok()) {
+        Some(classification) => is_retryable_with(error, &classification),
+        None => is_retryable_with(error, &RetryClassificationConfig::default()),
+    }
+}
+
+/// [`is_retryable_connect_error`] with an explicit classification table
+#[must_use]
+pub fn is_retryable_with(error: &ConnectError, classification: &RetryClassificationConfig) -> bool {
+    if let ConnectError::Connection(mysql::Error::MySqlError(e)) = error
+        && let Some(retryable) = classification.classify(e.code)
+    {
+        return retryable;
+    }
+    match error {
+        ConnectError::Connection(mysql::Error::IoError(_))
+        | ConnectError::Io(_)
+        | ConnectError::Timeout(_)
+        | ConnectError::Network(_) => true,
+        ConnectError::Connection(mysql::Error::DriverError(e)) => matches!(
+            e,
+            DriverError::ConnectTimeout | DriverError::CouldNotConnect(_) | DriverError::Timeout
+        ),
+        ConnectError::Connection(mysql::Error::MySqlError(e)) => {
+            RETRYABLE_SERVER_CODES.contains(&e.code)
+        }
+        _ => false,
+    }
+}
+
+/// Server error codes meaning an established connection was dropped: server
+/// shutdown in progress, connection killed, server has gone away and lost
+/// connection during query
+const CONNECTION_DROP_CODES: &[u16] = &[1053, 1927, 2006, 2013];
+
+/// Whether a query failed because the server dropped an established
+/// connection, e.g. on a restart, so reconnecting may let the workflow resume
+///
+/// Errors the server returned on a live connection, such as a syntax error,
+/// are not drops.
+#[must_use]
+pub fn is_connection_drop(error: &ConnectError) -> bool {
+    let io_drop = |e: &std::io::Error| {
+        matches!(
+            e.kind(),
+            std::io::ErrorKind::BrokenPipe
+                | std::io::ErrorKind::ConnectionReset
+                | std::io::ErrorKind::ConnectionAborted
+                | std::io::ErrorKind::UnexpectedEof
+        )
+    };
+    match error {
+        ConnectError::Connection(mysql::Error::IoError(e)) | ConnectError::Io(e) => io_drop(e),
+        ConnectError::Connection(mysql::Error::MySqlError(e)) => {
+            CONNECTION_DROP_CODES.contains(&e.code)
+        }
+        _ => false,
+    }
+}
+
+/// Connect and run `SELECT 1`, retrying transient failures with backoff
+///
+/// Useful when the server may still be starting, e.g. in CI. Errors that a
+/// retry cannot fix, such as access denied or an unknown database, are
+/// returned immediately (see [`is_retryable_connect_error`]).
+///
+/// # Errors
+///
+/// Returns the first non-retryable error, or the last error once
+/// `retry.max_retries` attempts have failed.
+pub async fn connect_with_retry(
+    host: &str,
+    port: u16,
+    user: &str,
+    password: &str,
+    database: Option<&str>,
+    retry: &RetryConfig,
+) -> Result<PooledConn> {
+    connect_with_retry_timeouts(
+        host,
+        port,
+        user,
+        password,
+        database,
+        retry,
+        &Timeouts::default(),
+    )
+    .await
+}
 
-    Ok(conn)
+/// [`connect_with_retry`] with the connect and statement timeouts of `timeouts`
+///
+/// # Errors
+///
+/// See [`connect_with_retry`].
+pub async fn connect_with_retry_timeouts(
+    host: &str,
+    port: u16,
+    user: &str,
+    password: &str,
+    database: Option<&str>,
+    retry: &RetryConfig,
+    timeouts: &Timeouts,
+) -> Result<PooledConn> {
+    let mut config = database_config(host, port, user, password, database, None);
+    timeouts.apply_to(&mut config);
+    connect_with_retry_config(&config, retry).await
+}
+
+/// [`connect_with_retry`] to the server described by `config`, including its
+/// TLS settings and timeouts
+///
+/// # Errors
+///
+/// See [`connect_with_retry`].
+pub async fn connect_with_retry_config(
+    config: &DatabaseConfig,
+    retry: &RetryConfig,
+) -> Result<PooledConn> {
+    retry_with_backoff_if(
+        retry,
+        || {
+            let mut conn = create_connection_with_config(config)?;
+            test_connection(&mut conn)?;
+            Ok::<_, ConnectError>(conn)
+        },
+        is_retryable_connect_error,
+    )
+    .await
 }
 
 /// Verify if a database exists
@@ -133,6 +548,399 @@ pub fn test_connection(conn: &mut PooledConn) -> Result<()> {
 ///
 /// Returns an error if the version query fails.
 pub fn get_server_version(conn: &mut PooledConn) -> Result<Option<String>> {
-    let version: Option<String> = conn.query_first("SELECT VERSION()")?;
+    let (version, _) = timed_query(conn, "SELECT VERSION()", |conn, sql| {
+        Ok(conn.query_first::<String, _>(sql)?)
+    })?;
     Ok(version)
 }
+
+/// Run `f` for `sql`, logging the elapsed time at debug level
+///
+/// Returns the closure's result together with how long it took.
+///
+/// # Errors
+///
+/// Returns the error produced by `f`.
+pub fn timed_query<C, T, F>(conn: &mut C, sql: &str, f: F) -> Result<(T, Duration)>
+where
+    F: FnOnce(&mut C, &str) -> Result<T>,
+{
+    let started = Instant::now();
+    let result = f(conn, sql);
+    let elapsed = started.elapsed();
+    tracing::debug!("Query took {:?}: {}", elapsed, sql);
+    Ok((result?, elapsed))
+}
+
+/// Create a connection pool from `TIDB_HOST`, `TIDB_USER`, `TIDB_PASSWORD` and `TIDB_DATABASE`
+///
+/// Returns `Ok(None)` when `TIDB_HOST` is not set, which lets tests and tools
+/// that need a live server skip gracefully. `DATABASE_URL` and `MYSQL_HOST`
+/// are deliberately not consulted (see [`crate::env_params`]).
+///
+/// # Errors
+///
+/// Returns an error if `TIDB_HOST` is malformed or the pool cannot be created.
+pub fn pool_from_env() -> Result<Option<Pool>> {
+    let Some(params) = EnvParams::tidb() else {
+        return Ok(None);
+    };
+    let (host, port) = parse_host_port(&params.host)?;
+    let user = params.user.unwrap_or_else(|| "root".to_string());
+    let password = params.password.unwrap_or_default();
+
+    let pool = create_connection_pool(&host, port, &user, &password, params.database.as_deref())?;
+    Ok(Some(pool))
+}
+
+/// Create a connection from the environment
+///
+/// Returns `Ok(None)` when `TIDB_HOST` is not set. See [`pool_from_env`].
+///
+/// # Errors
+///
+/// Returns an error if the discovered host is malformed or the connection cannot be established.
+pub fn connect_from_env() -> Result<Option<PooledConn>> {
+    match pool_from_env()? {
+        Some(pool) => Ok(Some(get_pooled_connection(
+            &pool,
+            &DatabaseConfig::default(),
+        )?)),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server_error(code: u16, message: &str) -> ConnectError {
+        ConnectError::Connection(mysql::Error::MySqlError(mysql::MySqlError {
+            state: "HY000".to_string(),
+            message: message.to_string(),
+            code,
+        }))
+    }
+
+    #[test]
+    fn test_host_port_round_trip() {
+        for (host, port) in [("localhost", 4000), ("10.0.0.1", 4001), ("::1", 4000)] {
+            let joined = format_host_port(host, port);
+            assert_eq!(parse_host_port(&joined).unwrap(), (host.to_string(), port));
+        }
+        assert_eq!(format_host_port("::1", 4000), "[::1]:4000");
+        assert_eq!(
+            parse_host_port("[fe80::1%eth0]:4000").unwrap().0,
+            "fe80::1%eth0"
+        );
+        for bad in [
+            "localhost",
+            "::1:4000",
+            "[::1]",
+            "[]:4000",
+            "[::1]4000",
+            "h:x",
+        ] {
+            assert!(parse_host_port(bad).is_err(), "{bad} should be rejected");
+        }
+
+        let config = database_config("::1", 4000, "root", "", None, None);
+        assert_eq!(config.host, "[::1]:4000");
+        assert!(config.opts().is_ok());
+    }
+
+    #[test]
+    fn test_connection_opts_without_tls() {
+        let opts = connection_opts("localhost", 4000, "root", "", Some("test"), None).unwrap();
+        assert!(opts.get_ssl_opts().is_none());
+        assert_eq!(opts.get_db_name(), Some("test"));
+    }
+
+    #[test]
+    fn test_connection_opts_with_tls() {
+        let tls = TlsConfig {
+            ca: Some(PathBuf::from("/etc/tidb/ca.pem")),
+            skip_verify: true,
+            ..TlsConfig::default()
+        };
+        let opts = connection_opts("localhost", 4000, "root", "", None, Some(&tls)).unwrap();
+        let ssl = opts.get_ssl_opts().expect("ssl_opts should be set");
+        assert_eq!(
+            ssl.root_cert_path(),
+            Some(std::path::Path::new("/etc/tidb/ca.pem"))
+        );
+        assert!(ssl.skip_domain_validation());
+        assert!(ssl.accept_invalid_certs());
+
+        // An empty TLS section still enables TLS, verifying against system roots
+        let opts = connection_opts(
+            "localhost",
+            4000,
+            "root",
+            "",
+            None,
+            Some(&TlsConfig::default()),
+        )
+        .unwrap();
+        let ssl = opts.get_ssl_opts().unwrap();
+        assert!(ssl.root_cert_path().is_none());
+        assert!(!ssl.accept_invalid_certs());
+    }
+
+    #[test]
+    fn test_tls_client_certificate() {
+        let cert_only = TlsConfig {
+            cert: Some(PathBuf::from("client.pem")),
+            ..TlsConfig::default()
+        };
+        assert!(cert_only.ssl_opts().is_err());
+
+        let mutual = TlsConfig {
+            cert: Some(PathBuf::from("client.pem")),
+            key: Some(PathBuf::from("client-key.pem")),
+            ..TlsConfig::default()
+        };
+        #[cfg(feature = "tls")]
+        {
+            let ssl = mutual.ssl_opts().unwrap();
+            let identity = ssl.client_identity().unwrap();
+            assert_eq!(
+                identity.cert_chain_path(),
+                std::path::Path::new("client.pem")
+            );
+            assert_eq!(
+                identity.priv_key_path(),
+                std::path::Path::new("client-key.pem")
+            );
+        }
+        #[cfg(not(feature = "tls"))]
+        assert!(mutual.ssl_opts().is_err());
+    }
+
+    #[test]
+    fn test_default_pool_settings() {
+        let config = database_config("tidb", 4000, "root", "secret", Some("test"), None);
+        let opts = config.opts().unwrap();
+        let defaults = DatabaseConfig::default();
+
+        assert_eq!(opts.get_ip_or_hostname(), "tidb");
+        assert_eq!(opts.get_tcp_port(), 4000);
+        assert_eq!(opts.get_pass(), Some("secret"));
+        assert_eq!(opts.get_pool_opts().constraints().min(), 0);
+        assert_eq!(
+            opts.get_pool_opts().constraints().max(),
+            defaults.pool_size as usize
+        );
+        assert_eq!(
+            opts.get_tcp_connect_timeout(),
+            Some(Duration::from_secs(defaults.timeout_secs))
+        );
+    }
+
+    /// Pool whose connections are all checked out
+    struct ExhaustedPool;
+
+    impl ConnectionSource for ExhaustedPool {
+        type Conn = ();
+
+        fn try_checkout(&self, _timeout: Duration) -> std::result::Result<(), mysql::Error> {
+            Err(mysql::Error::DriverError(DriverError::Timeout))
+        }
+    }
+
+    /// Pool that fails to open a new connection
+    struct RefusingPool;
+
+    impl ConnectionSource for RefusingPool {
+        type Conn = ();
+
+        fn try_checkout(&self, _timeout: Duration) -> std::result::Result<(), mysql::Error> {
+            Err(mysql::Error::IoError(std::io::Error::from(
+                std::io::ErrorKind::ConnectionRefused,
+            )))
+        }
+    }
+
+    #[test]
+    fn test_exhausted_pool_is_reported() {
+        let err = checkout_connection(&ExhaustedPool, Duration::from_millis(250), 4).unwrap_err();
+        assert!(matches!(err, ConnectError::Resource(_)));
+        let message = err.to_string();
+        assert!(message.contains("pool exhausted"), "{message}");
+        assert!(message.contains("all 4 connections"), "{message}");
+        assert!(message.contains("250ms"), "{message}");
+        assert!(message.contains("increasing pool_size"), "{message}");
+
+        let err = checkout_connection(&RefusingPool, Duration::from_millis(250), 4).unwrap_err();
+        assert!(matches!(err, ConnectError::Connection(_)));
+    }
+
+    #[test]
+    fn test_pool_respects_configured_max_connections() {
+        let Ok(host) = std::env::var("TIDB_HOST") else {
+            eprintln!("TIDB_HOST not set, skipping live pool size test");
+            return;
+        };
+        let config = DatabaseConfig {
+            host,
+            username: std::env::var("TIDB_USER").unwrap_or_else(|_| "root".to_string()),
+            password: std::env::var("TIDB_PASSWORD").ok(),
+            pool_size: 2,
+            timeout_secs: 5,
+            ..DatabaseConfig::default()
+        };
+
+        let pool = create_connection_pool_with_config(&config).unwrap();
+        let _first = pool.get_conn().unwrap();
+        let _second = pool.get_conn().unwrap();
+        let err = pool.try_get_conn(Duration::from_millis(200)).unwrap_err();
+        assert!(matches!(
+            err,
+            mysql::Error::DriverError(DriverError::Timeout)
+        ));
+        let err =
+            checkout_connection(&pool, Duration::from_millis(200), config.pool_size).unwrap_err();
+        assert!(err.to_string().contains("pool exhausted"));
+    }
+
+    #[test]
+    fn test_retryable_connect_errors() {
+        let refused = std::io::Error::from(std::io::ErrorKind::ConnectionRefused);
+        assert!(is_retryable_connect_error(&ConnectError::Connection(
+            mysql::Error::IoError(refused)
+        )));
+        assert!(is_retryable_connect_error(&ConnectError::Connection(
+            mysql::Error::DriverError(DriverError::ConnectTimeout)
+        )));
+        assert!(is_retryable_connect_error(&server_error(
+            1040,
+            "Too many connections"
+        )));
+        assert!(is_retryable_connect_error(&server_error(
+            9005,
+            "Region is unavailable"
+        )));
+    }
+
+    #[test]
+    fn test_non_retryable_connect_errors() {
+        assert!(!is_retryable_connect_error(&server_error(
+            1045,
+            "Access denied for user 'root'@'localhost' (using password: YES)"
+        )));
+        assert!(!is_retryable_connect_error(&server_error(
+            1049,
+            "Unknown database 'missing'"
+        )));
+        assert!(!is_retryable_connect_error(&ConnectError::Connection(
+            mysql::Error::DriverError(DriverError::UnknownAuthPlugin("x".to_string()))
+        )));
+        assert!(!is_retryable_connect_error(&ConnectError::Authentication(
+            "bad password".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_configured_codes_override_default_classification() {
+        let classification = RetryClassificationConfig {
+            retryable_codes: vec![8028],
+            non_retryable_codes: vec![1040],
+        };
+        let schema_changed = server_error(8028, "Information schema is changed");
+        assert!(!is_retryable_with(
+            &schema_changed,
+            &RetryClassificationConfig::default()
+        ));
+        assert!(is_retryable_with(&schema_changed, &classification));
+
+        let too_many = server_error(1040, "Too many connections");
+        assert!(is_retryable_with(
+            &too_many,
+            &RetryClassificationConfig::default()
+        ));
+        assert!(!is_retryable_with(&too_many, &classification));
+
+        // Unlisted codes and non-server errors keep the default
+        assert!(!is_retryable_with(
+            &server_error(1045, "Access denied"),
+            &classification
+        ));
+        assert!(is_retryable_with(
+            &ConnectError::Timeout("connect".to_string()),
+            &classification
+        ));
+    }
+
+    #[test]
+    fn test_connection_drop_classification() {
+        for kind in [
+            std::io::ErrorKind::BrokenPipe,
+            std::io::ErrorKind::ConnectionReset,
+            std::io::ErrorKind::UnexpectedEof,
+        ] {
+            assert!(is_connection_drop(&ConnectError::Connection(
+                mysql::Error::IoError(std::io::Error::from(kind))
+            )));
+        }
+        assert!(is_connection_drop(&server_error(
+            2013,
+            "Lost connection to MySQL server during query"
+        )));
+        assert!(is_connection_drop(&server_error(
+            1053,
+            "Server shutdown in progress"
+        )));
+    }
+
+    #[test]
+    fn test_errors_on_live_connection_are_not_drops() {
+        assert!(!is_connection_drop(&server_error(
+            1064,
+            "You have an error in your SQL syntax"
+        )));
+        assert!(!is_connection_drop(&server_error(
+            1146,
+            "Table 'test.t' doesn't exist"
+        )));
+        // Refused: the server was never reached, so nothing was dropped
+        assert!(!is_connection_drop(&ConnectError::Connection(
+            mysql::Error::IoError(std::io::Error::from(std::io::ErrorKind::ConnectionRefused))
+        )));
+        assert!(!is_connection_drop(&ConnectError::Timeout(
+            "query timed out".to_string()
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_connect_with_retry_gives_up_on_refused_connection() {
+        // Nothing listens on port 1, so every attempt is refused and retried
+        let retry = RetryConfig {
+            max_retries: 2,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(1),
+            backoff_multiplier: 1.0,
+        };
+        let err = connect_with_retry("127.0.0.1", 1, "root", "", None, &retry)
+            .await
+            .unwrap_err();
+        assert!(is_retryable_connect_error(&err));
+    }
+
+    #[test]
+    fn test_timed_query_returns_closure_result() {
+        let mut calls = Vec::new();
+        let (value, elapsed) = timed_query(&mut calls, "SELECT 1", |calls, sql| {
+            calls.push(sql.to_string());
+            Ok(42)
+        })
+        .unwrap();
+        assert_eq!(value, 42);
+        assert!(elapsed >= Duration::ZERO);
+        assert_eq!(calls, vec!["SELECT 1"]);
+
+        let err = timed_query(&mut (), "SELECT 1", |(), _| -> Result<()> {
+            Err(ConnectError::Database("boom".into()))
+        });
+        assert!(err.is_err());
+    }
+}
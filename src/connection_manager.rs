@@ -72,6 +72,8 @@ pub enum CoordinationMessage {
 pub struct ConnectionCoordinator {
     shared_state: Arc<Mutex<SharedState>>,
     connections: HashMap<String, ConnectionInfo>,
+    // `AllConnectionsReady` was requested before every connection was ready
+    ready_pending: bool,
     pub tx: mpsc::Sender<CoordinationMessage>,
     pub rx: mpsc::Receiver<CoordinationMessage>,
 }
@@ -98,6 +100,7 @@ impl ConnectionCoordinator {
         Self {
             shared_state,
             connections: HashMap::new(),
+            ready_pending: false,
             tx,
             rx,
         }
@@ -147,6 +150,10 @@ impl ConnectionCoordinator {
 
     /// Process incoming messages
     ///
+    /// An `AllConnectionsReady` broadcast is withheld until every tracked
+    /// connection has reported a ready status (see
+    /// [`ConnectionCoordinator::all_connections_ready`]), then sent once.
+    ///
     /// # Panics
     ///
     /// Panics if the shared state mutex is poisoned.
@@ -159,17 +166,20 @@ impl ConnectionCoordinator {
                             .connection_status
                             .insert(status.connection_id.clone(), status);
                     }
-                }
-                CoordinationMessage::BroadcastEvent(event) => {
-                    if let Ok(mut state) = self.shared_state.lock() {
-                        state.coordination_events.push(event.clone());
+                    if self.ready_pending && self.all_connections_ready() {
+                        self.ready_pending = false;
+                        self.broadcast(CoordinationEvent::AllConnectionsReady).await;
                     }
-                    // Forward the broadcast event to the test's receiver
-                    let _ = self
-                        .tx
-                        .send(CoordinationMessage::BroadcastEvent(event))
-                        .await;
                 }
+                CoordinationMessage::BroadcastEvent(CoordinationEvent::AllConnectionsReady)
+                    if !self.all_connections_ready() =>
+                {
+                    tracing::debug!(
+                        "Withholding AllConnectionsReady until every connection is ready"
+                    );
+                    self.ready_pending = true;
+                }
+                CoordinationMessage::BroadcastEvent(event) => self.broadcast(event).await,
                 CoordinationMessage::RequestGlobalState => {
                     let state = self.shared_state.lock().unwrap().clone();
                     let _ = self
@@ -183,6 +193,17 @@ impl ConnectionCoordinator {
         }
     }
 
+    /// Record `event` and forward it to the test's receiver
+    async fn broadcast(&self, event: CoordinationEvent) {
+        if let Ok(mut state) = self.shared_state.lock() {
+            state.coordination_events.push(event.clone());
+        }
+        let _ = self
+            .tx
+            .send(CoordinationMessage::BroadcastEvent(event))
+            .await;
+    }
+
     /// Check if all connections are ready
     #[must_use]
     pub fn all_connections_ready(&self) -> bool {
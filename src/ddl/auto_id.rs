@@ -0,0 +1,266 @@
+//! AUTO_INCREMENT and AUTO_RANDOM id allocation
+//!
+//! TiDB allocates `AUTO_INCREMENT` ids in cached batches per server, so ids
+//! seen by one session are unique and increasing but may have gaps.
+//! `AUTO_RANDOM` ids additionally carry random shard bits in the high bits to
+//! scatter writes across regions; only the low sequence bits increase. The
+//! helpers here insert rows without an id, collect the generated ids and check
+//! both properties, reporting how the ids are distributed.
+
+use mysql::PooledConn;
+use mysql::prelude::*;
+use std::collections::BTreeMap;
+use test_rig::capabilities::{Capability, detect_version};
+use test_rig::errors::{ConnectError, Result};
+
+/// How the primary key is generated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoIdMode {
+    AutoIncrement,
+    /// `AUTO_RANDOM(shard_bits)`
+    AutoRandom {
+        shard_bits: u8,
+    },
+}
+
+impl AutoIdMode {
+    /// Primary key column definition for this mode
+    #[must_use]
+    pub fn column_definition(self) -> String {
+        match self {
+            AutoIdMode::AutoIncrement => "BIGINT AUTO_INCREMENT PRIMARY KEY".to_string(),
+            AutoIdMode::AutoRandom { shard_bits } => {
+                format!("BIGINT AUTO_RANDOM({shard_bits}) PRIMARY KEY")
+            }
+        }
+    }
+
+    /// Number of low bits holding the increasing sequence (the sign bit is never used)
+    fn sequence_bits(self) -> u32 {
+        match self {
+            AutoIdMode::AutoIncrement => 64,
+            AutoIdMode::AutoRandom { shard_bits } => 63 - u32::from(shard_bits),
+        }
+    }
+
+    /// The increasing part of an id
+    #[must_use]
+    pub fn sequence(self, id: u64) -> u64 {
+        match self.sequence_bits() {
+            64 => id,
+            bits => id & ((1u64 << bits) - 1),
+        }
+    }
+
+    /// The shard bits of an id (always `0` for `AUTO_INCREMENT`)
+    #[must_use]
+    pub fn shard(self, id: u64) -> u64 {
+        match self.sequence_bits() {
+            64 => 0,
+            bits => id >> bits,
+        }
+    }
+}
+
+impl std::fmt::Display for AutoIdMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AutoIdMode::AutoIncrement => write!(f, "AUTO_INCREMENT"),
+            AutoIdMode::AutoRandom { shard_bits } => write!(f, "AUTO_RANDOM({shard_bits})"),
+        }
+    }
+}
+
+/// Check that no id was generated twice
+///
+/// # Errors
+///
+/// Returns `ConnectError::Validation` naming the first duplicate.
+pub fn assert_unique_ids(ids: &[u64]) -> Result<()> {
+    let mut seen = std::collections::HashSet::with_capacity(ids.len());
+    match ids.iter().find(|id| !seen.insert(**id)) {
+        Some(duplicate) => Err(ConnectError::Validation(format!(
+            "Generated id {duplicate} is not unique"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Check that the sequence part of the ids strictly increases in insertion order
+///
+/// # Errors
+///
+/// Returns `ConnectError::Validation` naming the first id that did not increase.
+pub fn assert_monotonic_ids(mode: AutoIdMode, ids: &[u64]) -> Result<()> {
+    match ids
+        .windows(2)
+        .find(|pair| mode.sequence(pair[1]) <= mode.sequence(pair[0]))
+    {
+        Some(pair) => Err(ConnectError::Validation(format!(
+            "{mode} id {} (sequence {}) did not increase after {} (sequence {})",
+            pair[1],
+            mode.sequence(pair[1]),
+            pair[0],
+            mode.sequence(pair[0])
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Generated ids and how they are spread out
+#[derive(Debug, Clone)]
+pub struct AutoIdReport {
+    pub mode: AutoIdMode,
+    /// Ids in insertion order
+    pub ids: Vec<u64>,
+    /// Row count per shard value
+    pub shards: BTreeMap<u64, usize>,
+}
+
+impl AutoIdReport {
+    #[must_use]
+    pub fn new(mode: AutoIdMode, ids: Vec<u64>) -> Self {
+        let mut shards = BTreeMap::new();
+        for id in &ids {
+            *shards.entry(mode.shard(*id)).or_insert(0) += 1;
+        }
+        Self { mode, ids, shards }
+    }
+
+    /// Check uniqueness and per-sequence monotonicity
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConnectError::Validation` describing the first violation.
+    pub fn check(&self) -> Result<()> {
+        assert_unique_ids(&self.ids)?;
+        assert_monotonic_ids(self.mode, &self.ids)
+    }
+
+    pub fn print(&self) {
+        let min = self.ids.iter().min().copied().unwrap_or_default();
+        let max = self.ids.iter().max().copied().unwrap_or_default();
+        println!(
+            "{}: {} ids, min {min}, max {max}, {} distinct shard(s)",
+            self.mode,
+            self.ids.len(),
+            self.shards.len()
+        );
+        for (shard, count) in &self.shards {
+            println!("  shard {shard}: {count} row(s)");
+        }
+    }
+}
+
+/// Insert `rows` rows into a new `database.table` without an id and collect the generated ids
+///
+/// Returns `Ok(None)` for `AUTO_RANDOM` on servers that do not support it.
+///
+/// # Errors
+///
+/// Returns an error if a statement fails or `ConnectError::Validation` if the
+/// generated ids are not unique and increasing.
+pub fn run_auto_id_test(
+    conn: &mut PooledConn,
+    database: &str,
+    table: &str,
+    mode: AutoIdMode,
+    rows: usize,
+) -> Result<Option<AutoIdReport>> {
+    if matches!(mode, AutoIdMode::AutoRandom { .. }) {
+        let version = detect_version(conn)?;
+        if !version.supports(Capability::AutoRandom) {
+            tracing::info!(
+                "Skipping {} test: TiDB {} predates {} support",
+                mode,
+                version,
+                Capability::AutoRandom
+            );
+            return Ok(None);
+        }
+    }
+
+    let qualified = format!("`{database}`.`{table}`");
+    conn.query_drop(format!(
+        "CREATE TABLE {qualified} (id {}, v INT)",
+        mode.column_definition()
+    ))?;
+
+    let insert = format!("INSERT INTO {qualified} (v) VALUES (?)");
+    let mut ids = Vec::with_capacity(rows);
+    for value in 0..rows {
+        conn.exec_drop(&insert, (value,))?;
+        ids.push(conn.last_insert_id());
+    }
+
+    let report = AutoIdReport::new(mode, ids);
+    report.check()?;
+    Ok(Some(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_rig::test_support::live_db;
+
+    const RANDOM: AutoIdMode = AutoIdMode::AutoRandom { shard_bits: 5 };
+
+    /// Build an `AUTO_RANDOM(5)` id from a shard and a sequence value
+    fn random_id(shard: u64, sequence: u64) -> u64 {
+        (shard << 58) | sequence
+    }
+
+    #[test]
+    fn test_unique_ids() {
+        assert!(assert_unique_ids(&[1, 2, 5, 3]).is_ok());
+        let err = assert_unique_ids(&[1, 2, 1]).unwrap_err();
+        assert!(err.to_string().contains("id 1 is not unique"));
+    }
+
+    #[test]
+    fn test_auto_increment_ids_must_increase() {
+        let mode = AutoIdMode::AutoIncrement;
+        // Gaps from batch allocation are fine
+        assert!(assert_monotonic_ids(mode, &[1, 2, 30001, 30002]).is_ok());
+        assert!(assert_monotonic_ids(mode, &[1, 3, 2]).is_err());
+        assert!(assert_monotonic_ids(mode, &[]).is_ok());
+    }
+
+    #[test]
+    fn test_auto_random_sequence_ignores_shard_bits() {
+        let ids = [random_id(7, 1), random_id(2, 2), random_id(31, 3)];
+        assert!(assert_monotonic_ids(RANDOM, &ids).is_ok());
+        // The raw ids are not increasing, only the sequence part is
+        assert!(assert_monotonic_ids(AutoIdMode::AutoIncrement, &ids).is_err());
+        assert!(assert_monotonic_ids(RANDOM, &[random_id(1, 5), random_id(9, 4)]).is_err());
+    }
+
+    #[test]
+    fn test_report_distribution() {
+        let ids = vec![random_id(3, 1), random_id(3, 2), random_id(8, 3)];
+        let report = AutoIdReport::new(RANDOM, ids);
+        assert!(report.check().is_ok());
+        assert_eq!(report.shards.len(), 2);
+        assert_eq!(report.shards[&3], 2);
+        assert_eq!(report.shards[&8], 1);
+        assert_eq!(
+            RANDOM.column_definition(),
+            "BIGINT AUTO_RANDOM(5) PRIMARY KEY"
+        );
+    }
+
+    #[test]
+    fn test_auto_ids_against_live_tidb() {
+        let Some(mut db) = live_db("live auto id test") else {
+            return;
+        };
+        for (suffix, mode) in [("inc", AutoIdMode::AutoIncrement), ("rand", RANDOM)] {
+            let table = db.table(&format!("ddl_auto_id_{suffix}"));
+            let report = run_auto_id_test(&mut db.conn, &db.database, table.name(), mode, 20);
+            if let Some(report) = report.unwrap() {
+                report.print();
+                assert_eq!(report.ids.len(), 20);
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Clustered and non-clustered primary keys
+//!
+//! With a clustered primary key TiDB stores rows keyed by the primary key
+//! itself; a non-clustered key adds a hidden `_tidb_rowid`. The kind is chosen
+//! per table with `CLUSTERED` / `NONCLUSTERED` and shows up in
+//! `SHOW CREATE TABLE` as a `/*T![clustered_index] ... */` comment. The helpers
+//! here create one table of each kind and check the reported kind matches.
+
+use mysql::PooledConn;
+use mysql::prelude::*;
+use test_rig::errors::{ConnectError, Result};
+use test_rig::schema::show_create_table;
+
+/// Storage layout of a primary key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrimaryKeyKind {
+    Clustered,
+    NonClustered,
+}
+
+impl PrimaryKeyKind {
+    /// Keyword used in `CREATE TABLE`
+    #[must_use]
+    pub fn keyword(self) -> &'static str {
+        match self {
+            PrimaryKeyKind::Clustered => "CLUSTERED",
+            PrimaryKeyKind::NonClustered => "NONCLUSTERED",
+        }
+    }
+}
+
+/// Read the primary key kind from `SHOW CREATE TABLE` output
+///
+/// Returns `None` if the table has no primary key or the server does not
+/// annotate it (clustered indexes predate the annotation).
+#[must_use]
+pub fn parse_primary_key_kind(create_table: &str) -> Option<PrimaryKeyKind> {
+    let line = create_table
+        .lines()
+        .find(|line| {
+            line.trim_start()
+                .to_ascii_uppercase()
+                .starts_with("PRIMARY KEY")
+        })?
+        .to_ascii_uppercase();
+    if line.contains("NONCLUSTERED") {
+        Some(PrimaryKeyKind::NonClustered)
+    } else if line.contains("CLUSTERED") {
+        Some(PrimaryKeyKind::Clustered)
+    } else {
+        None
+    }
+}
+
+/// Whether the server has the `tidb_enable_clustered_index` variable
+///
+/// # Errors
+///
+/// Returns an error if the variable lookup fails.
+pub fn clustered_index_supported(conn: &mut PooledConn) -> Result<bool> {
+    let row: Option<(String, String)> =
+        conn.query_first("SHOW VARIABLES LIKE 'tidb_enable_clustered_index'")?;
+    Ok(row.is_some())
+}
+
+/// Create `database.table` with a primary key of the requested kind and check
+/// `SHOW CREATE TABLE` reports that kind
+///
+/// # Errors
+///
+/// Returns an error if a statement fails, or `ConnectError::Validation` if the
+/// table was created with a different kind.
+pub fn verify_primary_key_kind(
+    conn: &mut PooledConn,
+    database: &str,
+    table: &str,
+    kind: PrimaryKeyKind,
+) -> Result<()> {
+    conn.query_drop(format!(
+        "CREATE TABLE `{database}`.`{table}` (id VARCHAR(32), v INT, PRIMARY KEY (id) {})",
+        kind.keyword()
+    ))?;
+    let create_table = show_create_table(conn, database, table)?;
+    match parse_primary_key_kind(&create_table) {
+        Some(actual) if actual == kind => Ok(()),
+        actual => Err(ConnectError::Validation(format!(
+            "Table {database}.{table} requested a {} primary key, SHOW CREATE TABLE reports {}",
+            kind.keyword(),
+            actual.map_or("no primary key kind", PrimaryKeyKind::keyword)
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_rig::test_support::live_db;
+
+    #[test]
+    fn test_parse_primary_key_kind() {
+        let clustered = "CREATE TABLE `t` (\n  `id` varchar(32) NOT NULL,\n  `v` int(11) DEFAULT NULL,\n  PRIMARY KEY (`id`) /*T![clustered_index] CLUSTERED */\n) ENGINE=InnoDB";
+        assert_eq!(
+            parse_primary_key_kind(clustered),
+            Some(PrimaryKeyKind::Clustered)
+        );
+
+        let nonclustered = clustered.replace("CLUSTERED", "NONCLUSTERED");
+        assert_eq!(
+            parse_primary_key_kind(&nonclustered),
+            Some(PrimaryKeyKind::NonClustered)
+        );
+
+        let unannotated = "CREATE TABLE `t` (\n  `id` int(11) NOT NULL,\n  PRIMARY KEY (`id`)\n)";
+        assert_eq!(parse_primary_key_kind(unannotated), None);
+
+        // A clustered_index comment on a secondary key is not the primary key
+        let no_pk = "CREATE TABLE `t` (\n  `id` int(11),\n  KEY `idx` (`id`) /*T![clustered_index] CLUSTERED */\n)";
+        assert_eq!(parse_primary_key_kind(no_pk), None);
+    }
+
+    #[test]
+    fn test_primary_key_kinds_against_live_tidb() {
+        let Some(mut db) = live_db("live clustered index test") else {
+            return;
+        };
+        if !clustered_index_supported(&mut db.conn).unwrap() {
+            eprintln!("tidb_enable_clustered_index not available, skipping");
+            return;
+        }
+        for kind in [PrimaryKeyKind::Clustered, PrimaryKeyKind::NonClustered] {
+            let table = db.table(&format!("ddl_{}", kind.keyword().to_ascii_lowercase()));
+            verify_primary_key_kind(&mut db.conn, &db.database, table.name(), kind).unwrap();
+        }
+    }
+}
@@ -0,0 +1,329 @@
+//! Column-type coverage
+//!
+//! Creates a table with one column per commonly used TiDB type, inserts a
+//! representative value into each and reads the row back, checking that every
+//! value survives the round trip. Values are compared in a type-aware way so
+//! that formatting differences the server is allowed to make (trailing zeros in
+//! a DECIMAL, whitespace in a JSON document) are not reported as mismatches.
+
+use mysql::prelude::*;
+use mysql::{PooledConn, Row};
+use test_rig::errors::{ConnectError, Result};
+use test_rig::query::value_to_string;
+
+/// One column in the coverage table and the value round-tripped through it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnTypeCase {
+    pub column: &'static str,
+    pub column_type: &'static str,
+    /// SQL literal inserted into the column
+    pub literal: &'static str,
+    /// Value expected back from `SELECT`
+    pub expected: &'static str,
+}
+
+/// Representative values for the types DDL tests care about
+pub const COLUMN_TYPE_CASES: &[ColumnTypeCase] = &[
+    ColumnTypeCase {
+        column: "c_tinyint",
+        column_type: "TINYINT",
+        literal: "-128",
+        expected: "-128",
+    },
+    ColumnTypeCase {
+        column: "c_smallint",
+        column_type: "SMALLINT",
+        literal: "32767",
+        expected: "32767",
+    },
+    ColumnTypeCase {
+        column: "c_int",
+        column_type: "INT",
+        literal: "-2147483648",
+        expected: "-2147483648",
+    },
+    ColumnTypeCase {
+        column: "c_bigint",
+        column_type: "BIGINT",
+        literal: "9223372036854775807",
+        expected: "9223372036854775807",
+    },
+    ColumnTypeCase {
+        column: "c_ubigint",
+        column_type: "BIGINT UNSIGNED",
+        literal: "18446744073709551615",
+        expected: "18446744073709551615",
+    },
+    ColumnTypeCase {
+        column: "c_decimal",
+        column_type: "DECIMAL(12,4)",
+        literal: "'12345678.5'",
+        expected: "12345678.5",
+    },
+    ColumnTypeCase {
+        column: "c_varchar",
+        column_type: "VARCHAR(64)",
+        literal: "'héllo, wörld'",
+        expected: "héllo, wörld",
+    },
+    ColumnTypeCase {
+        column: "c_text",
+        column_type: "TEXT",
+        literal: "'line one\\nline two'",
+        expected: "line one\nline two",
+    },
+    ColumnTypeCase {
+        column: "c_json",
+        column_type: "JSON",
+        literal: r#"'{"name": "tidb", "tags": ["a", "b"], "n": 1}'"#,
+        expected: r#"{"n": 1, "name": "tidb", "tags": ["a", "b"]}"#,
+    },
+    ColumnTypeCase {
+        column: "c_enum",
+        column_type: "ENUM('small','medium','large')",
+        literal: "'medium'",
+        expected: "medium",
+    },
+    ColumnTypeCase {
+        column: "c_timestamp",
+        column_type: "TIMESTAMP",
+        literal: "'2024-02-29 23:59:59'",
+        expected: "2024-02-29 23:59:59",
+    },
+];
+
+/// How values of a column type are compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Exact,
+    /// Numerically equal, ignoring trailing fractional zeros
+    Decimal,
+    /// Structurally equal documents, ignoring whitespace and key order
+    Json,
+}
+
+impl ValueKind {
+    /// Comparison used for a column type such as `DECIMAL(12,4)`
+    #[must_use]
+    pub fn for_type(column_type: &str) -> Self {
+        let upper = column_type.trim().to_ascii_uppercase();
+        if upper.starts_with("DECIMAL") || upper.starts_with("NUMERIC") {
+            ValueKind::Decimal
+        } else if upper == "JSON" {
+            ValueKind::Json
+        } else {
+            ValueKind::Exact
+        }
+    }
+}
+
+/// Strip trailing fractional zeros (and a bare trailing point) from a decimal
+fn normalize_decimal(value: &str) -> &str {
+    let value = value.trim();
+    if value.contains('.') {
+        value.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        value
+    }
+}
+
+/// Check whether a value read back from the server matches the expected value
+#[must_use]
+pub fn values_match(column_type: &str, expected: &str, actual: &str) -> bool {
+    match ValueKind::for_type(column_type) {
+        ValueKind::Exact => expected == actual,
+        ValueKind::Decimal => normalize_decimal(expected) == normalize_decimal(actual),
+        ValueKind::Json => {
+            match (
+                serde_json::from_str::<serde_json::Value>(expected),
+                serde_json::from_str::<serde_json::Value>(actual),
+            ) {
+                (Ok(expected), Ok(actual)) => expected == actual,
+                _ => expected == actual,
+            }
+        }
+    }
+}
+
+/// A column whose value did not survive the round trip
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeMismatch {
+    pub column: String,
+    pub column_type: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// Outcome of a column-type round trip
+#[derive(Debug, Clone, Default)]
+pub struct ColumnTypeReport {
+    /// Number of columns checked
+    pub checked: usize,
+    pub mismatches: Vec<TypeMismatch>,
+}
+
+impl ColumnTypeReport {
+    #[must_use]
+    pub fn all_matched(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    pub fn print(&self) {
+        println!(
+            "Column types: {} checked, {} mismatched",
+            self.checked,
+            self.mismatches.len()
+        );
+        for mismatch in &self.mismatches {
+            println!(
+                "  ❌ {} {}: expected {:?}, got {:?}",
+                mismatch.column, mismatch.column_type, mismatch.expected, mismatch.actual
+            );
+        }
+    }
+}
+
+/// `CREATE TABLE` statement with an `id` key and one column per case
+#[must_use]
+pub fn create_table_sql(qualified_table: &str, cases: &[ColumnTypeCase]) -> String {
+    let columns: Vec<String> = cases
+        .iter()
+        .map(|case| format!("`{}` {} NULL", case.column, case.column_type))
+        .collect();
+    format!(
+        "CREATE TABLE {qualified_table} (id INT PRIMARY KEY, {})",
+        columns.join(", ")
+    )
+}
+
+/// `INSERT` statement writing each case's literal into row `id = 1`
+#[must_use]
+pub fn insert_sql(qualified_table: &str, cases: &[ColumnTypeCase]) -> String {
+    let columns: Vec<String> = cases.iter().map(|c| format!("`{}`", c.column)).collect();
+    let literals: Vec<&str> = cases.iter().map(|c| c.literal).collect();
+    format!(
+        "INSERT INTO {qualified_table} (id, {}) VALUES (1, {})",
+        columns.join(", "),
+        literals.join(", ")
+    )
+}
+
+/// Compare the values read back for each case, in case order
+#[must_use]
+pub fn compare_round_trip(cases: &[ColumnTypeCase], actual: &[String]) -> ColumnTypeReport {
+    let mismatches = cases
+        .iter()
+        .zip(actual)
+        .filter(|(case, actual)| !values_match(case.column_type, case.expected, actual))
+        .map(|(case, actual)| TypeMismatch {
+            column: case.column.to_string(),
+            column_type: case.column_type.to_string(),
+            expected: case.expected.to_string(),
+            actual: actual.clone(),
+        })
+        .collect();
+    ColumnTypeReport {
+        checked: cases.len().min(actual.len()),
+        mismatches,
+    }
+}
+
+/// Create `database.table` with every case's column, insert one row and verify it reads back
+///
+/// # Errors
+///
+/// Returns an error if the table cannot be created, the row cannot be written,
+/// or `ConnectError::Validation` if the row cannot be read back.
+pub fn verify_column_type_round_trip(
+    conn: &mut PooledConn,
+    database: &str,
+    table: &str,
+    cases: &[ColumnTypeCase],
+) -> Result<ColumnTypeReport> {
+    let qualified = format!("`{database}`.`{table}`");
+    conn.query_drop(create_table_sql(&qualified, cases))?;
+    conn.query_drop(insert_sql(&qualified, cases))?;
+
+    let columns: Vec<String> = cases.iter().map(|c| format!("`{}`", c.column)).collect();
+    let select = format!(
+        "SELECT {} FROM {qualified} WHERE id = 1",
+        columns.join(", ")
+    );
+    let row: Row = conn.query_first(select)?.ok_or_else(|| {
+        ConnectError::Validation(format!("Inserted row missing from {qualified}"))
+    })?;
+    let actual: Vec<String> = row.unwrap().iter().map(value_to_string).collect();
+
+    Ok(compare_round_trip(cases, &actual))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_rig::test_support::live_db;
+
+    #[test]
+    fn test_values_match_by_type() {
+        assert!(values_match("INT", "42", "42"));
+        assert!(!values_match("INT", "42", "42.0"));
+        assert!(values_match("DECIMAL(12,4)", "12345678.5", "12345678.5000"));
+        assert!(values_match("decimal(10,2)", "7", "7.00"));
+        assert!(!values_match("DECIMAL(10,2)", "7.1", "7.10001"));
+        assert!(values_match(
+            "JSON",
+            r#"{"a": [1, 2], "b": null}"#,
+            r#"{"b":null,"a":[1,2]}"#
+        ));
+        assert!(!values_match("JSON", r#"{"a": 1}"#, r#"{"a": "1"}"#));
+        assert!(!values_match("VARCHAR(8)", "abc", "abc "));
+    }
+
+    #[test]
+    fn test_compare_round_trip_reports_mismatches() {
+        let mut actual: Vec<String> = COLUMN_TYPE_CASES
+            .iter()
+            .map(|c| c.expected.to_string())
+            .collect();
+        assert!(compare_round_trip(COLUMN_TYPE_CASES, &actual).all_matched());
+
+        let enum_index = COLUMN_TYPE_CASES
+            .iter()
+            .position(|c| c.column == "c_enum")
+            .unwrap();
+        actual[enum_index] = String::new();
+        let report = compare_round_trip(COLUMN_TYPE_CASES, &actual);
+        assert_eq!(report.checked, COLUMN_TYPE_CASES.len());
+        assert_eq!(report.mismatches.len(), 1);
+        assert_eq!(report.mismatches[0].column, "c_enum");
+    }
+
+    #[test]
+    fn test_generated_statements_cover_every_case() {
+        let cases = &COLUMN_TYPE_CASES[..2];
+        assert_eq!(
+            create_table_sql("`test`.`t`", cases),
+            "CREATE TABLE `test`.`t` (id INT PRIMARY KEY, `c_tinyint` TINYINT NULL, `c_smallint` SMALLINT NULL)"
+        );
+        assert_eq!(
+            insert_sql("`test`.`t`", cases),
+            "INSERT INTO `test`.`t` (id, `c_tinyint`, `c_smallint`) VALUES (1, -128, 32767)"
+        );
+    }
+
+    #[test]
+    fn test_column_type_round_trip_against_live_tidb() {
+        let Some(mut db) = live_db("live column-type test") else {
+            return;
+        };
+        let table = db.table("ddl_column_types");
+        let report = verify_column_type_round_trip(
+            &mut db.conn,
+            &db.database,
+            table.name(),
+            COLUMN_TYPE_CASES,
+        )
+        .unwrap();
+        report.print();
+        assert!(report.all_matched());
+    }
+}
@@ -0,0 +1,350 @@
+//! Concurrent ALTER TABLE verification
+//!
+//! TiDB serializes DDL through its job queue, so two ALTERs issued at the same
+//! time against one table should both complete rather than conflict. The
+//! helpers here launch ALTERs on separate connections, read `ADMIN SHOW DDL JOBS`
+//! to report the order in which TiDB executed them, and check that the final
+//! schema reflects every change.
+
+use mysql::prelude::*;
+use mysql::{Pool, Row};
+use std::thread;
+use std::time::{Duration, Instant};
+use test_rig::config::DatabaseConfig;
+use test_rig::connection::get_pooled_connection;
+use test_rig::errors::{ConnectError, Result};
+use test_rig::query::value_to_string;
+use test_rig::schema::{TableSchema, snapshot_table};
+
+/// A row from `ADMIN SHOW DDL JOBS`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DdlJob {
+    pub job_id: i64,
+    pub db_name: String,
+    pub table_name: String,
+    pub job_type: String,
+    pub state: String,
+}
+
+impl DdlJob {
+    /// Build a job from `(column name, value)` pairs, ignoring unknown columns
+    ///
+    /// The column set of `ADMIN SHOW DDL JOBS` differs between TiDB versions,
+    /// so fields are matched by name rather than position.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConnectError::Parse` if `JOB_ID` is missing or not numeric.
+    pub fn from_columns(columns: &[(String, String)]) -> Result<Self> {
+        let get = |name: &str| {
+            columns
+                .iter()
+                .find(|(col, _)| col.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default()
+        };
+
+        let job_id = get("JOB_ID")
+            .parse::<i64>()
+            .map_err(|_| ConnectError::Parse("DDL job row has no valid JOB_ID".to_string()))?;
+
+        Ok(Self {
+            job_id,
+            db_name: get("DB_NAME"),
+            table_name: get("TABLE_NAME"),
+            job_type: get("JOB_TYPE"),
+            state: get("STATE"),
+        })
+    }
+
+    /// Build a job from a raw result row
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConnectError::Parse` if `JOB_ID` is missing or not numeric.
+    pub fn from_row(row: &Row) -> Result<Self> {
+        let columns: Vec<(String, String)> = row
+            .columns_ref()
+            .iter()
+            .enumerate()
+            .map(|(i, col)| {
+                let value = row.as_ref(i).map(value_to_string).unwrap_or_default();
+                (col.name_str().to_string(), value)
+            })
+            .collect();
+        Self::from_columns(&columns)
+    }
+
+    /// Whether TiDB has finished the job successfully
+    pub fn is_synced(&self) -> bool {
+        self.state.eq_ignore_ascii_case("synced") || self.state.eq_ignore_ascii_case("done")
+    }
+}
+
+/// Outcome of a single ALTER issued by [`run_concurrent_alters`]
+#[derive(Debug, Clone)]
+pub struct AlterOutcome {
+    pub statement: String,
+    pub error: Option<String>,
+    pub elapsed: Duration,
+}
+
+/// Result of running several ALTERs concurrently against one table
+#[derive(Debug, Clone)]
+pub struct ConcurrentAlterReport {
+    pub outcomes: Vec<AlterOutcome>,
+    /// DDL jobs for the table in the order TiDB executed them
+    pub job_order: Vec<DdlJob>,
+    pub final_schema: TableSchema,
+}
+
+impl ConcurrentAlterReport {
+    /// Whether every ALTER statement completed without error
+    pub fn all_succeeded(&self) -> bool {
+        self.outcomes.iter().all(|o| o.error.is_none())
+    }
+
+    /// Print per-statement outcomes and the observed job ordering
+    pub fn print(&self) {
+        println!("Concurrent ALTER results:");
+        for outcome in &self.outcomes {
+            match &outcome.error {
+                None => println!("  ✓ {} ({:?})", outcome.statement, outcome.elapsed),
+                Some(e) => println!("  ✗ {} - {}", outcome.statement, e),
+            }
+        }
+        println!("DDL job order:");
+        for job in &self.job_order {
+            println!("  #{} {} [{}]", job.job_id, job.job_type, job.state);
+        }
+    }
+}
+
+/// Keep only the jobs for `database.table` and sort them by job id (execution order)
+pub fn order_jobs_for_table(jobs: &[DdlJob], database: &str, table: &str) -> Vec<DdlJob> {
+    let mut filtered: Vec<DdlJob> = jobs
+        .iter()
+        .filter(|j| {
+            j.db_name.eq_ignore_ascii_case(database) && j.table_name.eq_ignore_ascii_case(table)
+        })
+        .cloned()
+        .collect();
+    filtered.sort_by_key(|j| j.job_id);
+    filtered
+}
+
+/// Check that the final schema contains every expected column and index
+///
+/// # Errors
+///
+/// Returns `ConnectError::Validation` listing everything that is missing.
+pub fn verify_final_schema(
+    schema: &TableSchema,
+    expected_columns: &[&str],
+    expected_indexes: &[&str],
+) -> Result<()> {
+    let mut missing = Vec::new();
+    for column in expected_columns {
+        if schema.column(column).is_none() {
+            missing.push(format!("column {column}"));
+        }
+    }
+    for index in expected_indexes {
+        if schema.index(index).is_none() {
+            missing.push(format!("index {index}"));
+        }
+    }
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(ConnectError::Validation(format!(
+            "Final schema of {}.{} is missing: {}",
+            schema.database,
+            schema.table,
+            missing.join(", ")
+        )))
+    }
+}
+
+/// Fetch all rows of `ADMIN SHOW DDL JOBS`
+///
+/// `config` bounds how long the pool checkout may wait.
+///
+/// # Errors
+///
+/// Returns an error if no connection is free in time, the statement fails or
+/// a row cannot be parsed.
+pub fn show_ddl_jobs(pool: &Pool, config: &DatabaseConfig) -> Result<Vec<DdlJob>> {
+    let mut conn = get_pooled_connection(pool, config)?;
+    let rows: Vec<Row> = conn.query("ADMIN SHOW DDL JOBS")?;
+    rows.iter().map(DdlJob::from_row).collect()
+}
+
+/// Run each statement on its own connection at the same time, then collect
+/// the DDL job ordering and the final schema of `database.table`
+///
+/// Every connection is checked out with the timeout and pool size of `config`.
+///
+/// # Errors
+///
+/// Returns an error if the job history or final schema cannot be read. Failures
+/// of the individual ALTERs, including checkout timeouts, are recorded in the
+/// report instead.
+pub fn run_concurrent_alters(
+    pool: &Pool,
+    config: &DatabaseConfig,
+    database: &str,
+    table: &str,
+    statements: &[String],
+) -> Result<ConcurrentAlterReport> {
+    let handles: Vec<_> = statements
+        .iter()
+        .cloned()
+        .map(|statement| {
+            let pool = pool.clone();
+            let config = config.clone();
+            thread::spawn(move || {
+                let start = Instant::now();
+                let error = get_pooled_connection(&pool, &config)
+                    .and_then(|mut conn| Ok(conn.query_drop(&statement)?))
+                    .err()
+                    .map(|e| e.to_string());
+                AlterOutcome {
+                    statement,
+                    error,
+                    elapsed: start.elapsed(),
+                }
+            })
+        })
+        .collect();
+
+    let outcomes = handles
+        .into_iter()
+        .map(|h| {
+            h.join()
+                .map_err(|_| ConnectError::Unknown("ALTER worker thread panicked".to_string()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let job_order = order_jobs_for_table(&show_ddl_jobs(pool, config)?, database, table);
+    let mut conn = get_pooled_connection(pool, config)?;
+    let final_schema = snapshot_table(&mut conn, database, table)?;
+
+    Ok(ConcurrentAlterReport {
+        outcomes,
+        job_order,
+        final_schema,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_rig::schema::{ColumnSchema, IndexSchema};
+    use test_rig::test_support::live_db;
+
+    fn job(id: &str, table: &str, job_type: &str) -> DdlJob {
+        let columns: Vec<(String, String)> = [
+            ("JOB_ID", id),
+            ("DB_NAME", "test"),
+            ("TABLE_NAME", table),
+            ("JOB_TYPE", job_type),
+            ("SCHEMA_STATE", "public"),
+            ("STATE", "synced"),
+        ]
+        .iter()
+        .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+        .collect();
+        DdlJob::from_columns(&columns).unwrap()
+    }
+
+    fn schema_with(columns: &[&str], indexes: &[&str]) -> TableSchema {
+        let mut schema = TableSchema::new("test", "t1");
+        schema.columns = columns
+            .iter()
+            .map(|name| ColumnSchema {
+                name: (*name).to_string(),
+                column_type: "int".to_string(),
+                is_nullable: true,
+                default: None,
+                extra: String::new(),
+            })
+            .collect();
+        schema.indexes = indexes
+            .iter()
+            .map(|name| IndexSchema {
+                name: (*name).to_string(),
+                columns: vec!["id".to_string()],
+                unique: false,
+            })
+            .collect();
+        schema
+    }
+
+    #[test]
+    fn test_ddl_job_from_columns() {
+        let job = job("42", "t1", "add column");
+        assert_eq!(job.job_id, 42);
+        assert_eq!(job.job_type, "add column");
+        assert!(job.is_synced());
+
+        let bad = vec![("JOB_ID".to_string(), "abc".to_string())];
+        assert!(DdlJob::from_columns(&bad).is_err());
+    }
+
+    #[test]
+    fn test_order_jobs_for_table() {
+        let jobs = vec![
+            job("12", "t1", "add index"),
+            job("10", "other", "create table"),
+            job("11", "t1", "add column"),
+        ];
+        let ordered = order_jobs_for_table(&jobs, "test", "t1");
+        let ids: Vec<i64> = ordered.iter().map(|j| j.job_id).collect();
+        assert_eq!(ids, vec![11, 12]);
+    }
+
+    #[test]
+    fn test_verify_final_schema() {
+        let schema = schema_with(&["id", "c1", "c2"], &["PRIMARY", "idx_c1"]);
+        assert!(verify_final_schema(&schema, &["c1", "c2"], &["idx_c1"]).is_ok());
+
+        let err = verify_final_schema(&schema, &["c1", "c3"], &["idx_c2"]).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("column c3"));
+        assert!(message.contains("index idx_c2"));
+        assert!(!message.contains("column c1"));
+    }
+
+    #[test]
+    fn test_concurrent_alters_against_live_tidb() {
+        let Some(mut db) = live_db("live concurrent ALTER test") else {
+            return;
+        };
+        let table = db.table("ddl_concurrent");
+        let qualified = table.qualified();
+        db.conn
+            .query_drop(format!(
+                "CREATE TABLE {qualified} (id INT PRIMARY KEY, c1 INT)"
+            ))
+            .unwrap();
+
+        let statements = vec![
+            format!("ALTER TABLE {qualified} ADD COLUMN c2 VARCHAR(32)"),
+            format!("ALTER TABLE {qualified} ADD INDEX idx_c1 (c1)"),
+        ];
+        let report = run_concurrent_alters(
+            db.pool(),
+            &DatabaseConfig::default(),
+            &db.database,
+            table.name(),
+            &statements,
+        )
+        .unwrap();
+        report.print();
+        assert!(report.all_succeeded());
+        assert!(report.job_order.len() >= 2);
+        verify_final_schema(&report.final_schema, &["c2"], &["idx_c1"]).unwrap();
+    }
+}
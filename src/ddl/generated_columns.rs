@@ -0,0 +1,172 @@
+//! Generated columns
+//!
+//! Creates a table whose `total` column is generated from `price * quantity`,
+//! either `STORED` (computed on write) or `VIRTUAL` (computed on read), inserts
+//! base values and checks every generated value against the same expression
+//! evaluated locally.
+
+use mysql::PooledConn;
+use mysql::prelude::*;
+use test_rig::errors::{ConnectError, Result};
+
+/// When a generated column is computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeneratedKind {
+    Stored,
+    Virtual,
+}
+
+impl GeneratedKind {
+    /// Keyword used in the column definition
+    #[must_use]
+    pub fn keyword(self) -> &'static str {
+        match self {
+            GeneratedKind::Stored => "STORED",
+            GeneratedKind::Virtual => "VIRTUAL",
+        }
+    }
+}
+
+/// Base `(price, quantity)` values inserted by the test
+pub const BASE_VALUES: &[(i64, i64)] = &[(0, 5), (1, 1), (250, 4), (-3, 7), (1_000_000, 1_000)];
+
+/// Value the generated `total` column should hold for a row
+#[must_use]
+pub fn expected_total(price: i64, quantity: i64) -> i64 {
+    price * quantity
+}
+
+/// `CREATE TABLE` statement with a generated `total` column of the given kind
+#[must_use]
+pub fn create_table_sql(qualified_table: &str, kind: GeneratedKind) -> String {
+    format!(
+        "CREATE TABLE {qualified_table} (id INT PRIMARY KEY, price BIGINT, quantity BIGINT, \
+         total BIGINT AS (price * quantity) {})",
+        kind.keyword()
+    )
+}
+
+/// A row whose generated value did not match the expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedMismatch {
+    pub id: i64,
+    pub expected: i64,
+    pub actual: Option<i64>,
+}
+
+/// Compare `(id, price, quantity, total)` rows against [`expected_total`]
+#[must_use]
+pub fn check_generated_rows(rows: &[(i64, i64, i64, Option<i64>)]) -> Vec<GeneratedMismatch> {
+    rows.iter()
+        .filter_map(|&(id, price, quantity, actual)| {
+            let expected = expected_total(price, quantity);
+            (actual != Some(expected)).then_some(GeneratedMismatch {
+                id,
+                expected,
+                actual,
+            })
+        })
+        .collect()
+}
+
+/// Create `database.table` with a generated column of `kind`, insert [`BASE_VALUES`]
+/// and check every generated value
+///
+/// # Errors
+///
+/// Returns an error if a statement fails, or `ConnectError::Validation` listing
+/// rows whose generated value is wrong.
+pub fn verify_generated_column(
+    conn: &mut PooledConn,
+    database: &str,
+    table: &str,
+    kind: GeneratedKind,
+) -> Result<()> {
+    let qualified = format!("`{database}`.`{table}`");
+    conn.query_drop(create_table_sql(&qualified, kind))?;
+    conn.exec_batch(
+        format!("INSERT INTO {qualified} (id, price, quantity) VALUES (?, ?, ?)"),
+        BASE_VALUES
+            .iter()
+            .enumerate()
+            .map(|(id, (price, quantity))| (id, price, quantity)),
+    )?;
+
+    let rows: Vec<(i64, i64, i64, Option<i64>)> = conn.query(format!(
+        "SELECT id, price, quantity, total FROM {qualified} ORDER BY id"
+    ))?;
+    if rows.len() != BASE_VALUES.len() {
+        return Err(ConnectError::Validation(format!(
+            "Expected {} rows in {qualified}, found {}",
+            BASE_VALUES.len(),
+            rows.len()
+        )));
+    }
+
+    let mismatches = check_generated_rows(&rows);
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+    let details: Vec<String> = mismatches
+        .iter()
+        .map(|m| format!("id {}: expected {}, got {:?}", m.id, m.expected, m.actual))
+        .collect();
+    Err(ConnectError::Validation(format!(
+        "{} generated column in {qualified} is wrong: {}",
+        kind.keyword(),
+        details.join("; ")
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_rig::test_support::live_db;
+
+    #[test]
+    fn test_expected_values() {
+        assert_eq!(expected_total(250, 4), 1000);
+        assert_eq!(expected_total(-3, 7), -21);
+        assert_eq!(expected_total(0, 5), 0);
+        assert!(
+            create_table_sql("`test`.`g`", GeneratedKind::Virtual)
+                .ends_with("total BIGINT AS (price * quantity) VIRTUAL)")
+        );
+    }
+
+    #[test]
+    fn test_check_generated_rows() {
+        let rows = vec![(0, 2, 3, Some(6)), (1, 4, 5, Some(21)), (2, 1, 1, None)];
+        let mismatches = check_generated_rows(&rows);
+        assert_eq!(
+            mismatches,
+            vec![
+                GeneratedMismatch {
+                    id: 1,
+                    expected: 20,
+                    actual: Some(21)
+                },
+                GeneratedMismatch {
+                    id: 2,
+                    expected: 1,
+                    actual: None
+                },
+            ]
+        );
+        assert!(check_generated_rows(&rows[..1]).is_empty());
+    }
+
+    #[test]
+    fn test_generated_columns_against_live_tidb() {
+        let Some(mut db) = live_db("live generated column test") else {
+            return;
+        };
+        for kind in [GeneratedKind::Stored, GeneratedKind::Virtual] {
+            let table = db.table(&format!(
+                "ddl_generated_{}",
+                kind.keyword().to_ascii_lowercase()
+            ));
+            verify_generated_column(&mut db.conn, &db.database, table.name(), kind).unwrap();
+        }
+    }
+}
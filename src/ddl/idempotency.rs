@@ -0,0 +1,79 @@
+//! DDL idempotency verification
+//!
+//! Statements such as `CREATE TABLE IF NOT EXISTS` or `ADD COLUMN IF NOT EXISTS`
+//! must be safe to re-run. The helper here executes a statement twice and
+//! checks that the second run succeeds without altering the table schema.
+
+use mysql::PooledConn;
+use mysql::prelude::*;
+use test_rig::errors::{ConnectError, Result};
+use test_rig::schema::{TableSchema, assert_schema_unchanged, snapshot_table};
+
+/// Outcome of an idempotency check
+#[derive(Debug, Clone)]
+pub struct IdempotencyReport {
+    /// The statement that was executed twice
+    pub statement: String,
+    /// Warnings raised by the second run (`IF NOT EXISTS` no-ops usually emit a note)
+    pub second_run_warnings: u16,
+    /// Schema captured after the first run
+    pub schema: TableSchema,
+}
+
+/// Run `ddl` twice against `database.table` and verify the second run is a no-op
+///
+/// # Errors
+///
+/// Returns an error if the first run fails, or `ConnectError::Validation` if the
+/// second run fails or changes the schema captured after the first run.
+pub fn verify_ddl_idempotent(
+    conn: &mut PooledConn,
+    database: &str,
+    table: &str,
+    ddl: &str,
+) -> Result<IdempotencyReport> {
+    tracing::debug!("Idempotency check, first run: {}", ddl);
+    conn.query_drop(ddl)?;
+    let before = snapshot_table(conn, database, table)?;
+
+    tracing::debug!("Idempotency check, second run: {}", ddl);
+    conn.query_drop(ddl)
+        .map_err(|e| ConnectError::Validation(format!("Second run of '{ddl}' failed: {e}")))?;
+    let second_run_warnings = conn.warnings();
+    let after = snapshot_table(conn, database, table)?;
+
+    assert_schema_unchanged(&before, &after)?;
+
+    Ok(IdempotencyReport {
+        statement: ddl.to_string(),
+        second_run_warnings,
+        schema: after,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_rig::test_support::live_db;
+
+    #[test]
+    fn test_idempotent_ddl_against_live_tidb() {
+        let Some(mut db) = live_db("live idempotency test") else {
+            return;
+        };
+        let table = db.table("ddl_idempotency");
+        let qualified = table.qualified();
+        let create = format!(
+            "CREATE TABLE IF NOT EXISTS {qualified} (id INT PRIMARY KEY, name VARCHAR(64))"
+        );
+        let add_column =
+            format!("ALTER TABLE {qualified} ADD COLUMN IF NOT EXISTS email VARCHAR(255)");
+
+        let created = verify_ddl_idempotent(&mut db.conn, &db.database, table.name(), &create);
+        let altered = verify_ddl_idempotent(&mut db.conn, &db.database, table.name(), &add_column);
+        let created = created.unwrap();
+        assert_eq!(created.schema.columns.len(), 2);
+        let altered = altered.unwrap();
+        assert!(altered.schema.column("email").is_some());
+    }
+}
@@ -0,0 +1,126 @@
+//! Secondary index creation and usage
+//!
+//! Creates a table, adds a secondary index with `ALTER TABLE`, and checks via
+//! `EXPLAIN` that the planner chooses the index for a query filtering on the
+//! indexed column. A plan falling back to a full table scan is a planner
+//! regression.
+
+use mysql::PooledConn;
+use mysql::prelude::*;
+use test_rig::errors::{ConnectError, Result};
+use test_rig::explain::{QueryPlan, explain};
+
+/// Rows inserted so that a full scan is noticeably more expensive than the index
+pub const ROW_COUNT: usize = 1000;
+
+/// Check that `plan` reads through `index`
+///
+/// # Errors
+///
+/// Returns `ConnectError::Validation` naming the operators used instead.
+pub fn assert_index_used(plan: &QueryPlan, index: &str) -> Result<()> {
+    let indexes = plan.indexes();
+    if indexes.iter().any(|used| used.eq_ignore_ascii_case(index)) {
+        return Ok(());
+    }
+    let operators: Vec<String> = plan.rows.iter().map(|row| row.operator()).collect();
+    Err(ConnectError::Validation(format!(
+        "Expected `{}` to use index {index}, plan uses [{}] with operators [{}]",
+        plan.sql,
+        indexes.join(", "),
+        operators.join(", ")
+    )))
+}
+
+/// Create `database.table`, add index `idx_a` and verify a lookup on `a` uses it
+///
+/// Returns the checked plan. The table must not exist beforehand; the caller is
+/// responsible for dropping it.
+///
+/// # Errors
+///
+/// Returns an error if a statement fails, or `ConnectError::Validation` if the
+/// index is not chosen.
+pub fn verify_index_usage(conn: &mut PooledConn, database: &str, table: &str) -> Result<QueryPlan> {
+    let qualified = format!("`{database}`.`{table}`");
+    conn.query_drop(format!(
+        "CREATE TABLE {qualified} (id INT PRIMARY KEY, a INT, b VARCHAR(32))"
+    ))?;
+    conn.exec_batch(
+        format!("INSERT INTO {qualified} (id, a, b) VALUES (?, ?, ?)"),
+        (0..ROW_COUNT).map(|id| (id, id % 100, format!("row-{id}"))),
+    )?;
+    conn.query_drop(format!("ALTER TABLE {qualified} ADD INDEX idx_a (a)"))?;
+    conn.query_drop(format!("ANALYZE TABLE {qualified}"))?;
+
+    let plan = explain(
+        conn,
+        &format!("SELECT id, b FROM {qualified} WHERE a = 42"),
+        false,
+    )?;
+    assert_index_used(&plan, "idx_a")?;
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_rig::explain::PlanRow;
+    use test_rig::test_support::live_db;
+
+    fn plan(rows: &[(&str, &str)]) -> QueryPlan {
+        QueryPlan {
+            sql: "SELECT id, b FROM t WHERE a = 42".to_string(),
+            rows: rows
+                .iter()
+                .map(|(id, access_object)| PlanRow {
+                    id: (*id).to_string(),
+                    est_rows: "10.00".to_string(),
+                    task: "root".to_string(),
+                    access_object: (*access_object).to_string(),
+                    operator_info: String::new(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_index_lookup_passes() {
+        let lookup = plan(&[
+            ("IndexLookUp_10", ""),
+            ("├─IndexRangeScan_8(Build)", "table:t, index:idx_a(a)"),
+            ("└─TableRowIDScan_9(Probe)", "table:t"),
+        ]);
+        assert!(assert_index_used(&lookup, "idx_a").is_ok());
+        assert!(assert_index_used(&lookup, "IDX_A").is_ok());
+    }
+
+    #[test]
+    fn test_full_scan_or_other_index_fails() {
+        let full_scan = plan(&[
+            ("TableReader_7", ""),
+            ("└─Selection_6", ""),
+            ("  └─TableFullScan_5", "table:t"),
+        ]);
+        let err = assert_index_used(&full_scan, "idx_a").unwrap_err();
+        assert!(err.to_string().contains("TableFullScan"));
+
+        let other = plan(&[
+            ("IndexReader_6", ""),
+            ("└─IndexRangeScan_5", "table:t, index:idx_b(b)"),
+        ]);
+        let err = assert_index_used(&other, "idx_a").unwrap_err();
+        assert!(err.to_string().contains("plan uses [idx_b]"));
+    }
+
+    #[test]
+    fn test_index_usage_against_live_tidb() {
+        let Some(mut db) = live_db("live index usage test") else {
+            return;
+        };
+        let table = db.table("ddl_index_usage");
+        verify_index_usage(&mut db.conn, &db.database, table.name())
+            .unwrap()
+            .print();
+    }
+}
@@ -0,0 +1,229 @@
+//! JSON column support
+//!
+//! Creates a table with a `JSON` column, inserts a few documents and checks
+//! `JSON_EXTRACT` results against expected values. Servers older than the
+//! first release with JSON support are skipped rather than failed.
+
+use mysql::PooledConn;
+use mysql::prelude::*;
+use test_rig::capabilities::{Capability, detect_version};
+use test_rig::errors::{ConnectError, Result};
+
+/// Documents inserted by the JSON test, keyed by row id
+pub const JSON_DOCUMENTS: &[(u32, &str)] = &[
+    (
+        1,
+        r#"{"name": "alice", "age": 30, "tags": ["admin", "dev"]}"#,
+    ),
+    (2, r#"{"name": "bob", "address": {"city": "Berlin"}}"#),
+    (3, r#"{"name": "carol", "active": true}"#),
+];
+
+/// An expected `JSON_EXTRACT(doc, path)` result for one row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonExtractCheck {
+    pub id: u32,
+    pub path: &'static str,
+    /// Expected result as JSON text, or `None` if the path does not exist
+    pub expected: Option<&'static str>,
+}
+
+/// Extractions checked against [`JSON_DOCUMENTS`]
+pub const JSON_EXTRACT_CHECKS: &[JsonExtractCheck] = &[
+    JsonExtractCheck {
+        id: 1,
+        path: "$.name",
+        expected: Some(r#""alice""#),
+    },
+    JsonExtractCheck {
+        id: 1,
+        path: "$.age",
+        expected: Some("30"),
+    },
+    JsonExtractCheck {
+        id: 1,
+        path: "$.tags[1]",
+        expected: Some(r#""dev""#),
+    },
+    JsonExtractCheck {
+        id: 2,
+        path: "$.address.city",
+        expected: Some(r#""Berlin""#),
+    },
+    JsonExtractCheck {
+        id: 3,
+        path: "$.active",
+        expected: Some("true"),
+    },
+    JsonExtractCheck {
+        id: 3,
+        path: "$.age",
+        expected: None,
+    },
+];
+
+/// `INSERT` statement for every document in `documents`
+#[must_use]
+pub fn insert_documents_sql(qualified_table: &str, documents: &[(u32, &str)]) -> String {
+    let rows: Vec<String> = documents
+        .iter()
+        .map(|(id, doc)| format!("({id}, '{}')", doc.replace('\'', "''")))
+        .collect();
+    format!(
+        "INSERT INTO {qualified_table} (id, doc) VALUES {}",
+        rows.join(", ")
+    )
+}
+
+/// `SELECT JSON_EXTRACT(...)` statement for a check
+#[must_use]
+pub fn extract_sql(qualified_table: &str, check: &JsonExtractCheck) -> String {
+    format!(
+        "SELECT JSON_EXTRACT(doc, '{}') FROM {qualified_table} WHERE id = {}",
+        check.path, check.id
+    )
+}
+
+/// Compare an extraction result with the expected JSON value
+///
+/// Both sides are parsed so formatting differences do not matter.
+///
+/// # Errors
+///
+/// Returns `ConnectError::Validation` if the result differs from the expectation.
+pub fn assert_extract_result(check: &JsonExtractCheck, actual: Option<&str>) -> Result<()> {
+    let parse = |text: &str| serde_json::from_str::<serde_json::Value>(text).ok();
+    let matches = match (check.expected, actual) {
+        (None, None) => true,
+        (Some(expected), Some(actual)) => match (parse(expected), parse(actual)) {
+            (Some(expected), Some(actual)) => expected == actual,
+            _ => expected == actual,
+        },
+        _ => false,
+    };
+    if matches {
+        Ok(())
+    } else {
+        Err(ConnectError::Validation(format!(
+            "JSON_EXTRACT(doc, '{}') for id {}: expected {}, got {}",
+            check.path,
+            check.id,
+            check.expected.unwrap_or("NULL"),
+            actual.unwrap_or("NULL")
+        )))
+    }
+}
+
+/// Outcome of the JSON column test
+#[derive(Debug, Clone, Default)]
+pub struct JsonColumnReport {
+    /// Number of extractions checked
+    pub checked: usize,
+    /// Messages for extractions that did not match
+    pub failures: Vec<String>,
+}
+
+impl JsonColumnReport {
+    #[must_use]
+    pub fn all_passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    pub fn print(&self) {
+        println!(
+            "JSON_EXTRACT: {} checked, {} failed",
+            self.checked,
+            self.failures.len()
+        );
+        for failure in &self.failures {
+            println!("  ❌ {failure}");
+        }
+    }
+}
+
+/// Create `database.table` with a JSON column, insert the documents and check each extraction
+///
+/// Returns `Ok(None)` if the server does not support JSON.
+///
+/// # Errors
+///
+/// Returns an error if the server version cannot be detected or a statement fails.
+pub fn run_json_column_test(
+    conn: &mut PooledConn,
+    database: &str,
+    table: &str,
+) -> Result<Option<JsonColumnReport>> {
+    let version = detect_version(conn)?;
+    if !version.supports(Capability::Json) {
+        tracing::info!(
+            "Skipping JSON column test: TiDB {} predates {} support (needs {})",
+            version,
+            Capability::Json,
+            Capability::Json.min_version()
+        );
+        return Ok(None);
+    }
+
+    let qualified = format!("`{database}`.`{table}`");
+    conn.query_drop(format!(
+        "CREATE TABLE {qualified} (id INT PRIMARY KEY, doc JSON)"
+    ))?;
+    conn.query_drop(insert_documents_sql(&qualified, JSON_DOCUMENTS))?;
+
+    let mut report = JsonColumnReport::default();
+    for check in JSON_EXTRACT_CHECKS {
+        let actual: Option<Option<String>> = conn.query_first(extract_sql(&qualified, check))?;
+        report.checked += 1;
+        if let Err(e) = assert_extract_result(check, actual.flatten().as_deref()) {
+            report.failures.push(e.to_string());
+        }
+    }
+    Ok(Some(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_rig::test_support::live_db;
+
+    #[test]
+    fn test_statements_are_built_from_documents() {
+        assert_eq!(
+            insert_documents_sql("`test`.`j`", &[(7, r#"{"note": "it's"}"#)]),
+            r#"INSERT INTO `test`.`j` (id, doc) VALUES (7, '{"note": "it''s"}')"#
+        );
+        assert_eq!(
+            extract_sql("`test`.`j`", &JSON_EXTRACT_CHECKS[3]),
+            "SELECT JSON_EXTRACT(doc, '$.address.city') FROM `test`.`j` WHERE id = 2"
+        );
+    }
+
+    #[test]
+    fn test_assert_extract_result() {
+        let name = &JSON_EXTRACT_CHECKS[0];
+        assert!(assert_extract_result(name, Some(r#""alice""#)).is_ok());
+        assert!(assert_extract_result(name, Some(r#" "alice" "#)).is_ok());
+        let err = assert_extract_result(name, Some(r#""bob""#)).unwrap_err();
+        assert!(err.to_string().contains(r#"expected "alice", got "bob""#));
+        assert!(assert_extract_result(name, None).is_err());
+
+        let missing = &JSON_EXTRACT_CHECKS[5];
+        assert!(assert_extract_result(missing, None).is_ok());
+        assert!(assert_extract_result(missing, Some("30")).is_err());
+    }
+
+    #[test]
+    fn test_json_column_against_live_tidb() {
+        let Some(mut db) = live_db("live JSON column test") else {
+            return;
+        };
+        let table = db.table("ddl_json");
+        match run_json_column_test(&mut db.conn, &db.database, table.name()).unwrap() {
+            Some(report) => {
+                report.print();
+                assert!(report.all_passed());
+            }
+            None => eprintln!("Server does not support JSON, skipped"),
+        }
+    }
+}
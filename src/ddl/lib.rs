@@ -1,6 +1,56 @@
+//! # DDL Checks
+//!
+//! The `run_*` and `verify_*` runners that take a `database` and `table`
+//! create that table themselves, so it must not exist yet, and leave it in
+//! place afterwards so a failure can be inspected. Tests reserve the name with
+//! `test_rig::test_support::LiveDb::table`, whose guard drops the table.
+
 // Minimal lib.rs for the ddl crate
 
 // Re-export common test infrastructure if needed
 // pub use crate::common::python_tests;
 
-// Add any public API or test registration here as needed
+/// DDL idempotency verification (run a statement twice, schema must not change)
+pub mod idempotency;
+
+/// Concurrent ALTER TABLE execution and DDL job ordering
+pub mod concurrent_alter;
+
+/// Column-type round-trip coverage
+pub mod column_types;
+
+/// JSON column storage and `JSON_EXTRACT` checks
+pub mod json_column;
+
+/// AUTO_INCREMENT and AUTO_RANDOM id allocation checks
+pub mod auto_id;
+
+/// Clustered and non-clustered primary key checks
+pub mod clustered_index;
+
+/// Partitioned table creation and partition pruning checks
+pub mod partitioning;
+
+/// Row TTL table attribute checks
+pub mod ttl;
+
+/// Stored and virtual generated column checks
+pub mod generated_columns;
+
+/// Secondary index creation and EXPLAIN usage checks
+pub mod index_usage;
+
+/// DDL test suite driven by the dynamic state machine
+pub mod suite;
+
+pub use auto_id::{AutoIdMode, AutoIdReport, run_auto_id_test};
+pub use clustered_index::{PrimaryKeyKind, parse_primary_key_kind, verify_primary_key_kind};
+pub use column_types::{COLUMN_TYPE_CASES, ColumnTypeReport, verify_column_type_round_trip};
+pub use concurrent_alter::{ConcurrentAlterReport, DdlJob, run_concurrent_alters};
+pub use generated_columns::{GeneratedKind, verify_generated_column};
+pub use idempotency::{IdempotencyReport, verify_ddl_idempotent};
+pub use index_usage::{assert_index_used, verify_index_usage};
+pub use json_column::{JsonColumnReport, run_json_column_test};
+pub use partitioning::{PartitionReport, PartitionScheme, run_partitioned_table_test};
+pub use suite::{DdlOperationResult, DdlTestReport, DdlTestSuite};
+pub use ttl::{TtlClause, parse_ttl_clause, run_ttl_table_test};
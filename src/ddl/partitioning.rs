@@ -0,0 +1,307 @@
+//! Partitioned tables
+//!
+//! Creates a `RANGE` or `HASH` partitioned table, inserts one row into every
+//! partition, checks the partition count reported by
+//! `information_schema.PARTITIONS`, and verifies via `EXPLAIN` that a point
+//! lookup is pruned to the single partition holding the row.
+
+use mysql::PooledConn;
+use mysql::prelude::*;
+use std::num::NonZeroU32;
+use test_rig::capabilities::{Capability, detect_version};
+use test_rig::errors::{ConnectError, Result};
+use test_rig::explain::{QueryPlan, explain};
+
+/// How rows are assigned to partitions, by `id`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PartitionScheme {
+    /// One partition per exclusive upper bound, plus a final `MAXVALUE` partition
+    Range(Vec<i64>),
+    /// `HASH(id)` over this many partitions
+    Hash(NonZeroU32),
+}
+
+impl PartitionScheme {
+    /// Check the scheme describes at least one bounded partition
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConnectError::Validation` for a `Range` with no bounds, which
+    /// has no ids to insert.
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            PartitionScheme::Range(bounds) if bounds.is_empty() => Err(ConnectError::Validation(
+                "Range partition scheme needs at least one bound".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Number of partitions the table should have
+    #[must_use]
+    pub fn partition_count(&self) -> usize {
+        match self {
+            PartitionScheme::Range(bounds) => bounds.len() + 1,
+            PartitionScheme::Hash(count) => count.get() as usize,
+        }
+    }
+
+    /// `PARTITION BY` clause; partitions are named `p0`, `p1`, ...
+    #[must_use]
+    pub fn partition_clause(&self) -> String {
+        match self {
+            PartitionScheme::Range(bounds) => {
+                let mut partitions: Vec<String> = bounds
+                    .iter()
+                    .enumerate()
+                    .map(|(i, bound)| format!("PARTITION p{i} VALUES LESS THAN ({bound})"))
+                    .collect();
+                partitions.push(format!(
+                    "PARTITION p{} VALUES LESS THAN MAXVALUE",
+                    bounds.len()
+                ));
+                format!("PARTITION BY RANGE (id) ({})", partitions.join(", "))
+            }
+            PartitionScheme::Hash(count) => format!("PARTITION BY HASH (id) PARTITIONS {count}"),
+        }
+    }
+
+    /// Partition that holds the row with `id`
+    #[must_use]
+    pub fn partition_for(&self, id: i64) -> String {
+        let index = match self {
+            PartitionScheme::Range(bounds) => bounds
+                .iter()
+                .position(|bound| id < *bound)
+                .unwrap_or(bounds.len()),
+            PartitionScheme::Hash(count) => {
+                usize::try_from(id.rem_euclid(i64::from(count.get()))).unwrap_or_default()
+            }
+        };
+        format!("p{index}")
+    }
+
+    /// One id per partition, so inserted rows span every partition
+    #[must_use]
+    pub fn spanning_ids(&self) -> Vec<i64> {
+        match self {
+            PartitionScheme::Range(bounds) => bounds
+                .iter()
+                .map(|bound| bound - 1)
+                .chain(bounds.last().copied())
+                .collect(),
+            PartitionScheme::Hash(count) => (0..i64::from(count.get())).collect(),
+        }
+    }
+}
+
+/// Check the number of partitions reported for a table
+///
+/// # Errors
+///
+/// Returns `ConnectError::Validation` if the count differs.
+pub fn assert_partition_count(partitions: &[String], expected: usize) -> Result<()> {
+    if partitions.len() == expected {
+        Ok(())
+    } else {
+        Err(ConnectError::Validation(format!(
+            "Expected {expected} partition(s), found {}: {}",
+            partitions.len(),
+            partitions.join(", ")
+        )))
+    }
+}
+
+/// Check that a plan only touches `expected` partition
+///
+/// # Errors
+///
+/// Returns `ConnectError::Validation` if the plan reads other partitions or
+/// names none (no pruning information).
+pub fn assert_pruned_to(plan: &QueryPlan, expected: &str) -> Result<()> {
+    let partitions = plan.partitions();
+    if partitions == [expected] {
+        Ok(())
+    } else {
+        Err(ConnectError::Validation(format!(
+            "Expected `{}` to be pruned to partition {expected}, plan accesses [{}]",
+            plan.sql,
+            partitions.join(", ")
+        )))
+    }
+}
+
+/// Partition names of `database.table` from `information_schema.PARTITIONS`
+///
+/// # Errors
+///
+/// Returns an error if the query fails.
+pub fn partition_names(conn: &mut PooledConn, database: &str, table: &str) -> Result<Vec<String>> {
+    Ok(conn.exec(
+        "SELECT PARTITION_NAME FROM information_schema.PARTITIONS \
+         WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? AND PARTITION_NAME IS NOT NULL \
+         ORDER BY PARTITION_ORDINAL_POSITION",
+        (database, table),
+    )?)
+}
+
+/// Outcome of the partitioned table test
+#[derive(Debug, Clone)]
+pub struct PartitionReport {
+    pub partitions: Vec<String>,
+    /// Point lookup whose plan was checked, and its plan
+    pub pruning_plan: QueryPlan,
+}
+
+/// Create a partitioned `database.table`, fill every partition and verify pruning for `probe_id`
+///
+/// Returns `Ok(None)` on servers without partitioning support.
+///
+/// # Errors
+///
+/// Returns `ConnectError::Validation` if `scheme` is invalid, the partition
+/// count is wrong or the lookup is not pruned to one partition, or an error if
+/// a statement fails.
+pub fn run_partitioned_table_test(
+    conn: &mut PooledConn,
+    database: &str,
+    table: &str,
+    scheme: &PartitionScheme,
+    probe_id: i64,
+) -> Result<Option<PartitionReport>> {
+    scheme.validate()?;
+    let version = detect_version(conn)?;
+    if !version.supports(Capability::Partitioning) {
+        tracing::info!(
+            "Skipping partitioned table test: TiDB {} predates {} support",
+            version,
+            Capability::Partitioning
+        );
+        return Ok(None);
+    }
+
+    let qualified = format!("`{database}`.`{table}`");
+    conn.query_drop(format!(
+        "CREATE TABLE {qualified} (id BIGINT NOT NULL, v INT) {}",
+        scheme.partition_clause()
+    ))?;
+    let values: Vec<String> = scheme
+        .spanning_ids()
+        .iter()
+        .map(|id| format!("({id}, 0)"))
+        .collect();
+    conn.query_drop(format!(
+        "INSERT INTO {qualified} (id, v) VALUES {}",
+        values.join(", ")
+    ))?;
+
+    let partitions = partition_names(conn, database, table)?;
+    assert_partition_count(&partitions, scheme.partition_count())?;
+
+    let pruning_plan = explain(
+        conn,
+        &format!("SELECT * FROM {qualified} WHERE id = {probe_id}"),
+        false,
+    )?;
+    assert_pruned_to(&pruning_plan, &scheme.partition_for(probe_id))?;
+
+    Ok(Some(PartitionReport {
+        partitions,
+        pruning_plan,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_rig::explain::PlanRow;
+    use test_rig::test_support::live_db;
+
+    fn plan(access_objects: &[&str]) -> QueryPlan {
+        QueryPlan {
+            sql: "SELECT * FROM t WHERE id = 150".to_string(),
+            rows: access_objects
+                .iter()
+                .map(|access_object| PlanRow {
+                    id: "TableFullScan_5".to_string(),
+                    est_rows: "1.00".to_string(),
+                    task: "cop[tikv]".to_string(),
+                    access_object: (*access_object).to_string(),
+                    operator_info: String::new(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_range_scheme() {
+        let scheme = PartitionScheme::Range(vec![100, 200]);
+        assert_eq!(scheme.partition_count(), 3);
+        assert_eq!(
+            scheme.partition_clause(),
+            "PARTITION BY RANGE (id) (PARTITION p0 VALUES LESS THAN (100), \
+             PARTITION p1 VALUES LESS THAN (200), PARTITION p2 VALUES LESS THAN MAXVALUE)"
+        );
+        assert_eq!(scheme.spanning_ids(), vec![99, 199, 200]);
+        assert_eq!(scheme.partition_for(99), "p0");
+        assert_eq!(scheme.partition_for(150), "p1");
+        assert_eq!(scheme.partition_for(5000), "p2");
+
+        let hash = PartitionScheme::Hash(NonZeroU32::new(4).unwrap());
+        assert_eq!(hash.partition_for(6), "p2");
+        assert_eq!(hash.spanning_ids(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_empty_range_scheme_rejected() {
+        let err = PartitionScheme::Range(Vec::new()).validate().unwrap_err();
+        assert!(matches!(err, ConnectError::Validation(_)));
+        assert!(PartitionScheme::Range(vec![100]).validate().is_ok());
+        assert!(
+            PartitionScheme::Hash(NonZeroU32::new(1).unwrap())
+                .validate()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_partition_count_assertion() {
+        let partitions: Vec<String> = ["p0", "p1", "p2"].map(String::from).to_vec();
+        assert!(assert_partition_count(&partitions, 3).is_ok());
+        let err = assert_partition_count(&partitions, 4).unwrap_err();
+        assert!(err.to_string().contains("Expected 4 partition(s), found 3"));
+    }
+
+    #[test]
+    fn test_pruning_check_on_synthetic_plans() {
+        // Dynamic prune mode: one reader naming the surviving partition
+        assert!(assert_pruned_to(&plan(&["table:t, partition:p1"]), "p1").is_ok());
+        // Static prune mode: one reader per partition under a PartitionUnion
+        assert!(assert_pruned_to(&plan(&["", "table:t, partition:p1"]), "p1").is_ok());
+
+        let unpruned = plan(&["table:t, partition:p0,p1,p2"]);
+        assert!(assert_pruned_to(&unpruned, "p1").is_err());
+        assert!(assert_pruned_to(&plan(&["table:t, partition:all"]), "p1").is_err());
+        assert!(assert_pruned_to(&plan(&["table:t"]), "p1").is_err());
+    }
+
+    #[test]
+    fn test_partitioned_table_against_live_tidb() {
+        let Some(mut db) = live_db("live partitioned table test") else {
+            return;
+        };
+        let schemes = [
+            ("range", PartitionScheme::Range(vec![100, 200])),
+            ("hash", PartitionScheme::Hash(NonZeroU32::new(4).unwrap())),
+        ];
+        for (suffix, scheme) in schemes {
+            let table = db.table(&format!("ddl_partition_{suffix}"));
+            let report =
+                run_partitioned_table_test(&mut db.conn, &db.database, table.name(), &scheme, 150);
+            if let Some(report) = report.unwrap() {
+                report.pruning_plan.print();
+                assert_eq!(report.partitions.len(), scheme.partition_count());
+            }
+        }
+    }
+}
@@ -0,0 +1,503 @@
+//! DDL test suite
+//!
+//! Drives a [`DynamicStateMachine`] through a fixed sequence of DDL statements
+//! against a uniquely named scratch table: create, add/drop/modify a column,
+//! create an index and drop the table. After each statement the schema is read
+//! back from `information_schema` and checked against the expected change.
+//! Every operation is recorded as passed or failed; later operations still run
+//! after a failure, and the scratch table is dropped when the run ends.
+
+use mysql::prelude::*;
+use mysql::{Pool, PooledConn};
+use std::time::{SystemTime, UNIX_EPOCH};
+use test_rig::config::DatabaseConfig;
+use test_rig::connection::{create_connection_pool_with_config, get_pooled_connection};
+use test_rig::errors::{ConnectError, Result};
+use test_rig::schema::{TableSchema, try_snapshot_table};
+use test_rig::{DynamicState, DynamicStateContext, DynamicStateHandler, DynamicStateMachine};
+
+/// Database used when the configuration does not name one
+const DEFAULT_DATABASE: &str = "test";
+
+/// Custom data key holding the per-operation results while the machine runs
+const RESULTS_KEY: &str = "ddl_results";
+
+/// Schema change an operation is expected to produce
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaExpectation {
+    TableExists,
+    TableAbsent,
+    ColumnPresent(&'static str),
+    ColumnAbsent(&'static str),
+    /// Column type as reported by `information_schema`, e.g. `varchar(128)`
+    ColumnType {
+        column: &'static str,
+        column_type: &'static str,
+    },
+    IndexPresent(&'static str),
+}
+
+impl SchemaExpectation {
+    /// Check the expectation against the table's schema (`None` if the table does not exist)
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConnectError::Validation` describing the mismatch.
+    pub fn check(&self, schema: Option<&TableSchema>) -> Result<()> {
+        let fail = |message: String| Err(ConnectError::Validation(message));
+        match (self, schema) {
+            (SchemaExpectation::TableAbsent, None) => Ok(()),
+            (SchemaExpectation::TableAbsent, Some(schema)) => {
+                fail(format!("table {} still exists", schema.table))
+            }
+            (_, None) => fail("table does not exist".to_string()),
+            (SchemaExpectation::TableExists, Some(_)) => Ok(()),
+            (SchemaExpectation::ColumnPresent(column), Some(schema)) => {
+                if schema.column(column).is_some() {
+                    Ok(())
+                } else {
+                    fail(format!("column {column} is missing"))
+                }
+            }
+            (SchemaExpectation::ColumnAbsent(column), Some(schema)) => {
+                if schema.column(column).is_none() {
+                    Ok(())
+                } else {
+                    fail(format!("column {column} still exists"))
+                }
+            }
+            (
+                SchemaExpectation::ColumnType {
+                    column,
+                    column_type,
+                },
+                Some(schema),
+            ) => match schema.column(column) {
+                Some(actual) if actual.column_type.eq_ignore_ascii_case(column_type) => Ok(()),
+                Some(actual) => fail(format!(
+                    "column {column} has type {}, expected {column_type}",
+                    actual.column_type
+                )),
+                None => fail(format!("column {column} is missing")),
+            },
+            (SchemaExpectation::IndexPresent(index), Some(schema)) => {
+                if schema.index(index).is_some() {
+                    Ok(())
+                } else {
+                    fail(format!("index {index} is missing"))
+                }
+            }
+        }
+    }
+}
+
+/// One DDL statement in the suite
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DdlOperation {
+    pub name: &'static str,
+    /// Statement with `{table}` standing for the qualified scratch table
+    pub template: &'static str,
+    pub expectation: SchemaExpectation,
+}
+
+impl DdlOperation {
+    /// Statement for the given qualified table name
+    #[must_use]
+    pub fn statement(&self, qualified_table: &str) -> String {
+        self.template.replace("{table}", qualified_table)
+    }
+}
+
+/// The operations run by [`DdlTestSuite::run_tests`], in order
+#[must_use]
+pub fn ddl_operations() -> Vec<DdlOperation> {
+    vec![
+        DdlOperation {
+            name: "create_table",
+            template: "CREATE TABLE {table} (id INT PRIMARY KEY, name VARCHAR(64), legacy INT)",
+            expectation: SchemaExpectation::TableExists,
+        },
+        DdlOperation {
+            name: "add_column",
+            template: "ALTER TABLE {table} ADD COLUMN email VARCHAR(255)",
+            expectation: SchemaExpectation::ColumnPresent("email"),
+        },
+        DdlOperation {
+            name: "drop_column",
+            template: "ALTER TABLE {table} DROP COLUMN legacy",
+            expectation: SchemaExpectation::ColumnAbsent("legacy"),
+        },
+        DdlOperation {
+            name: "modify_column",
+            template: "ALTER TABLE {table} MODIFY COLUMN name VARCHAR(128)",
+            expectation: SchemaExpectation::ColumnType {
+                column: "name",
+                column_type: "varchar(128)",
+            },
+        },
+        DdlOperation {
+            name: "create_index",
+            template: "CREATE INDEX idx_email ON {table} (email)",
+            expectation: SchemaExpectation::IndexPresent("idx_email"),
+        },
+        DdlOperation {
+            name: "drop_table",
+            template: "DROP TABLE {table}",
+            expectation: SchemaExpectation::TableAbsent,
+        },
+    ]
+}
+
+/// Outcome of one DDL operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DdlOperationResult {
+    pub name: String,
+    pub statement: String,
+    /// `None` if the statement succeeded and the schema changed as expected
+    pub error: Option<String>,
+}
+
+impl DdlOperationResult {
+    #[must_use]
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Results of a DDL suite run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DdlTestReport {
+    /// Qualified scratch table the suite ran against
+    pub table: String,
+    pub operations: Vec<DdlOperationResult>,
+}
+
+impl DdlTestReport {
+    #[must_use]
+    pub fn all_passed(&self) -> bool {
+        self.operations.iter().all(DdlOperationResult::passed)
+    }
+
+    #[must_use]
+    pub fn failed(&self) -> Vec<&DdlOperationResult> {
+        self.operations.iter().filter(|op| !op.passed()).collect()
+    }
+
+    pub fn print(&self) {
+        println!("DDL suite on {}", self.table);
+        for operation in &self.operations {
+            match &operation.error {
+                None => println!("  ✓ {}", operation.name),
+                Some(error) => println!("  ❌ {}: {error}", operation.name),
+            }
+        }
+    }
+}
+
+/// State the machine is in while running `operation`
+fn operation_state(operation: &DdlOperation) -> DynamicState {
+    DynamicState::new(format!("ddl_{}", operation.name))
+}
+
+/// Runs one operation, records its outcome and moves on to `next`
+struct DdlOperationHandler {
+    operation: DdlOperation,
+    database: String,
+    table: String,
+    next: DynamicState,
+}
+
+impl DdlOperationHandler {
+    fn run(&self, conn: &mut PooledConn) -> Result<()> {
+        let qualified = format!("`{}`.`{}`", self.database, self.table);
+        conn.query_drop(self.operation.statement(&qualified))?;
+        let schema = try_snapshot_table(conn, &self.database, &self.table)?;
+        self.operation.expectation.check(schema.as_ref())
+    }
+}
+
+#[async_trait::async_trait]
+impl DynamicStateHandler for DdlOperationHandler {
+    async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+        tracing::debug!("DDL operation: {}", self.operation.name);
+        Ok(operation_state(&self.operation))
+    }
+
+    async fn execute(&self, context: &mut DynamicStateContext) -> Result<DynamicState> {
+        let conn = context.connection.as_mut().ok_or_else(|| {
+            ConnectError::StateMachine("No connection available for DDL suite".to_string())
+        })?;
+        let outcome = self.run(conn);
+        let result = DdlOperationResult {
+            name: self.operation.name.to_string(),
+            statement: self
+                .operation
+                .statement(&format!("`{}`.`{}`", self.database, self.table)),
+            error: outcome.err().map(|e| e.to_string()),
+        };
+        if let Some(results) = context.get_custom_data_mut::<Vec<DdlOperationResult>>(RESULTS_KEY) {
+            results.push(result);
+        }
+        Ok(self.next.clone())
+    }
+
+    async fn exit(&self, _context: &mut DynamicStateContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Runs [`ddl_operations`] against a scratch table on a live server
+pub struct DdlTestSuite {
+    config: DatabaseConfig,
+    table: String,
+}
+
+impl DdlTestSuite {
+    /// Create a suite with a uniquely named scratch table
+    #[must_use]
+    pub fn new(config: DatabaseConfig) -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or_default();
+        let table = format!("ddl_suite_{}_{nanos}", std::process::id());
+        Self { config, table }
+    }
+
+    /// Use `table` as the scratch table instead of the generated name
+    #[must_use]
+    pub fn with_table(mut self, table: impl Into<String>) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    /// Name of the scratch table
+    #[must_use]
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    fn database(&self) -> &str {
+        self.config.database.as_deref().unwrap_or(DEFAULT_DATABASE)
+    }
+
+    /// Run every DDL operation and collect the outcomes
+    ///
+    /// The scratch table is dropped afterwards even if an operation failed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server cannot be reached or the state machine
+    /// fails; failed operations are reported in the result, not as errors.
+    pub async fn run_tests(&self) -> Result<DdlTestReport> {
+        let pool = create_connection_pool_with_config(&self.config)?;
+        self.run_tests_on(&pool).await
+    }
+
+    /// [`DdlTestSuite::run_tests`] with connections from an existing `pool`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no connection can be checked out or the state
+    /// machine fails.
+    pub async fn run_tests_on(&self, pool: &Pool) -> Result<DdlTestReport> {
+        let database = self.database().to_string();
+        let mut conn = get_pooled_connection(pool, &self.config)?;
+        conn.query_drop(format!("CREATE DATABASE IF NOT EXISTS `{database}`"))?;
+
+        let operations = ddl_operations();
+        let mut machine = DynamicStateMachine::new();
+        let first = operation_state(&operations[0]);
+        machine.register_handler(
+            test_rig::states::initial(),
+            Box::new(StartHandler {
+                first: first.clone(),
+            }),
+        );
+        for (i, operation) in operations.iter().enumerate() {
+            let next = operations
+                .get(i + 1)
+                .map_or_else(test_rig::states::completed, operation_state);
+            machine.register_handler(
+                operation_state(operation),
+                Box::new(DdlOperationHandler {
+                    operation: operation.clone(),
+                    database: database.clone(),
+                    table: self.table.clone(),
+                    next,
+                }),
+            );
+        }
+
+        let context = machine.get_context_mut();
+        context.database = Some(database.clone());
+        context.connection = Some(conn);
+        context.set_custom_data(RESULTS_KEY.to_string(), Vec::<DdlOperationResult>::new());
+
+        let run = machine.run().await;
+
+        // Clean up on a fresh connection, whatever state the run left things in
+        let qualified = format!("`{database}`.`{}`", self.table);
+        if let Err(e) = get_pooled_connection(pool, &self.config)
+            .and_then(|mut conn| Ok(conn.query_drop(format!("DROP TABLE IF EXISTS {qualified}"))?))
+        {
+            tracing::warn!("Failed to drop DDL scratch table {}: {}", qualified, e);
+        }
+        run?;
+
+        let operations = machine
+            .get_context_mut()
+            .get_custom_data_mut::<Vec<DdlOperationResult>>(RESULTS_KEY)
+            .map(std::mem::take)
+            .unwrap_or_default();
+        Ok(DdlTestReport {
+            table: qualified,
+            operations,
+        })
+    }
+}
+
+/// Moves the machine from `initial` to the first operation
+struct StartHandler {
+    first: DynamicState,
+}
+
+#[async_trait::async_trait]
+impl DynamicStateHandler for StartHandler {
+    async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+        Ok(test_rig::states::initial())
+    }
+
+    async fn execute(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+        Ok(self.first.clone())
+    }
+
+    async fn exit(&self, _context: &mut DynamicStateContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_rig::schema::{ColumnSchema, IndexSchema};
+    use test_rig::test_support::live_db;
+
+    fn schema() -> TableSchema {
+        let mut schema = TableSchema::new("test", "t");
+        schema.columns = [("id", "int"), ("name", "varchar(128)")]
+            .iter()
+            .map(|(name, column_type)| ColumnSchema {
+                name: (*name).to_string(),
+                column_type: (*column_type).to_string(),
+                is_nullable: true,
+                default: None,
+                extra: String::new(),
+            })
+            .collect();
+        schema.indexes = vec![IndexSchema {
+            name: "idx_name".to_string(),
+            columns: vec!["name".to_string()],
+            unique: false,
+        }];
+        schema
+    }
+
+    #[test]
+    fn test_schema_expectations() {
+        let schema = schema();
+        let present = Some(&schema);
+        assert!(SchemaExpectation::TableExists.check(present).is_ok());
+        assert!(SchemaExpectation::TableExists.check(None).is_err());
+        assert!(SchemaExpectation::TableAbsent.check(None).is_ok());
+        assert!(SchemaExpectation::TableAbsent.check(present).is_err());
+        assert!(
+            SchemaExpectation::ColumnPresent("name")
+                .check(present)
+                .is_ok()
+        );
+        assert!(
+            SchemaExpectation::ColumnAbsent("name")
+                .check(present)
+                .is_err()
+        );
+        assert!(
+            SchemaExpectation::IndexPresent("idx_name")
+                .check(present)
+                .is_ok()
+        );
+        assert!(
+            SchemaExpectation::IndexPresent("idx_x")
+                .check(present)
+                .is_err()
+        );
+
+        let err = SchemaExpectation::ColumnType {
+            column: "name",
+            column_type: "varchar(64)",
+        }
+        .check(present)
+        .unwrap_err();
+        assert!(err.to_string().contains("has type varchar(128)"));
+    }
+
+    #[test]
+    fn test_operations_cover_requested_ddl() {
+        let operations = ddl_operations();
+        let names: Vec<&str> = operations.iter().map(|op| op.name).collect();
+        assert_eq!(
+            names,
+            vec![
+                "create_table",
+                "add_column",
+                "drop_column",
+                "modify_column",
+                "create_index",
+                "drop_table"
+            ]
+        );
+        assert_eq!(
+            operations[1].statement("`test`.`t`"),
+            "ALTER TABLE `test`.`t` ADD COLUMN email VARCHAR(255)"
+        );
+    }
+
+    #[test]
+    fn test_report_outcomes() {
+        let report = DdlTestReport {
+            table: "`test`.`t`".to_string(),
+            operations: vec![
+                DdlOperationResult {
+                    name: "create_table".to_string(),
+                    statement: String::new(),
+                    error: None,
+                },
+                DdlOperationResult {
+                    name: "add_column".to_string(),
+                    statement: String::new(),
+                    error: Some("column email is missing".to_string()),
+                },
+            ],
+        };
+        assert!(!report.all_passed());
+        assert_eq!(report.failed().len(), 1);
+        assert_eq!(report.failed()[0].name, "add_column");
+    }
+
+    #[tokio::test]
+    async fn test_ddl_suite_against_live_tidb() {
+        let Some(db) = live_db("live DDL suite") else {
+            return;
+        };
+        let table = db.table("ddl_suite");
+        let config = DatabaseConfig {
+            database: Some(db.database.clone()),
+            ..DatabaseConfig::default()
+        };
+        let suite = DdlTestSuite::new(config).with_table(table.name());
+        let report = suite.run_tests_on(db.pool()).await.unwrap();
+        report.print();
+        assert_eq!(report.operations.len(), ddl_operations().len());
+        assert!(report.all_passed());
+    }
+}
@@ -0,0 +1,195 @@
+//! Row TTL tables
+//!
+//! Newer TiDB releases can expire rows automatically with a table-level
+//! `TTL = <column> + INTERVAL <n> <unit>` clause. `SHOW CREATE TABLE` reports
+//! the clause inside `/*T![ttl] ... */` comments. The helpers here create a TTL
+//! table, insert rows and check the attribute was stored as requested; servers
+//! without TTL support are skipped.
+
+use mysql::PooledConn;
+use mysql::prelude::*;
+use test_rig::capabilities::{Capability, TidbVersion, detect_version};
+use test_rig::errors::{ConnectError, Result};
+use test_rig::schema::show_create_table;
+
+/// A table's TTL attribute
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TtlClause {
+    /// Timestamp column the expiry is computed from
+    pub column: String,
+    /// Interval amount, e.g. `3`
+    pub amount: String,
+    /// Interval unit, e.g. `MONTH`
+    pub unit: String,
+    /// Whether the background TTL job is enabled (`TTL_ENABLE`)
+    pub enabled: bool,
+}
+
+impl TtlClause {
+    /// Table option for `CREATE TABLE`
+    #[must_use]
+    pub fn table_option(&self) -> String {
+        format!(
+            "TTL = `{}` + INTERVAL {} {} TTL_ENABLE = '{}'",
+            self.column,
+            self.amount,
+            self.unit,
+            if self.enabled { "ON" } else { "OFF" }
+        )
+    }
+}
+
+/// Read the TTL attribute from `SHOW CREATE TABLE` output
+///
+/// Returns `None` if the table has no TTL.
+#[must_use]
+pub fn parse_ttl_clause(create_table: &str) -> Option<TtlClause> {
+    let upper = create_table.to_ascii_uppercase();
+    // `TTL_ENABLE` and `TTL_JOB_INTERVAL` also start with `TTL`, so require `=` after optional spaces
+    let start = upper.match_indices("TTL").find_map(|(i, _)| {
+        let rest = upper[i + 3..].trim_start();
+        rest.starts_with('=')
+            .then(|| create_table.len() - rest.len() + 1)
+    })?;
+
+    let mut tokens = create_table[start..]
+        .split_whitespace()
+        .filter(|token| *token != "+");
+    let column = tokens.next()?.trim_matches('`').to_string();
+    if !tokens.next()?.eq_ignore_ascii_case("INTERVAL") {
+        return None;
+    }
+    let amount = tokens.next()?.to_string();
+    let unit = tokens
+        .next()?
+        .trim_end_matches(|c: char| !c.is_ascii_alphabetic())
+        .to_ascii_uppercase();
+
+    let enabled = !upper
+        .split_once("TTL_ENABLE")
+        .map(|(_, rest)| rest.trim_start_matches([' ', '=']).starts_with("'OFF'"))
+        .unwrap_or(false);
+
+    Some(TtlClause {
+        column,
+        amount,
+        unit,
+        enabled,
+    })
+}
+
+/// Whether the TTL test should run against `version`
+#[must_use]
+pub fn ttl_supported(version: TidbVersion) -> bool {
+    version.supports(Capability::Ttl)
+}
+
+/// Create `database.table` with `ttl`, insert rows and check the stored TTL attribute
+///
+/// Returns `Ok(None)` on servers without TTL support.
+///
+/// # Errors
+///
+/// Returns an error if a statement fails, or `ConnectError::Validation` if the
+/// TTL attribute read back differs from `ttl`.
+pub fn run_ttl_table_test(
+    conn: &mut PooledConn,
+    database: &str,
+    table: &str,
+    ttl: &TtlClause,
+) -> Result<Option<TtlClause>> {
+    let version = detect_version(conn)?;
+    if !ttl_supported(version) {
+        tracing::info!(
+            "Skipping TTL table test: TiDB {} predates {} support (needs {})",
+            version,
+            Capability::Ttl,
+            Capability::Ttl.min_version()
+        );
+        return Ok(None);
+    }
+
+    let qualified = format!("`{database}`.`{table}`");
+    conn.query_drop(format!(
+        "CREATE TABLE {qualified} (id INT PRIMARY KEY, `{}` TIMESTAMP NOT NULL) {}",
+        ttl.column,
+        ttl.table_option()
+    ))?;
+    conn.query_drop(format!(
+        "INSERT INTO {qualified} (id, `{0}`) VALUES (1, NOW()), (2, NOW() - INTERVAL 1 YEAR)",
+        ttl.column
+    ))?;
+
+    let create_table = show_create_table(conn, database, table)?;
+    match parse_ttl_clause(&create_table) {
+        Some(actual) if actual == *ttl => Ok(Some(actual)),
+        actual => Err(ConnectError::Validation(format!(
+            "Table {database}.{table} was created with `{}`, SHOW CREATE TABLE reports {}",
+            ttl.table_option(),
+            actual.map_or_else(
+                || "no TTL".to_string(),
+                |t| format!("`{}`", t.table_option())
+            )
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_rig::test_support::live_db;
+
+    fn clause(enabled: bool) -> TtlClause {
+        TtlClause {
+            column: "created_at".to_string(),
+            amount: "3".to_string(),
+            unit: "MONTH".to_string(),
+            enabled,
+        }
+    }
+
+    #[test]
+    fn test_parse_ttl_clause() {
+        let create_table = "CREATE TABLE `t` (\n  `id` int(11) NOT NULL,\n  `created_at` timestamp NOT NULL\n) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 /*T![ttl] TTL=`created_at` + INTERVAL 3 MONTH */ /*T![ttl] TTL_ENABLE='ON' */ /*T![ttl] TTL_JOB_INTERVAL='1h' */";
+        assert_eq!(parse_ttl_clause(create_table), Some(clause(true)));
+
+        let disabled = create_table.replace("TTL_ENABLE='ON'", "TTL_ENABLE='OFF'");
+        assert_eq!(parse_ttl_clause(&disabled), Some(clause(false)));
+
+        assert_eq!(
+            parse_ttl_clause("CREATE TABLE `t` (`id` int) ENGINE=InnoDB"),
+            None
+        );
+        // A column merely named like the option is not a TTL clause
+        assert_eq!(
+            parse_ttl_clause("CREATE TABLE `t` (`ttl_days` int) ENGINE=InnoDB"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_table_option_round_trips_through_parser() {
+        let ttl = clause(false);
+        let statement = format!("CREATE TABLE t (id INT) {}", ttl.table_option());
+        assert_eq!(parse_ttl_clause(&statement), Some(ttl));
+    }
+
+    #[test]
+    fn test_ttl_capability_gating() {
+        assert!(ttl_supported(TidbVersion::new(6, 5, 0)));
+        assert!(ttl_supported(TidbVersion::new(7, 5, 1)));
+        assert!(!ttl_supported(TidbVersion::new(6, 4, 0)));
+    }
+
+    #[test]
+    fn test_ttl_table_against_live_tidb() {
+        let Some(mut db) = live_db("live TTL table test") else {
+            return;
+        };
+        let table = db.table("ddl_ttl");
+        let result = run_ttl_table_test(&mut db.conn, &db.database, table.name(), &clause(true));
+        if result.unwrap().is_none() {
+            eprintln!("Server does not support TTL, skipped");
+        }
+    }
+}
@@ -0,0 +1,251 @@
+//! # Multi-Endpoint Connections
+//!
+//! Support for HA deployments that expose several `TiDB` endpoints. Connection
+//! attempts fan out to every endpoint at once and the first one to succeed is
+//! used; connections that complete later are dropped (and thereby closed).
+//! When the active connection dies mid-run, [`select_next_endpoint`] walks the
+//! remaining endpoints round-robin to fail over.
+
+use crate::config::Timeouts;
+use crate::connection::{TlsConfig, create_connection_with_timeouts, parse_host_port};
+use crate::errors::{ConnectError, Result};
+use mysql::PooledConn;
+use std::sync::{Arc, mpsc};
+use std::thread;
+
+/// An endpoint that was successfully connected to
+#[derive(Debug)]
+pub struct SelectedEndpoint<C> {
+    /// Position of the endpoint in the configured list
+    pub index: usize,
+    /// The `host:port` string that was selected
+    pub endpoint: String,
+    pub connection: C,
+}
+
+/// Parse a comma-separated `host:port` list, ignoring empty entries
+///
+/// # Errors
+///
+/// Returns an error if the list is empty or an entry is not a valid `host:port`.
+pub fn parse_hosts(list: &str) -> Result<Vec<String>> {
+    let hosts: Vec<String> = list
+        .split(',')
+        .map(str::trim)
+        .filter(|h| !h.is_empty())
+        .map(ToString::to_string)
+        .collect();
+
+    if hosts.is_empty() {
+        return Err(ConnectError::CliArgument(
+            "--hosts must list at least one hostname:port".to_string(),
+        ));
+    }
+    for host in &hosts {
+        parse_host_port(host)?;
+    }
+    Ok(hosts)
+}
+
+/// Try every endpoint concurrently and return the first successful connection
+///
+/// `connect` is called once per endpoint on its own thread. Connections that
+/// succeed after the winner has been chosen are dropped.
+///
+/// # Errors
+///
+/// Returns `ConnectError::Network` listing each endpoint's failure if none connect.
+pub fn select_first_available<C, F>(endpoints: &[String], connect: F) -> Result<SelectedEndpoint<C>>
+where
+    C: Send + 'static,
+    F: Fn(&str) -> Result<C> + Send + Sync + 'static,
+{
+    if endpoints.is_empty() {
+        return Err(ConnectError::Configuration(
+            "No endpoints configured".to_string(),
+        ));
+    }
+
+    let connect = Arc::new(connect);
+    let (tx, rx) = mpsc::channel();
+    for (index, endpoint) in endpoints.iter().cloned().enumerate() {
+        let tx = tx.clone();
+        let connect = Arc::clone(&connect);
+        thread::spawn(move || {
+            let result = connect(&endpoint);
+            // The receiver is gone once a winner was chosen; the connection is dropped here
+            let _ = tx.send((index, endpoint, result));
+        });
+    }
+    drop(tx);
+
+    let mut failures = Vec::new();
+    for (index, endpoint, result) in rx {
+        match result {
+            Ok(connection) => {
+                tracing::info!("Selected endpoint {} ({})", endpoint, index);
+                return Ok(SelectedEndpoint {
+                    index,
+                    endpoint,
+                    connection,
+                });
+            }
+            Err(e) => {
+                tracing::warn!("Endpoint {} failed: {}", endpoint, e);
+                failures.push(format!("{endpoint}: {e}"));
+            }
+        }
+    }
+
+    Err(ConnectError::Network(format!(
+        "No endpoint reachable ({})",
+        failures.join("; ")
+    )))
+}
+
+/// Order in which endpoints are tried when failing over from `active`
+///
+/// Starts with the endpoint after `active` and wraps around, so the failed
+/// endpoint is retried last. With no active endpoint the list order is used.
+pub fn failover_order(len: usize, active: Option<usize>) -> Vec<usize> {
+    let start = active.map_or(0, |i| i + 1);
+    (0..len).map(|offset| (start + offset) % len).collect()
+}
+
+/// Connect to the next healthy endpoint after `active`, round-robin
+///
+/// # Errors
+///
+/// Returns `ConnectError::Network` listing each endpoint's failure if none connect.
+pub fn select_next_endpoint<C, F>(
+    endpoints: &[String],
+    active: Option<usize>,
+    mut connect: F,
+) -> Result<SelectedEndpoint<C>>
+where
+    F: FnMut(&str) -> Result<C>,
+{
+    let mut failures = Vec::new();
+    for index in failover_order(endpoints.len(), active) {
+        let endpoint = &endpoints[index];
+        match connect(endpoint) {
+            Ok(connection) => {
+                tracing::info!("Failed over to endpoint {}", endpoint);
+                return Ok(SelectedEndpoint {
+                    index,
+                    endpoint: endpoint.clone(),
+                    connection,
+                });
+            }
+            Err(e) => {
+                tracing::warn!("Failover endpoint {} failed: {}", endpoint, e);
+                failures.push(format!("{endpoint}: {e}"));
+            }
+        }
+    }
+
+    Err(ConnectError::Network(format!(
+        "No healthy endpoint to fail over to ({})",
+        failures.join("; ")
+    )))
+}
+
+/// Connect to the first reachable endpoint using the given credentials, over
+/// TLS if `tls` is given and with the connect and statement timeouts of `timeouts`
+///
+/// # Errors
+///
+/// Returns an error if no endpoint accepts a connection.
+pub fn connect_first_available(
+    endpoints: &[String],
+    user: &str,
+    password: &str,
+    database: Option<&str>,
+    tls: Option<&TlsConfig>,
+    timeouts: &Timeouts,
+) -> Result<SelectedEndpoint<PooledConn>> {
+    let user = user.to_string();
+    let password = password.to_string();
+    let database = database.map(ToString::to_string);
+    let tls = tls.cloned();
+    let timeouts = timeouts.clone();
+    select_first_available(endpoints, move |endpoint| {
+        let (host, port) = parse_host_port(endpoint)?;
+        create_connection_with_timeouts(
+            &host,
+            port,
+            &user,
+            &password,
+            database.as_deref(),
+            tls.as_ref(),
+            &timeouts,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct MockConnection(String);
+
+    fn mock_factory(endpoint: &str) -> Result<MockConnection> {
+        if endpoint.starts_with("down") {
+            Err(ConnectError::Network(format!("{endpoint} refused")))
+        } else {
+            Ok(MockConnection(endpoint.to_string()))
+        }
+    }
+
+    #[test]
+    fn test_parse_hosts() {
+        let hosts = parse_hosts("a:4000, b:4001,,").unwrap();
+        assert_eq!(hosts, vec!["a:4000", "b:4001"]);
+        assert!(parse_hosts(" , ").is_err());
+        assert!(parse_hosts("a:4000,b").is_err());
+    }
+
+    #[test]
+    fn test_selects_succeeding_endpoint() {
+        let endpoints = vec!["down-1:4000".to_string(), "up-2:4000".to_string()];
+        let selected = select_first_available(&endpoints, mock_factory).unwrap();
+        assert_eq!(selected.index, 1);
+        assert_eq!(selected.endpoint, "up-2:4000");
+        assert_eq!(selected.connection.0, "up-2:4000");
+    }
+
+    #[test]
+    fn test_failover_order_wraps_around() {
+        assert_eq!(failover_order(3, None), vec![0, 1, 2]);
+        assert_eq!(failover_order(3, Some(0)), vec![1, 2, 0]);
+        assert_eq!(failover_order(3, Some(2)), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_failover_skips_dead_endpoints() {
+        let endpoints = vec![
+            "up-0:4000".to_string(),
+            "down-1:4000".to_string(),
+            "up-2:4000".to_string(),
+        ];
+        // The active endpoint 0 died; 1 is also down, so 2 is selected
+        let mut attempts = Vec::new();
+        let selected = select_next_endpoint(&endpoints, Some(0), |endpoint| {
+            attempts.push(endpoint.to_string());
+            mock_factory(endpoint)
+        })
+        .unwrap();
+        assert_eq!(selected.index, 2);
+        assert_eq!(attempts, vec!["down-1:4000", "up-2:4000"]);
+    }
+
+    #[test]
+    fn test_all_endpoints_failing_reports_each() {
+        let endpoints = vec!["down-1:4000".to_string(), "down-2:4000".to_string()];
+        let err = select_first_available(&endpoints, mock_factory).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("down-1:4000"));
+        assert!(message.contains("down-2:4000"));
+    }
+}
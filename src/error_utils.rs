@@ -3,9 +3,13 @@
 //! Enhanced error utilities and context management for resilient database operations.
 //! Provides error classification, recovery strategies, and enhanced error context building.
 
+use crate::config::DatabaseConfig;
+use crate::connection::checkout_connection;
 use crate::errors::RetryConfig;
 use crate::errors::{ConnectError, EnhancedError, ErrorContext};
-use crate::retry::{CircuitBreaker, CircuitBreakerConfig, retry_with_circuit_breaker};
+use crate::retry::{
+    CircuitBreaker, CircuitBreakerConfig, CircuitBreakerStats, retry_with_circuit_breaker,
+};
 use mysql::{Pool, PooledConn};
 use std::time::Duration;
 
@@ -14,6 +18,8 @@ pub struct ResilientConnectionManager {
     pool: Pool,
     circuit_breaker: CircuitBreaker,
     retry_config: RetryConfig,
+    checkout_timeout: Duration,
+    pool_size: u32,
     host: String,
     database: String,
     user: String,
@@ -29,6 +35,8 @@ impl ResilientConnectionManager {
             pool,
             circuit_breaker: CircuitBreaker::new(circuit_config),
             retry_config,
+            checkout_timeout: Duration::from_secs(DatabaseConfig::default().checkout_timeout_secs),
+            pool_size: DatabaseConfig::default().pool_size,
             host,
             database,
             user,
@@ -48,12 +56,33 @@ impl ResilientConnectionManager {
             pool,
             circuit_breaker: CircuitBreaker::new(circuit_config),
             retry_config,
+            checkout_timeout: Duration::from_secs(DatabaseConfig::default().checkout_timeout_secs),
+            pool_size: DatabaseConfig::default().pool_size,
             host,
             database,
             user,
         }
     }
 
+    /// Bound pool checkouts by the checkout timeout and pool size of `config`
+    #[must_use]
+    pub fn with_pool_config(mut self, config: &DatabaseConfig) -> Self {
+        self.checkout_timeout = Duration::from_secs(config.checkout_timeout_secs);
+        self.pool_size = config.pool_size;
+        self
+    }
+
+    /// Check out a connection, failing once the checkout timeout elapses
+    fn checkout(&self) -> Result<PooledConn, ConnectError> {
+        checkout_connection(&self.pool, self.checkout_timeout, self.pool_size)
+    }
+
+    /// State and call counters of the manager's circuit breaker
+    #[must_use]
+    pub fn circuit_breaker_stats(&self) -> CircuitBreakerStats {
+        self.circuit_breaker.stats()
+    }
+
     /// Execute an operation with resilience and retry logic
     ///
     /// # Errors
@@ -112,10 +141,8 @@ impl ResilientConnectionManager {
     ///
     /// Returns an error if a connection cannot be obtained from the pool.
     pub async fn get_connection(&self) -> Result<PooledConn, EnhancedError> {
-        self.execute_with_resilience("get_connection", || {
-            self.pool.get_conn().map_err(ConnectError::from)
-        })
-        .await
+        self.execute_with_resilience("get_connection", || self.checkout())
+            .await
     }
 
     /// Execute a query with error handling
@@ -128,7 +155,7 @@ impl ResilientConnectionManager {
         F: Fn(&mut PooledConn) -> Result<T, ConnectError> + Send + Sync,
     {
         self.execute_with_resilience("execute_query", || {
-            let mut conn = self.pool.get_conn()?;
+            let mut conn = self.checkout()?;
             f(&mut conn)
         })
         .await
@@ -179,6 +206,10 @@ pub fn classify_error(error: &ConnectError) -> ErrorCategory {
         | ConnectError::Protocol(_)
         | ConnectError::Resource(_)
         | ConnectError::Unknown(_) => ErrorCategory::Transient,
+        // Whether a retry helps depends on what the Python code raised
+        ConnectError::PythonHandler { .. } => ErrorCategory::Unknown,
+        // Retrying has already been tried and did not help
+        ConnectError::RetriesExhausted { .. } => ErrorCategory::Permanent,
     }
 }
 
@@ -259,6 +290,49 @@ impl ErrorContextBuilder {
     }
 }
 
+/// How binaries report a fatal error on stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ErrorFormat {
+    /// Human-readable message
+    #[default]
+    Text,
+    /// A single JSON object (see [`ErrorReport`])
+    Json,
+}
+
+/// Machine-readable description of a fatal error
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct ErrorReport {
+    /// Error kind, e.g. `"configuration"` or `"connection"`
+    pub category: String,
+    /// Whether the error is considered transient (see [`classify_error`])
+    pub transient: bool,
+    pub message: String,
+    pub exit_code: i32,
+    /// State the machine was in when the error occurred, if known
+    pub state: Option<String>,
+}
+
+impl ErrorReport {
+    #[must_use]
+    pub fn from_error(error: &ConnectError, state: Option<&str>) -> Self {
+        Self {
+            category: error.kind().to_string(),
+            transient: classify_error(error) == ErrorCategory::Transient,
+            message: error.to_string(),
+            exit_code: error.exit_code(),
+            state: state.map(ToString::to_string),
+        }
+    }
+
+    /// Render the report as a single-line JSON object
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self)
+            .unwrap_or_else(|_| format!("{{\"message\":{:?}}}", self.message))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -287,6 +361,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_error_report_json_shape() {
+        let error = ConnectError::Configuration("missing host".to_string());
+        let report = ErrorReport::from_error(&error, Some("parsing_config"));
+        let value: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+
+        assert_eq!(value["category"], "configuration");
+        assert_eq!(value["transient"], false);
+        assert_eq!(value["message"], "Configuration error: missing host");
+        assert_eq!(value["exit_code"], 2);
+        assert_eq!(value["state"], "parsing_config");
+
+        let report = ErrorReport::from_error(&ConnectError::Timeout("slow".into()), None);
+        let value: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(value["exit_code"], 4);
+        assert!(value["state"].is_null());
+    }
+
     #[test]
     fn test_error_context_builder() {
         let context = ErrorContextBuilder::new("test_operation".to_string())
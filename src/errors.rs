@@ -63,10 +63,74 @@ pub enum ConnectError {
     #[error("Resource error: {0}")]
     Resource(String),
 
+    #[error("Python handler {handler} failed: {message}")]
+    PythonHandler { handler: String, message: String },
+
+    #[error("Failed after {attempts} attempt(s): {source}")]
+    RetriesExhausted {
+        attempts: usize,
+        source: Box<ConnectError>,
+    },
+
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
 
+impl ConnectError {
+    /// Short machine-readable name of the error kind (e.g. `"configuration"`)
+    #[must_use]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ConnectError::Connection(_) => "connection",
+            ConnectError::Authentication(_) => "authentication",
+            ConnectError::Configuration(_) => "configuration",
+            ConnectError::StateMachine(_) => "state_machine",
+            ConnectError::Database(_) => "database",
+            ConnectError::IsolationTest(_) => "isolation_test",
+            ConnectError::CliArgument(_) => "cli_argument",
+            ConnectError::Logging(_) => "logging",
+            ConnectError::Io(_) => "io",
+            ConnectError::Parse(_) => "parse",
+            ConnectError::Validation(_) => "validation",
+            ConnectError::Timeout(_) => "timeout",
+            ConnectError::Retry(_) => "retry",
+            ConnectError::CircuitBreaker(_) => "circuit_breaker",
+            ConnectError::Network(_) => "network",
+            ConnectError::Protocol(_) => "protocol",
+            ConnectError::Resource(_) => "resource",
+            ConnectError::PythonHandler { .. } => "python_handler",
+            ConnectError::RetriesExhausted { .. } => "retries_exhausted",
+            ConnectError::Unknown(_) => "unknown",
+        }
+    }
+
+    /// Process exit code for this error
+    ///
+    /// - `2`: invalid arguments or configuration
+    /// - `3`: authentication failure
+    /// - `4`: server unreachable (connection, network, timeout)
+    /// - `5`: the test itself failed (database, isolation or Python handler checks)
+    /// - `1`: anything else
+    ///
+    /// Exhausted retries use the exit code of the last underlying error.
+    #[must_use]
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ConnectError::RetriesExhausted { source, .. } => source.exit_code(),
+            ConnectError::CliArgument(_)
+            | ConnectError::Configuration(_)
+            | ConnectError::Parse(_)
+            | ConnectError::Validation(_) => 2,
+            ConnectError::Authentication(_) => 3,
+            ConnectError::Connection(_) | ConnectError::Network(_) | ConnectError::Timeout(_) => 4,
+            ConnectError::Database(_)
+            | ConnectError::IsolationTest(_)
+            | ConnectError::PythonHandler { .. } => 5,
+            _ => 1,
+        }
+    }
+}
+
 /// Enhanced state machine error with specific variants
 #[derive(Error, Debug)]
 pub enum StateError {
@@ -266,8 +330,13 @@ pub enum ConnectionError {
     #[error("DNS resolution failed for {host}: {message}")]
     DnsResolutionFailed { host: String, message: String },
 
-    #[error("Connection pool exhausted (max: {max_connections})")]
-    PoolExhausted { max_connections: usize },
+    #[error(
+        "Connection pool exhausted: all {max_connections} connections stayed in use for {waited:?}; consider increasing pool_size"
+    )]
+    PoolExhausted {
+        max_connections: usize,
+        waited: Duration,
+    },
 
     #[error("Connection validation failed: {reason}")]
     ValidationFailed { reason: String },
@@ -448,9 +517,7 @@ impl From<ConnectionError> for ConnectError {
             ConnectionError::DnsResolutionFailed { host, message } => {
                 ConnectError::Network(format!("DNS resolution failed for {host}: {message}"))
             }
-            ConnectionError::PoolExhausted { max_connections } => ConnectError::Resource(format!(
-                "Connection pool exhausted (max: {max_connections})"
-            )),
+            err @ ConnectionError::PoolExhausted { .. } => ConnectError::Resource(err.to_string()),
             ConnectionError::ValidationFailed { reason } => ConnectError::Validation(reason),
         }
     }
@@ -0,0 +1,484 @@
+//! # Query Plans
+//!
+//! Run `EXPLAIN` / `EXPLAIN ANALYZE`, store plans as baselines, and diff a
+//! current plan against a baseline for query-plan regression testing.
+
+use crate::errors::{ConnectError, Result};
+use mysql::prelude::*;
+use mysql::{PooledConn, Row, Value};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// A single operator row from TiDB's `EXPLAIN` output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanRow {
+    /// Operator id as printed by TiDB, including tree drawing and numeric suffix
+    pub id: String,
+    pub est_rows: String,
+    pub task: String,
+    pub access_object: String,
+    pub operator_info: String,
+}
+
+impl PlanRow {
+    /// Build a plan row from `(column name, value)` pairs
+    pub fn from_columns(columns: &[(String, String)]) -> Self {
+        let get = |name: &str| {
+            columns
+                .iter()
+                .find(|(col, _)| col.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default()
+        };
+        Self {
+            id: get("id"),
+            est_rows: get("estRows"),
+            task: get("task"),
+            access_object: get("access object"),
+            operator_info: get("operator info"),
+        }
+    }
+
+    /// Operator name with tree drawing characters, the `_N` suffix and any
+    /// child role such as `(Build)` or `(Probe)` removed
+    ///
+    /// `└─IndexRangeScan_8(Build)` becomes `IndexRangeScan`, so plans from
+    /// different runs can be compared even though TiDB renumbers operators.
+    pub fn operator(&self) -> String {
+        let trimmed = self
+            .id
+            .trim_start_matches(|c: char| !c.is_ascii_alphanumeric());
+        let trimmed = trimmed.split_once('(').map_or(trimmed, |(name, _)| name);
+        match trimmed.rsplit_once('_') {
+            Some((name, suffix)) if suffix.chars().all(|c| c.is_ascii_digit()) => name.to_string(),
+            _ => trimmed.to_string(),
+        }
+    }
+
+    /// Index named in the access object (`table:t, index:idx_a(a)` yields `idx_a`)
+    pub fn index_name(&self) -> Option<String> {
+        let (_, rest) = self.access_object.split_once("index:")?;
+        let name: String = rest
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+            .collect();
+        (!name.is_empty()).then_some(name)
+    }
+
+    /// Partitions named in the access object (`table:t, partition:p0,p1` yields `p0`, `p1`)
+    pub fn partitions(&self) -> Vec<String> {
+        let Some((_, rest)) = self.access_object.split_once("partition:") else {
+            return Vec::new();
+        };
+        let list = rest.split(", ").next().unwrap_or_default();
+        list.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(ToString::to_string)
+            .collect()
+    }
+}
+
+/// A full query plan
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct QueryPlan {
+    pub sql: String,
+    pub rows: Vec<PlanRow>,
+}
+
+impl QueryPlan {
+    /// Indexes used anywhere in the plan
+    pub fn indexes(&self) -> Vec<String> {
+        let mut indexes: Vec<String> = self.rows.iter().filter_map(PlanRow::index_name).collect();
+        indexes.sort();
+        indexes.dedup();
+        indexes
+    }
+
+    /// Partitions accessed anywhere in the plan (`all` if pruning did not apply)
+    pub fn partitions(&self) -> Vec<String> {
+        let mut partitions: Vec<String> = self.rows.iter().flat_map(PlanRow::partitions).collect();
+        partitions.sort();
+        partitions.dedup();
+        partitions
+    }
+
+    /// Whether any operator was estimated from pseudo statistics
+    ///
+    /// `TiDB` marks such operators with `stats:pseudo` when the table has not
+    /// been analyzed or its statistics are too stale to use.
+    pub fn uses_pseudo_stats(&self) -> bool {
+        self.rows
+            .iter()
+            .any(|row| row.operator_info.contains("stats:pseudo"))
+    }
+
+    /// Load a baseline plan from a JSON file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn load_baseline(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|e| {
+            ConnectError::Parse(format!("Invalid baseline plan {}: {e}", path.display()))
+        })
+    }
+
+    /// Save this plan as a JSON baseline
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save_baseline(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| ConnectError::Parse(format!("Failed to serialize plan: {e}")))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Print the plan as an indented table
+    pub fn print(&self) {
+        println!("Plan for: {}", self.sql);
+        for row in &self.rows {
+            println!(
+                "  {:<40} {:>10} {:<10} {:<30} {}",
+                row.id, row.est_rows, row.task, row.access_object, row.operator_info
+            );
+        }
+    }
+}
+
+/// A difference between a baseline plan and the current plan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanDifference {
+    /// An index used by the baseline no longer appears in the plan
+    IndexNoLongerUsed(String),
+    /// The plan now uses an index the baseline did not
+    NewIndexUsed(String),
+    /// The operator at this position changed (e.g. `IndexRangeScan` -> `TableFullScan`)
+    OperatorChanged {
+        position: usize,
+        before: String,
+        after: String,
+    },
+    /// The plan has a different number of operators
+    ShapeChanged { before: usize, after: usize },
+}
+
+impl fmt::Display for PlanDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanDifference::IndexNoLongerUsed(index) => {
+                write!(f, "index no longer used: {index}")
+            }
+            PlanDifference::NewIndexUsed(index) => write!(f, "new index used: {index}"),
+            PlanDifference::OperatorChanged {
+                position,
+                before,
+                after,
+            } => write!(f, "operator {position} changed: {before} -> {after}"),
+            PlanDifference::ShapeChanged { before, after } => {
+                write!(f, "plan has {after} operators, baseline had {before}")
+            }
+        }
+    }
+}
+
+/// How a query is expected to read its table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessPath {
+    /// Through the named index (`IndexRangeScan`, `IndexLookUp`, ...)
+    Index(String),
+    /// A `Point_Get` or `Batch_Point_Get` on the primary key or a unique index
+    PointGet,
+    /// A `TableFullScan`
+    FullScan,
+}
+
+impl fmt::Display for AccessPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessPath::Index(index) => write!(f, "index {index}"),
+            AccessPath::PointGet => write!(f, "point get"),
+            AccessPath::FullScan => write!(f, "full table scan"),
+        }
+    }
+}
+
+/// Check that `plan` reads its table through `expected`
+///
+/// # Errors
+///
+/// Returns `ConnectError::Validation` naming the indexes and operators used
+/// instead, and noting when the plan was built from pseudo statistics.
+pub fn assert_access_path(plan: &QueryPlan, expected: &AccessPath) -> Result<()> {
+    let operators: Vec<String> = plan.rows.iter().map(PlanRow::operator).collect();
+    let found = match expected {
+        AccessPath::Index(index) => plan
+            .indexes()
+            .iter()
+            .any(|used| used.eq_ignore_ascii_case(index)),
+        AccessPath::PointGet => operators
+            .iter()
+            .any(|op| op == "Point_Get" || op == "Batch_Point_Get"),
+        AccessPath::FullScan => operators.iter().any(|op| op == "TableFullScan"),
+    };
+    if found {
+        return Ok(());
+    }
+    let hint = if plan.uses_pseudo_stats() {
+        " (plan uses pseudo statistics; was the table analyzed?)"
+    } else {
+        ""
+    };
+    Err(ConnectError::Validation(format!(
+        "Expected `{}` to use {expected}, plan uses indexes [{}] with operators [{}]{hint}",
+        plan.sql,
+        plan.indexes().join(", "),
+        operators.join(", ")
+    )))
+}
+
+/// Compare a current plan against a baseline
+///
+/// Estimated row counts and operator numbering are ignored; only the operator
+/// sequence and the set of indexes used are compared.
+pub fn diff_plans(baseline: &QueryPlan, current: &QueryPlan) -> Vec<PlanDifference> {
+    let mut differences = Vec::new();
+
+    let before_indexes = baseline.indexes();
+    let after_indexes = current.indexes();
+    for index in &before_indexes {
+        if !after_indexes.contains(index) {
+            differences.push(PlanDifference::IndexNoLongerUsed(index.clone()));
+        }
+    }
+    for index in &after_indexes {
+        if !before_indexes.contains(index) {
+            differences.push(PlanDifference::NewIndexUsed(index.clone()));
+        }
+    }
+
+    if baseline.rows.len() != current.rows.len() {
+        differences.push(PlanDifference::ShapeChanged {
+            before: baseline.rows.len(),
+            after: current.rows.len(),
+        });
+    }
+    for (position, (before, after)) in baseline.rows.iter().zip(&current.rows).enumerate() {
+        let (before, after) = (before.operator(), after.operator());
+        if before != after {
+            differences.push(PlanDifference::OperatorChanged {
+                position,
+                before,
+                after,
+            });
+        }
+    }
+
+    differences
+}
+
+/// `(column name, value)` pairs of a result row, with values as display strings
+pub(crate) fn row_columns(row: &Row) -> Vec<(String, String)> {
+    row.columns_ref()
+        .iter()
+        .enumerate()
+        .map(|(i, col)| {
+            let value = match row.as_ref(i) {
+                Some(Value::Bytes(bytes)) => String::from_utf8_lossy(bytes).to_string(),
+                Some(Value::NULL) | None => String::new(),
+                Some(other) => other.as_sql(true),
+            };
+            (col.name_str().to_string(), value)
+        })
+        .collect()
+}
+
+/// Run `EXPLAIN` (or `EXPLAIN ANALYZE`) for `sql` and collect the plan
+///
+/// # Errors
+///
+/// Returns an error if the statement fails.
+pub fn explain(conn: &mut PooledConn, sql: &str, analyze: bool) -> Result<QueryPlan> {
+    let prefix = if analyze {
+        "EXPLAIN ANALYZE"
+    } else {
+        "EXPLAIN"
+    };
+    let rows: Vec<Row> = conn.query(format!("{prefix} {sql}"))?;
+
+    let rows = rows
+        .iter()
+        .map(|row| PlanRow::from_columns(&row_columns(row)))
+        .collect();
+
+    Ok(QueryPlan {
+        sql: sql.to_string(),
+        rows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: &str, access_object: &str) -> PlanRow {
+        PlanRow {
+            id: id.to_string(),
+            est_rows: "10.00".to_string(),
+            task: "root".to_string(),
+            access_object: access_object.to_string(),
+            operator_info: String::new(),
+        }
+    }
+
+    fn index_plan() -> QueryPlan {
+        QueryPlan {
+            sql: "SELECT * FROM t WHERE a = 1".to_string(),
+            rows: vec![
+                row("IndexLookUp_10", ""),
+                row("├─IndexRangeScan_8(Build)", "table:t, index:idx_a(a)"),
+                row("└─TableRowIDScan_9(Probe)", "table:t"),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_operator_normalization() {
+        assert_eq!(row("└─TableFullScan_5", "").operator(), "TableFullScan");
+        assert_eq!(row("Projection_4", "").operator(), "Projection");
+        assert_eq!(row("Point_Get_1", "").operator(), "Point_Get");
+        assert_eq!(
+            row("x", "table:t, index:idx_a(a)").index_name(),
+            Some("idx_a".to_string())
+        );
+        assert_eq!(row("x", "table:t").index_name(), None);
+        assert_eq!(
+            row("x", "table:t, partition:p0,p2, index:idx_a(a)").partitions(),
+            vec!["p0", "p2"]
+        );
+        assert!(row("x", "table:t").partitions().is_empty());
+    }
+
+    #[test]
+    fn test_join_plan_operators_drop_child_role() {
+        let join = QueryPlan {
+            sql: "SELECT * FROM t JOIN u ON t.a = u.a".to_string(),
+            rows: vec![
+                row("HashJoin_8", ""),
+                row("├─TableReader_15(Build)", ""),
+                row("│ └─TableFullScan_14", "table:u"),
+                row("└─IndexReader_12(Probe)", ""),
+                row("  └─IndexFullScan_11", "table:t, index:idx_a(a)"),
+            ],
+        };
+        let operators: Vec<String> = join.rows.iter().map(PlanRow::operator).collect();
+        assert_eq!(
+            operators,
+            vec![
+                "HashJoin",
+                "TableReader",
+                "TableFullScan",
+                "IndexReader",
+                "IndexFullScan"
+            ]
+        );
+        assert!(assert_access_path(&join, &AccessPath::FullScan).is_ok());
+        assert!(assert_access_path(&join, &AccessPath::Index("idx_a".to_string())).is_ok());
+
+        let mut renumbered = join.clone();
+        renumbered.rows[1].id = "├─TableReader_31(Build)".to_string();
+        renumbered.rows[3].id = "└─IndexReader_27(Probe)".to_string();
+        assert!(diff_plans(&join, &renumbered).is_empty());
+    }
+
+    #[test]
+    fn test_identical_plans_have_no_differences() {
+        let mut renumbered = index_plan();
+        renumbered.rows[0].id = "IndexLookUp_21".to_string();
+        renumbered.rows[1].est_rows = "3.00".to_string();
+        assert!(diff_plans(&index_plan(), &renumbered).is_empty());
+    }
+
+    #[test]
+    fn test_index_no_longer_used_is_flagged() {
+        let current = QueryPlan {
+            sql: "SELECT * FROM t WHERE a = 1".to_string(),
+            rows: vec![
+                row("TableReader_7", ""),
+                row("└─Selection_6", ""),
+                row("  └─TableFullScan_5", "table:t"),
+            ],
+        };
+
+        let differences = diff_plans(&index_plan(), &current);
+        assert!(differences.contains(&PlanDifference::IndexNoLongerUsed("idx_a".to_string())));
+        assert!(differences.contains(&PlanDifference::OperatorChanged {
+            position: 0,
+            before: "IndexLookUp".to_string(),
+            after: "TableReader".to_string(),
+        }));
+        assert!(
+            !differences
+                .iter()
+                .any(|d| matches!(d, PlanDifference::ShapeChanged { .. }))
+        );
+    }
+
+    #[test]
+    fn test_shape_change_and_baseline_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plan.json");
+        index_plan().save_baseline(&path).unwrap();
+        let baseline = QueryPlan::load_baseline(&path).unwrap();
+        assert_eq!(baseline, index_plan());
+
+        let mut current = index_plan();
+        current.rows.truncate(2);
+        let differences = diff_plans(&baseline, &current);
+        assert_eq!(
+            differences,
+            vec![PlanDifference::ShapeChanged {
+                before: 3,
+                after: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn test_assert_access_path() {
+        let plan = index_plan();
+        assert!(assert_access_path(&plan, &AccessPath::Index("IDX_A".to_string())).is_ok());
+        assert!(assert_access_path(&plan, &AccessPath::PointGet).is_err());
+
+        let point = QueryPlan {
+            sql: "SELECT * FROM t WHERE id = 1".to_string(),
+            rows: vec![row("Point_Get_1", "table:t, handle:1")],
+        };
+        assert!(assert_access_path(&point, &AccessPath::PointGet).is_ok());
+
+        let mut full_scan = QueryPlan {
+            sql: "SELECT * FROM t WHERE a = 1".to_string(),
+            rows: vec![
+                row("TableReader_7", ""),
+                row("└─Selection_6", ""),
+                row("  └─TableFullScan_5", "table:t"),
+            ],
+        };
+        assert!(assert_access_path(&full_scan, &AccessPath::FullScan).is_ok());
+        let err = assert_access_path(&full_scan, &AccessPath::Index("idx_a".to_string()))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("operators [TableReader, Selection, TableFullScan]"));
+        assert!(!err.contains("pseudo"));
+
+        full_scan.rows[2].operator_info = "keep order:false, stats:pseudo".to_string();
+        assert!(full_scan.uses_pseudo_stats());
+        let err =
+            assert_access_path(&full_scan, &AccessPath::Index("idx_a".to_string())).unwrap_err();
+        assert!(err.to_string().contains("was the table analyzed?"));
+    }
+}
@@ -0,0 +1,128 @@
+//! # Health Check Endpoint
+//!
+//! A minimal HTTP liveness probe for binaries run as long-lived services.
+//! `GET /healthz` returns `200 OK` while the run is active and
+//! `503 Service Unavailable` once it has completed or failed.
+
+use crate::errors::Result;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// Lifecycle of the monitored run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStatus {
+    Active,
+    Completed,
+    Failed,
+}
+
+/// Shared, cheaply clonable run status read by the health endpoint
+#[derive(Debug, Clone)]
+pub struct HealthState {
+    status: Arc<AtomicU8>,
+}
+
+impl Default for HealthState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HealthState {
+    /// Create a state that starts out active
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            status: Arc::new(AtomicU8::new(RunStatus::Active as u8)),
+        }
+    }
+
+    pub fn set(&self, status: RunStatus) {
+        self.status.store(status as u8, Ordering::SeqCst);
+    }
+
+    #[must_use]
+    pub fn get(&self) -> RunStatus {
+        match self.status.load(Ordering::SeqCst) {
+            0 => RunStatus::Active,
+            1 => RunStatus::Completed,
+            _ => RunStatus::Failed,
+        }
+    }
+}
+
+/// Compute the HTTP status code and reason phrase for a request line
+///
+/// Only `GET /healthz` is served; everything else is `404 Not Found`.
+#[must_use]
+pub fn health_response(request_line: &str, state: &HealthState) -> (u16, &'static str) {
+    let mut parts = request_line.split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/healthz")) => match state.get() {
+            RunStatus::Active => (200, "OK"),
+            RunStatus::Completed | RunStatus::Failed => (503, "Service Unavailable"),
+        },
+        _ => (404, "Not Found"),
+    }
+}
+
+/// Serve the health endpoint on `0.0.0.0:<port>` in a background task
+///
+/// # Errors
+///
+/// Returns an error if the port cannot be bound.
+pub async fn start_health_server(port: u16, state: HealthState) -> Result<JoinHandle<()>> {
+    let listener = TcpListener::bind(("0.0.0.0", port)).await?;
+    tracing::info!("Health endpoint listening on port {}", port);
+
+    Ok(tokio::spawn(async move {
+        loop {
+            let Ok((mut socket, _)) = listener.accept().await else {
+                continue;
+            };
+            let state = state.clone();
+            tokio::spawn(async move {
+                let mut buffer = [0u8; 1024];
+                let Ok(read) = socket.read(&mut buffer).await else {
+                    return;
+                };
+                let request = String::from_utf8_lossy(&buffer[..read]);
+                let request_line = request.lines().next().unwrap_or_default();
+                let (code, reason) = health_response(request_line, &state);
+                let response = format!(
+                    "HTTP/1.1 {code} {reason}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{reason}",
+                    reason.len()
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            });
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_status_follows_run_state() {
+        let state = HealthState::new();
+        assert_eq!(health_response("GET /healthz HTTP/1.1", &state).0, 200);
+
+        state.set(RunStatus::Completed);
+        assert_eq!(health_response("GET /healthz HTTP/1.1", &state).0, 503);
+
+        state.set(RunStatus::Failed);
+        assert_eq!(health_response("GET /healthz HTTP/1.1", &state).0, 503);
+    }
+
+    #[test]
+    fn test_unknown_paths_are_not_found() {
+        let state = HealthState::new();
+        assert_eq!(health_response("GET / HTTP/1.1", &state).0, 404);
+        assert_eq!(health_response("POST /healthz HTTP/1.1", &state).0, 404);
+        assert_eq!(health_response("", &state).0, 404);
+    }
+}
@@ -0,0 +1,276 @@
+//! # Import Jobs
+//!
+//! Typed `SHOW IMPORT JOBS` output. Rows are parsed by column name, so the
+//! extra columns newer `TiDB` releases add are ignored, and helpers tell
+//! whether a job is still running and how far along it is.
+
+use crate::errors::Result;
+use crate::explain::row_columns;
+use chrono::NaiveDateTime;
+use mysql::prelude::*;
+use mysql::{PooledConn, Row};
+
+/// Statuses after which a job no longer changes
+pub const TERMINAL_STATUSES: [&str; 3] = ["finished", "failed", "cancelled"];
+
+/// One row of `SHOW IMPORT JOBS`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportJob {
+    pub job_id: i64,
+    pub data_source: String,
+    pub target_table: String,
+    pub table_id: Option<i64>,
+    pub phase: String,
+    pub status: String,
+    /// Human-readable size, e.g. `1.5GiB`
+    pub source_file_size: String,
+    pub imported_rows: Option<u64>,
+    pub result_message: String,
+    pub create_time: Option<NaiveDateTime>,
+    pub start_time: Option<NaiveDateTime>,
+    pub end_time: Option<NaiveDateTime>,
+    pub created_by: String,
+    /// Bytes processed by the current step (`Cur_Step_Processed_Size`, `TiDB` 8.x+)
+    pub step_processed_bytes: Option<u64>,
+    /// Bytes the current step will process (`Cur_Step_Total_Size`, `TiDB` 8.x+)
+    pub step_total_bytes: Option<u64>,
+}
+
+impl ImportJob {
+    /// Build from `(column name, value)` pairs
+    ///
+    /// Returns `None` if `Job_ID` is missing or not a number. Other columns
+    /// that are missing or unparsable are left empty.
+    #[must_use]
+    pub fn from_columns(columns: &[(String, String)]) -> Option<Self> {
+        Some(Self {
+            job_id: column(columns, "Job_ID").trim().parse().ok()?,
+            data_source: column(columns, "Data_Source"),
+            target_table: column(columns, "Target_Table"),
+            table_id: column(columns, "Table_ID").trim().parse().ok(),
+            phase: column(columns, "Phase"),
+            status: column(columns, "Status"),
+            source_file_size: column(columns, "Source_File_Size"),
+            imported_rows: column(columns, "Imported_Rows").trim().parse().ok(),
+            result_message: column(columns, "Result_Message"),
+            create_time: parse_time(&column(columns, "Create_Time")),
+            start_time: parse_time(&column(columns, "Start_Time")),
+            end_time: parse_time(&column(columns, "End_Time")),
+            created_by: column(columns, "Created_By"),
+            step_processed_bytes: parse_size(&column(columns, "Cur_Step_Processed_Size")),
+            step_total_bytes: parse_size(&column(columns, "Cur_Step_Total_Size")),
+        })
+    }
+
+    /// Whether the job may still make progress: it has no end time and its
+    /// status is not one of [`TERMINAL_STATUSES`]
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.end_time.is_none() && !self.is_terminal_status()
+    }
+
+    /// Whether the job ended with status `failed` or `cancelled`
+    #[must_use]
+    pub fn is_failed(&self) -> bool {
+        self.is_terminal_status() && !self.status.eq_ignore_ascii_case("finished")
+    }
+
+    /// Fraction of the work done, between 0 and 1, if it can be derived
+    ///
+    /// `SHOW IMPORT JOBS` has no total row count, so `Imported_Rows` alone
+    /// does not give progress. Finished jobs report 1; running jobs use the
+    /// current step's processed over total bytes when the server reports them.
+    #[must_use]
+    pub fn progress_ratio(&self) -> Option<f64> {
+        if self.status.eq_ignore_ascii_case("finished") {
+            return Some(1.0);
+        }
+        match (self.step_processed_bytes, self.step_total_bytes) {
+            (Some(done), Some(total)) if total > 0 => {
+                Some((done as f64 / total as f64).clamp(0.0, 1.0))
+            }
+            _ => None,
+        }
+    }
+
+    fn is_terminal_status(&self) -> bool {
+        TERMINAL_STATUSES
+            .iter()
+            .any(|status| self.status.eq_ignore_ascii_case(status))
+    }
+}
+
+/// Parse `SHOW IMPORT JOBS` rows given as `(column name, value)` pairs
+///
+/// Rows without a numeric `Job_ID` are skipped with a warning.
+#[must_use]
+pub fn parse_import_jobs(rows: &[Vec<(String, String)>]) -> Vec<ImportJob> {
+    rows.iter()
+        .filter_map(|columns| {
+            let job = ImportJob::from_columns(columns);
+            if job.is_none() {
+                tracing::warn!("Skipping import job row without a Job_ID: {:?}", columns);
+            }
+            job
+        })
+        .collect()
+}
+
+/// Whether every job in `jobs` has stopped; true for no jobs
+#[must_use]
+pub fn all_terminal(jobs: &[ImportJob]) -> bool {
+    jobs.iter().all(|job| !job.is_active())
+}
+
+/// Run `SHOW IMPORT JOBS`
+///
+/// # Errors
+///
+/// Returns an error if the statement fails.
+pub fn show_import_jobs(conn: &mut PooledConn) -> Result<Vec<ImportJob>> {
+    query_jobs(conn, "SHOW IMPORT JOBS")
+}
+
+/// Run `SHOW IMPORT JOB <job_id>`
+///
+/// # Errors
+///
+/// Returns an error if the statement fails.
+pub fn show_import_job(conn: &mut PooledConn, job_id: i64) -> Result<Vec<ImportJob>> {
+    query_jobs(conn, &format!("SHOW IMPORT JOB {job_id}"))
+}
+
+fn query_jobs(conn: &mut PooledConn, sql: &str) -> Result<Vec<ImportJob>> {
+    let rows: Vec<Row> = conn.query(sql)?;
+    let rows: Vec<_> = rows.iter().map(row_columns).collect();
+    Ok(parse_import_jobs(&rows))
+}
+
+/// Value of `name` in `columns`, compared case-insensitively; empty if absent
+fn column(columns: &[(String, String)], name: &str) -> String {
+    columns
+        .iter()
+        .find(|(col, _)| col.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.clone())
+        .unwrap_or_default()
+}
+
+/// Parse a `DATETIME` value; `None` for NULL (empty) or unparsable values
+fn parse_time(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim().trim_matches('\'');
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S%.f").ok()
+}
+
+/// Parse a size such as `512B`, `1.5GiB` or `20MB` into bytes
+fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier: f64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1.0,
+        "kib" => 1024.0,
+        "mib" => 1024.0 * 1024.0,
+        "gib" => 1024.0 * 1024.0 * 1024.0,
+        "tib" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        "kb" => 1e3,
+        "mb" => 1e6,
+        "gb" => 1e9,
+        "tb" => 1e12,
+        _ => return None,
+    };
+    Some((number * multiplier).round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job_row(job_id: &str, status: &str, end_time: &str) -> Vec<(String, String)> {
+        [
+            ("Job_ID", job_id),
+            ("Data_Source", "s3://bucket/orders/*.csv"),
+            ("Target_Table", "`test`.`orders`"),
+            ("Table_ID", "112"),
+            ("Phase", "importing"),
+            ("Status", status),
+            ("Source_File_Size", "1.5GiB"),
+            ("Imported_Rows", "250000"),
+            ("Result_Message", ""),
+            ("Create_Time", "2026-01-05 10:00:00.000000"),
+            ("Start_Time", "2026-01-05 10:00:01.000000"),
+            ("End_Time", end_time),
+            ("Created_By", "root@%"),
+        ]
+        .iter()
+        .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+        .collect()
+    }
+
+    #[test]
+    fn test_parse_active_completed_and_failed_jobs() {
+        let jobs = parse_import_jobs(&[
+            job_row("1", "running", ""),
+            job_row("2", "finished", "2026-01-05 10:30:00.000000"),
+            job_row("3", "failed", "2026-01-05 10:05:00.000000"),
+            job_row("not-a-number", "running", ""),
+        ]);
+        assert_eq!(jobs.len(), 3);
+
+        let (running, finished, failed) = (&jobs[0], &jobs[1], &jobs[2]);
+        assert_eq!(running.job_id, 1);
+        assert_eq!(running.table_id, Some(112));
+        assert_eq!(running.imported_rows, Some(250_000));
+        assert!(running.start_time.is_some());
+        assert!(running.is_active());
+        assert!(!running.is_failed());
+
+        assert!(!finished.is_active());
+        assert!(!finished.is_failed());
+        assert!(finished.end_time.is_some());
+        assert!(!failed.is_active());
+        assert!(failed.is_failed());
+
+        assert!(!all_terminal(&jobs));
+        assert!(all_terminal(&jobs[1..]));
+        assert!(all_terminal(&[]));
+    }
+
+    #[test]
+    fn test_terminal_status_without_end_time_is_not_active() {
+        let cancelled = ImportJob::from_columns(&job_row("4", "CANCELLED", "")).unwrap();
+        assert!(!cancelled.is_active());
+        assert!(cancelled.is_failed());
+        // A pending job has not started but is still active
+        let pending = ImportJob::from_columns(&job_row("5", "pending", "")).unwrap();
+        assert!(pending.is_active());
+    }
+
+    #[test]
+    fn test_progress_ratio() {
+        let mut row = job_row("1", "running", "");
+        let running = ImportJob::from_columns(&row).unwrap();
+        assert_eq!(running.progress_ratio(), None);
+
+        row.push(("Cur_Step_Processed_Size".to_string(), "512MiB".to_string()));
+        row.push(("Cur_Step_Total_Size".to_string(), "2GiB".to_string()));
+        let running = ImportJob::from_columns(&row).unwrap();
+        assert_eq!(running.step_total_bytes, Some(2 * 1024 * 1024 * 1024));
+        assert_eq!(running.progress_ratio(), Some(0.25));
+
+        let finished =
+            ImportJob::from_columns(&job_row("2", "finished", "2026-01-05 10:30:00")).unwrap();
+        assert_eq!(finished.progress_ratio(), Some(1.0));
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512B"), Some(512));
+        assert_eq!(parse_size("1.5KiB"), Some(1536));
+        assert_eq!(parse_size("20MB"), Some(20_000_000));
+        assert_eq!(parse_size(""), None);
+        assert_eq!(parse_size("lots"), None);
+    }
+}
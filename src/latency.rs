@@ -0,0 +1,145 @@
+//! # Latency Statistics
+//!
+//! Collect durations from many concurrent tasks and summarize them as mean and
+//! nearest-rank percentiles, e.g. the connect latency across all connections of
+//! a multi-connection run.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Value at percentile `pct` (0-100) of `sorted`, using the nearest-rank method
+///
+/// Returns `None` for an empty slice.
+#[must_use]
+pub fn percentile(sorted: &[Duration], pct: f64) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (pct.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.saturating_sub(1).min(sorted.len() - 1)])
+}
+
+/// Distribution of a set of durations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencySummary {
+    pub count: usize,
+    pub mean: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+}
+
+impl LatencySummary {
+    /// Summarize `durations`, or `None` if there are none
+    #[must_use]
+    pub fn from_durations(durations: &[Duration]) -> Option<Self> {
+        let mut sorted = durations.to_vec();
+        sorted.sort_unstable();
+        let count = sorted.len();
+        let total: Duration = sorted.iter().sum();
+        Some(Self {
+            count,
+            mean: total / u32::try_from(count).ok().filter(|&n| n > 0)?,
+            min: *sorted.first()?,
+            max: *sorted.last()?,
+            p50: percentile(&sorted, 50.0)?,
+            p95: percentile(&sorted, 95.0)?,
+            p99: percentile(&sorted, 99.0)?,
+        })
+    }
+}
+
+impl fmt::Display for LatencySummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "n={} mean={:?} p50={:?} p95={:?} p99={:?} min={:?} max={:?}",
+            self.count, self.mean, self.p50, self.p95, self.p99, self.min, self.max
+        )
+    }
+}
+
+/// Thread-safe collector of durations; clones share the same samples
+#[derive(Debug, Clone, Default)]
+pub struct LatencyRecorder {
+    samples: Arc<Mutex<Vec<Duration>>>,
+}
+
+impl LatencyRecorder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a sample
+    pub fn record(&self, duration: Duration) {
+        if let Ok(mut samples) = self.samples.lock() {
+            samples.push(duration);
+        }
+    }
+
+    /// Summary of the samples recorded so far
+    #[must_use]
+    pub fn summary(&self) -> Option<LatencySummary> {
+        let samples = self.samples.lock().ok()?;
+        LatencySummary::from_durations(&samples)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_percentiles_over_connection_durations() {
+        // 100 connections taking 1ms..=100ms, shuffled
+        let durations: Vec<Duration> = (1..=100).rev().map(ms).collect();
+        let summary = LatencySummary::from_durations(&durations).unwrap();
+
+        assert_eq!(summary.count, 100);
+        assert_eq!(summary.p50, ms(50));
+        assert_eq!(summary.p95, ms(95));
+        assert_eq!(summary.p99, ms(99));
+        assert_eq!(summary.min, ms(1));
+        assert_eq!(summary.max, ms(100));
+        assert_eq!(summary.mean, Duration::from_micros(50_500));
+    }
+
+    #[test]
+    fn test_small_and_empty_sets() {
+        assert!(LatencySummary::from_durations(&[]).is_none());
+        assert_eq!(percentile(&[], 50.0), None);
+
+        let summary = LatencySummary::from_durations(&[ms(30), ms(10), ms(20)]).unwrap();
+        assert_eq!(summary.p50, ms(20));
+        assert_eq!(summary.p99, ms(30));
+        assert_eq!(summary.mean, ms(20));
+    }
+
+    #[test]
+    fn test_recorder_shared_across_clones() {
+        let recorder = LatencyRecorder::new();
+        assert!(recorder.summary().is_none());
+
+        let handles: Vec<_> = (1..=4)
+            .map(|i| {
+                let recorder = recorder.clone();
+                std::thread::spawn(move || recorder.record(ms(i * 10)))
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let summary = recorder.summary().unwrap();
+        assert_eq!(summary.count, 4);
+        assert_eq!(summary.max, ms(40));
+    }
+}
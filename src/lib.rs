@@ -11,6 +11,12 @@
 //! - Configuration management with file and environment support
 //! - CLI utilities for common operations
 
+/// Server version parsing and feature capability detection
+pub mod capabilities;
+
+/// Scratch table guards that drop their table on a fresh connection
+pub mod cleanup;
+
 /// Command-line interface support and argument parsing
 pub mod cli;
 
@@ -20,18 +26,43 @@ pub mod config;
 /// Configuration extensions for dynamic configuration
 pub mod config_extensions;
 
+/// Reloading safe settings from a changed config file
+pub mod config_reload;
+
 /// Low-level database connection utilities and parsing
 pub mod connection;
 
 /// High-level connection management and coordination
 pub mod connection_manager;
 
+/// Parsing `mysql://` connection URLs
+pub mod dsn;
+
+/// EXPLAIN plan capture and baseline comparison
+pub mod explain;
+
+/// Multi-endpoint connection selection for HA deployments
+pub mod endpoints;
+
+/// Connection parameters discovered from `TIDB_*`, `DATABASE_URL` or `MYSQL_*`
+pub mod env_params;
+
 /// Enhanced error utilities and context management
 pub mod error_utils;
 
 /// Comprehensive error types and retry mechanisms
 pub mod errors;
 
+/// HTTP liveness endpoint for long-running binaries
+#[cfg(feature = "health_check")]
+pub mod health;
+
+/// Typed `SHOW IMPORT JOBS` output and job completion checks
+pub mod import_jobs;
+
+/// Latency collection and percentile summaries
+pub mod latency;
+
 /// Common utility functions and helpers
 pub mod lib_utils;
 
@@ -41,9 +72,30 @@ pub mod logging;
 /// State machine for managing multiple database connections
 pub mod multi_connection_state_machine;
 
+/// Password sources for `CommonArgs`, including files and custom secret stores
+pub mod password;
+
+/// ANALYZE-then-EXPLAIN workflow asserting a query's access path
+pub mod plan_check;
+
+/// Streaming query helpers for ad hoc and diagnostic queries
+pub mod query;
+
+/// Query recording and offline replay
+pub mod recording;
+
+/// JSON and JUnit XML test reports for CI
+pub mod report;
+
 /// Retry mechanisms with circuit breaker pattern
 pub mod retry;
 
+/// Table schema snapshots and diffing for DDL tests
+pub mod schema;
+
+/// Splitting and running SQL script files, with `DELIMITER` support
+pub mod sql_script;
+
 /// Built-in state handler implementations
 pub mod state_handlers;
 
@@ -53,6 +105,18 @@ pub mod state_machine;
 /// Dynamic state machine implementation for test-defined states
 pub mod state_machine_dynamic;
 
+/// Per-state timing metrics with Prometheus output
+pub mod state_metrics;
+
+/// `ANALYZE TABLE` and parsed `SHOW STATS_*` output
+pub mod stats;
+
+/// Helpers for DB-gated tests
+pub mod test_support;
+
+/// Transaction helpers with isolation level control
+pub mod txn;
+
 /// Common state definitions shared across binaries
 pub mod common_states;
 
@@ -71,18 +135,20 @@ pub use config_extensions::{
 };
 pub use connection_manager::{ConnectionCoordinator, ConnectionInfo, GlobalConfig, SharedState};
 pub use errors::{ConnectError, Result, RetryConfig, StateError};
-pub use lib_utils::{print_error_and_exit, print_success, print_test_header};
+pub use lib_utils::{print_error_and_exit, print_startup_banner, print_success, print_test_header};
 pub use logging::init_logging;
 pub use multi_connection_state_machine::MultiConnectionStateMachine;
 pub use retry::{
-    CircuitBreaker, CircuitBreakerConfig, CircuitState, retry_with_backoff,
-    retry_with_circuit_breaker,
+    CircuitBreaker, CircuitBreakerConfig, CircuitBreakerStats, CircuitState, StateChangeCallback,
+    retry_with_backoff, retry_with_backoff_if, retry_with_circuit_breaker,
 };
 pub use state_handlers::*;
 pub use state_machine::{State, StateContext, StateHandler, StateMachine};
 pub use state_machine_dynamic::{
-    DynamicState, DynamicStateContext, DynamicStateHandler, DynamicStateMachine, states,
+    DynamicState, DynamicStateContext, DynamicStateHandler, DynamicStateMachine, GraphError,
+    GraphProblem, TransitionObserver, states,
 };
+pub use txn::{IsolationLevel, run_in_transaction};
 
 #[cfg(feature = "python_plugins")]
 pub use python_bindings::{PyStateHandler, load_python_handlers, register_python_handler};
@@ -4,10 +4,13 @@
 //! Provides error reporting, success reporting, and test output formatting.
 
 use crate::cli::CommonArgs;
+use crate::config::AppConfig;
+use crate::error_utils::{ErrorFormat, ErrorReport};
 use crate::errors::{ConnectError, Result};
 use crate::state_handlers::InitialHandler;
 use crate::state_machine::{State, StateMachine};
 use std::process;
+use tokio::task::{JoinError, JoinHandle};
 
 /// Common setup for tests using the new `CommonArgs` approach
 pub struct TestSetup {
@@ -90,6 +93,84 @@ pub fn print_test_header(title: &str) {
     println!("{}", "=".repeat(title.len()));
 }
 
+/// Cargo features this build of `test_rig` was compiled with
+#[must_use]
+pub fn enabled_features() -> Vec<&'static str> {
+    [
+        ("import_jobs", cfg!(feature = "import_jobs")),
+        ("isolation_test", cfg!(feature = "isolation_test")),
+        ("multi_connection", cfg!(feature = "multi_connection")),
+        ("health_check", cfg!(feature = "health_check")),
+        ("hot_reload", cfg!(feature = "hot_reload")),
+        ("tls", cfg!(feature = "tls")),
+        ("python_plugins", cfg!(feature = "python_plugins")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
+}
+
+/// Summary of the effective settings of a run, with the password redacted
+///
+/// `extensions` are binary-specific settings such as the run mode, shown
+/// after the common ones in the given order.
+#[must_use]
+pub fn startup_banner(config: &AppConfig, extensions: &[(&str, String)]) -> String {
+    let features = enabled_features();
+    let mut lines = vec![
+        "Effective Settings:".to_string(),
+        format!("  Host: {}", config.database.host),
+        format!("  User: {}", config.database.username),
+        format!(
+            "  Password: {}",
+            if config.database.password.is_some() {
+                "********"
+            } else {
+                "(not set)"
+            }
+        ),
+        format!(
+            "  Database: {}",
+            config
+                .database
+                .database
+                .as_deref()
+                .unwrap_or("(not specified)")
+        ),
+        format!(
+            "  TLS: {}",
+            if config.database.tls.is_some() {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        ),
+        format!("  Log Level: {}", config.logging.level),
+    ];
+    if let Some(ref file) = config.logging.file {
+        lines.push(format!("  Log File: {file}"));
+    }
+    lines.push(format!(
+        "  Features: {}",
+        if features.is_empty() {
+            "(none)".to_string()
+        } else {
+            features.join(", ")
+        }
+    ));
+    lines.extend(
+        extensions
+            .iter()
+            .map(|(name, value)| format!("  {name}: {value}")),
+    );
+    lines.join("\n")
+}
+
+/// Print [`startup_banner`] at the start of a run
+pub fn print_startup_banner(config: &AppConfig, extensions: &[(&str, String)]) {
+    println!("{}", startup_banner(config, extensions));
+}
+
 /// Helper function to print a success message
 pub fn print_success(message: &str) {
     println!("\n✅ {message}");
@@ -101,6 +182,22 @@ pub fn print_error_and_exit(message: &str, error: &dyn std::error::Error) {
     std::process::exit(1);
 }
 
+/// Report a fatal error in the requested format and exit with its mapped exit code
+///
+/// With [`ErrorFormat::Json`] a single [`ErrorReport`] object is written to stderr.
+pub fn report_error_and_exit(
+    message: &str,
+    error: &ConnectError,
+    format: ErrorFormat,
+    state: Option<&str>,
+) -> ! {
+    match format {
+        ErrorFormat::Text => eprintln!("\n❌ {message}: {error}"),
+        ErrorFormat::Json => eprintln!("{}", ErrorReport::from_error(error, state).to_json()),
+    }
+    std::process::exit(error.exit_code());
+}
+
 /// Helper function to create a state machine with standard handlers for multi-connection scenarios
 #[must_use]
 pub fn create_state_machine_with_handlers(
@@ -114,6 +211,41 @@ pub fn create_state_machine_with_handlers(
     state_machine
 }
 
+/// Outcome of a spawned task awaited by [`join_all_tasks`]
+#[derive(Debug)]
+pub enum TaskResult<T> {
+    /// The task ran to completion and returned `Ok`
+    Success(T),
+    /// The task ran to completion and returned an error
+    HandlerError(ConnectError),
+    /// The task panicked or was cancelled
+    JoinError(JoinError),
+}
+
+impl<T> TaskResult<T> {
+    /// Whether the task completed successfully
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        matches!(self, TaskResult::Success(_))
+    }
+}
+
+/// Await every handle in order and classify each outcome
+///
+/// Waits for all tasks even if some fail, so one failure does not leave the
+/// others running unobserved.
+pub async fn join_all_tasks<T>(handles: Vec<JoinHandle<Result<T>>>) -> Vec<TaskResult<T>> {
+    let mut results = Vec::with_capacity(handles.len());
+    for handle in handles {
+        results.push(match handle.await {
+            Ok(Ok(value)) => TaskResult::Success(value),
+            Ok(Err(e)) => TaskResult::HandlerError(e),
+            Err(e) => TaskResult::JoinError(e),
+        });
+    }
+    results
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -124,5 +256,49 @@ mod tests {
         print_success("Success");
     }
 
+    #[test]
+    fn test_startup_banner_redacts_password() {
+        let mut config = AppConfig::default();
+        config.database.host = "tidb.example:4000".to_string();
+        config.database.username = "ci".to_string();
+        config.database.password = Some("s3cret-pass".to_string());
+        config.database.database = Some("orders".to_string());
+        config.logging.level = "debug".to_string();
+
+        let banner = startup_banner(&config, &[("Mode", "isolation test".to_string())]);
+        assert!(banner.contains("Host: tidb.example:4000"));
+        assert!(banner.contains("User: ci"));
+        assert!(banner.contains("Password: ********"));
+        assert!(banner.contains("Database: orders"));
+        assert!(banner.contains("TLS: disabled"));
+        assert!(banner.contains("Log Level: debug"));
+        assert!(banner.contains("Features: "));
+        assert!(banner.ends_with("  Mode: isolation test"));
+        assert!(!banner.contains("s3cret-pass"));
+
+        config.database.password = None;
+        assert!(startup_banner(&config, &[]).contains("Password: (not set)"));
+    }
+
     // print_error_and_exit cannot be tested as it exits the process
+
+    #[tokio::test]
+    async fn test_join_all_tasks_classifies_outcomes() {
+        let handles = vec![
+            tokio::spawn(async { Ok(1) }),
+            tokio::spawn(async { Err(ConnectError::Database("query failed".to_string())) }),
+            tokio::spawn(async { panic!("handler panicked") }),
+            tokio::spawn(async { Ok(4) }),
+        ];
+
+        let results = join_all_tasks(handles).await;
+        assert_eq!(results.len(), 4);
+        assert!(matches!(results[0], TaskResult::Success(1)));
+        assert!(
+            matches!(&results[1], TaskResult::HandlerError(ConnectError::Database(msg)) if msg == "query failed")
+        );
+        assert!(matches!(&results[2], TaskResult::JoinError(e) if e.is_panic()));
+        assert!(matches!(results[3], TaskResult::Success(4)));
+        assert_eq!(results.iter().filter(|r| r.is_success()).count(), 2);
+    }
 }
@@ -3,9 +3,81 @@
 //! Structured logging configuration and utilities.
 //! Provides multiple log formats, file and console output, and configurable log levels.
 
+use crate::errors::ConnectError;
 use std::fs;
 use std::path::PathBuf;
+use std::str::FromStr;
 use tracing::Level;
+use tracing_appender::rolling::{InitError, RollingFileAppender};
+use tracing_subscriber::fmt::writer::{BoxMakeWriter, MakeWriterExt};
+
+/// When the log file is rolled over to a new one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Rotation {
+    /// A single file that keeps growing
+    #[default]
+    Never,
+    /// A new file every hour, suffixed `.YYYY-MM-DD-HH`
+    Hourly,
+    /// A new file every day, suffixed `.YYYY-MM-DD`
+    Daily,
+}
+
+impl Rotation {
+    /// The equivalent `tracing-appender` rotation
+    #[must_use]
+    pub fn to_appender_rotation(self) -> tracing_appender::rolling::Rotation {
+        match self {
+            Self::Never => tracing_appender::rolling::Rotation::NEVER,
+            Self::Hourly => tracing_appender::rolling::Rotation::HOURLY,
+            Self::Daily => tracing_appender::rolling::Rotation::DAILY,
+        }
+    }
+}
+
+/// How log events are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per event, for log aggregation pipelines
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = ConnectError;
+
+    /// Parse `text` or `json`, ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(ConnectError::Configuration(format!(
+                "Unknown log format '{s}'; expected text or json"
+            ))),
+        }
+    }
+}
+
+/// Parse a log level name from [`crate::config::LOG_LEVELS`], ignoring case
+///
+/// # Errors
+///
+/// Returns `ConnectError::Configuration` for any other name.
+pub fn parse_level(level: &str) -> Result<Level, ConnectError> {
+    match level.to_ascii_lowercase().as_str() {
+        "trace" => Ok(Level::TRACE),
+        "debug" => Ok(Level::DEBUG),
+        "info" => Ok(Level::INFO),
+        "warn" => Ok(Level::WARN),
+        "error" => Ok(Level::ERROR),
+        _ => Err(ConnectError::Configuration(format!(
+            "Unknown log level '{level}'; expected one of {}",
+            crate::config::LOG_LEVELS.join(", ")
+        ))),
+    }
+}
 
 /// Logging configuration
 #[allow(clippy::struct_excessive_bools)]
@@ -21,14 +93,18 @@ pub struct LogConfig {
     pub file_path: PathBuf,
     /// Maximum log file size in MB (default: 10)
     pub max_file_size: usize,
-    /// Number of log files to keep (default: 5)
+    /// Number of rotated log files to keep, 0 for all (default: 5)
     pub max_files: usize,
+    /// When the log file is rolled over (default: never)
+    pub rotation: Rotation,
     /// Whether to include timestamps
     pub include_timestamps: bool,
     /// Whether to include thread IDs
     pub include_thread_ids: bool,
     /// Whether to include file and line numbers
     pub include_file_line: bool,
+    /// Output format (default: text)
+    pub format: LogFormat,
 }
 
 impl Default for LogConfig {
@@ -40,9 +116,11 @@ impl Default for LogConfig {
             file_path: PathBuf::from("logs/tidb_connect.log"),
             max_file_size: 10,
             max_files: 5,
+            rotation: Rotation::Never,
             include_timestamps: true,
             include_thread_ids: false,
             include_file_line: true,
+            format: LogFormat::Text,
         }
     }
 }
@@ -116,6 +194,82 @@ impl LogConfig {
         self.include_file_line = include;
         self
     }
+
+    /// Set the output format
+    #[must_use]
+    pub fn with_format(mut self, format: LogFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Set when the log file is rolled over
+    #[must_use]
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+}
+
+/// File appender for `config.file_path` following `config.rotation`
+///
+/// Without rotation the file is `file_path` itself; rotated files add a date
+/// suffix and only the newest `max_files` are kept.
+///
+/// # Errors
+///
+/// Returns an error if the log directory cannot be created or the file cannot be opened.
+pub fn rolling_appender(config: &LogConfig) -> Result<RollingFileAppender, InitError> {
+    let directory = config
+        .file_path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or_else(|| std::path::Path::new("."));
+    let prefix = config
+        .file_path
+        .file_name()
+        .map_or_else(|| "tidb_connect.log".into(), |name| name.to_string_lossy());
+    let mut builder = RollingFileAppender::builder()
+        .rotation(config.rotation.to_appender_rotation())
+        .filename_prefix(prefix);
+    if config.rotation != Rotation::Never && config.max_files > 0 {
+        builder = builder.max_log_files(config.max_files);
+    }
+    builder.build(directory)
+}
+
+/// Where events go: stdout, the log file, or both
+fn make_writer(config: &LogConfig) -> Result<BoxMakeWriter, InitError> {
+    if !config.file {
+        return Ok(BoxMakeWriter::new(std::io::stdout));
+    }
+    let appender = rolling_appender(config)?;
+    Ok(if config.console {
+        BoxMakeWriter::new(std::io::stdout.and(appender))
+    } else {
+        BoxMakeWriter::new(appender)
+    })
+}
+
+/// Subscriber formatting events per `config` into `writer`
+fn build_subscriber(
+    config: &LogConfig,
+    writer: BoxMakeWriter,
+) -> Box<dyn tracing::Subscriber + Send + Sync> {
+    let builder = tracing_subscriber::FmtSubscriber::builder()
+        .with_level(true)
+        .with_target(true)
+        .with_file(true)
+        .with_line_number(true)
+        .with_thread_ids(config.include_thread_ids)
+        .with_env_filter(format!("{}", config.level))
+        .with_writer(writer);
+
+    match config.format {
+        // Escape codes would end up in the log file
+        LogFormat::Text => Box::new(builder.with_ansi(!config.file).finish()),
+        // Escape codes would end up inside the JSON strings
+        LogFormat::Json => Box::new(builder.json().with_ansi(false).finish()),
+    }
 }
 
 /// Initialize logging system
@@ -131,19 +285,7 @@ pub fn init_logging(config: &LogConfig) -> Result<(), Box<dyn std::error::Error>
         fs::create_dir_all(parent)?;
     }
 
-    // Build the subscriber
-    let subscriber = tracing_subscriber::FmtSubscriber::builder()
-        .with_level(true)
-        .with_target(true)
-        .with_file(true)
-        .with_line_number(true)
-        .with_thread_ids(config.include_thread_ids)
-        .with_ansi(true)
-        .with_env_filter(format!("{}", config.level))
-        .finish();
-
-    // Set the global subscriber
-    tracing::subscriber::set_global_default(subscriber)?;
+    tracing::subscriber::set_global_default(build_subscriber(config, make_writer(config)?))?;
 
     // Log initialization
     tracing::info!("Logging system initialized");
@@ -283,3 +425,105 @@ pub fn log_memory_usage(component: &str, bytes: usize) {
         "Memory usage"
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_log_format_parsing() {
+        assert_eq!("json".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!("TEXT".parse::<LogFormat>().unwrap(), LogFormat::Text);
+        assert!("xml".parse::<LogFormat>().is_err());
+        assert_eq!(LogConfig::default().format, LogFormat::Text);
+    }
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(parse_level("Warn").unwrap(), Level::WARN);
+        assert_eq!(parse_level("trace").unwrap(), Level::TRACE);
+        assert!(parse_level("verbsoe").is_err());
+    }
+
+    #[test]
+    fn test_rolling_appender_follows_rotation() {
+        use std::io::Write;
+
+        assert_eq!(LogConfig::default().rotation, Rotation::Never);
+        for (rotation, expected) in [
+            (Rotation::Never, tracing_appender::rolling::Rotation::NEVER),
+            (
+                Rotation::Hourly,
+                tracing_appender::rolling::Rotation::HOURLY,
+            ),
+            (Rotation::Daily, tracing_appender::rolling::Rotation::DAILY),
+        ] {
+            assert_eq!(rotation.to_appender_rotation(), expected);
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let single = LogConfig::new()
+            .with_file(true)
+            .with_file_path(dir.path().join("single.log"));
+        rolling_appender(&single)
+            .unwrap()
+            .write_all(b"line\n")
+            .unwrap();
+        assert!(dir.path().join("single.log").exists());
+
+        let daily = LogConfig::new()
+            .with_file(true)
+            .with_file_path(dir.path().join("daily.log"))
+            .with_rotation(Rotation::Daily)
+            .with_max_files(3);
+        assert_eq!(daily.rotation, Rotation::Daily);
+        rolling_appender(&daily)
+            .unwrap()
+            .write_all(b"line\n")
+            .unwrap();
+        let rotated: Vec<String> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.starts_with("daily.log."))
+            .collect();
+        assert_eq!(rotated.len(), 1, "{rotated:?}");
+    }
+
+    /// In-memory log sink shared with the test
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_subscriber_writes_json_lines() {
+        let config = LogConfig::new()
+            .with_level(Level::DEBUG)
+            .with_format(LogFormat::Json);
+        let captured = Captured::default();
+        let sink = captured.clone();
+        let subscriber = build_subscriber(&config, BoxMakeWriter::new(move || sink.clone()));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(component = "logging_test", "JSON event");
+            tracing::trace!("below the configured level");
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 1, "unexpected output: {output}");
+        let event: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(event["level"], "INFO");
+        assert_eq!(event["fields"]["message"], "JSON event");
+        assert_eq!(event["fields"]["component"], "logging_test");
+    }
+}
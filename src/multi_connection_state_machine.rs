@@ -7,6 +7,7 @@ use crate::connection_manager::{
     ConnectionInfo, ConnectionState, ConnectionStatus, CoordinationMessage,
 };
 use crate::errors::Result;
+use crate::lib_utils::{TaskResult, join_all_tasks};
 use crate::state_machine::{State, StateContext, StateHandler};
 use async_trait::async_trait;
 use tokio::sync::mpsc;
@@ -86,11 +87,11 @@ impl MultiConnectionStateMachine {
         }
 
         // Wait for all to complete
-        for handle in handles {
-            match handle.await {
-                Ok(Ok(())) => println!("✓ State machine completed successfully"),
-                Ok(Err(e)) => eprintln!("✗ State machine failed: {e}"),
-                Err(e) => eprintln!("✗ State machine task failed: {e}"),
+        for result in join_all_tasks(handles).await {
+            match result {
+                TaskResult::Success(()) => println!("✓ State machine completed successfully"),
+                TaskResult::HandlerError(e) => eprintln!("✗ State machine failed: {e}"),
+                TaskResult::JoinError(e) => eprintln!("✗ State machine task failed: {e}"),
             }
         }
 
@@ -292,11 +293,11 @@ mod tests {
             .unwrap();
         let mut shared_state = None;
         for _ in 0..10 {
-            if let Some(msg) = rx.recv().await {
-                if let CoordinationMessage::ResponseGlobalState(state) = msg {
-                    shared_state = Some(state);
-                    break;
-                }
+            if let Some(msg) = rx.recv().await
+                && let CoordinationMessage::ResponseGlobalState(state) = msg
+            {
+                shared_state = Some(state);
+                break;
             }
         }
         let _shared_state: SharedState = shared_state.expect("Did not receive global state");
@@ -351,11 +352,11 @@ mod tests {
             .unwrap();
         let mut shared_state = None;
         for _ in 0..10 {
-            if let Some(msg) = rx.recv().await {
-                if let CoordinationMessage::ResponseGlobalState(state) = msg {
-                    shared_state = Some(state);
-                    break;
-                }
+            if let Some(msg) = rx.recv().await
+                && let CoordinationMessage::ResponseGlobalState(state) = msg
+            {
+                shared_state = Some(state);
+                break;
             }
         }
         let _shared_state = shared_state.expect("Did not receive global state");
@@ -402,14 +403,14 @@ mod tests {
         // Wait for the broadcast event - the coordinator should forward it to the test's receiver
         let mut found_event = false;
         for _ in 0..10 {
-            if let Some(msg) = rx.recv().await {
-                if let CoordinationMessage::BroadcastEvent(event) = msg {
-                    found_event = matches!(
-                        event,
-                        crate::connection_manager::CoordinationEvent::AllConnectionsReady
-                    );
-                    break;
-                }
+            if let Some(msg) = rx.recv().await
+                && let CoordinationMessage::BroadcastEvent(event) = msg
+            {
+                found_event = matches!(
+                    event,
+                    crate::connection_manager::CoordinationEvent::AllConnectionsReady
+                );
+                break;
             }
         }
         assert!(found_event, "Did not receive AllConnectionsReady event");
@@ -419,6 +420,80 @@ mod tests {
         let _ = handle.await;
     }
 
+    /// Send `RequestGlobalState` and collect the messages forwarded before the response
+    async fn drain_until_state(
+        coord_tx: &mpsc::Sender<CoordinationMessage>,
+        rx: &mut mpsc::Receiver<CoordinationMessage>,
+    ) -> (Vec<CoordinationMessage>, SharedState) {
+        coord_tx
+            .send(CoordinationMessage::RequestGlobalState)
+            .await
+            .unwrap();
+        let mut forwarded = Vec::new();
+        while let Some(msg) = rx.recv().await {
+            match msg {
+                CoordinationMessage::ResponseGlobalState(state) => return (forwarded, state),
+                msg => forwarded.push(msg),
+            }
+        }
+        panic!("Coordinator stopped before sending the global state");
+    }
+
+    /// Tests that `AllConnectionsReady` is withheld until every tracked connection is ready
+    ///
+    /// The coordinator tracks two connections. The coordination handler asks for
+    /// the event while both are disconnected and again after only one has
+    /// connected; the event is broadcast once, when the second connection reports
+    /// `Connected`.
+    #[tokio::test]
+    async fn test_all_connections_ready_waits_for_every_connection() {
+        let (tx, mut rx) = mpsc::channel::<CoordinationMessage>(16);
+        let mut coordinator = super::tests::create_test_coordinator();
+        coordinator.tx = tx.clone();
+        let (coord_tx, coord_rx) = mpsc::channel::<CoordinationMessage>(16);
+        coordinator.rx = coord_rx;
+
+        let mut machine = MultiConnectionStateMachine::new(coord_tx.clone());
+        for i in 0..2 {
+            coordinator.add_connection(format!("conn{i}"), create_test_connection_info());
+            machine.add_connection(format!("conn{i}"), create_test_connection_info());
+        }
+        let handle = tokio::spawn(async move {
+            coordinator.process_messages().await;
+        });
+
+        let handler = CoordinationHandler::new(coord_tx.clone());
+        let mut context = StateContext::new();
+        handler.execute(&mut context).await.unwrap();
+        let (forwarded, state) = drain_until_state(&coord_tx, &mut rx).await;
+        assert!(forwarded.is_empty());
+        assert!(state.coordination_events.is_empty());
+
+        machine.state_machines[0]
+            .update_status(ConnectionState::Connected, None)
+            .await;
+        handler.execute(&mut context).await.unwrap();
+        let (forwarded, state) = drain_until_state(&coord_tx, &mut rx).await;
+        assert!(forwarded.is_empty());
+        assert!(state.coordination_events.is_empty());
+
+        machine.state_machines[1]
+            .update_status(ConnectionState::Testing, None)
+            .await;
+        let (forwarded, state) = drain_until_state(&coord_tx, &mut rx).await;
+        assert_eq!(forwarded.len(), 1);
+        assert!(matches!(
+            forwarded[0],
+            CoordinationMessage::BroadcastEvent(
+                crate::connection_manager::CoordinationEvent::AllConnectionsReady
+            )
+        ));
+        assert_eq!(state.coordination_events.len(), 1);
+
+        coord_tx.send(CoordinationMessage::Shutdown).await.unwrap();
+        let _ = handle.await;
+    }
+
     /// Tests managing multiple connections simultaneously and their status tracking
     ///
     /// This test verifies:
@@ -466,11 +541,11 @@ mod tests {
             .unwrap();
         let mut shared_state = None;
         for _ in 0..10 {
-            if let Some(msg) = rx.recv().await {
-                if let CoordinationMessage::ResponseGlobalState(state) = msg {
-                    shared_state = Some(state);
-                    break;
-                }
+            if let Some(msg) = rx.recv().await
+                && let CoordinationMessage::ResponseGlobalState(state) = msg
+            {
+                shared_state = Some(state);
+                break;
             }
         }
         let _shared_state = shared_state.expect("Did not receive global state");
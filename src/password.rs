@@ -0,0 +1,162 @@
+//! # Password Providers
+//!
+//! Where [`CommonArgs::get_password_with`](crate::cli::CommonArgs::get_password_with)
+//! looks for a password once neither `--password` nor a `--host` URL gave one.
+//! The default [`EnvOrPromptProvider`] reads `TIDB_PASSWORD` (or `DATABASE_URL`
+//! / `MYSQL_PWD`), then `--password-file`, then prompts. Teams using a secret
+//! manager can implement [`PasswordProvider`] to fetch it from there instead.
+//! With `--password-stdin` the password is read from a line of stdin by
+//! [`ReaderPasswordProvider`], which keeps it out of process listings.
+
+use crate::errors::{ConnectError, Result};
+use std::cell::RefCell;
+use std::io::{BufRead, StdinLock};
+use std::path::{Path, PathBuf};
+
+/// Source of a database password
+pub trait PasswordProvider {
+    /// The password, or `Ok(None)` if this provider has none
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the provider has a password source configured but
+    /// cannot read it.
+    fn password(&self) -> Result<Option<String>>;
+}
+
+/// Password read from a file, e.g. a mounted Kubernetes secret
+///
+/// Trailing line endings are stripped; other whitespace is kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePasswordProvider {
+    path: PathBuf,
+}
+
+impl FilePasswordProvider {
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// File the password is read from
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl PasswordProvider for FilePasswordProvider {
+    fn password(&self) -> Result<Option<String>> {
+        let contents = std::fs::read_to_string(&self.path).map_err(|e| {
+            ConnectError::Configuration(format!(
+                "Cannot read password file {}: {e}",
+                self.path.display()
+            ))
+        })?;
+        Ok(Some(contents.trim_end_matches(['\r', '\n']).to_string()))
+    }
+}
+
+/// Password read from the first line of a reader, normally stdin
+///
+/// The trailing line ending is stripped. Each call reads the next line.
+pub struct ReaderPasswordProvider<R: BufRead> {
+    reader: RefCell<R>,
+}
+
+impl<R: BufRead> ReaderPasswordProvider<R> {
+    #[must_use]
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: RefCell::new(reader),
+        }
+    }
+}
+
+impl ReaderPasswordProvider<StdinLock<'static>> {
+    /// Provider reading the password from stdin (`--password-stdin`)
+    #[must_use]
+    pub fn stdin() -> Self {
+        Self::new(std::io::stdin().lock())
+    }
+}
+
+impl<R: BufRead> PasswordProvider for ReaderPasswordProvider<R> {
+    fn password(&self) -> Result<Option<String>> {
+        let mut line = String::new();
+        if self.reader.borrow_mut().read_line(&mut line)? == 0 {
+            return Err(ConnectError::Configuration(
+                "--password-stdin was given but stdin is empty".to_string(),
+            ));
+        }
+        Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+    }
+}
+
+/// Default provider: the environment, then an optional password file, then a prompt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvOrPromptProvider {
+    prompt: bool,
+    file: Option<FilePasswordProvider>,
+}
+
+impl EnvOrPromptProvider {
+    /// Provider that prompts on the terminal as a last resort if `prompt` is set
+    #[must_use]
+    pub fn new(prompt: bool) -> Self {
+        Self { prompt, file: None }
+    }
+
+    /// Read the password from `file` when the environment has none
+    #[must_use]
+    pub fn with_file(mut self, file: FilePasswordProvider) -> Self {
+        self.file = Some(file);
+        self
+    }
+}
+
+impl PasswordProvider for EnvOrPromptProvider {
+    fn password(&self) -> Result<Option<String>> {
+        if let Some(password) = std::env::var("TIDB_PASSWORD")
+            .ok()
+            .or_else(|| crate::cli::env_params().and_then(|params| params.password))
+        {
+            return Ok(Some(password));
+        }
+        if let Some(file) = &self.file {
+            return file.password();
+        }
+        if self.prompt {
+            return Ok(Some(rpassword::prompt_password("Password: ")?));
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_provider_strips_line_ending() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("password");
+        std::fs::write(&path, " s3cret \r\n").unwrap();
+        let provider = FilePasswordProvider::new(&path);
+        assert_eq!(provider.password().unwrap().as_deref(), Some(" s3cret "));
+
+        let missing = FilePasswordProvider::new(dir.path().join("missing"));
+        let err = missing.password().unwrap_err();
+        assert!(matches!(err, ConnectError::Configuration(_)));
+        assert!(err.to_string().contains("Cannot read password file"));
+    }
+
+    #[test]
+    fn test_reader_provider_reads_one_line() {
+        let provider = ReaderPasswordProvider::new(std::io::Cursor::new("s3cret\r\nnext line\n"));
+        assert_eq!(provider.password().unwrap().as_deref(), Some("s3cret"));
+
+        let empty = ReaderPasswordProvider::new(std::io::Cursor::new(""));
+        assert!(empty.password().is_err());
+    }
+}
@@ -0,0 +1,320 @@
+//! # Analyze-and-Verify-Plan Workflow
+//!
+//! Query plans depend on statistics, so a plan check against a table that was
+//! never analyzed (or whose stats were not loaded) can pass or fail by luck.
+//! [`PlanCheck`] runs a fixed flow on the dynamic state machine: create and
+//! populate a table, `ANALYZE TABLE` and confirm statistics exist, then
+//! `EXPLAIN` a query and assert its access path. A plan still built from
+//! pseudo statistics after the analyze fails the check.
+
+use crate::dynamic_state;
+use crate::errors::{ConnectError, Result};
+use crate::explain::{AccessPath, QueryPlan, assert_access_path, explain};
+use crate::state_machine_dynamic::{
+    DynamicState, DynamicStateContext, DynamicStateHandler, DynamicStateMachine, states,
+};
+use crate::stats::{AnalyzeTableHandler, analyzing_table};
+use async_trait::async_trait;
+use mysql::PooledConn;
+use mysql::prelude::*;
+
+/// Context key under which the verified plan is stored
+pub const VERIFIED_PLAN_KEY: &str = "verified_plan";
+
+/// Placeholder replaced by the qualified table name in [`PlanCheck`] SQL
+pub const TABLE_PLACEHOLDER: &str = "{table}";
+
+/// State that creates and populates the table
+#[must_use]
+pub fn creating_plan_table() -> DynamicState {
+    dynamic_state!("creating_plan_table", "Creating Plan Table")
+}
+
+/// State that runs `EXPLAIN` and checks the access path
+#[must_use]
+pub fn verifying_plan() -> DynamicState {
+    dynamic_state!("verifying_plan", "Verifying Plan")
+}
+
+/// A table, the data to load into it and the plan expected for a query
+///
+/// `create_sql`, `populate_sql` and `query` refer to the table as `{table}`,
+/// which is replaced by the backquoted, database-qualified name.
+#[derive(Debug, Clone)]
+pub struct PlanCheck {
+    pub table: String,
+    pub create_sql: String,
+    pub populate_sql: Vec<String>,
+    pub query: String,
+    pub expected: AccessPath,
+}
+
+impl PlanCheck {
+    /// Index lookup check: 1000 rows over 100 distinct values of `a`, and a
+    /// lookup on `a` that should use `idx_a`
+    #[must_use]
+    pub fn index_lookup(table: impl Into<String>) -> Self {
+        let values: Vec<String> = (0..1000)
+            .map(|id| format!("({id}, {}, 'row-{id}')", id % 100))
+            .collect();
+        Self {
+            table: table.into(),
+            create_sql: "CREATE TABLE {table} (id INT PRIMARY KEY, a INT, b VARCHAR(32), \
+                         KEY idx_a (a))"
+                .to_string(),
+            populate_sql: vec![format!(
+                "INSERT INTO {{table}} (id, a, b) VALUES {}",
+                values.join(", ")
+            )],
+            query: "SELECT id, b FROM {table} WHERE a = 42".to_string(),
+            expected: AccessPath::Index("idx_a".to_string()),
+        }
+    }
+
+    /// Register the workflow's handlers, starting at [`creating_plan_table`]
+    /// and moving to `next_state` once the plan has been verified
+    ///
+    /// The context must hold a connection and a database when the first
+    /// state runs.
+    pub fn register(&self, machine: &mut DynamicStateMachine, next_state: DynamicState) {
+        machine.register_handler(
+            creating_plan_table(),
+            Box::new(CreatePlanTableHandler {
+                check: self.clone(),
+            }),
+        );
+        machine.register_handler(
+            analyzing_table(),
+            Box::new(AnalyzeTableHandler::new(
+                self.table.clone(),
+                verifying_plan(),
+            )),
+        );
+        machine.register_handler(
+            verifying_plan(),
+            Box::new(VerifyPlanHandler {
+                check: self.clone(),
+                next_state: next_state.clone(),
+            }),
+        );
+        machine.register_transitions(creating_plan_table(), vec![analyzing_table()]);
+        machine.register_transitions(analyzing_table(), vec![verifying_plan()]);
+        machine.register_transitions(verifying_plan(), vec![next_state]);
+    }
+
+    /// Run the workflow on `conn` in `database`, dropping the table afterwards
+    ///
+    /// The table must not exist beforehand. Returns the verified plan.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a statement fails, statistics are missing after
+    /// `ANALYZE`, or `ConnectError::Validation` if the plan does not use the
+    /// expected access path or was built from pseudo statistics.
+    pub async fn run(&self, conn: PooledConn, database: &str) -> Result<QueryPlan> {
+        let mut machine = DynamicStateMachine::new();
+        machine.register_handler(states::initial(), Box::new(StartHandler));
+        machine.register_transitions(states::initial(), vec![creating_plan_table()]);
+        self.register(&mut machine, states::completed());
+
+        let context = machine.get_context_mut();
+        context.database = Some(database.to_string());
+        context.connection = Some(conn);
+
+        let run = machine.run().await;
+
+        let context = machine.get_context_mut();
+        if let Some(conn) = context.connection.as_mut() {
+            let qualified = qualified_table(database, &self.table);
+            if let Err(e) = conn.query_drop(format!("DROP TABLE IF EXISTS {qualified}")) {
+                tracing::warn!("Failed to drop plan check table {}: {}", qualified, e);
+            }
+        }
+        run?;
+
+        context
+            .get_custom_data::<QueryPlan>(VERIFIED_PLAN_KEY)
+            .cloned()
+            .ok_or_else(|| ConnectError::StateMachine("Plan was not verified".to_string()))
+    }
+
+    /// `sql` with the table placeholder replaced
+    fn render(&self, sql: &str, database: &str) -> String {
+        sql.replace(TABLE_PLACEHOLDER, &qualified_table(database, &self.table))
+    }
+}
+
+/// Check `plan` after `ANALYZE`: it must not use pseudo statistics and must
+/// read through `expected`
+///
+/// # Errors
+///
+/// Returns `ConnectError::Validation` describing the problem.
+pub fn verify_analyzed_plan(plan: &QueryPlan, expected: &AccessPath) -> Result<()> {
+    if plan.uses_pseudo_stats() {
+        return Err(ConnectError::Validation(format!(
+            "Plan for `{}` still uses pseudo statistics after ANALYZE",
+            plan.sql
+        )));
+    }
+    assert_access_path(plan, expected)
+}
+
+fn qualified_table(database: &str, table: &str) -> String {
+    format!("`{database}`.`{table}`")
+}
+
+fn connection_and_database(context: &mut DynamicStateContext) -> Result<(&mut PooledConn, String)> {
+    let database = context.database.clone().ok_or_else(|| {
+        ConnectError::Configuration("A database is required for the plan check".to_string())
+    })?;
+    let conn = context.connection.as_mut().ok_or_else(|| {
+        ConnectError::StateMachine("No connection available for the plan check".to_string())
+    })?;
+    Ok((conn, database))
+}
+
+struct StartHandler;
+
+#[async_trait]
+impl DynamicStateHandler for StartHandler {
+    async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+        Ok(states::initial())
+    }
+
+    async fn execute(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+        Ok(creating_plan_table())
+    }
+
+    async fn exit(&self, _context: &mut DynamicStateContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct CreatePlanTableHandler {
+    check: PlanCheck,
+}
+
+#[async_trait]
+impl DynamicStateHandler for CreatePlanTableHandler {
+    async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+        Ok(creating_plan_table())
+    }
+
+    async fn execute(&self, context: &mut DynamicStateContext) -> Result<DynamicState> {
+        let (conn, database) = connection_and_database(context)?;
+        conn.query_drop(self.check.render(&self.check.create_sql, &database))?;
+        for sql in &self.check.populate_sql {
+            conn.query_drop(self.check.render(sql, &database))?;
+        }
+        Ok(analyzing_table())
+    }
+
+    async fn exit(&self, _context: &mut DynamicStateContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct VerifyPlanHandler {
+    check: PlanCheck,
+    next_state: DynamicState,
+}
+
+#[async_trait]
+impl DynamicStateHandler for VerifyPlanHandler {
+    async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+        Ok(verifying_plan())
+    }
+
+    async fn execute(&self, context: &mut DynamicStateContext) -> Result<DynamicState> {
+        let (conn, database) = connection_and_database(context)?;
+        let plan = explain(
+            conn,
+            &self.check.render(&self.check.query, &database),
+            false,
+        )?;
+        verify_analyzed_plan(&plan, &self.check.expected)?;
+        context.set_custom_data(VERIFIED_PLAN_KEY.to_string(), plan);
+        Ok(self.next_state.clone())
+    }
+
+    async fn exit(&self, _context: &mut DynamicStateContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::explain::PlanRow;
+    use crate::test_support::live_db;
+
+    fn plan(operator_info: &str) -> QueryPlan {
+        QueryPlan {
+            sql: "SELECT id, b FROM t WHERE a = 42".to_string(),
+            rows: vec![
+                PlanRow {
+                    id: "IndexLookUp_10".to_string(),
+                    est_rows: "10.00".to_string(),
+                    task: "root".to_string(),
+                    access_object: String::new(),
+                    operator_info: String::new(),
+                },
+                PlanRow {
+                    id: "├─IndexRangeScan_8(Build)".to_string(),
+                    est_rows: "10.00".to_string(),
+                    task: "cop[tikv]".to_string(),
+                    access_object: "table:t, index:idx_a(a)".to_string(),
+                    operator_info: operator_info.to_string(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_verify_analyzed_plan() {
+        let expected = AccessPath::Index("idx_a".to_string());
+        assert!(verify_analyzed_plan(&plan("range:[42,42], keep order:false"), &expected).is_ok());
+
+        let err = verify_analyzed_plan(
+            &plan("range:[42,42], keep order:false, stats:pseudo"),
+            &expected,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("pseudo statistics after ANALYZE"));
+
+        let err = verify_analyzed_plan(&plan(""), &AccessPath::FullScan).unwrap_err();
+        assert!(err.to_string().contains("full table scan"));
+    }
+
+    #[test]
+    fn test_index_lookup_check_renders_table() {
+        let check = PlanCheck::index_lookup("plan_t");
+        assert_eq!(
+            check.render(&check.query, "test"),
+            "SELECT id, b FROM `test`.`plan_t` WHERE a = 42"
+        );
+        assert!(
+            check
+                .render(&check.populate_sql[0], "test")
+                .starts_with("INSERT INTO `test`.`plan_t` (id, a, b) VALUES (0, 0, 'row-0'), ")
+        );
+
+        let mut machine = DynamicStateMachine::new();
+        machine.register_handler(states::initial(), Box::new(StartHandler));
+        machine.register_transitions(states::initial(), vec![creating_plan_table()]);
+        check.register(&mut machine, states::completed());
+        assert_eq!(machine.validate_graph(), Ok(()));
+    }
+
+    #[tokio::test]
+    async fn test_plan_check_against_live_tidb() {
+        let Some(db) = live_db("live plan check") else {
+            return;
+        };
+        let table = db.table("plan_check");
+        let check = PlanCheck::index_lookup(table.name());
+        let plan = check.run(db.conn, &db.database).await.unwrap();
+        assert!(plan.indexes().contains(&"idx_a".to_string()));
+    }
+}
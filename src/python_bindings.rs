@@ -29,47 +29,103 @@ impl PythonHandler {
     pub fn new(py_handler: PyObject) -> Self {
         Self { py_handler }
     }
-}
 
-#[async_trait]
-impl StateHandler for PythonHandler {
-    async fn enter(&self, context: &mut StateContext) -> Result<State> {
+    /// Name of the wrapped Python handler class
+    fn handler_name(&self, py: Python) -> String {
+        self.py_handler
+            .as_ref(py)
+            .get_type()
+            .name()
+            .map_or_else(|_| "<unknown>".to_string(), ToString::to_string)
+    }
+
+    /// Call `method` with a context that borrows `context`'s connection
+    ///
+    /// The connection is moved into the Python context for the duration of the
+    /// call and handed back to `context` afterwards, even if the call fails.
+    /// If the method is a coroutine function, the returned coroutine is run to
+    /// completion before the connection is handed back.
+    async fn call(&self, context: &mut StateContext, method: &str) -> Result<PyObject> {
+        let (result, connection) = Python::with_gil(|py| {
+            let context_py = PyStateContext::lend(context);
+            let connection = context_py.connection.clone();
+            let result = self.py_handler.call_method1(py, method, (context_py,));
+            (result, connection)
+        });
+        let result = match result {
+            Ok(value) if Python::with_gil(|py| is_coroutine(py, &value)) => {
+                run_coroutine(value).await
+            }
+            other => other,
+        };
+        if let Some(conn) = connection.and_then(|connection| connection.take()) {
+            context.connection = Some(conn);
+        }
+        result.map_err(|e| Python::with_gil(|py| self.handler_error(py, method, e)))
+    }
+
+    /// Call `method` and parse the state string it returns
+    async fn call_for_state(&self, context: &mut StateContext, method: &str) -> Result<State> {
+        let result = self.call(context, method).await?;
         Python::with_gil(|py| {
-            let context_py = PyStateContext::new(context);
-            let result = self
-                .py_handler
-                .call_method1(py, "enter", (context_py,))
-                .map_err(|e| ConnectError::StateMachine(format!("Python handler error: {}", e)))?;
             let state_str: String = result.extract(py).map_err(|e| {
-                ConnectError::StateMachine(format!("Failed to extract state: {}", e))
+                self.handler_error(py, method, format!("expected a state string: {e}"))
             })?;
             Ok(parse_state_string(&state_str))
         })
     }
 
+    /// Wrap an error raised by (or extracted from) one of the handler's methods
+    fn handler_error(
+        &self,
+        py: Python,
+        method: &str,
+        error: impl std::fmt::Display,
+    ) -> ConnectError {
+        ConnectError::PythonHandler {
+            handler: self.handler_name(py),
+            message: format!("{method}(): {error}"),
+        }
+    }
+}
+
+#[async_trait]
+impl StateHandler for PythonHandler {
+    async fn enter(&self, context: &mut StateContext) -> Result<State> {
+        self.call_for_state(context, "enter").await
+    }
+
     async fn execute(&self, context: &mut StateContext) -> Result<State> {
-        Python::with_gil(|py| {
-            let context_py = PyStateContext::new(context);
-            let result = self
-                .py_handler
-                .call_method1(py, "execute", (context_py,))
-                .map_err(|e| ConnectError::StateMachine(format!("Python handler error: {}", e)))?;
-            let state_str: String = result.extract(py).map_err(|e| {
-                ConnectError::StateMachine(format!("Failed to extract state: {}", e))
-            })?;
-            Ok(parse_state_string(&state_str))
-        })
+        self.call_for_state(context, "execute").await
     }
 
     async fn exit(&self, context: &mut StateContext) -> Result<()> {
+        self.call(context, "exit").await?;
+        Ok(())
+    }
+}
+
+/// Whether `value` is a coroutine, i.e. the result of calling an `async def`
+fn is_coroutine(py: Python, value: &PyObject) -> bool {
+    py.import("inspect")
+        .and_then(|inspect| inspect.call_method1("iscoroutine", (value,)))
+        .and_then(|result| result.extract())
+        .unwrap_or(false)
+}
+
+/// Run `coroutine` to completion with `asyncio.run`
+///
+/// The event loop runs on tokio's blocking pool, so a handler awaiting I/O or
+/// `asyncio.sleep` does not stall the runtime's worker threads.
+async fn run_coroutine(coroutine: PyObject) -> PyResult<PyObject> {
+    tokio::task::spawn_blocking(move || {
         Python::with_gil(|py| {
-            let context_py = PyStateContext::new(context);
-            self.py_handler
-                .call_method1(py, "exit", (context_py,))
-                .map_err(|e| ConnectError::StateMachine(format!("Python handler error: {}", e)))?;
-            Ok(())
+            let asyncio = py.import("asyncio")?;
+            Ok(asyncio.call_method1("run", (coroutine,))?.into())
         })
-    }
+    })
+    .await
+    .map_err(runtime_error)?
 }
 
 /// Parse state string to State enum
@@ -100,11 +156,15 @@ pub struct PyStateContext {
     pub password: Option<String>,
     #[pyo3(get)]
     pub database: Option<String>,
+    /// True when no database is configured and table names must be qualified
+    #[pyo3(get)]
+    pub no_database: bool,
     #[pyo3(get)]
     pub connection: Option<PyConnection>,
 }
 
 impl PyStateContext {
+    /// Snapshot of `context` without a connection
     pub fn new(context: &StateContext) -> Self {
         Self {
             host: Some(context.host.clone()),
@@ -112,75 +172,130 @@ impl PyStateContext {
             username: Some(context.username.clone()),
             password: Some(context.password.clone()),
             database: context.database.clone(),
-            // TODO: PyConnection expects a clone of PooledConn, but PooledConn does not implement Clone.
-            // For now, do not provide a connection if not available.
+            no_database: context.no_database,
             connection: None,
         }
     }
+
+    /// Snapshot of `context` that takes its connection, if any
+    ///
+    /// `PooledConn` cannot be shared, so the connection is moved out of
+    /// `context`; return it with [`PyConnection::take`] once Python is done.
+    pub fn lend(context: &mut StateContext) -> Self {
+        Self {
+            connection: context.connection.take().map(PyConnection::new),
+            ..Self::new(context)
+        }
+    }
 }
 
 /// Python wrapper for database connection
+///
+/// Clones share the connection. Once it has been taken back with
+/// [`PyConnection::take`], every clone raises `RuntimeError` when used.
 #[pyclass]
 #[derive(Clone)]
 pub struct PyConnection {
-    inner: Arc<Mutex<mysql::PooledConn>>,
+    inner: Arc<Mutex<Option<mysql::PooledConn>>>,
 }
 
 impl PyConnection {
     pub fn new(conn: mysql::PooledConn) -> Self {
         Self {
-            inner: Arc::new(Mutex::new(conn)),
+            inner: Arc::new(Mutex::new(Some(conn))),
         }
     }
+
+    /// Take the connection back from Python, leaving every clone without one
+    pub fn take(&self) -> Option<mysql::PooledConn> {
+        self.inner.try_lock().ok()?.take()
+    }
+
+    /// Run `f` with the connection locked for a single call from Python
+    fn with_conn<R>(&self, f: impl FnOnce(&mut mysql::PooledConn) -> PyResult<R>) -> PyResult<R> {
+        let mut guard = self.inner.try_lock().map_err(runtime_error)?;
+        let conn = guard.as_mut().ok_or_else(|| {
+            runtime_error("connection is no longer available; the handler call has returned")
+        })?;
+        f(conn)
+    }
 }
 
 #[pymethods]
 impl PyConnection {
-    /// Execute a query and return results as a list of dictionaries
+    /// Execute a query and return results as a list of dictionaries keyed by
+    /// column name
     #[allow(unsafe_op_in_unsafe_fn)]
     pub fn execute_query(&self, query: String) -> PyResult<Vec<PyObject>> {
         #[allow(unsafe_code)]
         Python::with_gil(|py| {
-            let mut conn_guard = self
-                .inner
-                .try_lock()
-                .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
-
-            let results: Vec<mysql::Row> = conn_guard
-                .exec(&query, ())
-                .map_err(|e| PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(e.to_string()))?;
-
-            let mut py_results = Vec::new();
-            for row in results {
-                let row_dict = PyDict::new(py);
-                for (i, value) in row.unwrap().into_iter().enumerate() {
-                    let key = format!("col_{}", i);
-                    let py_value = match value {
-                        mysql::Value::NULL => py.None(),
-                        mysql::Value::Bytes(bytes) => {
-                            String::from_utf8_lossy(&bytes).to_string().into_py(py)
-                        }
-                        mysql::Value::Int(i) => i.into_py(py),
-                        mysql::Value::UInt(u) => u.into_py(py),
-                        mysql::Value::Float(f) => f.into_py(py),
-                        mysql::Value::Double(d) => d.into_py(py),
-                        mysql::Value::Date(year, month, day, hour, minute, second, _) => format!(
-                            "{}-{:02}-{:02} {:02}:{:02}:{:02}",
-                            year, month, day, hour, minute, second
-                        )
-                        .into_py(py),
-                        _ => format!("{:?}", value).into_py(py),
-                    };
-                    row_dict.set_item(key, py_value)?;
-                }
-                py_results.push(row_dict.into());
-            }
+            let results: Vec<mysql::Row> =
+                self.with_conn(|conn| conn.exec(&query, ()).map_err(runtime_error))?;
+            results
+                .into_iter()
+                .map(|row| row_to_dict(py, row))
+                .collect()
+        })
+    }
 
-            Ok(py_results)
+    /// Execute a statement that returns no rows, such as `DROP TABLE`
+    #[allow(unsafe_op_in_unsafe_fn)]
+    pub fn execute_drop(&self, query: String) -> PyResult<()> {
+        self.with_conn(|conn| conn.query_drop(&query).map_err(runtime_error))
+    }
+
+    /// Execute a query and return its first row as a dictionary, or `None`
+    /// if it returned no rows
+    #[allow(unsafe_op_in_unsafe_fn)]
+    pub fn query_first(&self, query: String) -> PyResult<Option<PyObject>> {
+        Python::with_gil(|py| {
+            let row: Option<mysql::Row> =
+                self.with_conn(|conn| conn.query_first(&query).map_err(runtime_error))?;
+            row.map(|row| row_to_dict(py, row)).transpose()
         })
     }
 }
 
+fn runtime_error(error: impl std::fmt::Display) -> PyErr {
+    PyErr::new::<pyo3::exceptions::PyRuntimeError, _>(error.to_string())
+}
+
+/// Convert `row` to a dictionary keyed by column name; columns without a name
+/// are keyed by position as `col_<index>`
+fn row_to_dict(py: Python, row: mysql::Row) -> PyResult<PyObject> {
+    let names: Vec<String> = row
+        .columns_ref()
+        .iter()
+        .map(|column| column.name_str().into_owned())
+        .collect();
+    let row_dict = PyDict::new(py);
+    for (i, value) in row.unwrap().into_iter().enumerate() {
+        let key = match names.get(i) {
+            Some(name) if !name.is_empty() => name.clone(),
+            _ => format!("col_{i}"),
+        };
+        row_dict.set_item(key, value_to_py(py, value))?;
+    }
+    Ok(row_dict.into())
+}
+
+fn value_to_py(py: Python, value: mysql::Value) -> PyObject {
+    match value {
+        mysql::Value::NULL => py.None(),
+        mysql::Value::Bytes(bytes) => String::from_utf8_lossy(&bytes).to_string().into_py(py),
+        mysql::Value::Int(i) => i.into_py(py),
+        mysql::Value::UInt(u) => u.into_py(py),
+        mysql::Value::Float(f) => f.into_py(py),
+        mysql::Value::Double(d) => d.into_py(py),
+        mysql::Value::Date(year, month, day, hour, minute, second, _) => format!(
+            "{}-{:02}-{:02} {:02}:{:02}:{:02}",
+            year, month, day, hour, minute, second
+        )
+        .into_py(py),
+        _ => format!("{:?}", value).into_py(py),
+    }
+}
+
 /// Python wrapper for State enum
 #[pyclass]
 pub struct PyState;
@@ -276,34 +391,79 @@ pub fn register_python_handler(
 }
 
 /// Load Python handlers from a module
+///
+/// Every class defined in the module whose name ends in `Handler` is
+/// registered for the state it declares, either with a `state_name` class
+/// attribute or a `STATE` classmethod returning the state string, e.g.
+/// `state_name = "connecting"`. Classes without a recognizable state are
+/// skipped with a warning. Returns the states that were registered.
 pub fn load_python_handlers(
-    _state_machine: &mut crate::state_machine::StateMachine,
+    state_machine: &mut crate::state_machine::StateMachine,
     module_path: &str,
-) -> PyResult<()> {
+) -> PyResult<Vec<State>> {
     Python::with_gil(|py| {
         let sys = py.import("sys")?;
         let path = sys.getattr("path")?;
         path.call_method1("append", ("",))?;
 
         let module = py.import(module_path)?;
+        let module_name: String = module.getattr("__name__")?.extract()?;
 
-        // Look for handler classes in the module
+        let mut registered = Vec::new();
         for attr_name in module.dir() {
             let attr_name: String = attr_name.extract()?;
-            if attr_name.ends_with("Handler") && !attr_name.starts_with('_') {
-                // All handler names are currently skipped, so just continue
+            if !attr_name.ends_with("Handler") || attr_name.starts_with('_') {
+                continue;
+            }
+            let class = module.getattr(attr_name.as_str())?;
+            // Skip imported classes such as the PyStateHandler base
+            let defined_here = class
+                .getattr("__module__")
+                .and_then(|m| m.extract::<String>())
+                .is_ok_and(|m| m == module_name);
+            if !class.is_instance_of::<pyo3::types::PyType>() || !defined_here {
                 continue;
             }
+
+            let Some(state) = declared_state(class) else {
+                tracing::warn!(
+                    "Skipping Python handler {}: declare its state with `state_name` or `STATE`",
+                    attr_name
+                );
+                continue;
+            };
+            register_python_handler(state_machine, state.clone(), class.call0()?.into())?;
+            tracing::info!("Registered Python handler {} for {}", attr_name, state);
+            registered.push(state);
         }
 
-        Ok(())
+        Ok(registered)
     })
 }
 
+/// State declared by a handler class through `state_name` or `STATE()`
+fn declared_state(class: &PyAny) -> Option<State> {
+    let name: String = class
+        .getattr("state_name")
+        .ok()
+        .and_then(|name| name.extract().ok())
+        .or_else(|| {
+            class
+                .getattr("STATE")
+                .and_then(|state| state.call0())
+                .and_then(|state| state.extract())
+                .ok()
+        })?;
+    // parse_state_string falls back to Completed for unknown names
+    let state = parse_state_string(&name);
+    (state != State::Completed || name.eq_ignore_ascii_case("completed")).then_some(state)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::state_machine::StateMachine;
+    use crate::test_support::live_db;
 
     #[test]
     fn test_parse_state_string() {
@@ -357,10 +517,12 @@ mod tests {
         context.username = "testuser".to_string();
         context.password = "testpass".to_string();
         context.database = None;
+        context.no_database = true;
 
         let py_context = PyStateContext::new(&context);
 
         assert!(py_context.database.is_none());
+        assert!(py_context.no_database);
     }
 
     #[test]
@@ -490,6 +652,96 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_py_connection_rows_keyed_by_column_name() {
+        let Some(db) = live_db("live PyConnection test") else {
+            return;
+        };
+        let py_conn = PyConnection::new(db.conn);
+        Python::with_gil(|py| {
+            let rows = py_conn
+                .execute_query("SELECT 1 AS id, 'a' AS name, NULL AS missing".to_string())
+                .unwrap();
+            assert_eq!(rows.len(), 1);
+            let row: &PyDict = rows[0].as_ref(py).downcast().unwrap();
+            let id: i64 = row.get_item("id").unwrap().unwrap().extract().unwrap();
+            assert_eq!(id, 1);
+            let name: String = row.get_item("name").unwrap().unwrap().extract().unwrap();
+            assert_eq!(name, "a");
+            assert!(row.get_item("missing").unwrap().unwrap().is_none());
+            assert!(row.get_item("col_0").unwrap().is_none());
+        });
+    }
+
+    #[test]
+    fn test_py_connection_execute_drop_and_query_first() {
+        let Some(db) = live_db("live PyConnection test") else {
+            return;
+        };
+        let scratch = db.table("py_conn_drop");
+        let table = scratch.qualified();
+        let py_conn = PyConnection::new(db.conn);
+        py_conn
+            .execute_drop(format!("CREATE TABLE {table} (id INT PRIMARY KEY)"))
+            .unwrap();
+        py_conn
+            .execute_drop(format!("INSERT INTO {table} VALUES (7)"))
+            .unwrap();
+
+        Python::with_gil(|py| {
+            let first = py_conn
+                .query_first(format!("SELECT id FROM {table}"))
+                .unwrap()
+                .expect("one row");
+            let id: i64 = first.as_ref(py).get_item("id").unwrap().extract().unwrap();
+            assert_eq!(id, 7);
+            let none = py_conn
+                .query_first(format!("SELECT id FROM {table} WHERE id = 0"))
+                .unwrap();
+            assert!(none.is_none());
+        });
+
+        py_conn.execute_drop(format!("DROP TABLE {table}")).unwrap();
+        assert!(
+            py_conn
+                .execute_query(format!("SELECT id FROM {table}"))
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_python_handler_queries_lent_connection() {
+        let Some(db) = live_db("lent connection test") else {
+            return;
+        };
+        let handler = Python::with_gil(|py| {
+            let locals = PyDict::new(py);
+            py.run(
+                "class QueryingHandler:\n    def execute(self, context):\n        rows = context.connection.execute_query('SELECT 41 + 1 AS answer')\n        return 'completed' if rows[0]['answer'] == 42 else 'initial'\n",
+                None,
+                Some(locals),
+            )
+            .unwrap();
+            let class = locals.get_item("QueryingHandler").unwrap().unwrap();
+            PythonHandler::new(class.call0().unwrap().into())
+        });
+
+        let mut context = StateContext::default();
+        context.connection = Some(db.conn);
+        let next = handler.execute(&mut context).await.unwrap();
+        assert_eq!(next, State::Completed);
+        // The connection is handed back once the handler returns
+        assert!(context.connection.is_some());
+    }
+
+    #[test]
+    fn test_lend_without_connection() {
+        let mut context = StateContext::default();
+        let py_context = PyStateContext::lend(&mut context);
+        assert!(py_context.connection.is_none());
+        assert!(context.connection.is_none());
+    }
+
     #[test]
     fn test_py_state_context_clone() {
         let mut context = StateContext::default();
@@ -548,6 +800,64 @@ mod tests {
         });
     }
 
+    #[tokio::test]
+    async fn test_failing_python_handler_reports_handler_name() {
+        let handler = Python::with_gil(|py| {
+            let locals = PyDict::new(py);
+            py.run(
+                "class FailingHandler:\n    def execute(self, context):\n        raise ValueError('table missing')\n",
+                None,
+                Some(locals),
+            )
+            .unwrap();
+            let class = locals.get_item("FailingHandler").unwrap().unwrap();
+            PythonHandler::new(class.call0().unwrap().into())
+        });
+
+        let err = handler
+            .execute(&mut StateContext::default())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), "python_handler");
+        match err {
+            ConnectError::PythonHandler { handler, message } => {
+                assert_eq!(handler, "FailingHandler");
+                assert!(message.contains("execute()"));
+                assert!(message.contains("table missing"));
+            }
+            other => panic!("expected a PythonHandler error, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_async_python_handler_does_not_block_runtime() {
+        let handler = Python::with_gil(|py| {
+            let locals = PyDict::new(py);
+            py.run(
+                "class SleepingHandler:\n    async def execute(self, context):\n        import asyncio\n        await asyncio.sleep(0.2)\n        return 'completed'\n    async def exit(self, context):\n        pass\n",
+                None,
+                Some(locals),
+            )
+            .unwrap();
+            let class = locals.get_item("SleepingHandler").unwrap().unwrap();
+            PythonHandler::new(class.call0().unwrap().into())
+        });
+
+        let started = std::time::Instant::now();
+        let mut context = StateContext::default();
+        // On this single-threaded runtime the ticker only fires mid-sleep if
+        // the handler yields to the runtime
+        let (next, ticked_after) = tokio::join!(handler.execute(&mut context), async {
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            started.elapsed()
+        });
+        assert_eq!(next.unwrap(), State::Completed);
+        assert!(ticked_after < std::time::Duration::from_millis(150));
+        assert!(started.elapsed() >= std::time::Duration::from_millis(200));
+
+        assert!(handler.exit(&mut context).await.is_ok());
+    }
+
     #[test]
     fn test_error_handling() {
         Python::with_gil(|_py| {
@@ -593,6 +903,24 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_load_python_handlers_registers_declared_states() {
+        Python::with_gil(|py| {
+            PyModule::from_code(
+                py,
+                "class ConnectingHandler:\n    state_name = 'connecting'\n    def execute(self, context):\n        return 'testing_connection'\n\nclass VersionHandler:\n    @classmethod\n    def STATE(cls):\n        return 'getting_version'\n\nclass UnmappedHandler:\n    pass\n\nclass TypoHandler:\n    state_name = 'conecting'\n",
+                "declared_handlers.py",
+                "declared_handlers",
+            )
+            .unwrap();
+        });
+
+        let mut state_machine = StateMachine::new();
+        let mut registered = load_python_handlers(&mut state_machine, "declared_handlers").unwrap();
+        registered.sort_by_key(ToString::to_string);
+        assert_eq!(registered, vec![State::Connecting, State::GettingVersion]);
+    }
+
     #[test]
     fn test_py_state_context_attributes() {
         Python::with_gil(|py| {
@@ -0,0 +1,397 @@
+//! # Query Helpers
+//!
+//! Shared helpers for running ad hoc and diagnostic queries. Rows are streamed
+//! with `query_iter` so a row cap or a [`RowSampler`] can be applied without
+//! loading the whole result set into memory.
+
+use crate::errors::{ConnectError, Result};
+use mysql::prelude::*;
+use mysql::{PooledConn, Row, Value};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+/// Rows collected from a query, converted to display strings
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryOutput {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// Set when the result had more rows than the configured cap
+    pub truncated: bool,
+    /// Rows left out by a [`RowSampler`]
+    pub omitted: Option<OmittedRows>,
+}
+
+/// Rows a sampler skipped between the head and the tail of a result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OmittedRows {
+    /// Number of kept rows printed before the omission marker
+    pub after: usize,
+    pub count: usize,
+}
+
+impl QueryOutput {
+    /// Render the rows as tab-separated lines, with notes for truncated or sampled output
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        let _ = writeln!(output, "{}", self.columns.join("\t"));
+        for (i, row) in self.rows.iter().enumerate() {
+            if let Some(omitted) = self.omitted
+                && omitted.after == i
+            {
+                let _ = writeln!(output, "... {} more rows ...", omitted.count);
+            }
+            let _ = writeln!(output, "{}", row.join("\t"));
+        }
+        if let Some(omitted) = self.omitted
+            && omitted.after == self.rows.len()
+        {
+            let _ = writeln!(output, "... {} more rows ...", omitted.count);
+        }
+        if self.truncated {
+            let _ = writeln!(
+                output,
+                "... output truncated at {} rows (raise --max-rows to see more)",
+                self.rows.len()
+            );
+        }
+        output
+    }
+
+    /// Print the rendered rows to stdout
+    pub fn print(&self) {
+        print!("{}", self.render());
+    }
+}
+
+/// Keeps the first `head` and last `tail` rows of a result
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowSampler {
+    pub head: usize,
+    pub tail: usize,
+}
+
+impl RowSampler {
+    #[must_use]
+    pub fn new(head: usize, tail: usize) -> Self {
+        Self { head, tail }
+    }
+
+    /// Sample a row stream, holding at most `head + tail` rows in memory
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error produced by the stream.
+    pub fn sample<T, I>(&self, rows: I) -> Result<(Vec<T>, Option<OmittedRows>)>
+    where
+        I: IntoIterator<Item = Result<T>>,
+    {
+        let mut head = Vec::new();
+        let mut tail = VecDeque::with_capacity(self.tail);
+        let mut skipped = 0;
+
+        for row in rows {
+            let row = row?;
+            if head.len() < self.head {
+                head.push(row);
+            } else if self.tail > 0 {
+                if tail.len() == self.tail {
+                    tail.pop_front();
+                    skipped += 1;
+                }
+                tail.push_back(row);
+            } else {
+                skipped += 1;
+            }
+        }
+
+        let omitted = (skipped > 0).then_some(OmittedRows {
+            after: head.len(),
+            count: skipped,
+        });
+        head.extend(tail);
+        Ok((head, omitted))
+    }
+}
+
+/// Convert a column value to a display string (`NULL` for SQL NULL)
+pub fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::NULL => "NULL".to_string(),
+        Value::Bytes(bytes) => String::from_utf8_lossy(bytes).to_string(),
+        Value::Int(i) => i.to_string(),
+        Value::UInt(u) => u.to_string(),
+        other => other.as_sql(true),
+    }
+}
+
+/// Collect at most `max_rows` items from a row stream
+///
+/// Returns the collected items and whether the stream had more. Exactly one
+/// extra item is pulled to detect truncation; it is discarded, never stored.
+///
+/// # Errors
+///
+/// Returns the first error produced by the stream.
+pub fn collect_limited<T, I>(rows: I, max_rows: Option<usize>) -> Result<(Vec<T>, bool)>
+where
+    I: IntoIterator<Item = Result<T>>,
+{
+    let mut iter = rows.into_iter();
+    let mut collected = Vec::new();
+
+    loop {
+        if max_rows.is_some_and(|max| collected.len() >= max) {
+            let truncated = iter.next().transpose()?.is_some();
+            return Ok((collected, truncated));
+        }
+        match iter.next().transpose()? {
+            Some(item) => collected.push(item),
+            None => return Ok((collected, false)),
+        }
+    }
+}
+
+/// Run `sql` and stream its first result set, keeping at most `max_rows` rows
+///
+/// # Errors
+///
+/// Returns an error if the query fails or a row cannot be read.
+pub fn query_rows(
+    conn: &mut PooledConn,
+    sql: &str,
+    max_rows: Option<usize>,
+) -> Result<QueryOutput> {
+    stream_rows(conn, sql, |rows| {
+        let (rows, truncated) = collect_limited(rows, max_rows)?;
+        Ok((rows, truncated, None))
+    })
+}
+
+/// Run `sql` and keep only the rows selected by `sampler`
+///
+/// # Errors
+///
+/// Returns an error if the query fails or a row cannot be read.
+pub fn query_rows_sampled(
+    conn: &mut PooledConn,
+    sql: &str,
+    sampler: RowSampler,
+) -> Result<QueryOutput> {
+    stream_rows(conn, sql, |rows| {
+        let (rows, omitted) = sampler.sample(rows)?;
+        Ok((rows, false, omitted))
+    })
+}
+
+/// Which rows of a result to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RowLimit {
+    /// Keep at most this many rows (`None` keeps all)
+    Max(Option<usize>),
+    /// Keep the head and tail selected by a sampler
+    Sample(RowSampler),
+}
+
+/// Run `sql` with the given row limit
+///
+/// # Errors
+///
+/// Returns an error if the query fails or a row cannot be read.
+pub fn query_with_limit(conn: &mut PooledConn, sql: &str, limit: RowLimit) -> Result<QueryOutput> {
+    match limit {
+        RowLimit::Max(max_rows) => query_rows(conn, sql, max_rows),
+        RowLimit::Sample(sampler) => query_rows_sampled(conn, sql, sampler),
+    }
+}
+
+/// MySQL error code for "No database selected"
+const ER_NO_DB_ERROR: u16 = 1046;
+
+/// Convert a query error, turning "No database selected" into an actionable message
+#[must_use]
+pub fn map_query_error(err: mysql::Error) -> ConnectError {
+    match err {
+        mysql::Error::MySqlError(ref e) if e.code == ER_NO_DB_ERROR => {
+            ConnectError::Configuration(format!(
+                "{} (error {ER_NO_DB_ERROR}): the query uses an unqualified table name; \
+                 pass -d <database> or qualify the table as database.table",
+                e.message
+            ))
+        }
+        other => other.into(),
+    }
+}
+
+/// `table` as a name that resolves without a current database
+///
+/// Names already in `database.table` form are returned unchanged; bare names
+/// are qualified with `database`.
+///
+/// # Errors
+///
+/// Returns `ConnectError::Configuration` if `table` is unqualified and there is
+/// no `database`, since the statement would fail with error 1046.
+pub fn qualify_table(database: Option<&str>, table: &str) -> Result<String> {
+    if table.contains('.') {
+        return Ok(table.to_string());
+    }
+    match database {
+        Some(database) => Ok(format!("`{database}`.`{table}`")),
+        None => Err(ConnectError::Configuration(format!(
+            "Cannot use table '{table}': no database configured; \
+             qualify it as database.{table} or pass -d <database>"
+        ))),
+    }
+}
+
+type CollectedRows = (Vec<Vec<String>>, bool, Option<OmittedRows>);
+
+/// Stream the first result set of `sql` as display strings through `collect`
+fn stream_rows<F>(conn: &mut PooledConn, sql: &str, collect: F) -> Result<QueryOutput>
+where
+    F: FnOnce(&mut dyn Iterator<Item = Result<Vec<String>>>) -> Result<CollectedRows>,
+{
+    let mut result = conn.query_iter(sql).map_err(map_query_error)?;
+    let columns = result
+        .columns()
+        .as_ref()
+        .iter()
+        .map(|c| c.name_str().to_string())
+        .collect();
+
+    let mut stream = result.by_ref().map(|row| {
+        row.map(|row: Row| {
+            (0..row.len())
+                .map(|i| row.as_ref(i).map(value_to_string).unwrap_or_default())
+                .collect::<Vec<_>>()
+        })
+        .map_err(map_query_error)
+    });
+    let (rows, truncated, omitted) = collect(&mut stream)?;
+
+    Ok(QueryOutput {
+        columns,
+        rows,
+        truncated,
+        omitted,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_qualify_table() {
+        assert_eq!(qualify_table(None, "app.events").unwrap(), "app.events");
+        assert_eq!(
+            qualify_table(Some("app"), "events").unwrap(),
+            "`app`.`events`"
+        );
+        let err = qualify_table(None, "events").unwrap_err();
+        assert!(matches!(err, ConnectError::Configuration(_)));
+        assert!(err.to_string().contains("database.events"));
+    }
+
+    #[test]
+    fn test_no_database_selected_is_actionable() {
+        let err = map_query_error(mysql::Error::MySqlError(mysql::MySqlError {
+            state: "3D000".to_string(),
+            message: "No database selected".to_string(),
+            code: 1046,
+        }));
+        assert!(matches!(err, ConnectError::Configuration(_)));
+        let message = err.to_string();
+        assert!(message.contains("No database selected"));
+        assert!(message.contains("-d <database>"));
+        assert_eq!(err.exit_code(), 2);
+
+        let other = map_query_error(mysql::Error::MySqlError(mysql::MySqlError {
+            state: "42S02".to_string(),
+            message: "Table 'test.t' doesn't exist".to_string(),
+            code: 1146,
+        }));
+        assert!(matches!(other, ConnectError::Connection(_)));
+    }
+
+    #[test]
+    fn test_collect_limited_materializes_only_cap() {
+        let pulled = Cell::new(0usize);
+        let source = (0..10_000).map(|i| {
+            pulled.set(pulled.get() + 1);
+            Ok(i)
+        });
+
+        let (rows, truncated) = collect_limited(source, Some(5)).unwrap();
+        assert_eq!(rows, vec![0, 1, 2, 3, 4]);
+        assert!(truncated);
+        // The cap plus one look-ahead row, nowhere near the full 10,000
+        assert_eq!(pulled.get(), 6);
+    }
+
+    #[test]
+    fn test_collect_limited_without_truncation() {
+        let (rows, truncated) = collect_limited((0..3).map(Ok), Some(3)).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert!(!truncated);
+
+        let (rows, truncated) = collect_limited((0..3).map(Ok), None).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert!(!truncated);
+    }
+
+    #[test]
+    fn test_collect_limited_propagates_errors() {
+        let source = vec![
+            Ok(1),
+            Err(crate::errors::ConnectError::Database("boom".into())),
+        ];
+        assert!(collect_limited(source, Some(10)).is_err());
+    }
+
+    #[test]
+    fn test_sampler_keeps_head_and_tail() {
+        let (rows, omitted) = RowSampler::new(3, 2).sample((0..100).map(Ok)).unwrap();
+        assert_eq!(rows, vec![0, 1, 2, 98, 99]);
+        assert_eq!(
+            omitted,
+            Some(OmittedRows {
+                after: 3,
+                count: 95
+            })
+        );
+
+        let output = QueryOutput {
+            columns: vec!["id".to_string()],
+            rows: rows.iter().map(|i| vec![i.to_string()]).collect(),
+            truncated: false,
+            omitted,
+        };
+        let rendered = output.render();
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(
+            lines,
+            vec!["id", "0", "1", "2", "... 95 more rows ...", "98", "99"]
+        );
+    }
+
+    #[test]
+    fn test_sampler_with_small_result_omits_nothing() {
+        let (rows, omitted) = RowSampler::new(3, 2).sample((0..4).map(Ok)).unwrap();
+        assert_eq!(rows, vec![0, 1, 2, 3]);
+        assert_eq!(omitted, None);
+
+        let (rows, omitted) = RowSampler::new(2, 0).sample((0..5).map(Ok)).unwrap();
+        assert_eq!(rows, vec![0, 1]);
+        assert_eq!(omitted.map(|o| o.count), Some(3));
+    }
+
+    #[test]
+    fn test_value_to_string() {
+        assert_eq!(value_to_string(&Value::NULL), "NULL");
+        assert_eq!(value_to_string(&Value::Int(-3)), "-3");
+        assert_eq!(value_to_string(&Value::Bytes(b"abc".to_vec())), "abc");
+    }
+}
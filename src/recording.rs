@@ -0,0 +1,237 @@
+//! # Query Recording and Replay
+//!
+//! Record every query and its result during a live run, then replay the
+//! recording through a mock connection to reproduce the run without a database.
+//! Recordings are JSON files holding the queries in execution order.
+
+use crate::errors::{ConnectError, Result};
+use crate::query::{OmittedRows, QueryOutput, RowLimit, query_with_limit};
+use mysql::PooledConn;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::Path;
+
+/// Something that can run a query and return its rows
+pub trait QueryExecutor {
+    /// Run `sql`, keeping the rows selected by `limit`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    fn run_query(&mut self, sql: &str, limit: RowLimit) -> Result<QueryOutput>;
+}
+
+impl QueryExecutor for PooledConn {
+    fn run_query(&mut self, sql: &str, limit: RowLimit) -> Result<QueryOutput> {
+        query_with_limit(self, sql, limit)
+    }
+}
+
+/// A query and its outcome as seen during the live run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedQuery {
+    pub sql: String,
+    #[serde(default)]
+    pub columns: Vec<String>,
+    #[serde(default)]
+    pub rows: Vec<Vec<String>>,
+    #[serde(default)]
+    pub truncated: bool,
+    #[serde(default)]
+    pub omitted: Option<OmittedRows>,
+    /// Error message if the query failed
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Queries recorded in execution order
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueryRecording {
+    pub queries: Vec<RecordedQuery>,
+}
+
+impl QueryRecording {
+    /// Record the outcome of a query
+    pub fn push(&mut self, sql: &str, result: &Result<QueryOutput>) {
+        let recorded = match result {
+            Ok(output) => RecordedQuery {
+                sql: sql.to_string(),
+                columns: output.columns.clone(),
+                rows: output.rows.clone(),
+                truncated: output.truncated,
+                omitted: output.omitted,
+                error: None,
+            },
+            Err(e) => RecordedQuery {
+                sql: sql.to_string(),
+                columns: Vec::new(),
+                rows: Vec::new(),
+                truncated: false,
+                omitted: None,
+                error: Some(e.to_string()),
+            },
+        };
+        self.queries.push(recorded);
+    }
+
+    /// Load a recording from a JSON file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content).map_err(|e| {
+            ConnectError::Parse(format!("Invalid query recording {}: {e}", path.display()))
+        })
+    }
+
+    /// Save the recording as a JSON file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| ConnectError::Parse(format!("Failed to serialize recording: {e}")))?;
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+/// Wraps a live executor and records every query it runs
+pub struct RecordingConnection<E> {
+    inner: E,
+    recording: QueryRecording,
+}
+
+impl<E: QueryExecutor> RecordingConnection<E> {
+    #[must_use]
+    pub fn new(inner: E) -> Self {
+        Self {
+            inner,
+            recording: QueryRecording::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn recording(&self) -> &QueryRecording {
+        &self.recording
+    }
+
+    #[must_use]
+    pub fn into_recording(self) -> QueryRecording {
+        self.recording
+    }
+}
+
+impl<E: QueryExecutor> QueryExecutor for RecordingConnection<E> {
+    fn run_query(&mut self, sql: &str, limit: RowLimit) -> Result<QueryOutput> {
+        let result = self.inner.run_query(sql, limit);
+        self.recording.push(sql, &result);
+        result
+    }
+}
+
+/// Mock connection that serves recorded results in order
+pub struct ReplayConnection {
+    queries: VecDeque<RecordedQuery>,
+}
+
+impl ReplayConnection {
+    #[must_use]
+    pub fn new(recording: QueryRecording) -> Self {
+        Self {
+            queries: recording.queries.into(),
+        }
+    }
+
+    /// Number of recorded queries not yet replayed
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        self.queries.len()
+    }
+}
+
+impl QueryExecutor for ReplayConnection {
+    /// Serve the next recorded result; `limit` is ignored since the recording
+    /// already reflects the limit used during the live run
+    fn run_query(&mut self, sql: &str, _limit: RowLimit) -> Result<QueryOutput> {
+        let recorded = self.queries.pop_front().ok_or_else(|| {
+            ConnectError::Validation(format!("Replay exhausted: no recorded result for `{sql}`"))
+        })?;
+        if recorded.sql != sql {
+            return Err(ConnectError::Validation(format!(
+                "Replay diverged: expected `{}`, got `{sql}`",
+                recorded.sql
+            )));
+        }
+        if let Some(error) = recorded.error {
+            return Err(ConnectError::Database(error));
+        }
+        Ok(QueryOutput {
+            columns: recorded.columns,
+            rows: recorded.rows,
+            truncated: recorded.truncated,
+            omitted: recorded.omitted,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: RowLimit = RowLimit::Max(None);
+
+    /// Stand-in for a live connection
+    struct FakeLive;
+
+    impl QueryExecutor for FakeLive {
+        fn run_query(&mut self, sql: &str, _limit: RowLimit) -> Result<QueryOutput> {
+            if sql.contains("missing") {
+                return Err(ConnectError::Database(
+                    "Table 'test.missing' doesn't exist".into(),
+                ));
+            }
+            Ok(QueryOutput {
+                columns: vec!["id".to_string(), "name".to_string()],
+                rows: vec![vec!["1".to_string(), "a".to_string()]],
+                truncated: false,
+                omitted: None,
+            })
+        }
+    }
+
+    #[test]
+    fn test_record_and_replay_round_trip() {
+        let mut live = RecordingConnection::new(FakeLive);
+        let first = live.run_query("SELECT * FROM t", ALL).unwrap();
+        assert!(live.run_query("SELECT * FROM missing", ALL).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recording.json");
+        live.into_recording().save(&path).unwrap();
+
+        let mut replay = ReplayConnection::new(QueryRecording::load(&path).unwrap());
+        assert_eq!(replay.run_query("SELECT * FROM t", ALL).unwrap(), first);
+        let err = replay.run_query("SELECT * FROM missing", ALL).unwrap_err();
+        assert!(err.to_string().contains("doesn't exist"));
+        assert_eq!(replay.remaining(), 0);
+        assert!(replay.run_query("SELECT 1", ALL).is_err());
+    }
+
+    #[test]
+    fn test_replay_rejects_diverging_query() {
+        let mut recording = QueryRecording::default();
+        recording.push("SELECT 1", &FakeLive.run_query("SELECT 1", ALL));
+
+        let err = ReplayConnection::new(recording)
+            .run_query("SELECT 2", ALL)
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("expected `SELECT 1`, got `SELECT 2`")
+        );
+    }
+}
@@ -0,0 +1,427 @@
+//! # Test Reports
+//!
+//! Machine-readable results for CI. A [`TestReport`] holds one entry per step
+//! of a run and renders as JSON or JUnit XML. [`StepRecorder`] builds the
+//! report from a [`DynamicStateMachine`](crate::DynamicStateMachine) run by
+//! observing its transitions, so each state becomes one step.
+
+use crate::errors::{ConnectError, Result};
+use crate::state_machine_dynamic::{
+    DynamicState, DynamicStateContext, DynamicStateMachine, TransitionObserver,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Output format for `--report-format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// JUnit XML, understood by most CI systems
+    #[default]
+    Junit,
+    /// The [`TestReport`] serialized as JSON
+    Json,
+}
+
+/// Outcome of a single step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StepStatus {
+    Passed,
+    Failed,
+}
+
+/// One step of a test run, e.g. a state of the state machine
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestStep {
+    pub name: String,
+    pub status: StepStatus,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Results of one test binary run
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct TestReport {
+    pub suite: String,
+    /// Run metadata from `--tag`, written as JUnit properties
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+    pub steps: Vec<TestStep>,
+}
+
+impl TestReport {
+    #[must_use]
+    pub fn new(suite: impl Into<String>) -> Self {
+        Self {
+            suite: suite.into(),
+            tags: BTreeMap::new(),
+            steps: Vec::new(),
+        }
+    }
+
+    /// Record a step that succeeded
+    pub fn add_passed(&mut self, name: impl Into<String>, duration: Duration) {
+        self.add_step(name.into(), StepStatus::Passed, duration, None);
+    }
+
+    /// Record a step that failed with `error`
+    pub fn add_failed(
+        &mut self,
+        name: impl Into<String>,
+        duration: Duration,
+        error: impl Into<String>,
+    ) {
+        self.add_step(
+            name.into(),
+            StepStatus::Failed,
+            duration,
+            Some(error.into()),
+        );
+    }
+
+    fn add_step(
+        &mut self,
+        name: String,
+        status: StepStatus,
+        duration: Duration,
+        error: Option<String>,
+    ) {
+        self.steps.push(TestStep {
+            name,
+            status,
+            duration_ms: u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+            error,
+        });
+    }
+
+    /// Steps that failed
+    pub fn failures(&self) -> impl Iterator<Item = &TestStep> {
+        self.steps
+            .iter()
+            .filter(|step| step.status == StepStatus::Failed)
+    }
+
+    /// Total duration of all steps
+    #[must_use]
+    pub fn total_duration(&self) -> Duration {
+        Duration::from_millis(self.steps.iter().map(|step| step.duration_ms).sum())
+    }
+
+    /// Render as pretty-printed JSON
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self)
+            .unwrap_or_else(|_| format!("{{\"suite\":{:?}}}", self.suite))
+    }
+
+    /// Parse a report written by [`TestReport::to_json`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `json` is not a valid report.
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| ConnectError::Validation(format!("Invalid test report JSON: {e}")))
+    }
+
+    /// Render as a JUnit `<testsuite>` with one `<testcase>` per step
+    #[must_use]
+    pub fn to_junit_xml(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{}\">",
+            xml_escape(&self.suite),
+            self.steps.len(),
+            self.failures().count(),
+            seconds(self.total_duration().as_millis())
+        );
+        if !self.tags.is_empty() {
+            xml.push_str("  <properties>\n");
+            for (name, value) in &self.tags {
+                let _ = writeln!(
+                    xml,
+                    "    <property name=\"{}\" value=\"{}\"/>",
+                    xml_escape(name),
+                    xml_escape(value)
+                );
+            }
+            xml.push_str("  </properties>\n");
+        }
+        for step in &self.steps {
+            let _ = write!(
+                xml,
+                "  <testcase name=\"{}\" classname=\"{}\" time=\"{}\"",
+                xml_escape(&step.name),
+                xml_escape(&self.suite),
+                seconds(u128::from(step.duration_ms))
+            );
+            match (&step.status, &step.error) {
+                (StepStatus::Passed, _) => xml.push_str("/>\n"),
+                (StepStatus::Failed, error) => {
+                    let message = xml_escape(error.as_deref().unwrap_or("failed"));
+                    let _ = writeln!(
+                        xml,
+                        ">\n    <failure message=\"{message}\">{message}</failure>\n  </testcase>"
+                    );
+                }
+            }
+        }
+        xml.push_str("</testsuite>\n");
+        xml
+    }
+
+    /// Write the report to `path` in `format`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn write_to_file(&self, path: &Path, format: ReportFormat) -> Result<()> {
+        let contents = match format {
+            ReportFormat::Junit => self.to_junit_xml(),
+            ReportFormat::Json => self.to_json(),
+        };
+        std::fs::write(path, contents)?;
+        Ok(())
+    }
+}
+
+/// Milliseconds as fractional seconds, as JUnit expects
+fn seconds(millis: u128) -> String {
+    format!("{}.{:03}", millis / 1000, millis % 1000)
+}
+
+/// Escape text for use in XML attributes and content
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+struct RecorderState {
+    report: TestReport,
+    step_started: Instant,
+}
+
+/// Builds a [`TestReport`] from the transitions of a state machine run
+///
+/// Register a clone with `DynamicStateMachine::add_observer` right before
+/// `run()`; every completed state is recorded as a passed step. Call
+/// [`StepRecorder::finish`] with the run result to add the failing state.
+#[derive(Clone)]
+pub struct StepRecorder {
+    state: Arc<Mutex<RecorderState>>,
+}
+
+impl StepRecorder {
+    #[must_use]
+    pub fn new(suite: impl Into<String>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(RecorderState {
+                report: TestReport::new(suite),
+                step_started: Instant::now(),
+            })),
+        }
+    }
+
+    /// The report so far with the tags of `machine`, plus a failed step for
+    /// its current state if `result` is an error
+    #[must_use]
+    pub fn finish(&self, result: &Result<()>, machine: &DynamicStateMachine) -> TestReport {
+        let Ok(state) = self.state.lock() else {
+            return TestReport::default();
+        };
+        let mut report = state.report.clone();
+        report.tags = machine.get_context().tags.clone().into_iter().collect();
+        if let Err(e) = result {
+            report.add_failed(
+                machine.get_current_state().name(),
+                state.step_started.elapsed(),
+                e.to_string(),
+            );
+        }
+        report
+    }
+}
+
+impl TransitionObserver for StepRecorder {
+    fn on_transition(&self, from: &DynamicState, _to: &DynamicState, _ctx: &DynamicStateContext) {
+        if let Ok(mut state) = self.state.lock() {
+            let elapsed = state.step_started.elapsed();
+            state.report.add_passed(from.name(), elapsed);
+            state.step_started = Instant::now();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_machine_dynamic::{DynamicStateHandler, DynamicStateMachine, states};
+    use async_trait::async_trait;
+
+    fn sample_report() -> TestReport {
+        let mut report = TestReport::new("isolation <repeatable read>");
+        report.add_passed("connecting", Duration::from_millis(120));
+        report.add_passed("creating_table", Duration::from_millis(1_500));
+        report.add_failed(
+            "testing_isolation",
+            Duration::from_millis(30),
+            "expected 1 but saw \"101\" & <more>",
+        );
+        report
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let report = sample_report();
+        let json = report.to_json();
+        assert!(json.contains("\"status\": \"failed\""));
+        assert_eq!(TestReport::from_json(&json).unwrap(), report);
+        assert!(TestReport::from_json("{\"suite\": 1}").is_err());
+    }
+
+    #[test]
+    fn test_junit_xml_structure() {
+        let xml = sample_report().to_junit_xml();
+        let lines: Vec<&str> = xml.lines().collect();
+
+        assert_eq!(lines[0], "<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+        assert_eq!(
+            lines[1],
+            "<testsuite name=\"isolation &lt;repeatable read&gt;\" tests=\"3\" failures=\"1\" time=\"1.650\">"
+        );
+        assert_eq!(
+            lines[2],
+            "  <testcase name=\"connecting\" classname=\"isolation &lt;repeatable read&gt;\" time=\"0.120\"/>"
+        );
+        assert_eq!(xml.matches("<testcase ").count(), 3);
+        assert_eq!(xml.matches("<failure ").count(), 1);
+        assert_eq!(xml.matches("</failure>").count(), 1);
+        assert!(xml.contains(
+            "<failure message=\"expected 1 but saw &quot;101&quot; &amp; &lt;more&gt;\">"
+        ));
+        assert_eq!(lines.last(), Some(&"</testsuite>"));
+    }
+
+    #[test]
+    fn test_write_to_file() {
+        let dir = std::env::temp_dir().join(format!("test_rig_report_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("report.json");
+
+        let report = sample_report();
+        report.write_to_file(&path, ReportFormat::Json).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(TestReport::from_json(&written).unwrap(), report);
+
+        report.write_to_file(&path, ReportFormat::Junit).unwrap();
+        assert!(
+            std::fs::read_to_string(&path)
+                .unwrap()
+                .contains("<testsuite")
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    struct StepHandler {
+        next: DynamicState,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl DynamicStateHandler for StepHandler {
+        async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+            Ok(self.next.clone())
+        }
+
+        async fn execute(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+            if self.fail {
+                return Err(ConnectError::Database("table missing".to_string()));
+            }
+            Ok(self.next.clone())
+        }
+
+        async fn exit(&self, _context: &mut DynamicStateContext) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_step_recorder_records_states() {
+        let (first, second) = (DynamicState::new("first"), DynamicState::new("second"));
+        let mut machine = DynamicStateMachine::new();
+        machine.register_handler(
+            states::initial(),
+            Box::new(StepHandler {
+                next: first.clone(),
+                fail: false,
+            }),
+        );
+        machine.register_handler(
+            first.clone(),
+            Box::new(StepHandler {
+                next: second.clone(),
+                fail: false,
+            }),
+        );
+        machine.register_handler(
+            second.clone(),
+            Box::new(StepHandler {
+                next: states::completed(),
+                fail: true,
+            }),
+        );
+        machine.register_transitions(states::initial(), vec![first.clone()]);
+        machine.register_transitions(first, vec![second]);
+
+        machine
+            .get_context_mut()
+            .tags
+            .insert("env".to_string(), "ci".to_string());
+        let recorder = StepRecorder::new("machine");
+        machine.add_observer(Box::new(recorder.clone()));
+        let result = machine.run().await;
+        let report = recorder.finish(&result, &machine);
+
+        let steps: Vec<(&str, StepStatus)> = report
+            .steps
+            .iter()
+            .map(|step| (step.name.as_str(), step.status))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                ("initial", StepStatus::Passed),
+                ("first", StepStatus::Passed),
+                ("second", StepStatus::Failed),
+            ]
+        );
+        assert_eq!(report.tags["env"], "ci");
+        assert!(
+            report
+                .to_junit_xml()
+                .contains("  <properties>\n    <property name=\"env\" value=\"ci\"/>")
+        );
+        assert!(
+            report.steps[2]
+                .error
+                .as_deref()
+                .unwrap()
+                .contains("table missing")
+        );
+    }
+}
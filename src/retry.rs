@@ -4,13 +4,14 @@
 //! Provides exponential backoff, circuit breaker state management, and configurable retry strategies.
 
 use crate::errors::{ConnectError, RetryConfig};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 // RetryConfig is now defined in errors.rs
 
 /// Circuit breaker state
-#[derive(Debug, Clone, PartialEq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy)]
 pub enum CircuitState {
     Closed,   // Normal operation
     Open,     // Circuit is open, failing fast
@@ -41,6 +42,21 @@ impl Default for CircuitBreakerConfig {
     }
 }
 
+/// Called with the old and new state whenever a circuit breaker transitions
+pub type StateChangeCallback = Box<dyn Fn(CircuitState, CircuitState) + Send + Sync>;
+
+/// Counters of the calls a circuit breaker has seen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerStats {
+    pub state: CircuitState,
+    /// Every call, including those rejected while open
+    pub total_calls: u64,
+    pub successes: u64,
+    pub failures: u64,
+    /// Calls failed fast because the circuit was open
+    pub rejected: u64,
+}
+
 /// Circuit breaker implementation
 pub struct CircuitBreaker {
     config: CircuitBreakerConfig,
@@ -49,6 +65,11 @@ pub struct CircuitBreaker {
     last_failure_time: Arc<Mutex<Option<Instant>>>,
     success_count: Arc<Mutex<usize>>,
     last_state_change: Arc<Mutex<Instant>>,
+    on_state_change: Option<StateChangeCallback>,
+    total_calls: AtomicU64,
+    successes: AtomicU64,
+    failures: AtomicU64,
+    rejected: AtomicU64,
 }
 
 impl CircuitBreaker {
@@ -61,6 +82,33 @@ impl CircuitBreaker {
             last_failure_time: Arc::new(Mutex::new(None)),
             success_count: Arc::new(Mutex::new(0)),
             last_state_change: Arc::new(Mutex::new(Instant::now())),
+            on_state_change: None,
+            total_calls: AtomicU64::new(0),
+            successes: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    /// Call `callback` with the old and new state on every transition
+    #[must_use]
+    pub fn with_state_change_callback(
+        mut self,
+        callback: impl Fn(CircuitState, CircuitState) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_state_change = Some(Box::new(callback));
+        self
+    }
+
+    /// Current state and call counters
+    #[must_use]
+    pub fn stats(&self) -> CircuitBreakerStats {
+        CircuitBreakerStats {
+            state: self.get_state(),
+            total_calls: self.total_calls.load(Ordering::Relaxed),
+            successes: self.successes.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
         }
     }
 
@@ -84,6 +132,7 @@ impl CircuitBreaker {
         F: FnOnce() -> Result<T, E>,
         E: Into<ConnectError>,
     {
+        self.total_calls.fetch_add(1, Ordering::Relaxed);
         let state = self.get_state();
 
         match state {
@@ -92,6 +141,7 @@ impl CircuitBreaker {
                     self.set_state(CircuitState::HalfOpen);
                     self.call_half_open(f)
                 } else {
+                    self.rejected.fetch_add(1, Ordering::Relaxed);
                     Err(ConnectError::Connection(mysql::Error::server_disconnected()))
                 }
             }
@@ -139,17 +189,24 @@ impl CircuitBreaker {
     }
 
     fn set_state(&self, new_state: CircuitState) {
-        let mut state = self.state.lock().unwrap();
-        *state = new_state;
+        let old_state = std::mem::replace(&mut *self.state.lock().unwrap(), new_state);
         *self.last_state_change.lock().unwrap() = Instant::now();
+        if old_state != new_state {
+            tracing::info!("Circuit breaker {:?} -> {:?}", old_state, new_state);
+            if let Some(callback) = &self.on_state_change {
+                callback(old_state, new_state);
+            }
+        }
     }
 
     fn record_success(&self) {
+        self.successes.fetch_add(1, Ordering::Relaxed);
         let mut success_count = self.success_count.lock().unwrap();
         *success_count += 1;
     }
 
     fn record_failure(&self) {
+        self.failures.fetch_add(1, Ordering::Relaxed);
         let mut failure_count = self.failure_count.lock().unwrap();
         let mut last_failure_time = self.last_failure_time.lock().unwrap();
 
@@ -172,16 +229,23 @@ impl CircuitBreaker {
     }
 }
 
-/// Retry an operation with exponential backoff
-///
-/// # Errors
-///
-/// Returns an error if the operation fails after all retry attempts.
+/// Delay before the next attempt: `delay` scaled by the backoff multiplier, capped at `max_delay`
+#[must_use]
 #[allow(
     clippy::cast_possible_truncation,
     clippy::cast_sign_loss,
     clippy::cast_precision_loss
 )]
+pub fn next_backoff_delay(config: &RetryConfig, delay: Duration) -> Duration {
+    Duration::from_millis((delay.as_millis() as f64 * config.backoff_multiplier) as u64)
+        .min(config.max_delay)
+}
+
+/// Retry an operation with exponential backoff
+///
+/// # Errors
+///
+/// Returns an error if the operation fails after all retry attempts.
 pub async fn retry_with_backoff<F, T, E>(
     config: &RetryConfig,
     operation: F,
@@ -189,6 +253,26 @@ pub async fn retry_with_backoff<F, T, E>(
 where
     F: Fn() -> Result<T, E>,
     E: Into<ConnectError>,
+{
+    retry_with_backoff_if(config, operation, |_| true).await
+}
+
+/// Retry an operation with exponential backoff while `should_retry` accepts the error
+///
+/// An error rejected by `should_retry` is returned immediately.
+///
+/// # Errors
+///
+/// Returns the first non-retryable error, or the last error once all attempts fail.
+pub async fn retry_with_backoff_if<F, T, E, P>(
+    config: &RetryConfig,
+    operation: F,
+    should_retry: P,
+) -> Result<T, ConnectError>
+where
+    F: Fn() -> Result<T, E>,
+    E: Into<ConnectError>,
+    P: Fn(&ConnectError) -> bool,
 {
     let mut attempt = 0;
     let mut delay = config.base_delay;
@@ -199,18 +283,20 @@ where
         match operation() {
             Ok(result) => return Ok(result),
             Err(error) => {
-                if attempt >= config.max_retries {
-                    return Err(error.into());
+                let error = error.into();
+                if attempt >= config.max_retries || !should_retry(&error) {
+                    return Err(error);
                 }
+                tracing::debug!(
+                    "Attempt {} failed, retrying in {:?}: {}",
+                    attempt,
+                    delay,
+                    error
+                );
 
                 // Simple exponential backoff without jitter for now
                 tokio::time::sleep(delay).await;
-
-                // Exponential backoff
-                delay = Duration::from_millis(
-                    (delay.as_millis() as f64 * config.backoff_multiplier) as u64,
-                );
-                delay = delay.min(config.max_delay);
+                delay = next_backoff_delay(config, delay);
             }
         }
     }
@@ -325,6 +411,29 @@ mod tests {
         assert_eq!(counter.load(Ordering::SeqCst), 3);
     }
 
+    #[tokio::test]
+    async fn test_retry_with_backoff_if_stops_on_rejected_error() {
+        let config = RetryConfig {
+            max_retries: 5,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(10),
+            backoff_multiplier: 2.0,
+        };
+
+        let counter = AtomicUsize::new(0);
+        let result: Result<(), ConnectError> = retry_with_backoff_if(
+            &config,
+            || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Err(ConnectError::Authentication("access denied".to_string()))
+            },
+            |e| !matches!(e, ConnectError::Authentication(_)),
+        )
+        .await;
+        assert!(matches!(result, Err(ConnectError::Authentication(_))));
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+    }
+
     #[test]
     fn test_circuit_breaker() {
         let config = CircuitBreakerConfig {
@@ -354,4 +463,56 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(circuit_breaker.get_state(), CircuitState::Closed);
     }
+
+    #[test]
+    fn test_circuit_breaker_reports_transitions_and_stats() {
+        let config = CircuitBreakerConfig {
+            failure_threshold: 2,
+            failure_window: Duration::from_secs(60),
+            recovery_timeout: Duration::from_millis(50),
+            success_threshold: 1,
+        };
+        let transitions = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&transitions);
+        let circuit_breaker = CircuitBreaker::new(config)
+            .with_state_change_callback(move |old, new| recorded.lock().unwrap().push((old, new)));
+
+        for _ in 0..2 {
+            assert!(circuit_breaker.call(|| Err::<(), &str>("failure")).is_err());
+        }
+        assert_eq!(
+            *transitions.lock().unwrap(),
+            vec![(CircuitState::Closed, CircuitState::Open)]
+        );
+
+        // Rejected without running the call while open
+        let result = circuit_breaker.call(|| -> Result<(), &str> { panic!("must not run") });
+        assert!(result.is_err());
+        assert_eq!(
+            circuit_breaker.stats(),
+            CircuitBreakerStats {
+                state: CircuitState::Open,
+                total_calls: 3,
+                successes: 0,
+                failures: 2,
+                rejected: 1,
+            }
+        );
+
+        std::thread::sleep(Duration::from_millis(80));
+        assert!(circuit_breaker.call(|| Ok::<(), &str>(())).is_ok());
+        assert_eq!(
+            *transitions.lock().unwrap(),
+            vec![
+                (CircuitState::Closed, CircuitState::Open),
+                (CircuitState::Open, CircuitState::HalfOpen),
+                (CircuitState::HalfOpen, CircuitState::Closed),
+            ]
+        );
+        let stats = circuit_breaker.stats();
+        assert_eq!(stats.state, CircuitState::Closed);
+        assert_eq!(stats.total_calls, 4);
+        assert_eq!(stats.successes, 1);
+        assert_eq!(stats.rejected, 1);
+    }
 }
@@ -0,0 +1,405 @@
+//! # Schema Snapshots
+//!
+//! Utilities for capturing a table's schema from `information_schema` and
+//! diffing two snapshots. Used by DDL tests to assert that a statement did
+//! (or did not) change the schema.
+
+use crate::errors::{ConnectError, Result};
+use mysql::PooledConn;
+use mysql::prelude::*;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A single column as reported by `information_schema.COLUMNS`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnSchema {
+    pub name: String,
+    pub column_type: String,
+    pub is_nullable: bool,
+    pub default: Option<String>,
+    pub extra: String,
+}
+
+/// A single index as reported by `information_schema.STATISTICS`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexSchema {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub unique: bool,
+}
+
+/// Point-in-time snapshot of a table's columns and indexes
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TableSchema {
+    pub database: String,
+    pub table: String,
+    pub columns: Vec<ColumnSchema>,
+    pub indexes: Vec<IndexSchema>,
+}
+
+impl TableSchema {
+    /// Create an empty snapshot for the given table
+    pub fn new(database: &str, table: &str) -> Self {
+        Self {
+            database: database.to_string(),
+            table: table.to_string(),
+            columns: Vec::new(),
+            indexes: Vec::new(),
+        }
+    }
+
+    /// Look up a column by name (case-insensitive)
+    pub fn column(&self, name: &str) -> Option<&ColumnSchema> {
+        self.columns
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Look up an index by name (case-insensitive)
+    pub fn index(&self, name: &str) -> Option<&IndexSchema> {
+        self.indexes
+            .iter()
+            .find(|i| i.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// A single difference between two schema snapshots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaChange {
+    ColumnAdded(ColumnSchema),
+    ColumnRemoved(ColumnSchema),
+    ColumnModified {
+        before: ColumnSchema,
+        after: ColumnSchema,
+    },
+    IndexAdded(IndexSchema),
+    IndexRemoved(IndexSchema),
+    IndexModified {
+        before: IndexSchema,
+        after: IndexSchema,
+    },
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaChange::ColumnAdded(c) => write!(f, "column added: {} {}", c.name, c.column_type),
+            SchemaChange::ColumnRemoved(c) => write!(f, "column removed: {}", c.name),
+            SchemaChange::ColumnModified { before, after } => write!(
+                f,
+                "column modified: {} ({} -> {})",
+                before.name, before.column_type, after.column_type
+            ),
+            SchemaChange::IndexAdded(i) => write!(f, "index added: {}", i.name),
+            SchemaChange::IndexRemoved(i) => write!(f, "index removed: {}", i.name),
+            SchemaChange::IndexModified { before, .. } => {
+                write!(f, "index modified: {}", before.name)
+            }
+        }
+    }
+}
+
+/// Capture the current schema of `database.table`
+///
+/// # Errors
+///
+/// Returns an error if the `information_schema` queries fail or the table does not exist.
+pub fn snapshot_table(conn: &mut PooledConn, database: &str, table: &str) -> Result<TableSchema> {
+    try_snapshot_table(conn, database, table)?
+        .ok_or_else(|| ConnectError::Database(format!("Table {database}.{table} does not exist")))
+}
+
+/// Capture the current schema of `database.table`, or `None` if there is no
+/// such table
+///
+/// # Errors
+///
+/// Returns an error if the `information_schema` queries fail.
+pub fn try_snapshot_table(
+    conn: &mut PooledConn,
+    database: &str,
+    table: &str,
+) -> Result<Option<TableSchema>> {
+    let columns: Vec<(String, String, String, Option<String>, String)> = conn.exec(
+        "SELECT COLUMN_NAME, COLUMN_TYPE, IS_NULLABLE, COLUMN_DEFAULT, EXTRA \
+         FROM information_schema.COLUMNS \
+         WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? ORDER BY ORDINAL_POSITION",
+        (database, table),
+    )?;
+
+    if columns.is_empty() {
+        return Ok(None);
+    }
+
+    let index_rows: Vec<(String, String, i64)> = conn.exec(
+        "SELECT INDEX_NAME, COLUMN_NAME, NON_UNIQUE \
+         FROM information_schema.STATISTICS \
+         WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? ORDER BY INDEX_NAME, SEQ_IN_INDEX",
+        (database, table),
+    )?;
+
+    let mut schema = TableSchema::new(database, table);
+    schema.columns = columns
+        .into_iter()
+        .map(
+            |(name, column_type, nullable, default, extra)| ColumnSchema {
+                name,
+                column_type,
+                is_nullable: nullable.eq_ignore_ascii_case("YES"),
+                default,
+                extra,
+            },
+        )
+        .collect();
+    schema.indexes = group_index_rows(index_rows);
+
+    Ok(Some(schema))
+}
+
+/// Fetch the `SHOW CREATE TABLE` statement for `database.table`
+///
+/// # Errors
+///
+/// Returns an error if the query fails or the table does not exist.
+pub fn show_create_table(conn: &mut PooledConn, database: &str, table: &str) -> Result<String> {
+    let row: Option<(String, String)> =
+        conn.query_first(format!("SHOW CREATE TABLE `{database}`.`{table}`"))?;
+    row.map(|(_, statement)| statement)
+        .ok_or_else(|| ConnectError::Database(format!("Table {database}.{table} does not exist")))
+}
+
+fn group_index_rows(rows: Vec<(String, String, i64)>) -> Vec<IndexSchema> {
+    let mut indexes: BTreeMap<String, IndexSchema> = BTreeMap::new();
+    for (name, column, non_unique) in rows {
+        indexes
+            .entry(name.clone())
+            .or_insert_with(|| IndexSchema {
+                name,
+                columns: Vec::new(),
+                unique: non_unique == 0,
+            })
+            .columns
+            .push(column);
+    }
+    indexes.into_values().collect()
+}
+
+/// Compute the differences between two snapshots of the same table
+pub fn diff_schemas(before: &TableSchema, after: &TableSchema) -> Vec<SchemaChange> {
+    let mut changes = Vec::new();
+
+    for old in &before.columns {
+        match after.column(&old.name) {
+            None => changes.push(SchemaChange::ColumnRemoved(old.clone())),
+            Some(new) if new != old => changes.push(SchemaChange::ColumnModified {
+                before: old.clone(),
+                after: new.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for new in &after.columns {
+        if before.column(&new.name).is_none() {
+            changes.push(SchemaChange::ColumnAdded(new.clone()));
+        }
+    }
+
+    for old in &before.indexes {
+        match after.index(&old.name) {
+            None => changes.push(SchemaChange::IndexRemoved(old.clone())),
+            Some(new) if new != old => changes.push(SchemaChange::IndexModified {
+                before: old.clone(),
+                after: new.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for new in &after.indexes {
+        if before.index(&new.name).is_none() {
+            changes.push(SchemaChange::IndexAdded(new.clone()));
+        }
+    }
+
+    changes
+}
+
+/// Assert that two snapshots describe the same schema
+///
+/// # Errors
+///
+/// Returns `ConnectError::Validation` listing every difference if the schemas differ.
+pub fn assert_schema_unchanged(before: &TableSchema, after: &TableSchema) -> Result<()> {
+    let changes = diff_schemas(before, after);
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let details = changes
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ");
+    Err(ConnectError::Validation(format!(
+        "Schema of {}.{} changed: {details}",
+        before.database, before.table
+    )))
+}
+
+/// A table that must exist before tests run, e.g. a known probe target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableSpec {
+    pub name: String,
+    /// Column definitions as written inside `CREATE TABLE (...)`
+    pub columns: String,
+}
+
+impl TableSpec {
+    /// Build a spec from a table name and a column definition list
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConnectError::CliArgument` if the name is not a plain identifier
+    /// or the column list is empty.
+    pub fn new(name: &str, columns: &str) -> Result<Self> {
+        let name = name.trim();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        {
+            return Err(ConnectError::CliArgument(format!(
+                "Invalid table name '{name}': use letters, digits, '_' or '$'"
+            )));
+        }
+        let columns = columns.trim();
+        if columns.is_empty() {
+            return Err(ConnectError::CliArgument(format!(
+                "Column spec for table '{name}' must not be empty"
+            )));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            columns: columns.to_string(),
+        })
+    }
+
+    /// Statement that creates the table if it does not already exist
+    #[must_use]
+    pub fn create_statement(&self) -> String {
+        format!(
+            "CREATE TABLE IF NOT EXISTS `{}` ({})",
+            self.name, self.columns
+        )
+    }
+
+    /// Create the table in `database` if it does not already exist
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConnectError::Configuration` if there is no `database` (see
+    /// [`crate::query::qualify_table`]), or `ConnectError::Database` if the
+    /// server rejects the statement.
+    pub fn ensure<C: Queryable>(&self, conn: &mut C, database: Option<&str>) -> Result<()> {
+        let table = crate::query::qualify_table(database, &self.name)?;
+        let sql = format!("CREATE TABLE IF NOT EXISTS {table} ({})", self.columns);
+        conn.query_drop(sql).map_err(|e| {
+            ConnectError::Database(format!("Failed to ensure table '{}': {e}", self.name))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn column(name: &str, column_type: &str) -> ColumnSchema {
+        ColumnSchema {
+            name: name.to_string(),
+            column_type: column_type.to_string(),
+            is_nullable: true,
+            default: None,
+            extra: String::new(),
+        }
+    }
+
+    fn sample_schema() -> TableSchema {
+        let mut schema = TableSchema::new("test", "t1");
+        schema.columns = vec![column("id", "int"), column("name", "varchar(64)")];
+        schema.indexes = vec![IndexSchema {
+            name: "PRIMARY".to_string(),
+            columns: vec!["id".to_string()],
+            unique: true,
+        }];
+        schema
+    }
+
+    #[test]
+    fn test_identical_schemas_are_unchanged() {
+        let before = sample_schema();
+        let after = sample_schema();
+        assert!(diff_schemas(&before, &after).is_empty());
+        assert!(assert_schema_unchanged(&before, &after).is_ok());
+    }
+
+    #[test]
+    fn test_added_column_is_reported() {
+        let before = sample_schema();
+        let mut after = sample_schema();
+        after.columns.push(column("email", "varchar(255)"));
+
+        let changes = diff_schemas(&before, &after);
+        assert_eq!(changes.len(), 1);
+        assert!(matches!(&changes[0], SchemaChange::ColumnAdded(c) if c.name == "email"));
+
+        let err = assert_schema_unchanged(&before, &after).unwrap_err();
+        assert!(err.to_string().contains("column added: email"));
+    }
+
+    #[test]
+    fn test_modified_column_and_removed_index_are_reported() {
+        let before = sample_schema();
+        let mut after = sample_schema();
+        after.columns[1].column_type = "text".to_string();
+        after.indexes.clear();
+
+        let changes = diff_schemas(&before, &after);
+        assert_eq!(changes.len(), 2);
+        assert!(
+            changes
+                .iter()
+                .any(|c| matches!(c, SchemaChange::ColumnModified { .. }))
+        );
+        assert!(
+            changes
+                .iter()
+                .any(|c| matches!(c, SchemaChange::IndexRemoved(_)))
+        );
+    }
+
+    #[test]
+    fn test_group_index_rows() {
+        let rows = vec![
+            ("idx_ab".to_string(), "a".to_string(), 1),
+            ("idx_ab".to_string(), "b".to_string(), 1),
+            ("PRIMARY".to_string(), "id".to_string(), 0),
+        ];
+        let indexes = group_index_rows(rows);
+        assert_eq!(indexes.len(), 2);
+        assert_eq!(indexes[0].name, "PRIMARY");
+        assert!(indexes[0].unique);
+        assert_eq!(indexes[1].columns, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_table_spec_create_statement() {
+        let spec = TableSpec::new("probe", "id BIGINT PRIMARY KEY, note VARCHAR(64)").unwrap();
+        assert_eq!(
+            spec.create_statement(),
+            "CREATE TABLE IF NOT EXISTS `probe` (id BIGINT PRIMARY KEY, note VARCHAR(64))"
+        );
+
+        assert!(TableSpec::new("bad name", "id INT").is_err());
+        assert!(TableSpec::new("t`; DROP", "id INT").is_err());
+        assert!(TableSpec::new("probe", "  ").is_err());
+    }
+}
@@ -0,0 +1,266 @@
+//! # SQL Scripts
+//!
+//! Split a SQL file into statements and run them one by one. Statements end
+//! at the current delimiter, `;` by default. As in the MySQL client, a
+//! `DELIMITER <token>` line changes the delimiter for the rest of the file,
+//! so stored procedure and trigger bodies containing `;` can be written with
+//! e.g. `DELIMITER //` and terminated with `//`, then `DELIMITER ;` switches
+//! back. Delimiters inside quoted strings and comments are ignored.
+
+use crate::errors::{ConnectError, Result};
+use crate::query::RowLimit;
+use crate::recording::QueryExecutor;
+use std::path::Path;
+
+/// Statement delimiter at the start of every script
+pub const DEFAULT_DELIMITER: &str = ";";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scan {
+    Code,
+    Quoted(char),
+    LineComment,
+    BlockComment,
+}
+
+/// Split `script` into statements, honouring `DELIMITER` directives
+///
+/// Statements are returned without their delimiter and trimmed; pieces that
+/// contain only whitespace and comments are dropped. A `DELIMITER` directive
+/// is only recognized at the start of a line between statements.
+///
+/// # Errors
+///
+/// Returns `ConnectError::Parse` if a `DELIMITER` line has no delimiter or a
+/// quoted string or identifier is not closed.
+pub fn split_statements(script: &str) -> Result<Vec<String>> {
+    let mut statements = Vec::new();
+    let mut delimiter = DEFAULT_DELIMITER.to_string();
+    let mut current = String::new();
+    let mut has_code = false;
+    let mut scan = Scan::Code;
+    let mut at_line_start = true;
+    let mut rest = script;
+
+    while !rest.is_empty() {
+        if scan == Scan::Code
+            && at_line_start
+            && !has_code
+            && let Some((new_delimiter, after)) = delimiter_directive(rest)?
+        {
+            delimiter = new_delimiter;
+            current.clear();
+            rest = after;
+            continue;
+        }
+        if scan == Scan::Code && rest.starts_with(delimiter.as_str()) {
+            if has_code {
+                statements.push(current.trim().to_string());
+            }
+            current.clear();
+            has_code = false;
+            at_line_start = false;
+            rest = &rest[delimiter.len()..];
+            continue;
+        }
+
+        let mut chars = rest.chars();
+        let Some(c) = chars.next() else { break };
+        let next = chars.next();
+        let mut consumed = c.len_utf8();
+        match scan {
+            Scan::Code => match c {
+                '\'' | '"' | '`' => {
+                    scan = Scan::Quoted(c);
+                    has_code = true;
+                }
+                '#' => scan = Scan::LineComment,
+                '-' if rest.starts_with("--")
+                    && rest[2..].chars().next().is_none_or(char::is_whitespace) =>
+                {
+                    scan = Scan::LineComment;
+                }
+                '/' if next == Some('*') => {
+                    scan = Scan::BlockComment;
+                    consumed += 1;
+                    // `/*! ... */` is executed by MySQL-compatible servers
+                    has_code |= rest[2..].starts_with('!');
+                }
+                c if !c.is_whitespace() => has_code = true,
+                _ => {}
+            },
+            Scan::Quoted(quote) => {
+                if c == '\\' && quote != '`' {
+                    consumed += next.map_or(0, char::len_utf8);
+                } else if c == quote {
+                    scan = Scan::Code;
+                }
+            }
+            Scan::LineComment => {
+                if c == '\n' {
+                    scan = Scan::Code;
+                }
+            }
+            Scan::BlockComment => {
+                if c == '*' && next == Some('/') {
+                    scan = Scan::Code;
+                    consumed += 1;
+                }
+            }
+        }
+        current.push_str(&rest[..consumed]);
+        at_line_start = c == '\n';
+        rest = &rest[consumed..];
+    }
+
+    if let Scan::Quoted(quote) = scan {
+        return Err(ConnectError::Parse(format!(
+            "Unterminated {quote} quote in SQL script"
+        )));
+    }
+    if has_code {
+        statements.push(current.trim().to_string());
+    }
+    Ok(statements)
+}
+
+/// If `rest` starts with a `DELIMITER <token>` line, the token and the input after the line
+fn delimiter_directive(rest: &str) -> Result<Option<(String, &str)>> {
+    let (line, after) = rest.split_once('\n').unwrap_or((rest, ""));
+    let line = line.trim();
+    let Some(keyword) = line.get(..9) else {
+        return Ok(None);
+    };
+    let argument = &line[9..];
+    if !keyword.eq_ignore_ascii_case("delimiter")
+        || !(argument.is_empty() || argument.starts_with(char::is_whitespace))
+    {
+        return Ok(None);
+    }
+    match argument.split_whitespace().next() {
+        Some(token) => Ok(Some((token.to_string(), after))),
+        None => Err(ConnectError::Parse(
+            "DELIMITER requires a delimiter, e.g. `DELIMITER //`".to_string(),
+        )),
+    }
+}
+
+/// Run every statement of `script` on `conn`, discarding any rows
+///
+/// Stops at the first failing statement. Returns the number of statements run.
+///
+/// # Errors
+///
+/// Returns an error if the script cannot be split or a statement fails.
+pub fn run_script<C: QueryExecutor>(conn: &mut C, script: &str) -> Result<usize> {
+    let statements = split_statements(script)?;
+    for (i, sql) in statements.iter().enumerate() {
+        tracing::debug!("Running statement {}: {}", i + 1, sql);
+        if let Err(e) = conn.run_query(sql, RowLimit::Max(Some(0))) {
+            tracing::error!(
+                "Statement {} of {} failed: {}",
+                i + 1,
+                statements.len(),
+                sql
+            );
+            return Err(e);
+        }
+    }
+    Ok(statements.len())
+}
+
+/// Read `path` and run it with [`run_script`]
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read, split or run.
+pub fn run_script_file<C: QueryExecutor>(conn: &mut C, path: &Path) -> Result<usize> {
+    let script = std::fs::read_to_string(path)?;
+    run_script(conn, &script)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MockConnection;
+
+    #[test]
+    fn test_split_on_default_delimiter() {
+        let statements = split_statements(
+            "CREATE TABLE t (a INT);\n\
+             INSERT INTO t VALUES (1), (2);\n\
+             SELECT 'a;b', \"c;d\", `e;f` FROM t -- trailing; comment\n;\n\
+             # only a comment;\n\
+             /* block; comment */\n",
+        )
+        .unwrap();
+        assert_eq!(
+            statements,
+            vec![
+                "CREATE TABLE t (a INT)",
+                "INSERT INTO t VALUES (1), (2)",
+                "SELECT 'a;b', \"c;d\", `e;f` FROM t -- trailing; comment",
+            ]
+        );
+        assert_eq!(split_statements("SELECT 1").unwrap(), vec!["SELECT 1"]);
+        assert_eq!(
+            split_statements("SELECT 'it''s; \\'quoted\\';'; SELECT 2").unwrap(),
+            vec!["SELECT 'it''s; \\'quoted\\';'", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn test_delimiter_change_and_switch_back() {
+        let script = "\
+DROP PROCEDURE IF EXISTS fill;
+DELIMITER //
+CREATE PROCEDURE fill(n INT)
+BEGIN
+  DECLARE i INT DEFAULT 0;
+  WHILE i < n DO
+    INSERT INTO t VALUES (i);
+    SET i = i + 1;
+  END WHILE;
+END //
+DELIMITER ;
+CALL fill(10);
+delimiter $$
+SELECT 1$$ SELECT 2 $$
+DELIMITER ;
+SELECT 3;
+";
+        let statements = split_statements(script).unwrap();
+        assert_eq!(statements.len(), 6);
+        assert_eq!(statements[0], "DROP PROCEDURE IF EXISTS fill");
+        assert!(statements[1].starts_with("CREATE PROCEDURE fill(n INT)\nBEGIN"));
+        assert!(statements[1].ends_with("END WHILE;\nEND"));
+        assert_eq!(statements[1].matches(';').count(), 4);
+        assert_eq!(
+            &statements[2..],
+            ["CALL fill(10)", "SELECT 1", "SELECT 2", "SELECT 3"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_scripts() {
+        assert!(split_statements("DELIMITER\nSELECT 1;").is_err());
+        let err = split_statements("SELECT 'unterminated;").unwrap_err();
+        assert!(err.to_string().contains("Unterminated ' quote"));
+        // Not a directive: DELIMITER must stand alone at the start of a line
+        assert_eq!(
+            split_statements("SELECT delimiter_col FROM t;").unwrap(),
+            vec!["SELECT delimiter_col FROM t"]
+        );
+    }
+
+    #[test]
+    fn test_run_script_stops_at_first_failure() {
+        let mut conn = MockConnection::default();
+        assert_eq!(run_script(&mut conn, "SELECT 1; SELECT 2;").unwrap(), 2);
+        assert_eq!(conn.statements(), vec!["SELECT 1", "SELECT 2"]);
+
+        let mut conn = MockConnection::failing_on("bad");
+        assert!(run_script(&mut conn, "SELECT 1; SELECT bad; SELECT 3;").is_err());
+        assert_eq!(conn.statements(), vec!["SELECT 1"]);
+    }
+}
@@ -6,9 +6,13 @@
 //! Note: For extensible state handling, use the dynamic state machine system.
 //! The core StateMachine now only supports Initial, Completed, and Error states.
 
-use crate::connection::{create_connection_pool, parse_connection_string};
-use crate::errors::Result;
-use crate::state_machine::{State, StateContext, StateHandler};
+use crate::capabilities::require_tidb_version;
+use crate::connection::{
+    create_connection_with_timeouts, is_connection_drop, parse_connection_string,
+};
+use crate::endpoints::parse_hosts;
+use crate::errors::{ConnectError, Result};
+use crate::state_machine::{State, StateContext, StateHandler, restore_session};
 use async_trait::async_trait;
 use mysql::prelude::*;
 use mysql::{Error, Row};
@@ -54,6 +58,12 @@ impl ParsingConfigHandler {
             database,
         }
     }
+
+    /// Handler for the settings in `params`
+    #[must_use]
+    pub fn from_params(params: crate::common_states::ConnParams) -> Self {
+        Self::new(params.host, params.user, params.password, params.database)
+    }
 }
 
 #[async_trait]
@@ -64,8 +74,17 @@ impl StateHandler for ParsingConfigHandler {
     }
 
     async fn execute(&self, context: &mut StateContext) -> Result<State> {
+        // A comma-separated host list configures failover endpoints
+        let host = if self.host.contains(',') {
+            context.endpoints = parse_hosts(&self.host)?;
+            context.active_endpoint = Some(0);
+            context.endpoints[0].clone()
+        } else {
+            self.host.clone()
+        };
+
         // Parse host and port from connection string
-        let (host, port) = parse_connection_string(&self.host)?;
+        let (host, port) = parse_connection_string(&host)?;
 
         // Store configuration in context
         context.host = host;
@@ -103,18 +122,30 @@ impl StateHandler for ConnectingHandler {
             context.host, context.port, context.username
         );
 
-        // Create connection pool
-        let pool = create_connection_pool(
+        let connected = create_connection_with_timeouts(
             &context.host,
             context.port,
             &context.username,
             &context.password,
             context.database.as_deref(),
-        )?;
+            context.tls.as_ref(),
+            &context.timeouts,
+        );
 
-        // Get a connection from the pool
-        let conn = pool.get_conn()?;
-        context.connection = Some(conn);
+        match connected {
+            Ok(mut conn) => {
+                restore_session(&mut conn, &context.session_sql)?;
+                context.connection = Some(conn);
+            }
+            Err(e) if context.endpoints.len() > 1 => {
+                info!(
+                    "Connection to {} failed ({}), trying other endpoints",
+                    context.host, e
+                );
+                context.failover()?;
+            }
+            Err(e) => return Err(e),
+        }
 
         info!(
             "Connection established successfully to {}:{}",
@@ -183,25 +214,37 @@ impl StateHandler for VerifyingDatabaseHandler {
     }
 
     async fn execute(&self, context: &mut StateContext) -> Result<State> {
+        context.no_database = context.database.is_none();
+        if let Some(ref spec) = context.ensure_table
+            && let Err(e) = context.qualify_table(&spec.name)
+        {
+            context.set_error(e.to_string());
+            return Err(e);
+        }
+
         if let Some(ref mut conn) = context.connection {
             if let Some(ref db_name) = context.database {
                 // Test if we can access the specified database
                 let query = format!("USE `{db_name}`");
-                match conn.query_drop(query) {
-                    Ok(()) => {
-                        println!("✓ Database '{db_name}' verified");
-                        Ok(State::Completed)
-                    }
-                    Err(e) => {
-                        context.set_error(format!("Database verification failed: {e}"));
-                        Err(format!("Database verification failed: {e}").into())
-                    }
+                if let Err(e) = conn.query_drop(query) {
+                    context.set_error(format!("Database verification failed: {e}"));
+                    return Err(format!("Database verification failed: {e}").into());
                 }
+                println!("✓ Database '{db_name}' verified");
             } else {
-                // No specific database specified, just proceed
-                println!("✓ No specific database specified, proceeding...");
-                Ok(State::Completed)
+                // Later queries must use database.table names
+                println!("⚠ No database specified; queries must use fully-qualified table names");
             }
+
+            if let Some(ref spec) = context.ensure_table {
+                debug!("Ensuring table: {}", spec.create_statement());
+                if let Err(e) = spec.ensure(conn, context.database.as_deref()) {
+                    context.set_error(e.to_string());
+                    return Err(e);
+                }
+                println!("✓ Table '{}' ensured", spec.name);
+            }
+            Ok(State::Completed)
         } else {
             let error_msg = "No connection available for database verification";
             context.set_error(error_msg.to_string());
@@ -232,6 +275,10 @@ impl StateHandler for GettingVersionHandler {
                     context.server_version = Some(version.clone());
                     info!("Server version: {}", version);
                     println!("✓ Server version: {version}");
+                    if let Err(e) = require_tidb_version(&version, context.min_tidb_version) {
+                        context.set_error(e.to_string());
+                        return Err(e);
+                    }
                     Ok(State::Completed)
                 }
                 Ok(None) => {
@@ -283,6 +330,10 @@ impl StateHandler for NextStateVersionHandler {
                     context.server_version = Some(version.clone());
                     info!("Server version: {}", version);
                     println!("✓ Server version: {version}");
+                    if let Err(e) = require_tidb_version(&version, context.min_tidb_version) {
+                        context.set_error(e.to_string());
+                        return Err(e);
+                    }
                     Ok(self.next_state.clone())
                 }
                 Ok(None) => {
@@ -307,3 +358,127 @@ impl StateHandler for NextStateVersionHandler {
         Ok(())
     }
 }
+
+/// Handler that runs a statement, reconnecting once if the server dropped the
+/// connection (e.g. it restarted) so the workflow can resume
+///
+/// The reconnect re-applies the context's `session_sql` before the
+/// statement is retried.
+pub struct ResumingQueryHandler {
+    sql: String,
+    next_state: State,
+}
+
+impl ResumingQueryHandler {
+    #[must_use]
+    pub fn new(sql: impl Into<String>, next_state: State) -> Self {
+        Self {
+            sql: sql.into(),
+            next_state,
+        }
+    }
+}
+
+#[async_trait]
+impl StateHandler for ResumingQueryHandler {
+    async fn enter(&self, _context: &mut StateContext) -> Result<State> {
+        Ok(State::Initial)
+    }
+
+    async fn execute(&self, context: &mut StateContext) -> Result<State> {
+        let Some(ref mut conn) = context.connection else {
+            return Err("No connection available for running the query".into());
+        };
+        match conn.query_drop(&self.sql).map_err(ConnectError::from) {
+            Ok(()) => return Ok(self.next_state.clone()),
+            Err(e) if is_connection_drop(&e) => {
+                info!("Connection dropped ({}), reconnecting", e);
+                println!("⚠ Connection dropped, reconnecting...");
+            }
+            Err(e) => return Err(e),
+        }
+
+        context.reconnect()?;
+        if let Some(ref mut conn) = context.connection {
+            conn.query_drop(&self.sql)?;
+        }
+        println!("✓ Reconnected and resumed");
+        Ok(self.next_state.clone())
+    }
+
+    async fn exit(&self, _context: &mut StateContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::TableSpec;
+
+    #[tokio::test]
+    async fn test_no_database_flag_is_set_and_surfaced() {
+        let mut context = StateContext::new();
+        context.ensure_table = Some(TableSpec::new("events", "id INT PRIMARY KEY").unwrap());
+
+        let err = VerifyingDatabaseHandler
+            .execute(&mut context)
+            .await
+            .unwrap_err();
+        assert!(context.no_database);
+        assert!(matches!(err, ConnectError::Configuration(_)));
+        assert!(err.to_string().contains("pass -d <database>"));
+        assert!(
+            context
+                .error_message
+                .as_deref()
+                .is_some_and(|m| m.contains("no database configured"))
+        );
+
+        let err = context.qualify_table("events").unwrap_err();
+        assert!(err.to_string().contains("database.events"));
+        assert_eq!(context.qualify_table("app.events").unwrap(), "app.events");
+
+        context.database = Some("app".to_string());
+        assert_eq!(context.qualify_table("events").unwrap(), "`app`.`events`");
+    }
+
+    #[tokio::test]
+    async fn test_workflow_resumes_after_connection_drop() {
+        let Some(db) = crate::test_support::live_db("connection recovery test") else {
+            return;
+        };
+        let mut machine = crate::state_machine::StateMachine::new();
+        let context = machine.get_context_mut();
+        db.configure_context(context);
+        context.session_sql = vec!["SET @recovery_marker = 42".to_string()];
+        ConnectingHandler.execute(context).await.unwrap();
+
+        // Kill the session from another connection, as a server restart would
+        let conn = context.connection.as_mut().unwrap();
+        let dropped_id: u64 = conn.query_first("SELECT CONNECTION_ID()").unwrap().unwrap();
+        let mut killer = db.pool().get_conn().unwrap();
+        killer
+            .query_drop(format!("KILL CONNECTION {dropped_id}"))
+            .unwrap();
+
+        machine.register_handler(
+            State::Initial,
+            Box::new(ResumingQueryHandler::new(
+                "SELECT @recovery_marker",
+                State::Completed,
+            )),
+        );
+        machine.run().await.unwrap();
+
+        let context = machine.get_context_mut();
+        assert_eq!(context.reconnects, 1);
+        let conn = context.connection.as_mut().unwrap();
+        let (id, marker): (u64, Option<i64>) = conn
+            .query_first("SELECT CONNECTION_ID(), @recovery_marker")
+            .unwrap()
+            .unwrap();
+        assert_ne!(id, dropped_id);
+        assert_eq!(marker, Some(42));
+    }
+}
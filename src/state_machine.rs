@@ -4,10 +4,39 @@
 //! Provides a flexible framework for defining and executing state-based operations
 //! with support for async handlers and context management.
 
+use crate::config::Timeouts;
+use crate::connection::{create_connection_with_timeouts, parse_host_port};
+use crate::endpoints::select_next_endpoint;
 use crate::errors::ConnectError;
+use crate::query::RowLimit;
+use crate::recording::QueryExecutor;
 use mysql::PooledConn;
 use std::any::Any;
 use std::fmt;
+use std::time::Duration;
+
+/// Deadline for running `state` and the timeout message if it passes
+///
+/// Whichever of the run budget (`run_deadline`, started from the total
+/// timeout) and the per-state budget runs out first; `None` if neither is set.
+pub(crate) fn state_deadline(
+    run_deadline: Option<(tokio::time::Instant, Duration)>,
+    state_timeout: Option<Duration>,
+    state: &dyn fmt::Display,
+) -> Option<(tokio::time::Instant, String)> {
+    let total_limit = run_deadline.map(|(deadline, timeout)| {
+        let message = format!("Total timeout of {timeout:?} exceeded in state {state}");
+        (deadline, message)
+    });
+    let state_limit = state_timeout.map(|timeout| {
+        let message = format!("State timeout of {timeout:?} exceeded in state {state}");
+        (tokio::time::Instant::now() + timeout, message)
+    });
+    match (total_limit, state_limit) {
+        (Some(total), Some(state)) => Some(if state.0 < total.0 { state } else { total }),
+        (total, state) => total.or(state),
+    }
+}
 
 /// Represents the different states in the `TiDB` connection process
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -47,6 +76,26 @@ pub struct StateContext {
     pub connection: Option<PooledConn>,
     pub server_version: Option<String>,
     pub error_message: Option<String>,
+    /// Configured `host:port` endpoints for failover (empty when only `host` is used)
+    pub endpoints: Vec<String>,
+    /// Index into `endpoints` of the endpoint currently connected to
+    pub active_endpoint: Option<usize>,
+    /// Table created during database verification if it does not exist
+    pub ensure_table: Option<crate::schema::TableSpec>,
+    /// TLS settings used when connecting
+    pub tls: Option<crate::connection::TlsConfig>,
+    /// Oldest `TiDB` release the version check accepts (`--require-tidb-version`)
+    pub min_tidb_version: Option<crate::capabilities::TidbVersion>,
+    /// Set by database verification when no database is configured, so
+    /// unqualified table names would fail with "No database selected"
+    pub no_database: bool,
+    /// Session SQL (`SET SESSION ...`, `--init-sql`) run on every new
+    /// connection, so a reconnect after failover matches the old session
+    pub session_sql: Vec<String>,
+    /// Times the connection was re-established after it was dropped
+    pub reconnects: u32,
+    /// Connect and statement timeouts for every connection made from this context
+    pub timeouts: Timeouts,
     // Handler-specific context storage
     handler_contexts: std::collections::HashMap<State, Box<dyn Any + Send + Sync>>,
 }
@@ -69,10 +118,135 @@ impl StateContext {
             connection: None,
             server_version: None,
             error_message: None,
+            endpoints: Vec::new(),
+            active_endpoint: None,
+            ensure_table: None,
+            tls: None,
+            min_tidb_version: None,
+            no_database: false,
+            session_sql: Vec::new(),
+            reconnects: 0,
+            timeouts: Timeouts::default(),
             handler_contexts: std::collections::HashMap::new(),
         }
     }
 
+    /// The endpoint currently connected to, if endpoints are configured
+    #[must_use]
+    pub fn active_endpoint_name(&self) -> Option<&str> {
+        self.active_endpoint
+            .and_then(|i| self.endpoints.get(i))
+            .map(String::as_str)
+    }
+
+    /// Reconnect to the next healthy configured endpoint (round-robin)
+    ///
+    /// On success the new connection replaces the old one and `host`, `port`
+    /// and `active_endpoint` are updated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no endpoints are configured or none accept a connection.
+    pub fn failover(&mut self) -> Result<(), ConnectError> {
+        if self.endpoints.is_empty() {
+            return Err(ConnectError::Configuration(
+                "Failover requires multiple endpoints (--hosts)".to_string(),
+            ));
+        }
+
+        self.connection = None;
+        let (username, password) = (self.username.clone(), self.password.clone());
+        let database = self.database.clone();
+        let tls = self.tls.clone();
+        let timeouts = self.timeouts.clone();
+        let selected = select_next_endpoint(&self.endpoints, self.active_endpoint, |endpoint| {
+            let (host, port) = parse_host_port(endpoint)?;
+            create_connection_with_timeouts(
+                &host,
+                port,
+                &username,
+                &password,
+                database.as_deref(),
+                tls.as_ref(),
+                &timeouts,
+            )
+        })?;
+
+        let mut connection = selected.connection;
+        restore_session(&mut connection, &self.session_sql)?;
+
+        let (host, port) = parse_host_port(&selected.endpoint)?;
+        self.host = host;
+        self.port = port;
+        self.active_endpoint = Some(selected.index);
+        self.connection = Some(connection);
+        Ok(())
+    }
+
+    /// Replace a dropped connection and re-apply `session_sql` on the new one
+    ///
+    /// Fails over to the next endpoint when several are configured, otherwise
+    /// connects to `host` again, e.g. once a restarted server is back.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no connection can be established or restoring the
+    /// session fails.
+    pub fn reconnect(&mut self) -> Result<(), ConnectError> {
+        if self.endpoints.is_empty() {
+            self.connection = None;
+            let mut conn = create_connection_with_timeouts(
+                &self.host,
+                self.port,
+                &self.username,
+                &self.password,
+                self.database.as_deref(),
+                self.tls.as_ref(),
+                &self.timeouts,
+            )?;
+            restore_session(&mut conn, &self.session_sql)?;
+            self.connection = Some(conn);
+        } else {
+            self.failover()?;
+        }
+        self.reconnects += 1;
+        Ok(())
+    }
+
+    /// Check the active connection and reconnect if it has died
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the connection is dead and cannot be re-established.
+    pub fn ensure_connection(&mut self) -> Result<(), ConnectError> {
+        let healthy = self
+            .connection
+            .as_mut()
+            .is_some_and(|conn| conn.as_mut().ping().is_ok());
+        if healthy {
+            return Ok(());
+        }
+
+        tracing::warn!(
+            "Connection to {} lost, reconnecting",
+            self.active_endpoint_name().unwrap_or(&self.host)
+        );
+        self.reconnect()
+    }
+
+    /// `table` as a name that resolves without a current database
+    ///
+    /// Names already in `database.table` form are returned unchanged; bare
+    /// names are qualified with the configured database.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConnectError::Configuration` if `table` is unqualified and no
+    /// database is configured.
+    pub fn qualify_table(&self, table: &str) -> Result<String, ConnectError> {
+        crate::query::qualify_table(self.database.as_deref(), table)
+    }
+
     pub fn set_error(&mut self, error: String) {
         self.error_message = Some(error);
     }
@@ -135,6 +309,25 @@ impl StateContext {
     }
 }
 
+/// Re-run recorded session SQL on a new connection, in order
+///
+/// # Errors
+///
+/// Returns the error of the first statement that fails.
+pub fn restore_session<C: QueryExecutor>(
+    conn: &mut C,
+    statements: &[String],
+) -> Result<(), ConnectError> {
+    for sql in statements {
+        tracing::debug!("Restoring session: {}", sql);
+        if let Err(e) = conn.run_query(sql, RowLimit::Max(Some(0))) {
+            tracing::error!("Failed to restore session with `{}`: {}", sql, e);
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
 /// Trait for state handlers
 #[async_trait::async_trait]
 pub trait StateHandler {
@@ -148,6 +341,10 @@ pub struct StateMachine {
     current_state: State,
     context: StateContext,
     handlers: std::collections::HashMap<State, Box<dyn StateHandler + Send + Sync>>,
+    /// Budget for the whole run, across all states
+    total_timeout: Option<Duration>,
+    /// Budget for each state
+    state_timeout: Option<Duration>,
 }
 
 impl Default for StateMachine {
@@ -163,9 +360,42 @@ impl StateMachine {
             current_state: State::Initial,
             context: StateContext::new(),
             handlers: std::collections::HashMap::new(),
+            total_timeout: None,
+            state_timeout: None,
         }
     }
 
+    /// Bound the total runtime of [`StateMachine::run`]
+    ///
+    /// Once the budget is exhausted the running handler is aborted and the
+    /// machine moves to the error state, whichever state it was in.
+    pub fn set_total_timeout(&mut self, timeout: Duration) {
+        self.total_timeout = Some(timeout);
+    }
+
+    /// Bound the runtime of each state (enter, execute and exit)
+    pub fn set_state_timeout(&mut self, timeout: Duration) {
+        self.state_timeout = Some(timeout);
+    }
+
+    /// Use the total and per-state timeouts of `timeouts`, and its connect
+    /// and statement timeouts for connections made from the context
+    pub fn apply_timeouts(&mut self, timeouts: &Timeouts) {
+        self.total_timeout = timeouts.total();
+        self.state_timeout = timeouts.state();
+        self.context.timeouts = timeouts.clone();
+    }
+
+    #[must_use]
+    pub fn total_timeout(&self) -> Option<Duration> {
+        self.total_timeout
+    }
+
+    #[must_use]
+    pub fn state_timeout(&self) -> Option<Duration> {
+        self.state_timeout
+    }
+
     pub fn register_handler(&mut self, state: State, handler: Box<dyn StateHandler + Send + Sync>) {
         self.handlers.insert(state, handler);
     }
@@ -185,24 +415,53 @@ impl StateMachine {
 
     /// Run the state machine
     ///
+    /// Before each state the context's connection, if any, is checked and
+    /// re-established (with `session_sql` replayed) if the server dropped it.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the state machine execution fails.
+    /// Returns an error if the state machine execution fails or a dropped
+    /// connection cannot be re-established.
     pub async fn run(&mut self) -> Result<(), ConnectError> {
         println!("Starting TiDB connection state machine...");
+        let deadline = self
+            .total_timeout
+            .map(|timeout| (tokio::time::Instant::now() + timeout, timeout));
 
         while self.current_state != State::Completed
             && self.current_state != State::Error(String::new())
         {
             if let Some(handler) = self.handlers.get(&self.current_state) {
-                // Enter state
-                let _next_state = handler.enter(&mut self.context).await?;
+                // Replace a connection the server dropped since the last state
+                if self.context.connection.is_some() {
+                    self.context.ensure_connection()?;
+                }
+
+                let state = &self.current_state;
+                let context = &mut self.context;
+                let step = async {
+                    // Enter state
+                    let _next_state = handler.enter(context).await?;
+
+                    // Execute state logic
+                    let next_state = handler.execute(context).await?;
 
-                // Execute state logic
-                let next_state = handler.execute(&mut self.context).await?;
+                    // Exit current state
+                    handler.exit(context).await?;
+                    Ok::<_, ConnectError>(next_state)
+                };
 
-                // Exit current state
-                handler.exit(&mut self.context).await?;
+                let next_state = match state_deadline(deadline, self.state_timeout, state) {
+                    Some((deadline, message)) => {
+                        if let Ok(result) = tokio::time::timeout_at(deadline, step).await {
+                            result?
+                        } else {
+                            self.current_state = State::Error(message.clone());
+                            return Err(ConnectError::Timeout(message));
+                        }
+                    }
+                    None => step.await?,
+                };
 
                 // Update current state
                 self.current_state = next_state;
@@ -223,3 +482,183 @@ impl StateMachine {
         &self.current_state
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MockConnection;
+
+    /// Handler that never finishes: each pass sleeps and stays in the same state
+    struct SlowLoopHandler;
+
+    #[async_trait::async_trait]
+    impl StateHandler for SlowLoopHandler {
+        async fn enter(&self, _context: &mut StateContext) -> Result<State, ConnectError> {
+            Ok(State::Initial)
+        }
+
+        async fn execute(&self, _context: &mut StateContext) -> Result<State, ConnectError> {
+            tokio::time::sleep(Duration::from_millis(30)).await;
+            Ok(State::Initial)
+        }
+
+        async fn exit(&self, _context: &mut StateContext) -> Result<(), ConnectError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_session_sql_rerun_after_reconnect() {
+        let mut context = StateContext::new();
+        context
+            .session_sql
+            .push("SET time_zone = '+00:00'".to_string());
+        let mut original = MockConnection::default();
+        restore_session(&mut original, &context.session_sql).unwrap();
+        context
+            .session_sql
+            .push("SET SESSION tidb_mem_quota_query = 1073741824".to_string());
+
+        // Simulated reconnect: the new connection starts with a fresh session
+        let mut reconnected = MockConnection::default();
+        restore_session(&mut reconnected, &context.session_sql).unwrap();
+        assert_eq!(reconnected.statements(), context.session_sql);
+
+        let mut reconnected = MockConnection::failing_on("bad_var");
+        let statements = vec![
+            "SET a = 1".to_string(),
+            "SET bad_var = 1".to_string(),
+            "SET b = 2".to_string(),
+        ];
+        assert!(restore_session(&mut reconnected, &statements).is_err());
+        assert_eq!(reconnected.statements(), vec!["SET a = 1"]);
+    }
+
+    /// Kills its own connection from a second one, as a server restart would
+    /// Kills the machine's connection from a second connection out of `pool`
+    struct DropConnectionHandler {
+        pool: mysql::Pool,
+    }
+
+    #[async_trait::async_trait]
+    impl StateHandler for DropConnectionHandler {
+        async fn enter(&self, _context: &mut StateContext) -> Result<State, ConnectError> {
+            Ok(State::Initial)
+        }
+
+        async fn execute(&self, context: &mut StateContext) -> Result<State, ConnectError> {
+            use mysql::prelude::Queryable;
+            let conn = context.connection.as_mut().unwrap();
+            let id: u64 = conn.query_first("SELECT CONNECTION_ID()")?.unwrap();
+            let mut killer = self.pool.get_conn()?;
+            killer.query_drop(format!("KILL CONNECTION {id}"))?;
+            context.set_handler_context(State::Initial, id);
+            Ok(State::GettingVersion)
+        }
+
+        async fn exit(&self, _context: &mut StateContext) -> Result<(), ConnectError> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_recovers_dropped_connection() {
+        let Some(db) = crate::test_support::live_db("dropped connection test") else {
+            return;
+        };
+        let mut machine = StateMachine::new();
+        let context = machine.get_context_mut();
+        db.configure_context(context);
+        context.session_sql = vec!["SET @recovery_marker = 7".to_string()];
+        crate::state_handlers::ConnectingHandler
+            .execute(context)
+            .await
+            .unwrap();
+
+        machine.register_handler(
+            State::Initial,
+            Box::new(DropConnectionHandler {
+                pool: db.pool().clone(),
+            }),
+        );
+        machine.register_handler(
+            State::GettingVersion,
+            Box::new(crate::state_handlers::GettingVersionHandler),
+        );
+        machine.run().await.unwrap();
+
+        let context = machine.get_context_mut();
+        assert_eq!(context.reconnects, 1);
+        assert!(context.server_version.is_some());
+        let dropped_id = *context.get_handler_context::<u64>(&State::Initial).unwrap();
+        let conn = context.connection.as_mut().unwrap();
+        let (id, marker): (u64, Option<i64>) = mysql::prelude::Queryable::query_first(
+            conn,
+            "SELECT CONNECTION_ID(), @recovery_marker",
+        )
+        .unwrap()
+        .unwrap();
+        assert_ne!(id, dropped_id);
+        assert_eq!(marker, Some(7));
+    }
+
+    #[tokio::test]
+    async fn test_total_timeout_aborts_run() {
+        let mut machine = StateMachine::new();
+        machine.register_handler(State::Initial, Box::new(SlowLoopHandler));
+        machine.set_total_timeout(Duration::from_millis(100));
+
+        let started = std::time::Instant::now();
+        let err = machine.run().await.unwrap_err();
+        assert!(matches!(err, ConnectError::Timeout(_)));
+        assert!(err.to_string().contains("exceeded in state Initial"));
+        assert!(matches!(machine.get_current_state(), State::Error(_)));
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_state_timeout_aborts_slow_state() {
+        let mut machine = StateMachine::new();
+        machine.register_handler(State::Initial, Box::new(SlowLoopHandler));
+        machine.set_total_timeout(Duration::from_secs(5));
+        machine.set_state_timeout(Duration::from_millis(10));
+
+        let err = machine.run().await.unwrap_err();
+        assert!(matches!(err, ConnectError::Timeout(_)));
+        assert!(
+            err.to_string()
+                .contains("State timeout of 10ms exceeded in state Initial")
+        );
+    }
+
+    #[test]
+    fn test_apply_timeouts_reaches_machine_and_context() {
+        let timeouts = Timeouts {
+            connect_secs: 3,
+            statement_secs: Some(20),
+            state_secs: Some(60),
+            total_secs: Some(600),
+        };
+        let mut machine = StateMachine::new();
+        machine.apply_timeouts(&timeouts);
+        assert_eq!(machine.total_timeout(), Some(Duration::from_secs(600)));
+        assert_eq!(machine.state_timeout(), Some(Duration::from_secs(60)));
+        assert_eq!(machine.get_context().timeouts, timeouts);
+    }
+
+    #[tokio::test]
+    async fn test_state_deadline_picks_earlier_budget() {
+        let now = tokio::time::Instant::now();
+        let run = Some((now + Duration::from_secs(5), Duration::from_secs(5)));
+        assert!(state_deadline(None, None, &State::Initial).is_none());
+
+        let (_, message) =
+            state_deadline(run, Some(Duration::from_secs(1)), &State::Initial).unwrap();
+        assert_eq!(message, "State timeout of 1s exceeded in state Initial");
+
+        let (deadline, message) =
+            state_deadline(run, Some(Duration::from_secs(60)), &State::Initial).unwrap();
+        assert_eq!(deadline, now + Duration::from_secs(5));
+        assert_eq!(message, "Total timeout of 5s exceeded in state Initial");
+    }
+}
@@ -3,11 +3,18 @@
 //! Dynamic state machine implementation that allows tests to define their own states.
 //! Uses string-based states instead of enums for maximum flexibility.
 
-use crate::errors::ConnectError;
+use crate::config::{DatabaseConfig, Timeouts};
+use crate::connection::is_retryable_connect_error;
+use crate::errors::{ConnectError, RetryConfig};
+use crate::retry::next_backoff_delay;
+use crate::state_machine::state_deadline;
+use crate::state_metrics::StateMetrics;
 use mysql::PooledConn;
 use std::any::Any;
 use std::collections::HashMap;
 use std::fmt;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
 
 /// Dynamic state representation using strings
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -64,6 +71,26 @@ impl From<String> for DynamicState {
     }
 }
 
+/// Descriptive information about a state for reports and metrics
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateMetadata {
+    /// Free-form group such as `setup`, `workload` or `verification`
+    pub category: Option<String>,
+    /// How long one run of the state normally takes; slower runs are counted in the metrics
+    pub expected_duration: Option<Duration>,
+    /// Whether a failure in this state invalidates the whole run
+    pub critical: bool,
+}
+
+/// One state of a machine's graph, from [`DynamicStateMachine::describe`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateDescription {
+    pub state: DynamicState,
+    /// Registered transitions out of the state, in registration order
+    pub transitions: Vec<DynamicState>,
+    pub metadata: Option<StateMetadata>,
+}
+
 /// Predefined states for common operations
 pub mod states {
     use super::DynamicState;
@@ -118,6 +145,18 @@ pub struct DynamicStateContext {
     pub connection: Option<PooledConn>,
     pub server_version: Option<String>,
     pub error_message: Option<String>,
+    /// Run metadata such as test name, environment or git sha
+    pub tags: HashMap<String, String>,
+    /// Oldest `TiDB` release the version check accepts (`--require-tidb-version`)
+    pub min_tidb_version: Option<crate::capabilities::TidbVersion>,
+    /// Connect and statement timeouts for connections made by handlers
+    pub timeouts: Timeouts,
+    /// TLS settings used when connecting
+    pub tls: Option<crate::connection::TlsConfig>,
+    /// Table created after the database is verified, if it does not exist
+    pub ensure_table: Option<crate::schema::TableSpec>,
+    /// Statements run on every new connection, e.g. `SET SESSION ...`
+    pub session_sql: Vec<String>,
     // Handler-specific context storage
     handler_contexts: HashMap<DynamicState, Box<dyn Any + Send + Sync>>,
     // Custom data storage for test-specific data
@@ -142,11 +181,55 @@ impl DynamicStateContext {
             connection: None,
             server_version: None,
             error_message: None,
+            tags: HashMap::new(),
+            min_tidb_version: None,
+            timeouts: Timeouts::default(),
+            tls: None,
+            ensure_table: None,
+            session_sql: Vec::new(),
             handler_contexts: HashMap::new(),
             custom_data: HashMap::new(),
         }
     }
 
+    /// Settings for a connection to the context's server, with its TLS
+    /// settings and timeouts
+    #[must_use]
+    pub fn database_config(&self) -> DatabaseConfig {
+        let mut config = crate::connection::database_config(
+            &self.host,
+            self.port,
+            &self.username,
+            &self.password,
+            self.database.as_deref(),
+            self.tls.as_ref(),
+        );
+        self.timeouts.apply_to(&mut config);
+        config
+    }
+
+    /// `table` as a name that resolves without a current database; see
+    /// [`crate::query::qualify_table`]
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConnectError::Configuration` if `table` is unqualified and no
+    /// database is configured.
+    pub fn qualify_table(&self, table: &str) -> Result<String, ConnectError> {
+        crate::query::qualify_table(self.database.as_deref(), table)
+    }
+
+    /// Tags as `key=value` pairs sorted by key, separated by `, `
+    #[must_use]
+    pub fn tags_summary(&self) -> String {
+        let mut tags: Vec<_> = self.tags.iter().collect();
+        tags.sort();
+        tags.iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
     pub fn set_error(&mut self, error: String) {
         self.error_message = Some(error);
     }
@@ -210,13 +293,84 @@ pub trait DynamicStateHandler {
     async fn exit(&self, context: &mut DynamicStateContext) -> Result<(), ConnectError>;
 }
 
+/// Observer notified of every state transition, e.g. for logging, timing or metrics
+pub trait TransitionObserver: Send + Sync {
+    /// Called after a state's `execute` returns `to`, before the transition is validated
+    fn on_transition(&self, from: &DynamicState, to: &DynamicState, ctx: &DynamicStateContext);
+}
+
+/// One problem in a machine's registered transition graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphProblem {
+    /// A state is the target or source of a transition but has no handler
+    MissingHandler(DynamicState),
+    /// A state has a handler or transitions but cannot be reached from `initial`
+    Unreachable(DynamicState),
+    /// A state other than `completed` has no outgoing transitions registered
+    DeadEnd(DynamicState),
+    /// No chain of transitions leads from `initial` to `completed`
+    CompletedUnreachable,
+}
+
+impl fmt::Display for GraphProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingHandler(state) => {
+                write!(f, "no handler registered for state '{}'", state.name())
+            }
+            Self::Unreachable(state) => {
+                write!(f, "state '{}' is unreachable from initial", state.name())
+            }
+            Self::DeadEnd(state) => {
+                write!(f, "state '{}' has no outgoing transitions", state.name())
+            }
+            Self::CompletedUnreachable => write!(f, "completed is unreachable from initial"),
+        }
+    }
+}
+
+/// Problems found by [`DynamicStateMachine::validate_graph`]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Invalid state graph: {}", join_problems(.problems))]
+pub struct GraphError {
+    pub problems: Vec<GraphProblem>,
+}
+
+fn join_problems(problems: &[GraphProblem]) -> String {
+    problems
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+impl From<GraphError> for ConnectError {
+    fn from(err: GraphError) -> Self {
+        ConnectError::StateMachine(err.to_string())
+    }
+}
+
 /// Dynamic state machine that manages the flow between states
 pub struct DynamicStateMachine {
     current_state: DynamicState,
     context: DynamicStateContext,
     handlers: HashMap<DynamicState, Box<dyn DynamicStateHandler + Send + Sync>>,
+    // Retry policies for the execute phase of individual states
+    retry_policies: HashMap<DynamicState, RetryConfig>,
+    // Observers notified of every transition, in registration order
+    observers: Vec<Box<dyn TransitionObserver>>,
+    // Per-state execution timings, accumulated across runs
+    metrics: StateMetrics,
     // State transitions for validation
     valid_transitions: HashMap<DynamicState, Vec<DynamicState>>,
+    // Descriptive metadata registered for reporting
+    state_metadata: HashMap<DynamicState, StateMetadata>,
+    // State whose handler failed or timed out, ending the last run
+    failed_state: Option<DynamicState>,
+    // Budget for the whole run, across all states
+    total_timeout: Option<Duration>,
+    // Budget for each state
+    state_timeout: Option<Duration>,
 }
 
 impl Default for DynamicStateMachine {
@@ -232,7 +386,14 @@ impl DynamicStateMachine {
             current_state: states::initial(),
             context: DynamicStateContext::new(),
             handlers: HashMap::new(),
+            retry_policies: HashMap::new(),
+            observers: Vec::new(),
+            metrics: StateMetrics::default(),
             valid_transitions: HashMap::new(),
+            state_metadata: HashMap::new(),
+            failed_state: None,
+            total_timeout: None,
+            state_timeout: None,
         }
     }
 
@@ -245,11 +406,81 @@ impl DynamicStateMachine {
         self.handlers.insert(state, handler);
     }
 
+    /// Register a handler whose `execute` is retried with exponential backoff
+    ///
+    /// `enter` and `exit` still run once. Only errors
+    /// [`crate::connection::is_retryable_connect_error`] classifies as
+    /// transient are retried (honouring the configured retry classification);
+    /// a permanent error such as a syntax error or access denied stops the
+    /// machine at once. `retry.max_retries` is the total number of `execute`
+    /// attempts; if all fail the machine stops with
+    /// `ConnectError::RetriesExhausted` wrapping the last error.
+    pub fn register_handler_with_retry(
+        &mut self,
+        state: DynamicState,
+        handler: Box<dyn DynamicStateHandler + Send + Sync>,
+        retry: RetryConfig,
+    ) {
+        self.retry_policies.insert(state.clone(), retry);
+        self.handlers.insert(state, handler);
+    }
+
+    /// Add an observer that sees every transition
+    pub fn add_observer(&mut self, observer: Box<dyn TransitionObserver>) {
+        self.observers.push(observer);
+    }
+
     /// Register valid transitions from a state
     pub fn register_transitions(&mut self, from_state: DynamicState, to_states: Vec<DynamicState>) {
         self.valid_transitions.insert(from_state, to_states);
     }
 
+    /// Attach descriptive metadata to a state
+    ///
+    /// The metadata shows up in [`DynamicStateMachine::describe`], the
+    /// metrics and, for a critical state the run stopped in, the summary.
+    pub fn register_state_metadata(&mut self, state: DynamicState, metadata: StateMetadata) {
+        self.metrics.set_metadata(&state, metadata.clone());
+        self.state_metadata.insert(state, metadata);
+    }
+
+    /// Metadata registered for `state`
+    #[must_use]
+    pub fn state_metadata(&self, state: &DynamicState) -> Option<&StateMetadata> {
+        self.state_metadata.get(state)
+    }
+
+    /// Bound the runtime of the whole run
+    ///
+    /// When the budget runs out the machine moves to an error state and
+    /// `run` returns `ConnectError::Timeout`.
+    pub fn set_total_timeout(&mut self, timeout: Duration) {
+        self.total_timeout = Some(timeout);
+    }
+
+    /// Bound the runtime of each state (enter, execute and exit)
+    pub fn set_state_timeout(&mut self, timeout: Duration) {
+        self.state_timeout = Some(timeout);
+    }
+
+    /// Use the total and per-state timeouts of `timeouts`, and its connect
+    /// and statement timeouts for connections made by handlers
+    pub fn apply_timeouts(&mut self, timeouts: &Timeouts) {
+        self.total_timeout = timeouts.total();
+        self.state_timeout = timeouts.state();
+        self.context.timeouts = timeouts.clone();
+    }
+
+    #[must_use]
+    pub fn total_timeout(&self) -> Option<Duration> {
+        self.total_timeout
+    }
+
+    #[must_use]
+    pub fn state_timeout(&self) -> Option<Duration> {
+        self.state_timeout
+    }
+
     /// Set the context
     pub fn set_context(&mut self, context: DynamicStateContext) {
         self.context = context;
@@ -283,50 +514,333 @@ impl DynamicStateMachine {
         }
     }
 
-    /// Run the dynamic state machine
+    /// Check the registered handlers and transitions without running anything
+    ///
+    /// Every state that has a handler or appears in a transition must have a
+    /// handler and be reachable from `initial`, every state except `completed`
+    /// and error states must have outgoing transitions, and `completed` must be
+    /// reachable. This catches a forgotten `register_transitions!` call before
+    /// a run against a real cluster.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`GraphError`] listing every problem found.
+    pub fn validate_graph(&self) -> Result<(), GraphError> {
+        let initial = states::initial();
+        let completed = states::completed();
+        let is_terminal =
+            |state: &DynamicState| *state == completed || state.name().starts_with("error:");
+
+        let mut all_states = vec![initial.clone()];
+        let referenced = self
+            .handlers
+            .keys()
+            .chain(self.valid_transitions.keys())
+            .chain(self.valid_transitions.values().flatten());
+        for state in referenced {
+            if !all_states.contains(state) {
+                all_states.push(state.clone());
+            }
+        }
+        all_states.sort_by(|a, b| a.name().cmp(b.name()));
+
+        let mut reachable = vec![initial.clone()];
+        let mut pending = vec![initial];
+        while let Some(state) = pending.pop() {
+            for next in self.valid_transitions.get(&state).into_iter().flatten() {
+                if !reachable.contains(next) {
+                    reachable.push(next.clone());
+                    pending.push(next.clone());
+                }
+            }
+        }
+
+        let mut problems = Vec::new();
+        for state in all_states.iter().filter(|s| !is_terminal(s)) {
+            if !self.handlers.contains_key(state) {
+                problems.push(GraphProblem::MissingHandler(state.clone()));
+            }
+        }
+        for state in all_states
+            .iter()
+            .filter(|s| **s != completed && !reachable.contains(s))
+        {
+            problems.push(GraphProblem::Unreachable(state.clone()));
+        }
+        for state in all_states.iter().filter(|s| !is_terminal(s)) {
+            let has_exits = self
+                .valid_transitions
+                .get(state)
+                .is_some_and(|to| !to.is_empty());
+            if self.handlers.contains_key(state) && !has_exits {
+                problems.push(GraphProblem::DeadEnd(state.clone()));
+            }
+        }
+        if !reachable.contains(&completed) {
+            problems.push(GraphProblem::CompletedUnreachable);
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(GraphError { problems })
+        }
+    }
+
+    /// Every state with registered transitions or metadata, sorted by name
+    #[must_use]
+    pub fn describe(&self) -> Vec<StateDescription> {
+        let mut described: Vec<StateDescription> = self
+            .valid_transitions
+            .iter()
+            .map(|(state, transitions)| StateDescription {
+                state: state.clone(),
+                transitions: transitions.clone(),
+                metadata: self.state_metadata.get(state).cloned(),
+            })
+            .collect();
+        for (state, metadata) in &self.state_metadata {
+            if !self.valid_transitions.contains_key(state) {
+                described.push(StateDescription {
+                    state: state.clone(),
+                    transitions: Vec::new(),
+                    metadata: Some(metadata.clone()),
+                });
+            }
+        }
+        described.sort_by(|a, b| a.state.name().cmp(b.state.name()));
+        described
+    }
+
+    /// Render the transition graph as a Mermaid `stateDiagram-v2`
+    ///
+    /// States are labelled with their display names. `initial` is entered
+    /// from the start marker and `completed` leads to the end marker.
+    #[must_use]
+    pub fn to_mermaid(&self) -> String {
+        let described = self.describe();
+        let mut all_states: Vec<&DynamicState> = Vec::new();
+        for state in described
+            .iter()
+            .flat_map(|d| std::iter::once(&d.state).chain(&d.transitions))
+        {
+            if !all_states.contains(&state) {
+                all_states.push(state);
+            }
+        }
+
+        let mut out = String::from("stateDiagram-v2\n");
+        for state in &all_states {
+            out.push_str(&format!(
+                "    state \"{}\" as {}\n",
+                state.display_name().replace('"', "'"),
+                mermaid_id(state)
+            ));
+        }
+        out.push_str(&format!("    [*] --> {}\n", mermaid_id(&states::initial())));
+        for description in &described {
+            for to in &description.transitions {
+                out.push_str(&format!(
+                    "    {} --> {}\n",
+                    mermaid_id(&description.state),
+                    mermaid_id(to)
+                ));
+            }
+        }
+        if let Some(completed) = all_states
+            .iter()
+            .find(|s| s.name() == states::completed().name())
+        {
+            out.push_str(&format!("    {} --> [*]\n", mermaid_id(completed)));
+        }
+        out
+    }
+
+    /// Write [`DynamicStateMachine::to_mermaid`] to `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn export_mermaid(&self, path: &std::path::Path) -> Result<(), ConnectError> {
+        std::fs::write(path, self.to_mermaid())?;
+        Ok(())
+    }
+
+    /// Run the dynamic state machine, printing [`DynamicStateMachine::summary`]
+    /// once it completes or fails
     ///
     /// # Errors
     ///
     /// Returns an error if the state machine execution fails.
     pub async fn run(&mut self) -> Result<(), ConnectError> {
         println!("Starting dynamic TiDB connection state machine...");
+        let span = tracing::info_span!("state_machine", tags = %self.context.tags_summary());
+        let result = self.run_states().instrument(span).await;
+        match &result {
+            Ok(()) => println!("Dynamic state machine completed. {}", self.summary()),
+            Err(e) => println!("Dynamic state machine failed: {e}. {}", self.summary()),
+        }
+        result
+    }
 
+    /// Timings of every state run so far
+    #[must_use]
+    pub fn metrics(&self) -> &StateMetrics {
+        &self.metrics
+    }
+
+    /// Final state and tags of the run, e.g. `state=completed tags=[env=ci]`
+    ///
+    /// After a failure the state is the one whose handler failed or timed out,
+    /// flagged if it is registered as critical, e.g.
+    /// `state=verifying_results critical=true tags=[]`.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let state = self.failed_state.as_ref().unwrap_or(&self.current_state);
+        let critical = self
+            .state_metadata
+            .get(state)
+            .is_some_and(|metadata| metadata.critical);
+        format!(
+            "state={}{} tags=[{}]",
+            state.name(),
+            if critical { " critical=true" } else { "" },
+            self.context.tags_summary()
+        )
+    }
+
+    /// Run states from the current one until completion or an error state
+    async fn run_states(&mut self) -> Result<(), ConnectError> {
+        let run_deadline = self
+            .total_timeout
+            .map(|timeout| (tokio::time::Instant::now() + timeout, timeout));
+        self.failed_state = None;
         while self.current_state != states::completed()
             && !self.current_state.name().starts_with("error:")
         {
-            if let Some(handler) = self.handlers.get(&self.current_state) {
-                // Enter state
-                let _next_state = handler.enter(&mut self.context).await?;
-
-                // Execute state logic
-                let next_state = handler.execute(&mut self.context).await?;
-
-                // Validate transition
-                if !self.is_valid_transition(&self.current_state, &next_state) {
-                    return Err(ConnectError::StateMachine(format!(
-                        "Invalid transition from {} to {}",
-                        self.current_state, next_state
-                    )));
+            let state = self.current_state.clone();
+            let started = Instant::now();
+            let outcome = match state_deadline(run_deadline, self.state_timeout, &state) {
+                Some((deadline, message)) => {
+                    if let Ok(outcome) =
+                        tokio::time::timeout_at(deadline, self.run_current_state()).await
+                    {
+                        outcome
+                    } else {
+                        if self.handlers.contains_key(&state) {
+                            self.metrics.record(&state, started.elapsed(), false);
+                        }
+                        self.failed_state = Some(state);
+                        self.current_state = states::error(message.clone());
+                        return Err(ConnectError::Timeout(message));
+                    }
                 }
+                None => self.run_current_state().await,
+            };
+            if self.handlers.contains_key(&state) {
+                self.metrics
+                    .record(&state, started.elapsed(), outcome.is_ok());
+            }
+            if outcome.is_err() {
+                self.failed_state = Some(state);
+            }
+            self.current_state = outcome?;
+        }
 
-                // Exit current state
-                handler.exit(&mut self.context).await?;
+        Ok(())
+    }
 
-                // Update current state
-                self.current_state = next_state;
-            } else {
-                return Err(ConnectError::StateMachine(format!(
-                    "No handler registered for state: {}",
-                    self.current_state
-                )));
+    /// Enter, execute and exit the current state, returning the next state
+    async fn run_current_state(&mut self) -> Result<DynamicState, ConnectError> {
+        let Some(handler) = self.handlers.get(&self.current_state) else {
+            return Err(ConnectError::StateMachine(format!(
+                "No handler registered for state: {}",
+                self.current_state
+            )));
+        };
+
+        // Enter state
+        let _next_state = handler.enter(&mut self.context).await?;
+
+        // Execute state logic
+        let next_state = match self.retry_policies.get(&self.current_state) {
+            Some(retry) => {
+                execute_with_retry(
+                    handler.as_ref(),
+                    &mut self.context,
+                    &self.current_state,
+                    retry,
+                )
+                .await?
             }
+            None => handler.execute(&mut self.context).await?,
+        };
+
+        for observer in &self.observers {
+            observer.on_transition(&self.current_state, &next_state, &self.context);
         }
 
-        println!("Dynamic state machine completed.");
-        Ok(())
+        // Validate transition
+        if !self.is_valid_transition(&self.current_state, &next_state) {
+            return Err(ConnectError::StateMachine(format!(
+                "Invalid transition from {} to {}",
+                self.current_state, next_state
+            )));
+        }
+
+        // Exit current state
+        handler.exit(&mut self.context).await?;
+
+        Ok(next_state)
     }
 }
 
+/// Run `handler.execute`, retrying transient failures with exponential backoff per `retry`
+async fn execute_with_retry(
+    handler: &(dyn DynamicStateHandler + Send + Sync),
+    context: &mut DynamicStateContext,
+    state: &DynamicState,
+    retry: &RetryConfig,
+) -> Result<DynamicState, ConnectError> {
+    let mut delay = retry.base_delay;
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match handler.execute(context).await {
+            Ok(next_state) => return Ok(next_state),
+            Err(e) if !is_retryable_connect_error(&e) => return Err(e),
+            Err(e) if attempt >= retry.max_retries => {
+                return Err(ConnectError::RetriesExhausted {
+                    attempts: attempt,
+                    source: Box::new(e),
+                });
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "State {} failed on attempt {}/{}, retrying in {:?}: {}",
+                    state,
+                    attempt,
+                    retry.max_retries,
+                    delay,
+                    e
+                );
+                tokio::time::sleep(delay).await;
+                delay = next_backoff_delay(retry, delay);
+            }
+        }
+    }
+}
+
+/// Mermaid state id for `state`: its name with anything but ASCII
+/// alphanumerics and `_` replaced by `_`
+fn mermaid_id(state: &DynamicState) -> String {
+    state
+        .name()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
 /// Helper macro to create dynamic states easily
 #[macro_export]
 macro_rules! dynamic_state {
@@ -398,6 +912,448 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    /// Fails `execute` until it has been called `succeed_on` times
+    struct FlakyHandler {
+        attempts: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+        succeed_on: usize,
+    }
+
+    #[async_trait::async_trait]
+    impl DynamicStateHandler for FlakyHandler {
+        async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+            Ok(states::connecting())
+        }
+
+        async fn execute(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+            let attempt = self
+                .attempts
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+                + 1;
+            if attempt < self.succeed_on {
+                Err(ConnectError::Network(format!(
+                    "dropped on attempt {attempt}"
+                )))
+            } else {
+                Ok(states::completed())
+            }
+        }
+
+        async fn exit(&self, _context: &mut DynamicStateContext) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn fast_retry(max_retries: usize) -> RetryConfig {
+        RetryConfig {
+            max_retries,
+            base_delay: std::time::Duration::from_millis(1),
+            max_delay: std::time::Duration::from_millis(5),
+            backoff_multiplier: 2.0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_policy_recovers_from_transient_failures() {
+        let attempts = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut machine = DynamicStateMachine::new();
+        machine.register_handler_with_retry(
+            states::initial(),
+            Box::new(FlakyHandler {
+                attempts: attempts.clone(),
+                succeed_on: 3,
+            }),
+            fast_retry(3),
+        );
+
+        machine.run().await.unwrap();
+        assert_eq!(machine.get_current_state(), &states::completed());
+        assert_eq!(attempts.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_summary_flags_failed_critical_state() {
+        let mut machine = DynamicStateMachine::new();
+        machine.register_handler(
+            states::initial(),
+            Box::new(FlakyHandler {
+                attempts: std::sync::Arc::default(),
+                succeed_on: 10,
+            }),
+        );
+        machine.register_state_metadata(
+            states::initial(),
+            StateMetadata {
+                critical: true,
+                ..StateMetadata::default()
+            },
+        );
+
+        assert!(machine.run().await.is_err());
+        assert_eq!(machine.summary(), "state=initial critical=true tags=[]");
+    }
+
+    #[tokio::test]
+    async fn test_exhausted_retries_preserve_original_error() {
+        let attempts = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut machine = DynamicStateMachine::new();
+        machine.register_handler_with_retry(
+            states::initial(),
+            Box::new(FlakyHandler {
+                attempts: attempts.clone(),
+                succeed_on: 10,
+            }),
+            fast_retry(2),
+        );
+
+        let err = machine.run().await.unwrap_err();
+        let ConnectError::RetriesExhausted {
+            attempts: tried,
+            source,
+        } = &err
+        else {
+            panic!("expected RetriesExhausted, got {err:?}");
+        };
+        assert_eq!(*tried, 2);
+        assert!(matches!(**source, ConnectError::Network(_)));
+        assert!(err.to_string().contains("dropped on attempt 2"));
+        assert_eq!(err.exit_code(), 4);
+        assert_eq!(attempts.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    /// Fails every `execute` with a syntax error
+    struct SyntaxErrorHandler {
+        attempts: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl DynamicStateHandler for SyntaxErrorHandler {
+        async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+            Ok(states::initial())
+        }
+
+        async fn execute(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+            self.attempts
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Err(ConnectError::Connection(mysql::Error::MySqlError(
+                mysql::MySqlError {
+                    state: "42000".to_string(),
+                    message: "You have an error in your SQL syntax".to_string(),
+                    code: 1064,
+                },
+            )))
+        }
+
+        async fn exit(&self, _context: &mut DynamicStateContext) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_permanent_errors_are_not_retried() {
+        let attempts = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut machine = DynamicStateMachine::new();
+        machine.register_handler_with_retry(
+            states::initial(),
+            Box::new(SyntaxErrorHandler {
+                attempts: attempts.clone(),
+            }),
+            fast_retry(5),
+        );
+
+        let err = machine.run().await.unwrap_err();
+        assert!(matches!(err, ConnectError::Connection(_)));
+        assert_eq!(attempts.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    /// Records every transition it is told about
+    struct RecordingObserver {
+        seen: std::sync::Arc<std::sync::Mutex<Vec<(String, String)>>>,
+    }
+
+    impl TransitionObserver for RecordingObserver {
+        fn on_transition(
+            &self,
+            from: &DynamicState,
+            to: &DynamicState,
+            _ctx: &DynamicStateContext,
+        ) {
+            self.seen
+                .lock()
+                .unwrap()
+                .push((from.name().to_string(), to.name().to_string()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_observers_see_every_transition() {
+        // Same sequence of states as the isolation test binary
+        let flow = [
+            states::initial(),
+            states::parsing_config(),
+            states::connecting(),
+            states::testing_connection(),
+            states::verifying_database(),
+            states::getting_version(),
+            dynamic_state!("creating_table", "Creating Test Table"),
+            dynamic_state!("populating_data", "Populating Test Data"),
+            dynamic_state!("testing_isolation", "Testing Isolation"),
+            dynamic_state!("verifying_results", "Verifying Results"),
+            states::completed(),
+        ];
+
+        let mut machine = DynamicStateMachine::new();
+        for pair in flow.windows(2) {
+            machine.register_handler(
+                pair[0].clone(),
+                Box::new(TestHandler {
+                    next_state: pair[1].clone(),
+                }),
+            );
+            machine.register_transitions(pair[0].clone(), vec![pair[1].clone()]);
+        }
+
+        let first = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let second = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        machine.add_observer(Box::new(RecordingObserver {
+            seen: first.clone(),
+        }));
+        machine.add_observer(Box::new(RecordingObserver {
+            seen: second.clone(),
+        }));
+
+        machine.run().await.unwrap();
+
+        let expected: Vec<(String, String)> = flow
+            .windows(2)
+            .map(|pair| (pair[0].name().to_string(), pair[1].name().to_string()))
+            .collect();
+        assert_eq!(*first.lock().unwrap(), expected);
+        assert_eq!(*second.lock().unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_observers_run_before_transition_validation() {
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut machine = DynamicStateMachine::new();
+        machine.register_handler(
+            states::initial(),
+            Box::new(TestHandler {
+                next_state: states::completed(),
+            }),
+        );
+        machine.register_transitions(states::initial(), vec![states::connecting()]);
+        machine.add_observer(Box::new(RecordingObserver { seen: seen.clone() }));
+
+        assert!(machine.run().await.is_err());
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![("initial".to_string(), "completed".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tags_in_context_and_summary() {
+        let mut machine = DynamicStateMachine::new();
+        machine.register_handler(
+            states::initial(),
+            Box::new(TestHandler {
+                next_state: states::completed(),
+            }),
+        );
+        machine.register_transitions(states::initial(), vec![states::completed()]);
+        machine.get_context_mut().tags = HashMap::from([
+            ("test".to_string(), "isolation".to_string()),
+            ("env".to_string(), "ci".to_string()),
+        ]);
+
+        machine.run().await.unwrap();
+        assert_eq!(machine.get_context().tags["env"], "ci");
+        assert_eq!(
+            machine.get_context().tags_summary(),
+            "env=ci, test=isolation"
+        );
+        assert_eq!(
+            machine.summary(),
+            "state=completed tags=[env=ci, test=isolation]"
+        );
+    }
+
+    fn handler_to(next_state: DynamicState) -> Box<TestHandler> {
+        Box::new(TestHandler { next_state })
+    }
+
+    #[test]
+    fn test_validate_graph_accepts_complete_path() {
+        let mut machine = DynamicStateMachine::new();
+        machine.register_handler(states::initial(), handler_to(states::connecting()));
+        machine.register_handler(states::connecting(), handler_to(states::completed()));
+        machine.register_transitions(states::initial(), vec![states::connecting()]);
+        machine.register_transitions(states::connecting(), vec![states::completed()]);
+        assert_eq!(machine.validate_graph(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_graph_reports_missing_handler() {
+        let mut machine = DynamicStateMachine::new();
+        machine.register_handler(states::initial(), handler_to(states::connecting()));
+        machine.register_transitions(states::initial(), vec![states::connecting()]);
+        machine.register_transitions(states::connecting(), vec![states::completed()]);
+
+        let err = machine.validate_graph().unwrap_err();
+        assert_eq!(
+            err.problems,
+            vec![GraphProblem::MissingHandler(states::connecting())]
+        );
+        assert!(
+            err.to_string()
+                .contains("no handler registered for state 'connecting'")
+        );
+    }
+
+    #[test]
+    fn test_validate_graph_reports_dangling_states() {
+        let mut machine = DynamicStateMachine::new();
+        let orphan = DynamicState::new("orphan");
+        machine.register_handler(states::initial(), handler_to(states::connecting()));
+        machine.register_handler(states::connecting(), handler_to(states::completed()));
+        machine.register_handler(orphan.clone(), handler_to(states::completed()));
+        machine.register_transitions(states::initial(), vec![states::connecting()]);
+        machine.register_transitions(orphan.clone(), vec![states::completed()]);
+        // Forgot: register_transitions!(machine, connecting, [completed])
+
+        let err = machine.validate_graph().unwrap_err();
+        assert_eq!(
+            err.problems,
+            vec![
+                GraphProblem::Unreachable(orphan),
+                GraphProblem::DeadEnd(states::connecting()),
+                GraphProblem::CompletedUnreachable,
+            ]
+        );
+        let err: ConnectError = err.into();
+        assert!(matches!(err, ConnectError::StateMachine(_)));
+    }
+
+    #[test]
+    fn test_mermaid_export() {
+        let mut machine = DynamicStateMachine::new();
+        let retrying = DynamicState::new("retry:backoff");
+        machine.register_transitions(states::initial(), vec![states::connecting()]);
+        machine.register_transitions(
+            states::connecting(),
+            vec![retrying.clone(), states::completed()],
+        );
+        machine.register_transitions(retrying.clone(), vec![states::connecting()]);
+
+        let described: Vec<_> = machine
+            .describe()
+            .into_iter()
+            .map(|d| d.state.name().to_string())
+            .collect();
+        assert_eq!(described, vec!["connecting", "initial", "retry:backoff"]);
+
+        let mermaid = machine.to_mermaid();
+        assert!(mermaid.starts_with("stateDiagram-v2\n"));
+        for line in [
+            "state \"Initial\" as initial",
+            "state \"Connecting to TiDB\" as connecting",
+            "state \"retry:backoff\" as retry_backoff",
+            "state \"Completed\" as completed",
+            "[*] --> initial",
+            "initial --> connecting",
+            "connecting --> retry_backoff",
+            "connecting --> completed",
+            "retry_backoff --> connecting",
+            "completed --> [*]",
+        ] {
+            assert!(
+                mermaid.lines().any(|l| l.trim() == line),
+                "missing `{line}` in:\n{mermaid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_state_metadata_in_description_and_summary() {
+        let mut machine = DynamicStateMachine::new();
+        machine.register_transitions(states::initial(), vec![states::connecting()]);
+        machine.register_transitions(states::connecting(), vec![states::completed()]);
+        let connecting = StateMetadata {
+            category: Some("setup".to_string()),
+            expected_duration: Some(Duration::from_secs(2)),
+            critical: true,
+        };
+        let completed = StateMetadata {
+            category: Some("report".to_string()),
+            ..StateMetadata::default()
+        };
+        machine.register_state_metadata(states::connecting(), connecting.clone());
+        machine.register_state_metadata(states::completed(), completed.clone());
+
+        assert_eq!(
+            machine.state_metadata(&states::connecting()),
+            Some(&connecting)
+        );
+        assert_eq!(machine.state_metadata(&states::initial()), None);
+
+        let described = machine.describe();
+        let names: Vec<_> = described.iter().map(|d| d.state.name()).collect();
+        assert_eq!(names, vec!["completed", "connecting", "initial"]);
+        assert_eq!(described[0].metadata, Some(completed));
+        assert!(described[0].transitions.is_empty());
+        assert_eq!(described[1].metadata, Some(connecting));
+        assert_eq!(described[1].transitions, vec![states::completed()]);
+        assert_eq!(described[2].metadata, None);
+
+        assert_eq!(machine.summary(), "state=initial tags=[]");
+        machine.current_state = states::connecting();
+        assert_eq!(machine.summary(), "state=connecting critical=true tags=[]");
+    }
+
+    struct SlowHandler;
+
+    #[async_trait::async_trait]
+    impl DynamicStateHandler for SlowHandler {
+        async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+            Ok(states::initial())
+        }
+
+        async fn execute(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(states::completed())
+        }
+
+        async fn exit(&self, _context: &mut DynamicStateContext) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_state_timeout_aborts_slow_state() {
+        let mut machine = DynamicStateMachine::new();
+        machine.register_handler(states::initial(), Box::new(SlowHandler));
+        machine.register_transitions(states::initial(), vec![states::completed()]);
+        machine.apply_timeouts(&Timeouts {
+            state_secs: Some(1),
+            total_secs: Some(60),
+            ..Timeouts::default()
+        });
+        assert_eq!(machine.state_timeout(), Some(Duration::from_secs(1)));
+        assert_eq!(machine.total_timeout(), Some(Duration::from_secs(60)));
+
+        let started = Instant::now();
+        let err = machine.run().await.unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(3));
+        assert!(matches!(err, ConnectError::Timeout(_)));
+        assert!(err.to_string().contains("State timeout of 1s exceeded"));
+        assert!(machine.get_current_state().name().starts_with("error:"));
+        assert_eq!(
+            machine.metrics().get(&states::initial()).unwrap().failures,
+            1
+        );
+        assert_eq!(machine.summary(), "state=initial tags=[]");
+    }
+
     #[test]
     fn test_dynamic_state_creation() {
         let state = dynamic_state!("custom_test_state", "Custom Test State");
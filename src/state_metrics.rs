@@ -0,0 +1,318 @@
+//! # State Metrics
+//!
+//! Per-state timing collected by the dynamic state machine, for finding slow
+//! phases of a run. Each visit to a state (enter, execute with any retries,
+//! and exit) counts as one execution.
+
+use crate::state_machine_dynamic::{DynamicState, StateMetadata};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Timings of one state
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StateStats {
+    pub executions: u64,
+    pub failures: u64,
+    pub total: Duration,
+    pub max: Duration,
+    /// Executions that took longer than the state's expected duration
+    pub over_expected: u64,
+}
+
+impl StateStats {
+    /// Mean duration per execution
+    #[must_use]
+    pub fn mean(&self) -> Duration {
+        u32::try_from(self.executions)
+            .ok()
+            .filter(|&n| n > 0)
+            .map_or(Duration::ZERO, |n| self.total / n)
+    }
+}
+
+/// Timings of every state a machine has run, keyed by state name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateMetrics {
+    states: BTreeMap<String, StateStats>,
+    metadata: BTreeMap<String, StateMetadata>,
+}
+
+impl StateMetrics {
+    /// Record one execution of `state`
+    pub fn record(&mut self, state: &DynamicState, duration: Duration, succeeded: bool) {
+        let expected = self
+            .metadata
+            .get(state.name())
+            .and_then(|metadata| metadata.expected_duration);
+        let stats = self.states.entry(state.name().to_string()).or_default();
+        stats.executions += 1;
+        stats.total += duration;
+        stats.max = stats.max.max(duration);
+        if !succeeded {
+            stats.failures += 1;
+        }
+        if expected.is_some_and(|expected| duration > expected) {
+            stats.over_expected += 1;
+        }
+    }
+
+    /// Attach `metadata` to `state`; it labels the state in the Prometheus output
+    pub fn set_metadata(&mut self, state: &DynamicState, metadata: StateMetadata) {
+        self.metadata.insert(state.name().to_string(), metadata);
+    }
+
+    /// Metadata attached to `state`
+    #[must_use]
+    pub fn metadata(&self, state: &DynamicState) -> Option<&StateMetadata> {
+        self.metadata.get(state.name())
+    }
+
+    /// Timings of `state`, if it has run
+    #[must_use]
+    pub fn get(&self, state: &DynamicState) -> Option<&StateStats> {
+        self.states.get(state.name())
+    }
+
+    /// All states in name order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &StateStats)> {
+        self.states
+            .iter()
+            .map(|(name, stats)| (name.as_str(), stats))
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// Render in the Prometheus text exposition format
+    #[must_use]
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "# HELP tidb_test_state_duration_seconds Time spent running each state"
+        );
+        let _ = writeln!(out, "# TYPE tidb_test_state_duration_seconds summary");
+        for (name, stats) in &self.states {
+            let label = escape_label(name);
+            let _ = writeln!(
+                out,
+                "tidb_test_state_duration_seconds_sum{{state=\"{label}\"}} {}",
+                stats.total.as_secs_f64()
+            );
+            let _ = writeln!(
+                out,
+                "tidb_test_state_duration_seconds_count{{state=\"{label}\"}} {}",
+                stats.executions
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP tidb_test_state_duration_seconds_max Longest single run of each state"
+        );
+        let _ = writeln!(out, "# TYPE tidb_test_state_duration_seconds_max gauge");
+        for (name, stats) in &self.states {
+            let _ = writeln!(
+                out,
+                "tidb_test_state_duration_seconds_max{{state=\"{}\"}} {}",
+                escape_label(name),
+                stats.max.as_secs_f64()
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP tidb_test_state_failures_total Runs of each state that returned an error"
+        );
+        let _ = writeln!(out, "# TYPE tidb_test_state_failures_total counter");
+        for (name, stats) in &self.states {
+            let _ = writeln!(
+                out,
+                "tidb_test_state_failures_total{{state=\"{}\"}} {}",
+                escape_label(name),
+                stats.failures
+            );
+        }
+
+        if self.metadata.is_empty() {
+            return out;
+        }
+        let _ = writeln!(
+            out,
+            "# HELP tidb_test_state_info Registered metadata of each state"
+        );
+        let _ = writeln!(out, "# TYPE tidb_test_state_info gauge");
+        for (name, metadata) in &self.metadata {
+            let _ = writeln!(
+                out,
+                "tidb_test_state_info{{state=\"{}\",category=\"{}\",critical=\"{}\"}} 1",
+                escape_label(name),
+                escape_label(metadata.category.as_deref().unwrap_or("")),
+                metadata.critical
+            );
+        }
+        let _ = writeln!(
+            out,
+            "# HELP tidb_test_state_over_expected_total Runs of each state slower than its expected duration"
+        );
+        let _ = writeln!(out, "# TYPE tidb_test_state_over_expected_total counter");
+        for (name, metadata) in &self.metadata {
+            if metadata.expected_duration.is_none() {
+                continue;
+            }
+            let count = self.states.get(name).map_or(0, |stats| stats.over_expected);
+            let _ = writeln!(
+                out,
+                "tidb_test_state_over_expected_total{{state=\"{}\"}} {count}",
+                escape_label(name)
+            );
+        }
+        out
+    }
+}
+
+/// Escape a Prometheus label value
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::{ConnectError, Result};
+    use crate::state_machine_dynamic::{
+        DynamicStateContext, DynamicStateHandler, DynamicStateMachine, states,
+    };
+
+    /// Sleeps briefly in `execute`, then moves to `next` or fails
+    struct SleepingHandler {
+        next: DynamicState,
+        fail: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl DynamicStateHandler for SleepingHandler {
+        async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+            Ok(self.next.clone())
+        }
+
+        async fn execute(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+            tokio::time::sleep(Duration::from_millis(2)).await;
+            if self.fail {
+                return Err(ConnectError::Network("connection reset".to_string()));
+            }
+            Ok(self.next.clone())
+        }
+
+        async fn exit(&self, _context: &mut DynamicStateContext) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn two_state_machine(fail_second: bool) -> DynamicStateMachine {
+        let mut machine = DynamicStateMachine::new();
+        machine.register_handler(
+            states::initial(),
+            Box::new(SleepingHandler {
+                next: states::connecting(),
+                fail: false,
+            }),
+        );
+        machine.register_handler(
+            states::connecting(),
+            Box::new(SleepingHandler {
+                next: states::completed(),
+                fail: fail_second,
+            }),
+        );
+        machine.register_transitions(states::initial(), vec![states::connecting()]);
+        machine.register_transitions(states::connecting(), vec![states::completed()]);
+        machine
+    }
+
+    #[tokio::test]
+    async fn test_metrics_cover_both_states() {
+        let mut machine = two_state_machine(false);
+        assert!(machine.metrics().is_empty());
+        machine.run().await.unwrap();
+
+        let metrics = machine.metrics();
+        for state in [states::initial(), states::connecting()] {
+            let stats = metrics.get(&state).unwrap();
+            assert_eq!(stats.executions, 1);
+            assert_eq!(stats.failures, 0);
+            assert!(stats.total >= Duration::from_millis(2));
+            assert_eq!(stats.max, stats.total);
+            assert_eq!(stats.mean(), stats.total);
+        }
+        assert!(metrics.get(&states::completed()).is_none());
+        assert_eq!(metrics.iter().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_state_is_counted() {
+        let mut machine = two_state_machine(true);
+        assert!(machine.run().await.is_err());
+
+        let stats = machine.metrics().get(&states::connecting()).unwrap();
+        assert_eq!(stats.executions, 1);
+        assert_eq!(stats.failures, 1);
+        assert!(stats.total > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_prometheus_output() {
+        let mut metrics = StateMetrics::default();
+        let connecting = states::connecting();
+        metrics.record(&connecting, Duration::from_millis(250), true);
+        metrics.record(&connecting, Duration::from_millis(750), false);
+        metrics.record(
+            &DynamicState::new("odd\"name"),
+            Duration::from_secs(1),
+            true,
+        );
+
+        let text = metrics.to_prometheus();
+        assert!(text.contains("# TYPE tidb_test_state_duration_seconds summary\n"));
+        assert!(text.contains("tidb_test_state_duration_seconds_sum{state=\"connecting\"} 1\n"));
+        assert!(text.contains("tidb_test_state_duration_seconds_count{state=\"connecting\"} 2\n"));
+        assert!(text.contains("tidb_test_state_duration_seconds_max{state=\"connecting\"} 0.75\n"));
+        assert!(text.contains("tidb_test_state_failures_total{state=\"connecting\"} 1\n"));
+        assert!(text.contains("{state=\"odd\\\"name\"}"));
+        assert_eq!(
+            metrics.get(&connecting).unwrap().mean(),
+            Duration::from_millis(500)
+        );
+        assert!(!text.contains("tidb_test_state_info"));
+    }
+
+    #[test]
+    fn test_metadata_in_metrics() {
+        let mut metrics = StateMetrics::default();
+        let connecting = states::connecting();
+        metrics.set_metadata(
+            &connecting,
+            StateMetadata {
+                category: Some("setup".to_string()),
+                expected_duration: Some(Duration::from_millis(500)),
+                critical: true,
+            },
+        );
+        metrics.record(&connecting, Duration::from_millis(250), true);
+        metrics.record(&connecting, Duration::from_millis(750), true);
+        assert_eq!(metrics.get(&connecting).unwrap().over_expected, 1);
+        assert!(metrics.metadata(&connecting).unwrap().critical);
+
+        let text = metrics.to_prometheus();
+        assert!(text.contains(
+            "tidb_test_state_info{state=\"connecting\",category=\"setup\",critical=\"true\"} 1\n"
+        ));
+        assert!(text.contains("tidb_test_state_over_expected_total{state=\"connecting\"} 1\n"));
+    }
+}
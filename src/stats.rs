@@ -0,0 +1,416 @@
+//! # Table Statistics
+//!
+//! Run `ANALYZE TABLE` and read `TiDB`'s `SHOW STATS_META` and
+//! `SHOW STATS_HISTOGRAMS` output, so tests can assert that statistics were
+//! collected before relying on plans that depend on them.
+
+use crate::dynamic_state;
+use crate::errors::{ConnectError, Result};
+use crate::explain::row_columns;
+use crate::state_machine_dynamic::{DynamicState, DynamicStateContext, DynamicStateHandler};
+use async_trait::async_trait;
+use mysql::prelude::*;
+use mysql::{PooledConn, Row};
+
+/// Context key under which [`AnalyzeTableHandler`] stores the [`TableStats`] it read
+pub const TABLE_STATS_KEY: &str = "table_stats";
+
+/// One row of `SHOW STATS_META`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatsMeta {
+    pub db_name: String,
+    pub table_name: String,
+    /// Empty for non-partitioned tables, `global` for a partitioned table's global stats
+    pub partition_name: String,
+    pub update_time: String,
+    /// Rows changed since the last `ANALYZE`
+    pub modify_count: u64,
+    pub row_count: u64,
+}
+
+impl StatsMeta {
+    /// Build from `(column name, value)` pairs
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConnectError::Parse` if a count is not a number.
+    pub fn from_columns(columns: &[(String, String)]) -> Result<Self> {
+        Ok(Self {
+            db_name: column(columns, "Db_name"),
+            table_name: column(columns, "Table_name"),
+            partition_name: column(columns, "Partition_name"),
+            update_time: column(columns, "Update_time"),
+            modify_count: parse_column(columns, "Modify_count")?,
+            row_count: parse_column(columns, "Row_count")?,
+        })
+    }
+}
+
+/// One row of `SHOW STATS_HISTOGRAMS`: the histogram of a column or index
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsHistogram {
+    pub db_name: String,
+    pub table_name: String,
+    pub partition_name: String,
+    /// Column name, or index name when `is_index` is set
+    pub column_name: String,
+    pub is_index: bool,
+    pub update_time: String,
+    pub distinct_count: u64,
+    pub null_count: u64,
+    pub avg_col_size: f64,
+    pub correlation: f64,
+}
+
+impl StatsHistogram {
+    /// Build from `(column name, value)` pairs
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConnectError::Parse` if a numeric column is not a number.
+    pub fn from_columns(columns: &[(String, String)]) -> Result<Self> {
+        Ok(Self {
+            db_name: column(columns, "Db_name"),
+            table_name: column(columns, "Table_name"),
+            partition_name: column(columns, "Partition_name"),
+            column_name: column(columns, "Column_name"),
+            is_index: parse_column::<u8>(columns, "Is_index")? != 0,
+            update_time: column(columns, "Update_time"),
+            distinct_count: parse_column(columns, "Distinct_count")?,
+            null_count: parse_column(columns, "Null_count")?,
+            avg_col_size: parse_column(columns, "Avg_col_size")?,
+            correlation: parse_column(columns, "Correlation")?,
+        })
+    }
+}
+
+/// Statistics of one table
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TableStats {
+    pub meta: Vec<StatsMeta>,
+    pub histograms: Vec<StatsHistogram>,
+}
+
+impl TableStats {
+    /// Check that the table has been analyzed
+    ///
+    /// # Errors
+    ///
+    /// Returns `ConnectError::Validation` if there is no `STATS_META` row, or
+    /// no histogram for `required_columns`, which are column or index names.
+    pub fn verify_collected(&self, required_columns: &[&str]) -> Result<()> {
+        if self.meta.is_empty() {
+            return Err(ConnectError::Validation(
+                "No statistics found; has the table been analyzed?".to_string(),
+            ));
+        }
+        if self.histograms.is_empty() {
+            return Err(ConnectError::Validation(
+                "Statistics have no histograms; has the table been analyzed?".to_string(),
+            ));
+        }
+        let missing: Vec<&str> = required_columns
+            .iter()
+            .copied()
+            .filter(|name| {
+                !self
+                    .histograms
+                    .iter()
+                    .any(|histogram| histogram.column_name.eq_ignore_ascii_case(name))
+            })
+            .collect();
+        if !missing.is_empty() {
+            return Err(ConnectError::Validation(format!(
+                "No histogram for {}",
+                missing.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// Row count from the table-level (or, if partitioned, global) stats
+    #[must_use]
+    pub fn row_count(&self) -> Option<u64> {
+        self.meta
+            .iter()
+            .find(|meta| meta.partition_name.is_empty() || meta.partition_name == "global")
+            .map(|meta| meta.row_count)
+    }
+}
+
+/// Value of `name` in `columns`, compared case-insensitively; empty if absent
+fn column(columns: &[(String, String)], name: &str) -> String {
+    columns
+        .iter()
+        .find(|(col, _)| col.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.clone())
+        .unwrap_or_default()
+}
+
+fn parse_column<T: std::str::FromStr>(columns: &[(String, String)], name: &str) -> Result<T> {
+    let value = column(columns, name);
+    value
+        .trim()
+        .parse()
+        .map_err(|_| ConnectError::Parse(format!("Invalid {name} in stats output: '{value}'")))
+}
+
+/// Quote `value` as a SQL string literal
+fn string_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''"))
+}
+
+/// `WHERE` clause selecting `database`.`table` in `SHOW STATS_*` statements
+fn stats_filter(database: &str, table: &str) -> String {
+    format!(
+        "WHERE Db_name = {} AND Table_name = {}",
+        string_literal(database),
+        string_literal(table)
+    )
+}
+
+fn query_rows<T>(
+    conn: &mut PooledConn,
+    sql: &str,
+    parse: fn(&[(String, String)]) -> Result<T>,
+) -> Result<Vec<T>> {
+    let rows: Vec<Row> = conn.query(sql)?;
+    rows.iter().map(|row| parse(&row_columns(row))).collect()
+}
+
+/// `SHOW STATS_META` rows of `database`.`table`
+///
+/// # Errors
+///
+/// Returns an error if the statement fails or its output cannot be parsed.
+pub fn show_stats_meta(
+    conn: &mut PooledConn,
+    database: &str,
+    table: &str,
+) -> Result<Vec<StatsMeta>> {
+    let sql = format!("SHOW STATS_META {}", stats_filter(database, table));
+    query_rows(conn, &sql, StatsMeta::from_columns)
+}
+
+/// `SHOW STATS_HISTOGRAMS` rows of `database`.`table`
+///
+/// # Errors
+///
+/// Returns an error if the statement fails or its output cannot be parsed.
+pub fn show_stats_histograms(
+    conn: &mut PooledConn,
+    database: &str,
+    table: &str,
+) -> Result<Vec<StatsHistogram>> {
+    let sql = format!("SHOW STATS_HISTOGRAMS {}", stats_filter(database, table));
+    query_rows(conn, &sql, StatsHistogram::from_columns)
+}
+
+/// Meta and histogram statistics of `database`.`table`
+///
+/// # Errors
+///
+/// Returns an error if either statement fails.
+pub fn table_stats(conn: &mut PooledConn, database: &str, table: &str) -> Result<TableStats> {
+    Ok(TableStats {
+        meta: show_stats_meta(conn, database, table)?,
+        histograms: show_stats_histograms(conn, database, table)?,
+    })
+}
+
+/// Run `ANALYZE TABLE` on `database`.`table`
+///
+/// # Errors
+///
+/// Returns an error if the statement fails.
+pub fn analyze_table(conn: &mut PooledConn, database: &str, table: &str) -> Result<()> {
+    conn.query_drop(format!("ANALYZE TABLE `{database}`.`{table}`"))?;
+    Ok(())
+}
+
+/// State in which [`AnalyzeTableHandler`] runs
+#[must_use]
+pub fn analyzing_table() -> DynamicState {
+    dynamic_state!("analyzing_table", "Analyzing Table")
+}
+
+/// Runs `ANALYZE TABLE`, then checks that statistics exist
+///
+/// The table is looked up in the context's database. The statistics read are
+/// stored in the context under [`TABLE_STATS_KEY`].
+pub struct AnalyzeTableHandler {
+    table: String,
+    required_columns: Vec<String>,
+    next_state: DynamicState,
+}
+
+impl AnalyzeTableHandler {
+    #[must_use]
+    pub fn new(table: impl Into<String>, next_state: DynamicState) -> Self {
+        Self {
+            table: table.into(),
+            required_columns: Vec::new(),
+            next_state,
+        }
+    }
+
+    /// Also require a histogram for each of `columns` (column or index names)
+    #[must_use]
+    pub fn require_columns<I, S>(mut self, columns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.required_columns
+            .extend(columns.into_iter().map(Into::into));
+        self
+    }
+}
+
+#[async_trait]
+impl DynamicStateHandler for AnalyzeTableHandler {
+    async fn enter(&self, _context: &mut DynamicStateContext) -> Result<DynamicState> {
+        Ok(analyzing_table())
+    }
+
+    async fn execute(&self, context: &mut DynamicStateContext) -> Result<DynamicState> {
+        let database = context.database.clone().ok_or_else(|| {
+            ConnectError::Configuration("A database is required to analyze a table".to_string())
+        })?;
+        let conn = context.connection.as_mut().ok_or_else(|| {
+            ConnectError::StateMachine("No connection available to analyze a table".to_string())
+        })?;
+
+        analyze_table(conn, &database, &self.table)?;
+        let stats = table_stats(conn, &database, &self.table)?;
+        let required: Vec<&str> = self.required_columns.iter().map(String::as_str).collect();
+        stats.verify_collected(&required)?;
+        tracing::info!(
+            "Analyzed {}.{}: {} row(s), {} histogram(s)",
+            database,
+            self.table,
+            stats.row_count().unwrap_or_default(),
+            stats.histograms.len()
+        );
+        context.set_custom_data(TABLE_STATS_KEY.to_string(), stats);
+        Ok(self.next_state.clone())
+    }
+
+    async fn exit(&self, _context: &mut DynamicStateContext) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::live_db;
+
+    fn columns(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+            .collect()
+    }
+
+    fn histogram(name: &str, is_index: &str) -> StatsHistogram {
+        StatsHistogram::from_columns(&columns(&[
+            ("Db_name", "test"),
+            ("Table_name", "t"),
+            ("Partition_name", ""),
+            ("Column_name", name),
+            ("Is_index", is_index),
+            ("Update_time", "2026-01-05 10:00:00"),
+            ("Distinct_count", "100"),
+            ("Null_count", "0"),
+            ("Avg_col_size", "8.5"),
+            ("Correlation", "-0.25"),
+            ("Load_status", "allEvicted"),
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_stats_meta_row() {
+        let meta = StatsMeta::from_columns(&columns(&[
+            ("Db_name", "test"),
+            ("Table_name", "t"),
+            ("Partition_name", ""),
+            ("Update_time", "2026-01-05 10:00:00"),
+            ("Modify_count", "3"),
+            ("Row_count", "1000"),
+        ]))
+        .unwrap();
+        assert_eq!(meta.table_name, "t");
+        assert_eq!(meta.modify_count, 3);
+        assert_eq!(meta.row_count, 1000);
+
+        let err = StatsMeta::from_columns(&columns(&[("Modify_count", "x"), ("Row_count", "1")]))
+            .unwrap_err();
+        assert!(err.to_string().contains("Modify_count"));
+    }
+
+    #[test]
+    fn test_parse_stats_histogram_row() {
+        let column = histogram("a", "0");
+        assert!(!column.is_index);
+        assert_eq!(column.distinct_count, 100);
+        assert!((column.avg_col_size - 8.5).abs() < f64::EPSILON);
+        assert!((column.correlation + 0.25).abs() < f64::EPSILON);
+        assert!(histogram("idx_a", "1").is_index);
+    }
+
+    #[test]
+    fn test_verify_collected() {
+        let mut stats = TableStats::default();
+        assert!(stats.verify_collected(&[]).is_err());
+
+        stats.meta.push(StatsMeta {
+            db_name: "test".to_string(),
+            table_name: "t".to_string(),
+            partition_name: String::new(),
+            update_time: "2026-01-05 10:00:00".to_string(),
+            modify_count: 0,
+            row_count: 1000,
+        });
+        assert!(stats.verify_collected(&[]).is_err());
+
+        stats.histograms = vec![histogram("a", "0"), histogram("idx_a", "1")];
+        assert!(stats.verify_collected(&["a", "IDX_A"]).is_ok());
+        let err = stats.verify_collected(&["a", "b"]).unwrap_err();
+        assert!(err.to_string().contains("No histogram for b"));
+        assert_eq!(stats.row_count(), Some(1000));
+    }
+
+    #[test]
+    fn test_stats_filter_quotes_names() {
+        assert_eq!(
+            stats_filter("test", "o'brien"),
+            "WHERE Db_name = 'test' AND Table_name = 'o''brien'"
+        );
+    }
+
+    #[test]
+    fn test_analyze_collects_stats_against_live_tidb() {
+        let Some(mut db) = live_db("live stats test") else {
+            return;
+        };
+        let table = db.table("stats_test");
+        let qualified = table.qualified();
+        db.conn
+            .query_drop(format!(
+                "CREATE TABLE {qualified} (id INT PRIMARY KEY, a INT, KEY idx_a (a))"
+            ))
+            .unwrap();
+        db.conn
+            .query_drop(format!(
+                "INSERT INTO {qualified} VALUES (1, 10), (2, 20), (3, 30)"
+            ))
+            .unwrap();
+
+        analyze_table(&mut db.conn, &db.database, table.name()).unwrap();
+        let stats = table_stats(&mut db.conn, &db.database, table.name()).unwrap();
+        stats.verify_collected(&["a", "idx_a"]).unwrap();
+        assert_eq!(stats.row_count(), Some(3));
+    }
+}
@@ -0,0 +1,418 @@
+//! # Test Support
+//!
+//! Helpers for tests. [`MockConnection`] stands in for a server in unit
+//! tests. DB-gated tests start with [`live_db`], which skips the test unless
+//! `TIDB_HOST` is set, and reserve tables with [`LiveDb::table`], which are
+//! dropped by a [`ScratchTable`] guard. Tests that create tables on a
+//! connection they already hold use [`scoped_table`] and the [`TableGuard`]
+//! it returns. Either way the tables go when the guard goes out of scope, so
+//! a failed assertion does not leave tables behind for the next run.
+//! Tests that change environment variables do so through an
+//! [`EnvVarGuard`], which puts them back when it is dropped.
+
+use crate::cleanup::ScratchTable;
+use crate::config::DatabaseConfig;
+use crate::connection::{get_pooled_connection, parse_host_port, pool_from_env};
+use crate::env_params::EnvParams;
+use crate::errors::{ConnectError, Result};
+use crate::query::{QueryOutput, RowLimit};
+use crate::recording::QueryExecutor;
+use crate::state_machine::StateContext;
+use mysql::prelude::Queryable;
+use mysql::{Pool, PooledConn};
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Server and database a DB-gated test runs against
+pub struct LiveDb {
+    pub conn: PooledConn,
+    /// `TIDB_DATABASE`, or `test` if unset
+    pub database: String,
+    pool: Pool,
+    params: EnvParams,
+}
+
+/// Connect for a DB-gated test, or print the skip message and return `None`
+/// if `TIDB_HOST` is not set
+///
+/// `test` names the test in the skip message. The database is created if
+/// it does not exist.
+///
+/// # Panics
+///
+/// Panics if `TIDB_HOST` is set but the server cannot be reached.
+#[must_use]
+pub fn live_db(test: &str) -> Option<LiveDb> {
+    let Some(pool) = pool_from_env().expect("invalid TIDB_HOST") else {
+        eprintln!("TIDB_HOST not set, skipping {test}");
+        return None;
+    };
+    let params = EnvParams::tidb().expect("TIDB_HOST is set");
+    let mut conn = get_pooled_connection(&pool, &DatabaseConfig::default())
+        .expect("failed to connect to TIDB_HOST");
+    let database = std::env::var("TIDB_DATABASE").unwrap_or_else(|_| "test".to_string());
+    conn.query_drop(format!("CREATE DATABASE IF NOT EXISTS `{database}`"))
+        .expect("failed to create the test database");
+    Some(LiveDb {
+        conn,
+        database,
+        pool,
+        params,
+    })
+}
+
+impl LiveDb {
+    /// Pool the connection came from, for tests that need more connections
+    #[must_use]
+    pub fn pool(&self) -> &Pool {
+        &self.pool
+    }
+
+    /// Point `context` at the test server, so handlers that connect or
+    /// reconnect from its settings reach the same server as [`LiveDb::conn`]
+    ///
+    /// # Panics
+    ///
+    /// Panics if `TIDB_HOST` is not a valid `host:port`.
+    pub fn configure_context(&self, context: &mut StateContext) {
+        let (host, port) = parse_host_port(&self.params.host).expect("invalid TIDB_HOST");
+        context.host = host;
+        context.port = port;
+        context.username = self
+            .params
+            .user
+            .clone()
+            .unwrap_or_else(|| "root".to_string());
+        context.password = self.params.password.clone().unwrap_or_default();
+    }
+
+    /// Reserve `<prefix>_<pid>` in the test database; the table is dropped,
+    /// if the test created it, when the returned guard goes out of scope
+    #[must_use]
+    pub fn table(&self, prefix: &str) -> LiveTable {
+        let name = format!("{prefix}_{}", std::process::id());
+        let qualified = format!("`{}`.`{name}`", self.database);
+        let pool = self.pool.clone();
+        let guard = ScratchTable::new(qualified.clone(), move || {
+            get_pooled_connection(&pool, &DatabaseConfig::default())
+        });
+        LiveTable {
+            name,
+            qualified,
+            _guard: guard,
+        }
+    }
+}
+
+/// Table reserved by [`LiveDb::table`]
+pub struct LiveTable {
+    name: String,
+    qualified: String,
+    _guard: ScratchTable<PooledConn>,
+}
+
+impl LiveTable {
+    /// Unqualified table name
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// `` `database`.`table` ``
+    #[must_use]
+    pub fn qualified(&self) -> &str {
+        &self.qualified
+    }
+}
+
+/// [`QueryExecutor`] that records statements instead of running them
+///
+/// Every query succeeds with an empty result, except those containing the
+/// pattern given to [`MockConnection::failing_on`], which fail without
+/// being recorded. Clones share the log, so a connection factory can hand out
+/// clones and the test still sees everything they ran.
+#[derive(Debug, Clone, Default)]
+pub struct MockConnection {
+    statements: Arc<Mutex<Vec<String>>>,
+    fail_on: Option<String>,
+}
+
+impl MockConnection {
+    /// Connection failing every statement that contains `pattern`
+    #[must_use]
+    pub fn failing_on(pattern: impl Into<String>) -> Self {
+        Self {
+            fail_on: Some(pattern.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Statements run so far, oldest first
+    #[must_use]
+    pub fn statements(&self) -> Vec<String> {
+        self.statements
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+impl QueryExecutor for MockConnection {
+    fn run_query(&mut self, sql: &str, _limit: RowLimit) -> Result<QueryOutput> {
+        if self
+            .fail_on
+            .as_deref()
+            .is_some_and(|pattern| sql.contains(pattern))
+        {
+            return Err(ConnectError::Database(format!("{sql} failed")));
+        }
+        self.statements
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(sql.to_string());
+        Ok(QueryOutput::default())
+    }
+}
+
+/// Table name created by a `CREATE TABLE` statement, as written
+#[must_use]
+pub fn created_table_name(ddl: &str) -> Option<&str> {
+    let mut tokens = ddl.split_whitespace();
+    if !tokens.next()?.eq_ignore_ascii_case("CREATE") {
+        return None;
+    }
+    let mut token = tokens.next()?;
+    if token.eq_ignore_ascii_case("TEMPORARY") {
+        token = tokens.next()?;
+    }
+    if !token.eq_ignore_ascii_case("TABLE") {
+        return None;
+    }
+    let mut name = tokens.next()?;
+    if name.eq_ignore_ascii_case("IF") {
+        tokens.next()?; // NOT
+        tokens.next()?; // EXISTS
+        name = tokens.next()?;
+    }
+    // The column list may follow the name without a space
+    let name = name.split('(').next()?;
+    (!name.is_empty()).then_some(name)
+}
+
+/// Drops the recorded tables when dropped
+///
+/// Derefs to the wrapped connection so the test keeps using it while the guard
+/// is alive. Tables are dropped on that connection in reverse creation order;
+/// failures are logged.
+pub struct TableGuard<'a, C: QueryExecutor> {
+    conn: &'a mut C,
+    tables: Vec<String>,
+}
+
+impl<'a, C: QueryExecutor> TableGuard<'a, C> {
+    /// Guard with no tables recorded yet
+    #[must_use]
+    pub fn new(conn: &'a mut C) -> Self {
+        Self {
+            conn,
+            tables: Vec::new(),
+        }
+    }
+
+    /// Record a table to drop, e.g. `` `test`.`t` ``
+    pub fn track(&mut self, table: impl Into<String>) {
+        self.tables.push(table.into());
+    }
+
+    /// Tables that will be dropped
+    #[must_use]
+    pub fn tables(&self) -> &[String] {
+        &self.tables
+    }
+}
+
+impl<C: QueryExecutor> Deref for TableGuard<'_, C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        self.conn
+    }
+}
+
+impl<C: QueryExecutor> DerefMut for TableGuard<'_, C> {
+    fn deref_mut(&mut self) -> &mut C {
+        self.conn
+    }
+}
+
+impl<C: QueryExecutor> Drop for TableGuard<'_, C> {
+    fn drop(&mut self) {
+        for table in self.tables.drain(..).rev() {
+            let sql = format!("DROP TABLE IF EXISTS {table}");
+            if let Err(e) = self.conn.run_query(&sql, RowLimit::Max(Some(0))) {
+                tracing::warn!("Failed to drop test table {}: {}", table, e);
+            }
+        }
+    }
+}
+
+/// Run a `CREATE TABLE` statement and return a guard that drops the table
+///
+/// # Errors
+///
+/// Returns `ConnectError::Validation` if `ddl` is not a `CREATE TABLE`
+/// statement, or the error from running it.
+pub fn scoped_table<'a, C: QueryExecutor>(conn: &'a mut C, ddl: &str) -> Result<TableGuard<'a, C>> {
+    let table = created_table_name(ddl).ok_or_else(|| {
+        ConnectError::Validation(format!("Not a CREATE TABLE statement: `{ddl}`"))
+    })?;
+    conn.run_query(ddl, RowLimit::Max(Some(0)))?;
+    let mut guard = TableGuard::new(conn);
+    guard.track(table);
+    Ok(guard)
+}
+
+/// Environment variables changed by a test, restored when dropped
+///
+/// Each variable's value is saved the first time the guard touches it, so
+/// the original environment comes back even if the test panics. Tests that
+/// use it should be `#[serial]`, since the environment is process-wide.
+#[derive(Debug, Default)]
+pub struct EnvVarGuard {
+    saved: Vec<(String, Option<String>)>,
+}
+
+impl EnvVarGuard {
+    /// Remove `vars` for the lifetime of the guard
+    #[must_use]
+    pub fn cleared(vars: &[&str]) -> Self {
+        let mut guard = Self::default();
+        for var in vars {
+            guard.remove(var);
+        }
+        guard
+    }
+
+    /// Set `var` to `value` until the guard is dropped
+    pub fn set(&mut self, var: &str, value: &str) {
+        self.save(var);
+        unsafe {
+            std::env::set_var(var, value);
+        }
+    }
+
+    /// Remove `var` until the guard is dropped
+    pub fn remove(&mut self, var: &str) {
+        self.save(var);
+        unsafe {
+            std::env::remove_var(var);
+        }
+    }
+
+    fn save(&mut self, var: &str) {
+        if !self.saved.iter().any(|(saved, _)| saved == var) {
+            self.saved.push((var.to_string(), std::env::var(var).ok()));
+        }
+    }
+}
+
+impl Drop for EnvVarGuard {
+    fn drop(&mut self) {
+        for (var, value) in self.saved.drain(..).rev() {
+            unsafe {
+                match value {
+                    Some(value) => std::env::set_var(var, value),
+                    None => std::env::remove_var(var),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    #[test]
+    fn test_recording_connection() {
+        let mut conn = MockConnection::failing_on("bad");
+        let mut clone = conn.clone();
+        conn.run_query("SELECT 1", RowLimit::Max(None)).unwrap();
+        clone.run_query("SELECT 2", RowLimit::Max(None)).unwrap();
+        let err = conn
+            .run_query("SELECT bad", RowLimit::Max(None))
+            .unwrap_err();
+        assert!(err.to_string().contains("SELECT bad failed"));
+        assert_eq!(conn.statements(), vec!["SELECT 1", "SELECT 2"]);
+    }
+
+    #[test]
+    fn test_created_table_name() {
+        assert_eq!(
+            created_table_name("CREATE TABLE `test`.`t` (id INT)"),
+            Some("`test`.`t`")
+        );
+        assert_eq!(
+            created_table_name("create table if not exists t(id INT)"),
+            Some("t")
+        );
+        assert_eq!(
+            created_table_name("CREATE TEMPORARY TABLE tmp (id INT)"),
+            Some("tmp")
+        );
+        assert_eq!(created_table_name("CREATE INDEX i ON t (a)"), None);
+        assert_eq!(created_table_name("DROP TABLE t"), None);
+    }
+
+    #[test]
+    fn test_guard_drops_tables_on_drop() {
+        let mut conn = MockConnection::default();
+        {
+            let mut guard = scoped_table(&mut conn, "CREATE TABLE `test`.`a` (id INT)").unwrap();
+            guard.track("`test`.`b`");
+            guard
+                .run_query("INSERT INTO `test`.`a` VALUES (1)", RowLimit::Max(None))
+                .unwrap();
+            assert_eq!(guard.tables(), ["`test`.`a`", "`test`.`b`"]);
+        }
+        assert_eq!(
+            conn.statements(),
+            vec![
+                "CREATE TABLE `test`.`a` (id INT)",
+                "INSERT INTO `test`.`a` VALUES (1)",
+                "DROP TABLE IF EXISTS `test`.`b`",
+                "DROP TABLE IF EXISTS `test`.`a`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scoped_table_rejects_other_statements() {
+        let mut conn = MockConnection::default();
+        assert!(scoped_table(&mut conn, "SELECT 1").is_err());
+        assert!(conn.statements().is_empty());
+    }
+
+    #[test]
+    #[serial]
+    fn test_env_var_guard_restores_on_panic() {
+        let var = "TIDB_TEST_RIG_ENV_GUARD";
+        unsafe {
+            std::env::set_var(var, "before");
+        }
+        let result = std::panic::catch_unwind(|| {
+            let mut guard = EnvVarGuard::cleared(&[var]);
+            assert!(std::env::var(var).is_err());
+            guard.set(var, "first");
+            guard.set(var, "second");
+            assert_eq!(std::env::var(var).unwrap(), "second");
+            panic!("test failure");
+        });
+        assert!(result.is_err());
+        assert_eq!(std::env::var(var).unwrap(), "before");
+        unsafe {
+            std::env::remove_var(var);
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! # Transaction Helpers
+//!
+//! Run a closure inside a transaction at a chosen isolation level, committing
+//! on success and rolling back on any error, instead of repeating the
+//! `START TRANSACTION` / `COMMIT` sequence in every test.
+
+use crate::errors::{ConnectError, Result};
+use crate::query::RowLimit;
+use crate::recording::QueryExecutor;
+use std::fmt;
+use std::str::FromStr;
+
+/// Transaction isolation level
+///
+/// `TiDB` only accepts `READ UNCOMMITTED` and `SERIALIZABLE` when
+/// `tidb_skip_isolation_level_check` is enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsolationLevel {
+    ReadUncommitted,
+    ReadCommitted,
+    RepeatableRead,
+    Serializable,
+}
+
+impl IsolationLevel {
+    /// Level as written in SQL, e.g. `REPEATABLE READ`
+    #[must_use]
+    pub fn as_sql(self) -> &'static str {
+        match self {
+            IsolationLevel::ReadUncommitted => "READ UNCOMMITTED",
+            IsolationLevel::ReadCommitted => "READ COMMITTED",
+            IsolationLevel::RepeatableRead => "REPEATABLE READ",
+            IsolationLevel::Serializable => "SERIALIZABLE",
+        }
+    }
+
+    /// Statement setting this level for the next transaction only, so pooled
+    /// connections keep their session default once it ends
+    #[must_use]
+    pub fn set_statement(self) -> String {
+        format!("SET TRANSACTION ISOLATION LEVEL {}", self.as_sql())
+    }
+}
+
+impl FromStr for IsolationLevel {
+    type Err = ConnectError;
+
+    /// Parse `read-committed`, `READ COMMITTED`, `read_committed` and so on
+    fn from_str(s: &str) -> Result<Self> {
+        let normalized = s.trim().to_ascii_uppercase().replace(['-', '_'], " ");
+        match normalized.as_str() {
+            "READ UNCOMMITTED" => Ok(IsolationLevel::ReadUncommitted),
+            "READ COMMITTED" => Ok(IsolationLevel::ReadCommitted),
+            "REPEATABLE READ" => Ok(IsolationLevel::RepeatableRead),
+            "SERIALIZABLE" => Ok(IsolationLevel::Serializable),
+            _ => Err(ConnectError::CliArgument(format!(
+                "Unknown isolation level '{s}'; expected read-uncommitted, read-committed, \
+                 repeatable-read or serializable"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for IsolationLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_sql())
+    }
+}
+
+/// Run a transaction-control statement, discarding any rows
+fn execute<C: QueryExecutor>(conn: &mut C, sql: &str) -> Result<()> {
+    conn.run_query(sql, RowLimit::Max(Some(0))).map(|_| ())
+}
+
+/// Run `f` in a transaction at `isolation`
+///
+/// Sets the isolation level of the next transaction, begins it, runs `f` and
+/// commits. If `f` or the commit fails the transaction is rolled back and the
+/// original error returned.
+///
+/// # Errors
+///
+/// Returns an error if the isolation level cannot be set, the transaction
+/// cannot be started, or `f` or the commit fails.
+pub fn run_in_transaction<C, F, T>(conn: &mut C, isolation: IsolationLevel, f: F) -> Result<T>
+where
+    C: QueryExecutor,
+    F: FnOnce(&mut C) -> Result<T>,
+{
+    execute(conn, &isolation.set_statement())?;
+    execute(conn, "START TRANSACTION")?;
+
+    let result = f(conn).and_then(|value| execute(conn, "COMMIT").map(|()| value));
+    if let Err(e) = &result {
+        tracing::debug!("Rolling back {} transaction: {}", isolation, e);
+        if let Err(rollback) = execute(conn, "ROLLBACK") {
+            tracing::warn!("Rollback failed: {}", rollback);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MockConnection;
+
+    #[test]
+    fn test_isolation_level_sql() {
+        assert_eq!(
+            IsolationLevel::ReadUncommitted.set_statement(),
+            "SET TRANSACTION ISOLATION LEVEL READ UNCOMMITTED"
+        );
+        assert_eq!(
+            IsolationLevel::ReadCommitted.set_statement(),
+            "SET TRANSACTION ISOLATION LEVEL READ COMMITTED"
+        );
+        assert_eq!(
+            IsolationLevel::RepeatableRead.set_statement(),
+            "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ"
+        );
+        assert_eq!(
+            IsolationLevel::Serializable.set_statement(),
+            "SET TRANSACTION ISOLATION LEVEL SERIALIZABLE"
+        );
+    }
+
+    #[test]
+    fn test_parse_isolation_level() {
+        for (input, level) in [
+            ("read-uncommitted", IsolationLevel::ReadUncommitted),
+            ("READ COMMITTED", IsolationLevel::ReadCommitted),
+            ("repeatable_read", IsolationLevel::RepeatableRead),
+            ("Serializable", IsolationLevel::Serializable),
+        ] {
+            assert_eq!(input.parse::<IsolationLevel>().unwrap(), level);
+            assert_eq!(level.as_sql().parse::<IsolationLevel>().unwrap(), level);
+        }
+        assert!("snapshot".parse::<IsolationLevel>().is_err());
+    }
+
+    #[test]
+    fn test_commits_on_success() {
+        let mut conn = MockConnection::default();
+        let value = run_in_transaction(&mut conn, IsolationLevel::RepeatableRead, |conn| {
+            execute(conn, "UPDATE t SET v = 1")?;
+            Ok(42)
+        })
+        .unwrap();
+
+        assert_eq!(value, 42);
+        assert_eq!(
+            conn.statements(),
+            vec![
+                "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ",
+                "START TRANSACTION",
+                "UPDATE t SET v = 1",
+                "COMMIT"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rolls_back_on_error() {
+        let mut conn = MockConnection::failing_on("UPDATE");
+        let err = run_in_transaction(&mut conn, IsolationLevel::ReadCommitted, |conn| {
+            execute(conn, "UPDATE t SET v = 1")?;
+            execute(conn, "UPDATE t SET v = 2")
+        })
+        .unwrap_err();
+
+        assert!(err.to_string().contains("UPDATE t SET v = 1 failed"));
+        let statements = conn.statements();
+        assert_eq!(statements.last().unwrap(), "ROLLBACK");
+        assert!(!statements.iter().any(|s| s == "COMMIT"));
+        assert!(!statements.iter().any(|s| s.contains("v = ")));
+    }
+
+    #[test]
+    fn test_rolls_back_on_failed_commit() {
+        let mut conn = MockConnection::failing_on("COMMIT");
+        let result = run_in_transaction(&mut conn, IsolationLevel::Serializable, |_| Ok(()));
+
+        assert!(result.is_err());
+        assert_eq!(conn.statements().last().unwrap(), "ROLLBACK");
+    }
+}
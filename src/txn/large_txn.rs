@@ -0,0 +1,412 @@
+//! Large transaction size limits
+//!
+//! TiDB caps the size of a single transaction (`performance.txn-total-size-limit`,
+//! a fixed 100MB before v4.0) and of a single entry (`txn-entry-size-limit`).
+//! The helpers here insert an increasing amount of data in one transaction per
+//! step and record the first size the server rejects, classifying the error,
+//! so a run documents the limits of the server under test. The growth stops one
+//! step past the limit the detected version and configuration imply, since
+//! larger transactions could not commit either.
+
+use mysql::prelude::*;
+use mysql::{PooledConn, TxOpts};
+use std::fmt;
+use test_rig::capabilities::{TidbVersion, detect_version};
+use test_rig::errors::Result;
+
+/// Why the server rejected a transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxnRejection {
+    /// Total transaction size exceeded (`8004`)
+    TooLarge,
+    /// A single key/value entry exceeded the entry limit (`8025`)
+    EntryTooLarge,
+    /// The statement exceeded the memory quota (`8175`, `8001`)
+    MemoryQuota,
+    /// Any other error, with its server code if there was one
+    Other { code: Option<u16>, message: String },
+}
+
+impl TxnRejection {
+    /// Classify an error from the transaction by its server error code
+    ///
+    /// Only the size-limit codes count as rejections; a message that merely
+    /// mentions a size is not trusted without the code.
+    #[must_use]
+    pub fn classify(code: Option<u16>, message: &str) -> Self {
+        match code {
+            Some(8004) => TxnRejection::TooLarge,
+            Some(8025) => TxnRejection::EntryTooLarge,
+            Some(8001 | 8175) => TxnRejection::MemoryQuota,
+            _ => TxnRejection::Other {
+                code,
+                message: message.to_string(),
+            },
+        }
+    }
+
+    /// Classify a `mysql` driver error
+    #[must_use]
+    pub fn from_mysql_error(error: &mysql::Error) -> Self {
+        match error {
+            mysql::Error::MySqlError(e) => Self::classify(Some(e.code), &e.message),
+            other => Self::classify(None, &other.to_string()),
+        }
+    }
+
+    /// Whether this is one of the size limits the test is looking for
+    #[must_use]
+    pub fn is_size_limit(&self) -> bool {
+        !matches!(self, TxnRejection::Other { .. })
+    }
+}
+
+impl fmt::Display for TxnRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TxnRejection::TooLarge => write!(f, "transaction too large"),
+            TxnRejection::EntryTooLarge => write!(f, "entry too large"),
+            TxnRejection::MemoryQuota => write!(f, "memory quota exceeded"),
+            TxnRejection::Other {
+                code: Some(code),
+                message,
+            } => write!(f, "error {code}: {message}"),
+            TxnRejection::Other {
+                code: None,
+                message,
+            } => write!(f, "{message}"),
+        }
+    }
+}
+
+/// One transaction attempted by the test
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeStep {
+    pub rows: usize,
+    /// Approximate payload bytes written by the transaction
+    pub bytes: u64,
+    /// `None` if the transaction committed
+    pub rejection: Option<TxnRejection>,
+}
+
+/// Bookkeeping for the growing transactions
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SizeTracker {
+    pub steps: Vec<SizeStep>,
+}
+
+impl SizeTracker {
+    pub fn record_commit(&mut self, rows: usize, bytes: u64) {
+        self.steps.push(SizeStep {
+            rows,
+            bytes,
+            rejection: None,
+        });
+    }
+
+    pub fn record_rejection(&mut self, rows: usize, bytes: u64, rejection: TxnRejection) {
+        self.steps.push(SizeStep {
+            rows,
+            bytes,
+            rejection: Some(rejection),
+        });
+    }
+
+    /// Largest transaction that committed
+    #[must_use]
+    pub fn largest_committed(&self) -> Option<&SizeStep> {
+        self.steps
+            .iter()
+            .filter(|step| step.rejection.is_none())
+            .max_by_key(|step| step.bytes)
+    }
+
+    /// First transaction the server rejected
+    #[must_use]
+    pub fn first_rejection(&self) -> Option<&SizeStep> {
+        self.steps.iter().find(|step| step.rejection.is_some())
+    }
+}
+
+/// Fixed transaction size limit of `TiDB` releases before v4.0
+pub const LEGACY_TXN_SIZE_LIMIT: u64 = 100 * 1024 * 1024;
+
+/// First release where `txn-total-size-limit` is configurable
+const CONFIGURABLE_LIMIT_VERSION: TidbVersion = TidbVersion::new(4, 0, 0);
+
+/// Transaction size limit the server should enforce, in bytes
+///
+/// Before v4.0 this is [`LEGACY_TXN_SIZE_LIMIT`]; later releases report it as
+/// `configured`. `None` if the limit is not known.
+#[must_use]
+pub fn expected_size_limit(version: TidbVersion, configured: Option<&str>) -> Option<u64> {
+    if version < CONFIGURABLE_LIMIT_VERSION {
+        return Some(LEGACY_TXN_SIZE_LIMIT);
+    }
+    configured.and_then(|limit| limit.trim().parse().ok())
+}
+
+/// How transaction sizes grow between steps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GrowthPlan {
+    /// Payload bytes per row
+    pub row_bytes: usize,
+    /// Rows in the first transaction
+    pub start_rows: usize,
+    /// Each step multiplies the row count by this factor
+    pub growth_factor: usize,
+    /// Stop once a transaction would exceed this many payload bytes
+    pub max_bytes: u64,
+}
+
+impl GrowthPlan {
+    /// Row counts of each step, up to `max_bytes`
+    #[must_use]
+    pub fn row_counts(&self) -> Vec<usize> {
+        let mut counts = Vec::new();
+        let mut rows = self.start_rows.max(1);
+        while self.bytes_for(rows) <= self.max_bytes {
+            counts.push(rows);
+            rows = rows.saturating_mul(self.growth_factor.max(2));
+        }
+        counts
+    }
+
+    /// This plan, stopping one growth step past `limit` bytes
+    #[must_use]
+    pub fn capped_at(self, limit: u64) -> Self {
+        let step = self.growth_factor.max(2) as u64;
+        Self {
+            max_bytes: self.max_bytes.min(limit.saturating_mul(step)),
+            ..self
+        }
+    }
+
+    /// Payload bytes written by a transaction of `rows` rows
+    #[must_use]
+    pub fn bytes_for(&self, rows: usize) -> u64 {
+        (rows as u64).saturating_mul(self.row_bytes as u64)
+    }
+}
+
+/// Outcome of the large transaction test
+#[derive(Debug, Clone)]
+pub struct LargeTxnReport {
+    pub version: TidbVersion,
+    /// `performance.txn-total-size-limit` if the server exposes it
+    pub configured_limit: Option<String>,
+    /// Limit implied by the version and configuration; see [`expected_size_limit`]
+    pub expected_limit: Option<u64>,
+    pub tracker: SizeTracker,
+}
+
+impl LargeTxnReport {
+    pub fn print(&self) {
+        println!("Transaction size limits on TiDB {}", self.version);
+        if let Some(limit) = &self.configured_limit {
+            println!("  txn-total-size-limit: {limit}");
+        }
+        if let Some(limit) = self.expected_limit {
+            println!("  expected limit: {limit} bytes");
+        }
+        for step in &self.tracker.steps {
+            match &step.rejection {
+                None => println!("  ✓ {} rows ({} bytes) committed", step.rows, step.bytes),
+                Some(rejection) => println!(
+                    "  ❌ {} rows ({} bytes) rejected: {rejection}",
+                    step.rows, step.bytes
+                ),
+            }
+        }
+        if self.tracker.first_rejection().is_none() {
+            println!("  No rejection within the tested range");
+        }
+    }
+}
+
+/// Read `performance.txn-total-size-limit` via `SHOW CONFIG` (TiDB 4.0+)
+///
+/// Returns `None` if the server does not support `SHOW CONFIG` or has no such item.
+#[must_use]
+pub fn configured_size_limit(conn: &mut PooledConn) -> Option<String> {
+    let rows: Vec<mysql::Row> = conn
+        .query("SHOW CONFIG WHERE type = 'tidb' AND name = 'performance.txn-total-size-limit'")
+        .ok()?;
+    rows.first()
+        .and_then(|row| row.get_opt::<String, _>("Value"))
+        .and_then(std::result::Result::ok)
+}
+
+/// Insert `rows` rows of `row_bytes` bytes into `qualified` in a single transaction
+fn insert_in_one_transaction(
+    conn: &mut PooledConn,
+    qualified: &str,
+    rows: usize,
+    row_bytes: usize,
+) -> mysql::Result<()> {
+    let payload = "x".repeat(row_bytes);
+    let mut tx = conn.start_transaction(TxOpts::default())?;
+    tx.exec_batch(
+        format!("INSERT INTO {qualified} (id, payload) VALUES (?, ?)"),
+        (0..rows).map(|id| (id, payload.as_str())),
+    )?;
+    tx.commit()
+}
+
+/// Create `database.table` and commit increasingly large transactions until one is rejected
+///
+/// Stops at the first size-limit rejection, or once `plan.max_bytes` or one
+/// growth step past the [`expected_size_limit`] is reached. The table is left in place for the caller, which normally holds a
+/// `LiveDb::table` guard for it.
+///
+/// # Errors
+///
+/// Returns an error if the version cannot be detected, the table cannot be
+/// created or emptied between steps, or a transaction fails for any reason
+/// other than a size limit. Size-limit rejections are recorded, not returned
+/// as errors.
+pub fn run_large_txn_test(
+    conn: &mut PooledConn,
+    database: &str,
+    table: &str,
+    plan: &GrowthPlan,
+) -> Result<LargeTxnReport> {
+    let version = detect_version(conn)?;
+    let configured_limit = configured_size_limit(conn);
+    let expected_limit = expected_size_limit(version, configured_limit.as_deref());
+    let plan = expected_limit.map_or(*plan, |limit| plan.capped_at(limit));
+
+    let qualified = format!("`{database}`.`{table}`");
+    conn.query_drop(format!(
+        "CREATE TABLE {qualified} (id BIGINT PRIMARY KEY, payload LONGBLOB)"
+    ))?;
+
+    let mut tracker = SizeTracker::default();
+    for rows in plan.row_counts() {
+        let bytes = plan.bytes_for(rows);
+        tracing::debug!("Large transaction step: {} rows, {} bytes", rows, bytes);
+        match insert_in_one_transaction(conn, &qualified, rows, plan.row_bytes) {
+            Ok(()) => {
+                tracker.record_commit(rows, bytes);
+                conn.query_drop(format!("TRUNCATE TABLE {qualified}"))?;
+            }
+            Err(e) => {
+                let rejection = TxnRejection::from_mysql_error(&e);
+                if !rejection.is_size_limit() {
+                    return Err(e.into());
+                }
+                tracker.record_rejection(rows, bytes, rejection);
+                break;
+            }
+        }
+    }
+
+    Ok(LargeTxnReport {
+        version,
+        configured_limit,
+        expected_limit,
+        tracker,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_rig::test_support::live_db;
+
+    #[test]
+    fn test_rejection_classification() {
+        assert_eq!(
+            TxnRejection::classify(Some(8004), "Transaction is too large, size: 104857600"),
+            TxnRejection::TooLarge
+        );
+        assert_eq!(
+            TxnRejection::classify(Some(8025), "entry too large, the max entry size is 6291456"),
+            TxnRejection::EntryTooLarge
+        );
+        assert_eq!(
+            TxnRejection::classify(
+                Some(8175),
+                "Your query has been cancelled due to exceeding the allowed memory limit"
+            ),
+            TxnRejection::MemoryQuota
+        );
+        assert!(!TxnRejection::classify(None, "ERROR: Transaction is too large").is_size_limit());
+        assert!(!TxnRejection::classify(Some(1105), "transaction is too large").is_size_limit());
+
+        let other = TxnRejection::classify(Some(1062), "Duplicate entry '1'");
+        assert!(!other.is_size_limit());
+        assert_eq!(other.to_string(), "error 1062: Duplicate entry '1'");
+    }
+
+    #[test]
+    fn test_growth_plan_steps() {
+        let plan = GrowthPlan {
+            row_bytes: 1024,
+            start_rows: 10,
+            growth_factor: 10,
+            max_bytes: 1024 * 10_000,
+        };
+        assert_eq!(plan.row_counts(), vec![10, 100, 1000, 10_000]);
+        assert_eq!(plan.bytes_for(100), 102_400);
+    }
+
+    #[test]
+    fn test_expected_limit_gates_the_plan() {
+        assert_eq!(
+            expected_size_limit(TidbVersion::new(3, 0, 20), Some("1073741824")),
+            Some(LEGACY_TXN_SIZE_LIMIT)
+        );
+        assert_eq!(
+            expected_size_limit(TidbVersion::new(7, 5, 0), Some("1073741824")),
+            Some(1_073_741_824)
+        );
+        assert_eq!(expected_size_limit(TidbVersion::new(7, 5, 0), None), None);
+
+        let plan = GrowthPlan {
+            row_bytes: 1024,
+            start_rows: 10,
+            growth_factor: 10,
+            max_bytes: u64::MAX,
+        };
+        let capped = plan.capped_at(100 * 1024);
+        assert_eq!(capped.max_bytes, 1_024_000);
+        assert_eq!(capped.row_counts(), vec![10, 100, 1000]);
+        assert_eq!(plan.capped_at(u64::MAX).max_bytes, u64::MAX);
+    }
+
+    #[test]
+    fn test_size_tracker_bookkeeping() {
+        let mut tracker = SizeTracker::default();
+        assert!(tracker.largest_committed().is_none());
+        assert!(tracker.first_rejection().is_none());
+
+        tracker.record_commit(10, 1_000);
+        tracker.record_commit(100, 10_000);
+        tracker.record_rejection(1_000, 100_000, TxnRejection::TooLarge);
+
+        assert_eq!(tracker.largest_committed().unwrap().rows, 100);
+        let rejected = tracker.first_rejection().unwrap();
+        assert_eq!(rejected.bytes, 100_000);
+        assert_eq!(rejected.rejection, Some(TxnRejection::TooLarge));
+    }
+
+    #[test]
+    fn test_large_transaction_against_live_tidb() {
+        let Some(mut db) = live_db("live large transaction test") else {
+            return;
+        };
+        let table = db.table("txn_large");
+
+        // Stay well below default limits so the live run is quick
+        let plan = GrowthPlan {
+            row_bytes: 1024,
+            start_rows: 100,
+            growth_factor: 4,
+            max_bytes: 8 * 1024 * 1024,
+        };
+        let report = run_large_txn_test(&mut db.conn, &db.database, table.name(), &plan).unwrap();
+        report.print();
+        assert!(!report.tracker.steps.is_empty());
+    }
+}
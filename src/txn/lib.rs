@@ -2,3 +2,8 @@
 // This crate provides Python-based tests for TiDB transaction operations
 
 pub use test_rig::common::python_tests;
+
+/// Large transaction size limit discovery
+pub mod large_txn;
+
+pub use large_txn::{GrowthPlan, LargeTxnReport, TxnRejection, run_large_txn_test};